@@ -102,6 +102,7 @@ mod macos {
                     channel_capacity: None,
                     output_format: OutputFormat::CVPixelBuffer,
                     start_frame: None,
+                    end_frame: None,
                 };
 
                 let provider = match config.create_provider() {
@@ -99,6 +99,7 @@ fn spawn_decoder(sender: SyncSender<Frame>, input_path: PathBuf) {
                 channel_capacity: None,
                 output_format: OutputFormat::Nv12,
                 start_frame: None,
+                end_frame: None,
             };
 
             let provider = match config.create_provider() {
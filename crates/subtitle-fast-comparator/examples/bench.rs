@@ -6,7 +6,9 @@ use std::time::{Duration, Instant};
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use subtitle_fast_comparator::{
-    ComparatorFactory, ComparatorKind, ComparatorSettings, PreprocessSettings,
+    BitsetCoverSettings, ComparatorFactory, ComparatorKind, ComparatorSettings,
+    DEFAULT_MIN_EDGE_POINTS, PreprocessMode, PreprocessSettings, SparseChamferSettings,
+    SsimSettings,
 };
 
 #[path = "common/roi_examples.rs"]
@@ -16,8 +18,14 @@ use roi_examples::{load_frame, load_rois};
 
 const YUV_DIR: &str = "./demo/decoder/yuv";
 const ROI_DIR: &str = "./demo/validator/projection";
-const COMPARATORS: &[ComparatorKind] =
-    &[ComparatorKind::SparseChamfer, ComparatorKind::BitsetCover];
+const COMPARATORS: &[ComparatorKind] = &[
+    ComparatorKind::SparseChamfer,
+    ComparatorKind::BitsetCover,
+    ComparatorKind::PHash,
+    ComparatorKind::Ssim,
+    ComparatorKind::Histogram,
+    ComparatorKind::HistogramChamfer,
+];
 
 #[derive(Debug, Clone, Copy)]
 struct BenchStats {
@@ -127,11 +135,17 @@ fn run_comparator_bench(
         let preprocess = PreprocessSettings {
             target: selection.luma_band.target,
             delta: selection.luma_band.delta,
+            mode: PreprocessMode::default(),
         };
         let comparator = ComparatorFactory::new(ComparatorSettings {
             kind,
             target: preprocess.target,
             delta: preprocess.delta,
+            bitset_cover: BitsetCoverSettings::default(),
+            min_edge_points: DEFAULT_MIN_EDGE_POINTS,
+            sparse_chamfer: SparseChamferSettings::default(),
+            ssim: SsimSettings::default(),
+            fallback: None,
         })
         .build();
 
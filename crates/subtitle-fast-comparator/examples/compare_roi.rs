@@ -2,7 +2,9 @@ use std::error::Error;
 use std::path::PathBuf;
 
 use subtitle_fast_comparator::{
-    ComparatorFactory, ComparatorKind, ComparatorSettings, PreprocessSettings,
+    BitsetCoverSettings, ComparatorFactory, ComparatorKind, ComparatorSettings,
+    DEFAULT_MIN_EDGE_POINTS, PreprocessMode, PreprocessSettings, SparseChamferSettings,
+    SsimSettings,
 };
 #[path = "common/roi_examples.rs"]
 mod roi_examples;
@@ -26,11 +28,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     let preprocess = PreprocessSettings {
         target: selections.luma_band.target,
         delta: selections.luma_band.delta,
+        mode: PreprocessMode::default(),
     };
     let comparator = ComparatorFactory::new(ComparatorSettings {
         kind: COMPARATOR,
         target: preprocess.target,
         delta: preprocess.delta,
+        bitset_cover: BitsetCoverSettings::default(),
+        min_edge_points: DEFAULT_MIN_EDGE_POINTS,
+        sparse_chamfer: SparseChamferSettings::default(),
+        ssim: SsimSettings::default(),
+        fallback: None,
     })
     .build();
 
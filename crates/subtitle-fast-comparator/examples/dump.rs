@@ -8,7 +8,9 @@ use indicatif::{ProgressBar, ProgressStyle};
 use serde::Serialize;
 use serde_json::to_writer_pretty;
 use subtitle_fast_comparator::{
-    ComparatorFactory, ComparatorKind, ComparatorSettings, PreprocessSettings,
+    BitsetCoverSettings, ComparatorFactory, ComparatorKind, ComparatorSettings,
+    DEFAULT_MIN_EDGE_POINTS, PreprocessMode, PreprocessSettings, SparseChamferSettings,
+    SsimSettings,
 };
 
 #[path = "common/roi_examples.rs"]
@@ -106,11 +108,17 @@ fn main() -> Result<(), Box<dyn Error>> {
         let preprocess = PreprocessSettings {
             target: selection.luma_band.target,
             delta: selection.luma_band.delta,
+            mode: PreprocessMode::default(),
         };
         let comparator = ComparatorFactory::new(ComparatorSettings {
             kind: COMPARATOR,
             target: preprocess.target,
             delta: preprocess.delta,
+            bitset_cover: BitsetCoverSettings::default(),
+            min_edge_points: DEFAULT_MIN_EDGE_POINTS,
+            sparse_chamfer: SparseChamferSettings::default(),
+            ssim: SsimSettings::default(),
+            fallback: None,
         })
         .build();
 
@@ -136,9 +144,8 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             let report = comparator.compare(&feature_a, &feature_b);
             let metrics = report
-                .details
-                .iter()
-                .map(|metric| (metric.name.to_string(), metric.value))
+                .metrics()
+                .map(|(name, value)| (name.to_string(), value))
                 .collect::<BTreeMap<_, _>>();
             roi_results.push(RoiResultDump {
                 description: entry.description.clone(),
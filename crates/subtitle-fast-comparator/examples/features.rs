@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::to_writer_pretty;
+use subtitle_fast_comparator::{
+    DebugFeatures, PreprocessMode, PreprocessSettings, SparseChamferComparator,
+};
+
+#[path = "common/roi_examples.rs"]
+mod roi_examples;
+
+use roi_examples::{load_frame, load_rois};
+
+const YUV_DIR: &str = "./demo/decoder/yuv";
+const ROI_DIR: &str = "./demo/validator/projection";
+const OUTPUT_DIR: &str = "./demo/comparator/features";
+const DEFAULT_FRAME_INDEX: u64 = 10;
+
+#[derive(Serialize)]
+struct RoiFeaturesDump {
+    description: String,
+    width: usize,
+    height: usize,
+    edge_count: usize,
+    stroke_width: f32,
+    points: Vec<(usize, usize)>,
+    edge_mask: String,
+}
+
+#[derive(Serialize)]
+struct FeaturesDump {
+    frame: String,
+    regions: Vec<RoiFeaturesDump>,
+}
+
+/// Dumps the sparse-chamfer edge points for one frame: `comparator-features [FRAME_INDEX]`.
+fn main() -> Result<(), Box<dyn Error>> {
+    let frame_index = match std::env::args().nth(1) {
+        Some(value) => value.parse::<u64>()?,
+        None => DEFAULT_FRAME_INDEX,
+    };
+    let stem = format!("{frame_index:05}");
+    let yuv_path = PathBuf::from(YUV_DIR).join(format!("{stem}.yuv"));
+    let roi_path = PathBuf::from(ROI_DIR).join(format!("{stem}.json"));
+
+    let selection = load_rois(&roi_path)?;
+    let frame = load_frame(&yuv_path, selection.frame_width, selection.frame_height)?;
+    let comparator = SparseChamferComparator::new(PreprocessSettings {
+        target: selection.luma_band.target,
+        delta: selection.luma_band.delta,
+        mode: PreprocessMode::default(),
+    });
+
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let mut regions = Vec::new();
+    for (idx, entry) in selection.regions.iter().enumerate() {
+        let Some(debug) = comparator.debug_extract(&frame, &entry.roi) else {
+            println!("[{}] skipped: no edges extracted", entry.description);
+            continue;
+        };
+        let edge_mask = format!("{stem}_roi{idx}.pgm");
+        write_edge_pgm(&Path::new(OUTPUT_DIR).join(&edge_mask), &debug)?;
+        println!(
+            "[{}] {}x{} edges={} points={} stroke={:.2}",
+            entry.description,
+            debug.width,
+            debug.height,
+            debug.edge_count,
+            debug.points.len(),
+            debug.stroke_width
+        );
+        regions.push(RoiFeaturesDump {
+            description: entry.description.clone(),
+            width: debug.width,
+            height: debug.height,
+            edge_count: debug.edge_count,
+            stroke_width: debug.stroke_width,
+            points: debug.points,
+            edge_mask,
+        });
+    }
+
+    let dump_path = PathBuf::from(OUTPUT_DIR).join(format!("{stem}.json"));
+    let writer = BufWriter::new(File::create(&dump_path)?);
+    to_writer_pretty(
+        writer,
+        &FeaturesDump {
+            frame: yuv_path.display().to_string(),
+            regions,
+        },
+    )?;
+    println!("features written to {}", dump_path.display());
+    Ok(())
+}
+
+/// Edge pixels are gray, sampled points white.
+fn write_edge_pgm(path: &Path, debug: &DebugFeatures) -> Result<(), Box<dyn Error>> {
+    let mut pixels: Vec<u8> = debug.edge_mask.iter().map(|&v| v * 96).collect();
+    for &(x, y) in &debug.points {
+        pixels[y * debug.width + x] = 255;
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "P5\n{} {}\n255\n", debug.width, debug.height)?;
+    writer.write_all(&pixels)?;
+    Ok(())
+}
@@ -15,13 +15,48 @@ thread_local! {
     static TLS_SCRATCH: UnsafeCell<BitsetScratch> = UnsafeCell::new(BitsetScratch::new());
 }
 
+/// Aggregation used to turn tolerant bit overlap into a similarity score.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum CoverMetric {
+    /// `1 - miss / union`, where misses are bits not covered by the other dilated mask.
+    #[default]
+    MissFraction,
+    /// `|A ∩ B| / |A ∪ B|`.
+    Jaccard,
+    /// `2 |A ∩ B| / (|A| + |B|)`; more forgiving for thin strokes.
+    Dice,
+    /// `|A ∩ B| / min(|A|, |B|)`.
+    Overlap,
+}
+
+impl CoverMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoverMetric::MissFraction => "miss-fraction",
+            CoverMetric::Jaccard => "jaccard",
+            CoverMetric::Dice => "dice",
+            CoverMetric::Overlap => "overlap",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct BitsetCoverSettings {
+    pub metric: CoverMetric,
+}
+
 pub struct BitsetCoverComparator {
     settings: PreprocessSettings,
+    cover: BitsetCoverSettings,
 }
 
 impl BitsetCoverComparator {
     pub fn new(settings: PreprocessSettings) -> Self {
-        Self { settings }
+        Self::with_settings(settings, BitsetCoverSettings::default())
+    }
+
+    pub fn with_settings(settings: PreprocessSettings, cover: BitsetCoverSettings) -> Self {
+        Self { settings, cover }
     }
 
     fn build_features(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<BitsetFeatures> {
@@ -100,7 +135,7 @@ impl BitsetCoverComparator {
             return Some((1.0, 0.0, false));
         }
         let parallel = should_parallel(total_words);
-        let (miss, union) = reduce_miss_union(
+        let counts = reduce_cover_counts(
             &a.bits,
             &b.bits,
             &a.dilated,
@@ -108,12 +143,8 @@ impl BitsetCoverComparator {
             parallel,
             total_words,
         );
-        let (similarity, miss_fraction) = if union == 0 {
-            (1.0, 0.0)
-        } else {
-            let miss_fraction = (miss as f32) / (union as f32);
-            ((1.0 - miss_fraction).clamp(0.0, 1.0), miss_fraction)
-        };
+        let miss_fraction = counts.miss_fraction();
+        let similarity = cover_similarity(self.cover.metric, &counts);
         Some((similarity, miss_fraction, parallel))
     }
 
@@ -149,11 +180,16 @@ impl SubtitleComparator for BitsetCoverComparator {
         else {
             return ComparisonReport::new(0.0, false);
         };
-        let same = miss_fraction <= MISS_THRESHOLD;
+        let metric = self.cover.metric;
+        let same = match metric {
+            CoverMetric::MissFraction => miss_fraction <= MISS_THRESHOLD,
+            _ => similarity >= 1.0 - MISS_THRESHOLD,
+        };
         ComparisonReport::with_details(
             similarity,
             same,
             vec![
+                ReportMetric::new(metric.as_str(), similarity),
                 ReportMetric::new("miss_fraction", miss_fraction),
                 ReportMetric::new("threshold_miss", MISS_THRESHOLD),
                 ReportMetric::new("tolerance_px", TOLERANCE_PX as f32),
@@ -417,44 +453,106 @@ fn dilate3x3_u64_once(
     }
 }
 
-fn reduce_miss_union(
+/// Popcounts gathered in one pass over both masks; intersections are tolerance-aware.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CoverCounts {
+    pub(crate) miss: usize,
+    pub(crate) union: usize,
+    pub(crate) a_total: usize,
+    pub(crate) b_total: usize,
+    pub(crate) a_covered: usize,
+    pub(crate) b_covered: usize,
+}
+
+impl CoverCounts {
+    fn add(self, other: Self) -> Self {
+        Self {
+            miss: self.miss + other.miss,
+            union: self.union + other.union,
+            a_total: self.a_total + other.a_total,
+            b_total: self.b_total + other.b_total,
+            a_covered: self.a_covered + other.a_covered,
+            b_covered: self.b_covered + other.b_covered,
+        }
+    }
+
+    fn miss_fraction(&self) -> f32 {
+        if self.union == 0 {
+            0.0
+        } else {
+            (self.miss as f32) / (self.union as f32)
+        }
+    }
+
+    fn intersection(&self) -> f32 {
+        (self.a_covered + self.b_covered) as f32 * 0.5
+    }
+}
+
+pub(crate) fn cover_similarity(metric: CoverMetric, counts: &CoverCounts) -> f32 {
+    if counts.a_total == 0 && counts.b_total == 0 {
+        return 1.0;
+    }
+    let intersection = counts.intersection();
+    let a_total = counts.a_total as f32;
+    let b_total = counts.b_total as f32;
+    let value = match metric {
+        CoverMetric::MissFraction => 1.0 - counts.miss_fraction(),
+        CoverMetric::Jaccard => {
+            let union = a_total + b_total - intersection;
+            if union <= 0.0 {
+                0.0
+            } else {
+                intersection / union
+            }
+        }
+        CoverMetric::Dice => 2.0 * intersection / (a_total + b_total),
+        CoverMetric::Overlap => {
+            let smaller = a_total.min(b_total);
+            if smaller <= 0.0 {
+                0.0
+            } else {
+                intersection / smaller
+            }
+        }
+    };
+    value.clamp(0.0, 1.0)
+}
+
+fn word_counts(abit: u64, bbit: u64, ad_bit: u64, bd_bit: u64) -> CoverCounts {
+    let a_covered = (abit & bd_bit).count_ones() as usize;
+    let b_covered = (bbit & ad_bit).count_ones() as usize;
+    let a_total = abit.count_ones() as usize;
+    let b_total = bbit.count_ones() as usize;
+    CoverCounts {
+        miss: (a_total - a_covered) + (b_total - b_covered),
+        union: (abit | bbit).count_ones() as usize,
+        a_total,
+        b_total,
+        a_covered,
+        b_covered,
+    }
+}
+
+fn reduce_cover_counts(
     a_bits: &[u64],
     b_bits: &[u64],
     ad: &[u64],
     bd: &[u64],
     use_parallel: bool,
     total_words: usize,
-) -> (usize, usize) {
+) -> CoverCounts {
     if use_parallel {
         (0..total_words)
             .into_par_iter()
-            .map(|i| {
-                let abit = a_bits[i];
-                let bbit = b_bits[i];
-                let ad_bit = ad[i];
-                let bd_bit = bd[i];
-                let miss =
-                    (abit & !bd_bit).count_ones() as usize + (bbit & !ad_bit).count_ones() as usize;
-                let union = (abit | bbit).count_ones() as usize;
-                (miss, union)
-            })
-            .reduce(
-                || (0usize, 0usize),
-                |acc, item| (acc.0 + item.0, acc.1 + item.1),
-            )
+            .map(|i| word_counts(a_bits[i], b_bits[i], ad[i], bd[i]))
+            .reduce(CoverCounts::default, CoverCounts::add)
     } else {
-        let mut miss = 0usize;
-        let mut union = 0usize;
+        let mut counts = CoverCounts::default();
         for i in 0..total_words {
-            let abit = a_bits[i];
-            let bbit = b_bits[i];
-            let ad_bit = ad[i];
-            let bd_bit = bd[i];
-            miss += (abit & !bd_bit).count_ones() as usize;
-            miss += (bbit & !ad_bit).count_ones() as usize;
-            union += (abit | bbit).count_ones() as usize;
+            counts = counts.add(word_counts(a_bits[i], b_bits[i], ad[i], bd[i]));
         }
-        (miss, union)
+        counts
     }
 }
 
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use subtitle_fast_types::{RoiConfig, VideoFrame};
+
+use super::SubtitleComparator;
+use crate::pipeline::{ComparisonReport, FeatureBlob, ReportMetric};
+
+const TAG: &str = "composite";
+
+/// Features of one ROI from both stages.
+struct CompositeFeatures {
+    prefilter: FeatureBlob,
+    precise: FeatureBlob,
+}
+
+/// Chains a cheap `prefilter` comparator with an expensive `precise` one. A prefilter
+/// similarity below `low` or above `high` is decisive and the prefilter's report is returned
+/// as is; only similarities inside `[low, high]` run the precise comparator, whose report is
+/// returned instead. Either way the report carries `decided_by_prefilter` (1.0 or 0.0) and
+/// `prefilter_similarity`, so callers can see which stage decided.
+pub struct CompositeComparator {
+    prefilter: Arc<dyn SubtitleComparator>,
+    precise: Arc<dyn SubtitleComparator>,
+    low: f32,
+    high: f32,
+}
+
+impl CompositeComparator {
+    /// Pass `f32::INFINITY` as `high` for a prefilter that may only reject, such as a
+    /// histogram, which cannot tell different lines over the same background apart.
+    pub fn new(
+        prefilter: Arc<dyn SubtitleComparator>,
+        precise: Arc<dyn SubtitleComparator>,
+        low: f32,
+        high: f32,
+    ) -> Self {
+        Self {
+            prefilter,
+            precise,
+            low,
+            high,
+        }
+    }
+}
+
+impl SubtitleComparator for CompositeComparator {
+    fn name(&self) -> &'static str {
+        TAG
+    }
+
+    fn extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<FeatureBlob> {
+        let prefilter = self.prefilter.extract(frame, roi)?;
+        let precise = self.precise.extract(frame, roi)?;
+        Some(FeatureBlob::new(
+            TAG,
+            CompositeFeatures { prefilter, precise },
+        ))
+    }
+
+    fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+        let (Some(reference), Some(candidate)) = (
+            reference.downcast::<CompositeFeatures>(TAG),
+            candidate.downcast::<CompositeFeatures>(TAG),
+        ) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        let cheap = self
+            .prefilter
+            .compare(&reference.prefilter, &candidate.prefilter);
+        let decisive = cheap.similarity < self.low || cheap.similarity > self.high;
+        let prefilter_similarity = cheap.similarity;
+        let mut report = if decisive {
+            cheap
+        } else {
+            self.precise.compare(&reference.precise, &candidate.precise)
+        };
+        report.details.extend([
+            ReportMetric::new("decided_by_prefilter", if decisive { 1.0 } else { 0.0 }),
+            ReportMetric::new("prefilter_similarity", prefilter_similarity),
+        ]);
+        report
+    }
+}
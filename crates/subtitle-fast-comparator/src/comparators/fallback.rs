@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use subtitle_fast_types::{RoiConfig, VideoFrame};
+
+use super::SubtitleComparator;
+use crate::pipeline::{ComparisonReport, FeatureBlob, ReportMetric};
+
+const TAG: &str = "fallback";
+
+/// Features produced by one link of the chain, remembered together with that link.
+struct FallbackFeatures {
+    producer: usize,
+    inner: FeatureBlob,
+}
+
+/// Tries an ordered list of comparators: `extract` uses the first one that yields features, and
+/// `compare` delegates to whichever comparator produced both blobs.
+pub struct FallbackComparator {
+    chain: Vec<Arc<dyn SubtitleComparator>>,
+}
+
+impl FallbackComparator {
+    pub fn new(chain: Vec<Arc<dyn SubtitleComparator>>) -> Self {
+        Self { chain }
+    }
+
+    /// Name of the comparator that extracted `blob`, if it came from this chain.
+    pub fn producer(&self, blob: &FeatureBlob) -> Option<&'static str> {
+        let features = blob.downcast::<FallbackFeatures>(TAG)?;
+        self.chain
+            .get(features.producer)
+            .map(|comparator| comparator.name())
+    }
+}
+
+impl SubtitleComparator for FallbackComparator {
+    fn name(&self) -> &'static str {
+        TAG
+    }
+
+    fn extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<FeatureBlob> {
+        self.chain
+            .iter()
+            .enumerate()
+            .find_map(|(producer, comparator)| {
+                let inner = comparator.extract(frame, roi)?;
+                Some(FeatureBlob::new(TAG, FallbackFeatures { producer, inner }))
+            })
+    }
+
+    fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+        let Some(reference) = reference.downcast::<FallbackFeatures>(TAG) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        let Some(candidate) = candidate.downcast::<FallbackFeatures>(TAG) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        // Scores from different comparators are not on the same scale.
+        if reference.producer != candidate.producer {
+            return ComparisonReport::with_details(
+                0.0,
+                false,
+                vec![ReportMetric::new("producer_mismatch", 1.0)],
+            );
+        }
+        let Some(comparator) = self.chain.get(reference.producer) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        comparator.compare(&reference.inner, &candidate.inner)
+    }
+}
@@ -0,0 +1,80 @@
+use subtitle_fast_types::{RoiConfig, VideoFrame};
+
+use crate::comparators::SubtitleComparator;
+use crate::pipeline::preprocess::extract_masked_patch;
+use crate::pipeline::{ComparisonReport, FeatureBlob, PreprocessSettings, ReportMetric};
+
+const TAG: &str = "histogram";
+/// Luma histogram resolution; each bin spans four 8-bit levels.
+pub const HISTOGRAM_BINS: usize = 64;
+/// Bhattacharyya coefficient needed for `histogram` alone to call two patches the same line.
+const SAME_SEGMENT_COEFFICIENT: f32 = 0.9;
+
+/// Compares the normalized 64-bin luma histograms of two ROIs by their Bhattacharyya
+/// coefficient, which is the similarity (1.0 for identical distributions, 0.0 for disjoint
+/// ones). Extraction and comparison are a single pass each, but the histogram ignores where
+/// pixels are: two different lines over the same background score nearly alike, while the
+/// same line over a changing background drifts apart. It is meant as a prefilter in front of
+/// a spatial comparator, see [`CompositeComparator`](super::CompositeComparator).
+pub struct HistogramComparator {
+    settings: PreprocessSettings,
+}
+
+impl HistogramComparator {
+    pub fn new(settings: PreprocessSettings) -> Self {
+        Self { settings }
+    }
+
+    fn histogram(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<[f32; HISTOGRAM_BINS]> {
+        let patch = extract_masked_patch(frame, roi, self.settings)?;
+        if patch.is_empty() {
+            return None;
+        }
+        let mut histogram = [0.0f32; HISTOGRAM_BINS];
+        for &value in &patch.original {
+            let bin = ((value * HISTOGRAM_BINS as f32) as usize).min(HISTOGRAM_BINS - 1);
+            histogram[bin] += 1.0;
+        }
+        let total = patch.len() as f32;
+        histogram.iter_mut().for_each(|count| *count /= total);
+        Some(histogram)
+    }
+
+    fn coefficient(reference: &[f32; HISTOGRAM_BINS], candidate: &[f32; HISTOGRAM_BINS]) -> f32 {
+        let sum: f32 = reference
+            .iter()
+            .zip(candidate)
+            .map(|(p, q)| (p * q).sqrt())
+            .sum();
+        sum.clamp(0.0, 1.0)
+    }
+}
+
+impl SubtitleComparator for HistogramComparator {
+    fn name(&self) -> &'static str {
+        TAG
+    }
+
+    fn extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<FeatureBlob> {
+        self.histogram(frame, roi)
+            .map(|histogram| FeatureBlob::new(TAG, histogram))
+    }
+
+    fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+        let (Some(reference), Some(candidate)) = (
+            reference.downcast::<[f32; HISTOGRAM_BINS]>(TAG),
+            candidate.downcast::<[f32; HISTOGRAM_BINS]>(TAG),
+        ) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        let coefficient = Self::coefficient(&reference, &candidate);
+        ComparisonReport::with_details(
+            coefficient,
+            coefficient >= SAME_SEGMENT_COEFFICIENT,
+            vec![
+                ReportMetric::new("bhattacharyya_distance", (1.0 - coefficient).sqrt()),
+                ReportMetric::new("threshold_coefficient", SAME_SEGMENT_COEFFICIENT),
+            ],
+        )
+    }
+}
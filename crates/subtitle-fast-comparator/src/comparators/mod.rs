@@ -1,8 +1,20 @@
 pub mod bitset_cover;
+pub mod composite;
+pub mod fallback;
+pub mod histogram;
+pub mod phash;
 pub mod sparse_chamfer;
+pub mod ssim;
 
-pub use bitset_cover::BitsetCoverComparator;
-pub use sparse_chamfer::SparseChamferComparator;
+pub use bitset_cover::{BitsetCoverComparator, BitsetCoverSettings, CoverMetric};
+pub use composite::CompositeComparator;
+pub use fallback::FallbackComparator;
+pub use histogram::{HISTOGRAM_BINS, HistogramComparator};
+pub use phash::PHashComparator;
+pub use sparse_chamfer::{
+    DEFAULT_MIN_EDGE_POINTS, DebugFeatures, SparseChamferComparator, SparseChamferSettings,
+};
+pub use ssim::{DEFAULT_SSIM_THRESHOLD, SsimComparator, SsimSettings};
 
 use crate::pipeline::{ComparisonReport, FeatureBlob};
 use subtitle_fast_types::{RoiConfig, VideoFrame};
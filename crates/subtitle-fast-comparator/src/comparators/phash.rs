@@ -0,0 +1,140 @@
+use std::f32::consts::PI;
+use std::sync::OnceLock;
+
+use subtitle_fast_types::{RoiConfig, VideoFrame};
+
+use crate::comparators::SubtitleComparator;
+use crate::pipeline::preprocess::extract_masked_patch;
+use crate::pipeline::{ComparisonReport, FeatureBlob, PreprocessSettings, ReportMetric};
+
+const TAG: &str = "phash";
+/// Side of the square the masked patch is resampled to before the DCT.
+const SAMPLE_SIZE: usize = 32;
+/// Side of the low-frequency coefficient block that forms the 64-bit hash.
+const HASH_SIZE: usize = 8;
+/// Hashes differing in at most this many bits belong to the same subtitle.
+const MAX_HAMMING: u32 = 10;
+
+/// Perceptual hash over the text mask: the ROI is binarised like the other comparators,
+/// box-resampled to 32x32 and reduced to the signs of its 8x8 lowest DCT frequencies against
+/// their median. Small shifts and anti-aliasing changes flip few bits, so the Hamming distance
+/// stays low for the same line while a different line lands around half the bits away.
+pub struct PHashComparator {
+    settings: PreprocessSettings,
+}
+
+impl PHashComparator {
+    pub fn new(settings: PreprocessSettings) -> Self {
+        Self { settings }
+    }
+
+    fn hash(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<u64> {
+        let patch = extract_masked_patch(frame, roi, self.settings)?;
+        if patch.is_empty() {
+            return None;
+        }
+        let samples = resample(&patch.masked, patch.width, patch.height);
+        Some(hash_bits(&low_frequencies(&samples)))
+    }
+}
+
+impl SubtitleComparator for PHashComparator {
+    fn name(&self) -> &'static str {
+        TAG
+    }
+
+    fn extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<FeatureBlob> {
+        self.hash(frame, roi)
+            .map(|hash| FeatureBlob::new(TAG, hash))
+    }
+
+    fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+        let (Some(reference), Some(candidate)) = (
+            reference.downcast::<u64>(TAG),
+            candidate.downcast::<u64>(TAG),
+        ) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        let distance = (*reference ^ *candidate).count_ones();
+        let similarity = 1.0 - distance as f32 / u64::BITS as f32;
+        ComparisonReport::with_details(
+            similarity,
+            distance <= MAX_HAMMING,
+            vec![
+                ReportMetric::new("hamming", distance as f32),
+                ReportMetric::new("threshold_hamming", MAX_HAMMING as f32),
+            ],
+        )
+    }
+}
+
+/// Box-averages a `width` x `height` patch onto the `SAMPLE_SIZE` grid; patches smaller than
+/// the grid repeat their pixels.
+fn resample(pixels: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let span = |index: usize, len: usize| {
+        let start = index * len / SAMPLE_SIZE;
+        let end = ((index + 1) * len).div_ceil(SAMPLE_SIZE).max(start + 1);
+        start..end.min(len)
+    };
+    let mut samples = vec![0.0; SAMPLE_SIZE * SAMPLE_SIZE];
+    for (row, out_row) in samples.chunks_exact_mut(SAMPLE_SIZE).enumerate() {
+        let rows = span(row, height);
+        for (col, sample) in out_row.iter_mut().enumerate() {
+            let cols = span(col, width);
+            let count = rows.len() * cols.len();
+            let sum: f32 = rows
+                .clone()
+                .flat_map(|y| &pixels[y * width + cols.start..y * width + cols.end])
+                .sum();
+            *sample = sum / count as f32;
+        }
+    }
+    samples
+}
+
+/// `cos((2x + 1) u pi / 2N)` for the first `HASH_SIZE` frequencies `u`.
+fn dct_basis() -> &'static [[f32; SAMPLE_SIZE]; HASH_SIZE] {
+    static BASIS: OnceLock<[[f32; SAMPLE_SIZE]; HASH_SIZE]> = OnceLock::new();
+    BASIS.get_or_init(|| {
+        let mut basis = [[0.0; SAMPLE_SIZE]; HASH_SIZE];
+        for (u, row) in basis.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = ((2 * x + 1) as f32 * u as f32 * PI / (2 * SAMPLE_SIZE) as f32).cos();
+            }
+        }
+        basis
+    })
+}
+
+/// The `HASH_SIZE` x `HASH_SIZE` lowest frequencies of the separable 2D DCT-II, row-major.
+/// Normalisation factors are skipped since only the signs against the median matter.
+fn low_frequencies(samples: &[f32]) -> [f32; HASH_SIZE * HASH_SIZE] {
+    let basis = dct_basis();
+    let mut rows = [[0.0f32; HASH_SIZE]; SAMPLE_SIZE];
+    for (y, row) in samples.chunks_exact(SAMPLE_SIZE).enumerate() {
+        for (u, coefficient) in rows[y].iter_mut().enumerate() {
+            *coefficient = row.iter().zip(&basis[u]).map(|(p, c)| p * c).sum();
+        }
+    }
+    let mut coefficients = [0.0; HASH_SIZE * HASH_SIZE];
+    for v in 0..HASH_SIZE {
+        for u in 0..HASH_SIZE {
+            coefficients[v * HASH_SIZE + u] =
+                rows.iter().zip(&basis[v]).map(|(row, c)| row[u] * c).sum();
+        }
+    }
+    coefficients
+}
+
+/// One bit per coefficient above the median of the AC terms; the DC term only tracks overall
+/// brightness and is left out of the median.
+fn hash_bits(coefficients: &[f32; HASH_SIZE * HASH_SIZE]) -> u64 {
+    let mut ac = coefficients[1..].to_vec();
+    ac.sort_by(f32::total_cmp);
+    let median = ac[ac.len() / 2];
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value > median)
+        .fold(0u64, |hash, (bit, _)| hash | (1 << bit))
+}
@@ -16,13 +16,60 @@ const GRID_STEP: usize = 3;
 const MAX_POINTS: usize = 400;
 const KEEP_QUANTILE: f32 = 0.7;
 const CLIP_PX: f32 = 4.0;
+/// Upper bound for `clip_px`; the distance transform's stencil tables hold 64 radii.
+const MAX_CLIP_PX: f32 = 32.0;
 const TIGHT_PX: f32 = 1.5;
-const SHIFT_RADIUS: isize = 2;
+const SHIFT_RADIUS: usize = 2;
 const SIM_THRESHOLD: f32 = 0.60;
 const MATCH_THRESHOLD: f32 = 0.55;
 const SIGMA_SCALE: f32 = 0.03;
 const STROKE_SIGMA: f32 = 1.8;
 const PARALLEL_MIN_POINTS: usize = 256;
+/// Smallest patch, in candidate edge points (pixels), that `extract` will sample.
+pub const DEFAULT_MIN_EDGE_POINTS: usize = 16;
+
+/// Tuning knobs of [`SparseChamferComparator`]; the defaults are the values it has always
+/// used.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SparseChamferSettings {
+    /// Spacing, in pixels, of the grid edge points are sampled on.
+    pub grid_step: usize,
+    /// Most edge points sampled per ROI.
+    pub max_points: usize,
+    /// Fraction of the closest point distances kept by the partial chamfer.
+    pub keep_quantile: f32,
+    /// Distances are clipped to this many pixels (at most 32).
+    pub clip_px: f32,
+    /// Points closer than this many pixels count towards `match_fraction`.
+    pub tight_px: f32,
+    /// Largest translation, in pixels along each axis, searched between two patches.
+    pub shift_radius: usize,
+    /// Similarity needed to call two patches the same line.
+    pub sim_threshold: f32,
+    /// `match_fraction` needed to call two patches the same line.
+    pub match_threshold: f32,
+    /// Chamfer cost falloff, as a fraction of the patch diagonal.
+    pub sigma_scale: f32,
+    /// Stroke width difference, in pixels, at which the stroke penalty reaches `1/e`.
+    pub stroke_sigma: f32,
+}
+
+impl Default for SparseChamferSettings {
+    fn default() -> Self {
+        Self {
+            grid_step: GRID_STEP,
+            max_points: MAX_POINTS,
+            keep_quantile: KEEP_QUANTILE,
+            clip_px: CLIP_PX,
+            tight_px: TIGHT_PX,
+            shift_radius: SHIFT_RADIUS,
+            sim_threshold: SIM_THRESHOLD,
+            match_threshold: MATCH_THRESHOLD,
+            sigma_scale: SIGMA_SCALE,
+            stroke_sigma: STROKE_SIGMA,
+        }
+    }
+}
 
 thread_local! {
     static TLS_SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::new());
@@ -338,10 +385,24 @@ struct Point {
     y: usize,
 }
 
+/// Snapshot of what the chamfer comparator extracts from one ROI, for tuning.
+#[derive(Debug, Clone)]
+pub struct DebugFeatures {
+    pub width: usize,
+    pub height: usize,
+    /// Sampled edge points as `(x, y)` in patch coordinates.
+    pub points: Vec<(usize, usize)>,
+    pub edge_count: usize,
+    pub stroke_width: f32,
+    /// Binary edge mask (1 = edge), row-major `width * height`.
+    pub edge_mask: Vec<u8>,
+}
+
 #[derive(Clone)]
 struct SparseChamferFeatures {
     width: usize,
     height: usize,
+    edge_count: usize,
     points: Vec<Point>,
     distance_map: Vec<f32>,
     stroke_width: f32,
@@ -395,11 +456,36 @@ impl Scratch {
 
 pub struct SparseChamferComparator {
     settings: PreprocessSettings,
+    chamfer: SparseChamferSettings,
+    min_edge_points: usize,
 }
 
 impl SparseChamferComparator {
     pub fn new(settings: PreprocessSettings) -> Self {
-        Self { settings }
+        Self::with_settings(settings, SparseChamferSettings::default())
+    }
+
+    pub fn with_settings(settings: PreprocessSettings, chamfer: SparseChamferSettings) -> Self {
+        let chamfer = SparseChamferSettings {
+            grid_step: chamfer.grid_step.max(1),
+            max_points: chamfer.max_points.max(1),
+            keep_quantile: chamfer.keep_quantile.clamp(0.0, 1.0),
+            clip_px: chamfer.clip_px.clamp(0.0, MAX_CLIP_PX),
+            ..chamfer
+        };
+        Self {
+            settings,
+            chamfer,
+            min_edge_points: DEFAULT_MIN_EDGE_POINTS,
+        }
+    }
+
+    /// Patches with fewer candidate edge points than this are treated as "no features".
+    /// Lower it to keep very small or thin subtitle patches; patches without any edge are
+    /// always rejected.
+    pub fn with_min_edge_points(mut self, min_edge_points: usize) -> Self {
+        self.min_edge_points = min_edge_points.max(1);
+        self
     }
 
     fn with_scratch<F, R>(&self, f: F) -> R
@@ -504,14 +590,14 @@ impl SparseChamferComparator {
         }
         let grid_w = width.div_ceil(step);
         let grid_h = height.div_ceil(step);
-        let max_points = grid_w.saturating_mul(grid_h).min(MAX_POINTS);
+        let max_points = grid_w.saturating_mul(grid_h).min(self.chamfer.max_points);
         let mut points = Vec::with_capacity(max_points);
         for y in (0..height).step_by(step) {
             for x in (0..width).step_by(step) {
                 let idx = y * width + x;
                 if edges[idx] > 0 {
                     points.push(Point { x, y });
-                    if points.len() == MAX_POINTS {
+                    if points.len() == self.chamfer.max_points {
                         return points;
                     }
                 }
@@ -521,14 +607,41 @@ impl SparseChamferComparator {
     }
 
     fn sample_points(&self, edges: &[u8], width: usize, height: usize) -> Vec<Point> {
-        let mut points = self.sample_points_step(edges, width, height, GRID_STEP);
-        if points.is_empty() && GRID_STEP > 1 {
+        let step = self.chamfer.grid_step;
+        let mut points = self.sample_points_step(edges, width, height, step);
+        if points.is_empty() && step > 1 {
             points = self.sample_points_step(edges, width, height, 1);
         }
         points
     }
 
+    /// Runs the same extraction as `extract` but also returns the edge mask and sampled points.
+    pub fn debug_extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<DebugFeatures> {
+        let patch = extract_masked_patch(frame, roi, self.settings)?;
+        if patch.len() < self.min_edge_points {
+            return None;
+        }
+        let mut edge_mask = Vec::new();
+        let features = self.build_features_with(&patch, Some(&mut edge_mask))?;
+        Some(DebugFeatures {
+            width: features.width,
+            height: features.height,
+            points: features.points.iter().map(|p| (p.x, p.y)).collect(),
+            edge_count: features.edge_count,
+            stroke_width: features.stroke_width,
+            edge_mask,
+        })
+    }
+
     fn build_features(&self, patch: &MaskedPatch) -> Option<SparseChamferFeatures> {
+        self.build_features_with(patch, None)
+    }
+
+    fn build_features_with(
+        &self,
+        patch: &MaskedPatch,
+        edges_out: Option<&mut Vec<u8>>,
+    ) -> Option<SparseChamferFeatures> {
         self.with_scratch(|scratch| {
             let mut mask = mem::take(&mut scratch.mask);
             if !self.build_mask(patch, scratch, &mut mask) {
@@ -542,14 +655,23 @@ impl SparseChamferComparator {
                 scratch.edges = edges;
                 return None;
             }
+            if let Some(out) = edges_out {
+                out.clear();
+                out.extend_from_slice(&edges[..patch.len()]);
+            }
             let points = self.sample_points(&edges, patch.width, patch.height);
             if points.is_empty() {
                 scratch.mask = mask;
                 scratch.edges = edges;
                 return None;
             }
-            let distance_map: Vec<f32> =
-                dt_euclidean_clipped(&edges, patch.width, patch.height, CLIP_PX, scratch);
+            let distance_map: Vec<f32> = dt_euclidean_clipped(
+                &edges,
+                patch.width,
+                patch.height,
+                self.chamfer.clip_px,
+                scratch,
+            );
             let area = mask.iter().map(|&v| v as usize).sum::<usize>();
             let stroke_width = if edge_count > 0 {
                 (2.0 * area as f32) / (edge_count as f32)
@@ -562,6 +684,7 @@ impl SparseChamferComparator {
             Some(SparseChamferFeatures {
                 width: patch.width,
                 height: patch.height,
+                edge_count,
                 points,
                 distance_map,
                 stroke_width,
@@ -579,7 +702,8 @@ impl SparseChamferComparator {
         dx: isize,
         dy: isize,
     ) -> (f32, f32) {
-        let clip_units: usize = ((CLIP_PX * 3.0).ceil() as usize).min(12);
+        let clip_px = self.chamfer.clip_px;
+        let clip_units: usize = ((clip_px * 3.0).ceil() as usize).min(12);
         let mut bins_cnt = [0usize; 13];
         let mut bins_sum = [0f32; 13];
         let mut tight = 0usize;
@@ -594,10 +718,10 @@ impl SparseChamferComparator {
             if !dist.is_finite() {
                 continue;
             }
-            if dist > CLIP_PX {
-                dist = CLIP_PX;
+            if dist > clip_px {
+                dist = clip_px;
             }
-            if dist <= TIGHT_PX {
+            if dist <= self.chamfer.tight_px {
                 tight += 1;
             }
             let bin = ((dist * 3.0) + 0.5).floor() as usize;
@@ -609,7 +733,7 @@ impl SparseChamferComparator {
         if total == 0 {
             return (f32::INFINITY, 0.0);
         }
-        let keep = ((total as f32 * KEEP_QUANTILE).round() as usize).max(1);
+        let keep = ((total as f32 * self.chamfer.keep_quantile).round() as usize).max(1);
         let mut acc = 0usize;
         let mut sum = 0f32;
         for b in 0..=clip_units {
@@ -653,8 +777,9 @@ impl SparseChamferComparator {
             let mut best_match = 0.0;
             let mut best_dx = 0isize;
             let mut best_dy = 0isize;
-            for dy in -SHIFT_RADIUS..=SHIFT_RADIUS {
-                for dx in -SHIFT_RADIUS..=SHIFT_RADIUS {
+            let radius = self.chamfer.shift_radius as isize;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
                     let (cost_ab, match_ab) = self.one_way_partial_chamfer(
                         &a.points,
                         &b.distance_map,
@@ -698,8 +823,9 @@ impl SparseChamferComparator {
         a: &SparseChamferFeatures,
         b: &SparseChamferFeatures,
     ) -> (f32, f32, isize, isize) {
-        let shifts: Vec<(isize, isize)> = (-SHIFT_RADIUS..=SHIFT_RADIUS)
-            .flat_map(|dy| (-SHIFT_RADIUS..=SHIFT_RADIUS).map(move |dx| (dx, dy)))
+        let radius = self.chamfer.shift_radius as isize;
+        let shifts: Vec<(isize, isize)> = (-radius..=radius)
+            .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
             .collect();
 
         let (best_cost, best_match, best_dx, best_dy) = shifts
@@ -752,7 +878,7 @@ impl SubtitleComparator for SparseChamferComparator {
 
     fn extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<FeatureBlob> {
         let patch = extract_masked_patch(frame, roi, self.settings)?;
-        if patch.len() < 16 {
+        if patch.len() < self.min_edge_points {
             return None;
         }
         let features = self.build_features(&patch)?;
@@ -771,12 +897,13 @@ impl SubtitleComparator for SparseChamferComparator {
             return ComparisonReport::new(0.0, false);
         }
         let diag = 0.5 * (reference.diag + candidate.diag);
-        let sigma = (SIGMA_SCALE * diag).max(1e-3);
+        let sigma = (self.chamfer.sigma_scale * diag).max(1e-3);
         let core_similarity = (-((cost / sigma).powi(2))).exp();
         let stroke_delta = (reference.stroke_width - candidate.stroke_width).abs();
-        let stroke_penalty = (-(stroke_delta / STROKE_SIGMA).powi(2)).exp();
+        let stroke_penalty = (-(stroke_delta / self.chamfer.stroke_sigma).powi(2)).exp();
         let similarity = core_similarity * stroke_penalty;
-        let same = similarity >= SIM_THRESHOLD && match_fraction >= MATCH_THRESHOLD;
+        let same = similarity >= self.chamfer.sim_threshold
+            && match_fraction >= self.chamfer.match_threshold;
         ComparisonReport::with_details(
             similarity,
             same,
@@ -786,8 +913,8 @@ impl SubtitleComparator for SparseChamferComparator {
                 ReportMetric::new("stroke_penalty", stroke_penalty),
                 ReportMetric::new("shift_dx", dx as f32),
                 ReportMetric::new("shift_dy", dy as f32),
-                ReportMetric::new("threshold_similarity", SIM_THRESHOLD),
-                ReportMetric::new("threshold_match", MATCH_THRESHOLD),
+                ReportMetric::new("threshold_similarity", self.chamfer.sim_threshold),
+                ReportMetric::new("threshold_match", self.chamfer.match_threshold),
             ],
         )
     }
@@ -0,0 +1,189 @@
+use subtitle_fast_types::{RoiConfig, VideoFrame};
+
+use crate::comparators::SubtitleComparator;
+use crate::pipeline::preprocess::extract_masked_patch;
+use crate::pipeline::{ComparisonReport, FeatureBlob, PreprocessSettings, ReportMetric};
+
+const TAG: &str = "ssim";
+const WINDOW: usize = 8;
+const WINDOW_STEP: usize = 4;
+/// Stabilisers from the SSIM paper for a dynamic range of 1.0.
+const C1: f32 = 0.01 * 0.01;
+const C2: f32 = 0.03 * 0.03;
+/// Mean SSIM at or above which two patches count as the same subtitle.
+pub const DEFAULT_SSIM_THRESHOLD: f32 = 0.8;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SsimSettings {
+    pub threshold: f32,
+}
+
+impl Default for SsimSettings {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_SSIM_THRESHOLD,
+        }
+    }
+}
+
+/// Mean structural similarity of the masked ROI patches over 8x8 windows spaced 4 pixels
+/// apart. Gradual anti-aliasing changes keep the windows' structure term high, where the
+/// edge-based comparators can lose matches.
+pub struct SsimComparator {
+    settings: PreprocessSettings,
+    ssim: SsimSettings,
+}
+
+impl SsimComparator {
+    pub fn new(settings: PreprocessSettings) -> Self {
+        Self::with_settings(settings, SsimSettings::default())
+    }
+
+    pub fn with_settings(settings: PreprocessSettings, ssim: SsimSettings) -> Self {
+        Self { settings, ssim }
+    }
+}
+
+impl SubtitleComparator for SsimComparator {
+    fn name(&self) -> &'static str {
+        TAG
+    }
+
+    fn extract(&self, frame: &VideoFrame, roi: &RoiConfig) -> Option<FeatureBlob> {
+        let patch = extract_masked_patch(frame, roi, self.settings)?;
+        if patch.is_empty() {
+            return None;
+        }
+        Some(FeatureBlob::new(
+            TAG,
+            SsimFeatures {
+                width: patch.width,
+                height: patch.height,
+                pixels: patch.masked,
+            },
+        ))
+    }
+
+    fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+        let (Some(reference), Some(candidate)) = (
+            reference.downcast::<SsimFeatures>(TAG),
+            candidate.downcast::<SsimFeatures>(TAG),
+        ) else {
+            return ComparisonReport::new(0.0, false);
+        };
+        if reference.width != candidate.width || reference.height != candidate.height {
+            return ComparisonReport::new(0.0, false);
+        }
+        let stats = windowed_ssim(&reference, &candidate);
+        let similarity = stats.ssim.clamp(0.0, 1.0);
+        ComparisonReport::with_details(
+            similarity,
+            stats.ssim >= self.ssim.threshold,
+            vec![
+                ReportMetric::new("ssim", stats.ssim),
+                ReportMetric::new("luminance", stats.luminance),
+                ReportMetric::new("contrast_structure", stats.contrast_structure),
+                ReportMetric::new("mean_reference", stats.mean_reference),
+                ReportMetric::new("mean_candidate", stats.mean_candidate),
+                ReportMetric::new("variance_reference", stats.variance_reference),
+                ReportMetric::new("variance_candidate", stats.variance_candidate),
+                ReportMetric::new("covariance", stats.covariance),
+                ReportMetric::new("threshold", self.ssim.threshold),
+                ReportMetric::new("windows", stats.windows as f32),
+            ],
+        )
+    }
+}
+
+struct SsimFeatures {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+/// Per-window SSIM terms averaged over all windows.
+#[derive(Default)]
+struct SsimStats {
+    ssim: f32,
+    luminance: f32,
+    contrast_structure: f32,
+    mean_reference: f32,
+    mean_candidate: f32,
+    variance_reference: f32,
+    variance_candidate: f32,
+    covariance: f32,
+    windows: usize,
+}
+
+/// Window origins along one axis; the last window is pinned to the far edge so every pixel is
+/// covered, and a side shorter than the window becomes a single window.
+fn window_starts(len: usize) -> Vec<usize> {
+    if len <= WINDOW {
+        return vec![0];
+    }
+    let last = len - WINDOW;
+    let mut starts: Vec<usize> = (0..=last).step_by(WINDOW_STEP).collect();
+    if starts.last() != Some(&last) {
+        starts.push(last);
+    }
+    starts
+}
+
+fn windowed_ssim(a: &SsimFeatures, b: &SsimFeatures) -> SsimStats {
+    let window_w = a.width.min(WINDOW);
+    let window_h = a.height.min(WINDOW);
+    let count = (window_w * window_h) as f32;
+    let xs = window_starts(a.width);
+    let ys = window_starts(a.height);
+
+    let mut stats = SsimStats::default();
+    for &y0 in &ys {
+        for &x0 in &xs {
+            let (mut sum_a, mut sum_b) = (0.0f32, 0.0f32);
+            let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0f32, 0.0f32, 0.0f32);
+            for y in y0..y0 + window_h {
+                let row = y * a.width;
+                for x in x0..x0 + window_w {
+                    let (pa, pb) = (a.pixels[row + x], b.pixels[row + x]);
+                    sum_a += pa;
+                    sum_b += pb;
+                    sum_aa += pa * pa;
+                    sum_bb += pb * pb;
+                    sum_ab += pa * pb;
+                }
+            }
+            let mean_a = sum_a / count;
+            let mean_b = sum_b / count;
+            let var_a = (sum_aa / count - mean_a * mean_a).max(0.0);
+            let var_b = (sum_bb / count - mean_b * mean_b).max(0.0);
+            let cov = sum_ab / count - mean_a * mean_b;
+
+            let luminance = (2.0 * mean_a * mean_b + C1) / (mean_a * mean_a + mean_b * mean_b + C1);
+            let contrast_structure = (2.0 * cov + C2) / (var_a + var_b + C2);
+            stats.ssim += luminance * contrast_structure;
+            stats.luminance += luminance;
+            stats.contrast_structure += contrast_structure;
+            stats.mean_reference += mean_a;
+            stats.mean_candidate += mean_b;
+            stats.variance_reference += var_a;
+            stats.variance_candidate += var_b;
+            stats.covariance += cov;
+            stats.windows += 1;
+        }
+    }
+
+    let windows = stats.windows.max(1) as f32;
+    for value in [
+        &mut stats.ssim,
+        &mut stats.luminance,
+        &mut stats.contrast_structure,
+        &mut stats.mean_reference,
+        &mut stats.mean_candidate,
+        &mut stats.variance_reference,
+        &mut stats.variance_candidate,
+        &mut stats.covariance,
+    ] {
+        *value /= windows;
+    }
+    stats
+}
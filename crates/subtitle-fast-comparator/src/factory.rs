@@ -2,13 +2,25 @@ use std::fmt;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::comparators::{BitsetCoverComparator, SparseChamferComparator, SubtitleComparator};
-use crate::pipeline::PreprocessSettings;
+use crate::comparators::{
+    BitsetCoverComparator, BitsetCoverSettings, CompositeComparator, FallbackComparator,
+    HistogramComparator, PHashComparator, SparseChamferComparator, SparseChamferSettings,
+    SsimComparator, SsimSettings, SubtitleComparator,
+};
+use crate::pipeline::{PreprocessMode, PreprocessSettings};
+
+/// `histogram-chamfer` skips the chamfer search below this histogram coefficient.
+const HISTOGRAM_REJECT_BELOW: f32 = 0.5;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ComparatorKind {
     BitsetCover,
     SparseChamfer,
+    PHash,
+    Ssim,
+    Histogram,
+    /// `histogram` as a fast reject in front of `sparse-chamfer`.
+    HistogramChamfer,
 }
 
 impl ComparatorKind {
@@ -16,6 +28,10 @@ impl ComparatorKind {
         match self {
             ComparatorKind::BitsetCover => "bitset-cover",
             ComparatorKind::SparseChamfer => "sparse-chamfer",
+            ComparatorKind::PHash => "phash",
+            ComparatorKind::Ssim => "ssim",
+            ComparatorKind::Histogram => "histogram",
+            ComparatorKind::HistogramChamfer => "histogram-chamfer",
         }
     }
 }
@@ -39,6 +55,10 @@ impl FromStr for ComparatorKind {
         match lower.as_str() {
             "bitset-cover" => Ok(ComparatorKind::BitsetCover),
             "sparse-chamfer" => Ok(ComparatorKind::SparseChamfer),
+            "phash" => Ok(ComparatorKind::PHash),
+            "ssim" => Ok(ComparatorKind::Ssim),
+            "histogram" => Ok(ComparatorKind::Histogram),
+            "histogram-chamfer" => Ok(ComparatorKind::HistogramChamfer),
             _ => Err(ComparatorKindParseError(lower)),
         }
     }
@@ -49,6 +69,15 @@ pub struct ComparatorSettings {
     pub kind: ComparatorKind,
     pub target: u8,
     pub delta: u8,
+    pub bitset_cover: BitsetCoverSettings,
+    /// Smallest patch `sparse-chamfer` will extract features from.
+    pub min_edge_points: usize,
+    /// Point sampling, shift search and thresholds of `sparse-chamfer`.
+    pub sparse_chamfer: SparseChamferSettings,
+    /// Same-segment threshold for `ssim`.
+    pub ssim: SsimSettings,
+    /// Comparator tried when `kind` cannot extract features from a ROI.
+    pub fallback: Option<ComparatorKind>,
 }
 
 impl ComparatorSettings {
@@ -56,6 +85,7 @@ impl ComparatorSettings {
         PreprocessSettings {
             target: self.target,
             delta: self.delta,
+            mode: PreprocessMode::default(),
         }
     }
 }
@@ -70,10 +100,43 @@ impl ComparatorFactory {
     }
 
     pub fn build(&self) -> Arc<dyn SubtitleComparator> {
+        let primary = self.build_kind(self.settings.kind);
+        match self.settings.fallback {
+            Some(fallback) if fallback != self.settings.kind => {
+                Arc::new(FallbackComparator::new(vec![
+                    primary,
+                    self.build_kind(fallback),
+                ]))
+            }
+            _ => primary,
+        }
+    }
+
+    fn build_kind(&self, kind: ComparatorKind) -> Arc<dyn SubtitleComparator> {
         let preprocess = self.settings.preprocess();
-        match self.settings.kind {
-            ComparatorKind::BitsetCover => Arc::new(BitsetCoverComparator::new(preprocess)),
-            ComparatorKind::SparseChamfer => Arc::new(SparseChamferComparator::new(preprocess)),
+        match kind {
+            ComparatorKind::BitsetCover => Arc::new(BitsetCoverComparator::with_settings(
+                preprocess,
+                self.settings.bitset_cover,
+            )),
+            ComparatorKind::SparseChamfer => Arc::new(self.sparse_chamfer(preprocess)),
+            ComparatorKind::PHash => Arc::new(PHashComparator::new(preprocess)),
+            ComparatorKind::Ssim => Arc::new(SsimComparator::with_settings(
+                preprocess,
+                self.settings.ssim,
+            )),
+            ComparatorKind::Histogram => Arc::new(HistogramComparator::new(preprocess)),
+            ComparatorKind::HistogramChamfer => Arc::new(CompositeComparator::new(
+                Arc::new(HistogramComparator::new(preprocess)),
+                Arc::new(self.sparse_chamfer(preprocess)),
+                HISTOGRAM_REJECT_BELOW,
+                f32::INFINITY,
+            )),
         }
     }
+
+    fn sparse_chamfer(&self, preprocess: PreprocessSettings) -> SparseChamferComparator {
+        SparseChamferComparator::with_settings(preprocess, self.settings.sparse_chamfer)
+            .with_min_edge_points(self.settings.min_edge_points)
+    }
 }
@@ -4,9 +4,16 @@ pub mod comparators;
 pub mod factory;
 pub mod pipeline;
 
-pub use comparators::{BitsetCoverComparator, SparseChamferComparator, SubtitleComparator};
+pub use comparators::{
+    BitsetCoverComparator, BitsetCoverSettings, CompositeComparator, CoverMetric,
+    DEFAULT_MIN_EDGE_POINTS, DEFAULT_SSIM_THRESHOLD, DebugFeatures, FallbackComparator,
+    HISTOGRAM_BINS, HistogramComparator, PHashComparator, SparseChamferComparator,
+    SparseChamferSettings, SsimComparator, SsimSettings, SubtitleComparator,
+};
 pub use factory::{ComparatorFactory, ComparatorKind, ComparatorSettings};
-pub use pipeline::{ComparisonReport, FeatureBlob, PreprocessSettings, ReportMetric};
+pub use pipeline::{
+    ComparisonReport, FeatureBlob, PreprocessMode, PreprocessSettings, ReportMetric,
+};
 
 #[cfg(test)]
 mod tests;
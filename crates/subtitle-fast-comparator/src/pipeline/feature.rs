@@ -1,6 +1,9 @@
 use std::any::Any;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+
 /// Type-erased container for per-comparator feature data.
 #[derive(Clone)]
 pub struct FeatureBlob {
@@ -75,4 +78,56 @@ impl ComparisonReport {
             metrics: 0.0,
         }
     }
+
+    /// Value of the detail named `name`; the first one wins if a comparator reported it twice.
+    pub fn metric(&self, name: &str) -> Option<f32> {
+        self.details
+            .iter()
+            .find(|metric| metric.name == name)
+            .map(|metric| metric.value)
+    }
+
+    /// Details as `(name, value)` pairs in the order the comparator reported them.
+    pub fn metrics(&self) -> impl Iterator<Item = (&'static str, f32)> + '_ {
+        self.details
+            .iter()
+            .map(|metric| (metric.name, metric.value))
+    }
+
+    /// Details keyed by name, with the same first-wins rule as [`ComparisonReport::metric`].
+    pub fn metric_map(&self) -> HashMap<&'static str, f32> {
+        let mut map = HashMap::with_capacity(self.details.len());
+        for (name, value) in self.metrics() {
+            map.entry(name).or_insert(value);
+        }
+        map
+    }
+}
+
+/// Serializes as `{"similarity", "same_segment", "metrics": {name: value, ...}}` with the
+/// metrics in report order.
+impl Serialize for ComparisonReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("ComparisonReport", 3)?;
+        report.serialize_field("similarity", &self.similarity)?;
+        report.serialize_field("same_segment", &self.same_segment)?;
+        report.serialize_field("metrics", &MetricsObject(self))?;
+        report.end()
+    }
+}
+
+struct MetricsObject<'a>(&'a ComparisonReport);
+
+impl Serialize for MetricsObject<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seen = Vec::with_capacity(self.0.details.len());
+        let mut map = serializer.serialize_map(None)?;
+        for (name, value) in self.0.metrics() {
+            if !seen.contains(&name) {
+                seen.push(name);
+                map.serialize_entry(name, &value)?;
+            }
+        }
+        map.end()
+    }
 }
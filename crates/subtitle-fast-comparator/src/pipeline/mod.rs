@@ -3,4 +3,4 @@ pub mod ops;
 pub mod preprocess;
 
 pub use feature::{ComparisonReport, FeatureBlob, ReportMetric};
-pub use preprocess::{MaskedPatch, PreprocessSettings};
+pub use preprocess::{MaskedPatch, PreprocessMode, PreprocessSettings, region_hash};
@@ -1,9 +1,22 @@
+use subtitle_fast_types::components::{ComponentBounds, mask_components};
 use subtitle_fast_types::{RoiConfig, VideoFrame};
 
 #[derive(Copy, Clone, Debug)]
 pub struct PreprocessSettings {
     pub target: u8,
     pub delta: u8,
+    pub mode: PreprocessMode,
+}
+
+/// What [`extract_masked_patch`] derives from the luma mask besides the mask itself.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PreprocessMode {
+    /// The thresholded mask only.
+    #[default]
+    Threshold,
+    /// Also label the mask's 4-connected components, roughly one per glyph, into
+    /// [`MaskedPatch::components`].
+    Components,
 }
 
 impl PreprocessSettings {
@@ -23,6 +36,9 @@ pub struct MaskedPatch {
     pub original: Vec<f32>,
     pub masked: Vec<f32>,
     pub mask: Vec<f32>,
+    /// Patch-relative bounding boxes of the masked components; empty unless the patch was
+    /// extracted with [`PreprocessMode::Components`].
+    pub components: Vec<ComponentBounds>,
 }
 
 impl MaskedPatch {
@@ -73,15 +89,38 @@ pub fn extract_masked_patch(
         }
     }
 
+    let components = match settings.mode {
+        PreprocessMode::Threshold => Vec::new(),
+        PreprocessMode::Components => {
+            let bytes: Vec<u8> = mask.iter().map(|&value| u8::from(value > 0.0)).collect();
+            mask_components(&bytes, width, height)
+        }
+    };
+
     Some(MaskedPatch {
         width,
         height,
         original,
         masked,
         mask,
+        components,
     })
 }
 
+/// FNV-1a hash of the luma pixels `extract_masked_patch` would read for `roi`, cheap enough
+/// to tell whether a region changed since the previous sample without extracting features.
+pub fn region_hash(frame: &VideoFrame, roi: &RoiConfig) -> Option<u64> {
+    let (x0, y0, x1, y1) = roi_bounds(frame, roi)?;
+    let stride = frame.stride();
+    let data = frame.data();
+    let hash = (y0..y1)
+        .flat_map(|y| &data[y * stride + x0..y * stride + x1])
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    Some(hash)
+}
+
 fn roi_bounds(frame: &VideoFrame, roi: &RoiConfig) -> Option<(usize, usize, usize, usize)> {
     let frame_w = frame.width() as usize;
     let frame_h = frame.height() as usize;
@@ -1,7 +1,14 @@
+use std::sync::Arc;
+
 use subtitle_fast_types::{RoiConfig, VideoFrame};
 
-use crate::pipeline::PreprocessSettings;
-use crate::{BitsetCoverComparator, SparseChamferComparator, SubtitleComparator};
+use crate::pipeline::preprocess::{extract_masked_patch, region_hash};
+use crate::pipeline::{PreprocessMode, PreprocessSettings};
+use crate::{
+    BitsetCoverComparator, BitsetCoverSettings, ComparatorKind, CompositeComparator, CoverMetric,
+    FallbackComparator, HistogramComparator, PHashComparator, SparseChamferComparator,
+    SparseChamferSettings, SsimComparator, SsimSettings, SubtitleComparator,
+};
 
 fn frame_from_pixels(width: usize, height: usize, data: &[u8]) -> VideoFrame {
     let uv_rows = height.div_ceil(2);
@@ -19,6 +26,39 @@ fn frame_from_pixels(width: usize, height: usize, data: &[u8]) -> VideoFrame {
     .unwrap()
 }
 
+/// Same pixels as [`frame_from_pixels`], but every luma row is followed by `padding` bytes of
+/// `fill`, the way decoders align strides.
+fn padded_frame(width: usize, height: usize, data: &[u8], padding: usize, fill: u8) -> VideoFrame {
+    let stride = width + padding;
+    let mut y_plane = vec![fill; stride * height];
+    for (row, pixels) in data.chunks(width).enumerate() {
+        y_plane[row * stride..row * stride + width].copy_from_slice(pixels);
+    }
+    let uv_rows = height.div_ceil(2);
+    VideoFrame::from_nv12_owned(
+        width as u32,
+        height as u32,
+        stride,
+        stride,
+        None,
+        None,
+        y_plane,
+        vec![128u8; stride * uv_rows],
+    )
+    .unwrap()
+}
+
+/// 16x12 frame with a bright bar on the left half only.
+fn left_bar_pixels() -> Vec<u8> {
+    let mut pixels = vec![5u8; 16 * 12];
+    for y in 3..9 {
+        for x in 1..7 {
+            pixels[y * 16 + x] = 205;
+        }
+    }
+    pixels
+}
+
 fn full_roi() -> RoiConfig {
     RoiConfig {
         x: 0.0,
@@ -33,6 +73,7 @@ fn sparse_chamfer_identical_frames_match() {
     let comparator = SparseChamferComparator::new(PreprocessSettings {
         target: 210,
         delta: 20,
+        mode: PreprocessMode::default(),
     });
     let mut data = vec![30u8; 12 * 12];
     for y in 3..9 {
@@ -48,11 +89,60 @@ fn sparse_chamfer_identical_frames_match() {
     assert!(report.similarity >= 0.9);
 }
 
+#[test]
+fn report_metrics_are_queryable_by_name_and_serialize_as_an_object() {
+    let comparator = SparseChamferComparator::new(PreprocessSettings {
+        target: 220,
+        delta: 25,
+        mode: PreprocessMode::default(),
+    });
+    let bar = |x0: usize| {
+        let mut data = vec![10u8; 20 * 12];
+        for y in 4..8 {
+            for x in x0..x0 + 12 {
+                data[y * 20 + x] = 230;
+            }
+        }
+        frame_from_pixels(20, 12, &data)
+    };
+    let roi = full_roi();
+    let base = comparator.extract(&bar(3), &roi).unwrap();
+    let identical = comparator.compare(&base, &base);
+    assert_eq!(identical.metric("best_cost_px"), Some(0.0));
+    assert_eq!(identical.metric("no_such_metric"), None);
+
+    let shifted = comparator.extract(&bar(5), &roi).unwrap();
+    let report = comparator.compare(&base, &shifted);
+    let recorded = report
+        .details
+        .iter()
+        .find(|detail| detail.name == "best_cost_px")
+        .unwrap()
+        .value;
+    assert_eq!(report.metric("best_cost_px"), Some(recorded));
+    assert_eq!(report.metric_map()["best_cost_px"], recorded);
+    let names: Vec<_> = report.metrics().map(|(name, _)| name).collect();
+    assert_eq!(names[0], "best_cost_px");
+    assert_eq!(names.len(), report.details.len());
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["same_segment"], report.same_segment);
+    assert_eq!(
+        json["metrics"]["best_cost_px"].as_f64().unwrap() as f32,
+        recorded
+    );
+    assert_eq!(
+        json["metrics"].as_object().unwrap().len(),
+        report.metric_map().len()
+    );
+}
+
 #[test]
 fn sparse_chamfer_detects_shift_and_style() {
     let comparator = SparseChamferComparator::new(PreprocessSettings {
         target: 220,
         delta: 25,
+        mode: PreprocessMode::default(),
     });
     let mut base = vec![10u8; 20 * 12];
     for y in 4..8 {
@@ -85,11 +175,112 @@ fn sparse_chamfer_detects_shift_and_style() {
     assert!(!style.same_segment);
 }
 
+#[test]
+fn sparse_chamfer_shift_radius_reaches_further_moves() {
+    let preprocess = PreprocessSettings {
+        target: 220,
+        delta: 25,
+        mode: PreprocessMode::default(),
+    };
+    let square = |dx: usize| {
+        let mut pixels = vec![10u8; 40 * 20];
+        for y in 5..15 {
+            for x in 4..16 {
+                pixels[y * 40 + x + dx] = 230;
+            }
+        }
+        frame_from_pixels(40, 20, &pixels)
+    };
+    let roi = full_roi();
+    let (base, moved) = (square(0), square(6));
+
+    let default = SparseChamferComparator::new(preprocess);
+    let report = default.compare(
+        &default.extract(&base, &roi).unwrap(),
+        &default.extract(&moved, &roi).unwrap(),
+    );
+    assert!(!report.same_segment);
+
+    let wide = SparseChamferComparator::with_settings(
+        preprocess,
+        SparseChamferSettings {
+            shift_radius: 8,
+            ..SparseChamferSettings::default()
+        },
+    );
+    let report = wide.compare(
+        &wide.extract(&base, &roi).unwrap(),
+        &wide.extract(&moved, &roi).unwrap(),
+    );
+    assert!(report.same_segment);
+    assert_eq!(report.metric("shift_dx"), Some(6.0));
+}
+
+#[test]
+fn sparse_chamfer_min_edge_points_keeps_sparse_patch() {
+    let preprocess = PreprocessSettings {
+        target: 210,
+        delta: 20,
+        mode: PreprocessMode::default(),
+    };
+    // A 3x4 ROI covers only 12 pixels, below the default minimum.
+    let mut data = vec![30u8; 16 * 16];
+    for y in 4..8 {
+        data[y * 16 + 5] = 210;
+    }
+    let frame = frame_from_pixels(16, 16, &data);
+    let roi = RoiConfig {
+        x: 0.25,
+        y: 0.25,
+        width: 0.1875,
+        height: 0.25,
+    };
+
+    let default = SparseChamferComparator::new(preprocess);
+    assert!(default.extract(&frame, &roi).is_none());
+
+    let relaxed = SparseChamferComparator::new(preprocess).with_min_edge_points(4);
+    let features = relaxed.extract(&frame, &roi).expect("sparse patch kept");
+    assert!(relaxed.compare(&features, &features).similarity > 0.0);
+}
+
+#[test]
+fn sparse_chamfer_debug_points_follow_grid_sampling() {
+    let comparator = SparseChamferComparator::new(PreprocessSettings {
+        target: 210,
+        delta: 20,
+        mode: PreprocessMode::default(),
+    });
+    let mut data = vec![30u8; 24 * 16];
+    for y in 4..12 {
+        for x in 3..21 {
+            data[y * 24 + x] = 210;
+        }
+    }
+    let frame = frame_from_pixels(24, 16, &data);
+    let debug = comparator.debug_extract(&frame, &full_roi()).unwrap();
+
+    assert_eq!(debug.edge_mask.len(), debug.width * debug.height);
+    assert_eq!(
+        debug.edge_count,
+        debug.edge_mask.iter().filter(|&&v| v > 0).count()
+    );
+    let expected = (0..debug.height)
+        .step_by(3)
+        .flat_map(|y| (0..debug.width).step_by(3).map(move |x| (x, y)))
+        .filter(|&(x, y)| debug.edge_mask[y * debug.width + x] > 0)
+        .count();
+    assert!(expected > 0);
+    assert_eq!(debug.points.len(), expected);
+    assert!(comparator.extract(&frame, &full_roi()).is_some());
+}
+
 #[test]
 fn bitset_cover_identical_frames_match() {
     let comparator = BitsetCoverComparator::new(PreprocessSettings {
         target: 200,
         delta: 15,
+        mode: PreprocessMode::default(),
     });
     let mut pixels = vec![5u8; 16 * 12];
     for y in 4..8 {
@@ -110,6 +301,7 @@ fn bitset_cover_detects_large_offset() {
     let comparator = BitsetCoverComparator::new(PreprocessSettings {
         target: 210,
         delta: 20,
+        mode: PreprocessMode::default(),
     });
     let mut base = vec![0u8; 24 * 14];
     for y in 5..9 {
@@ -132,3 +324,533 @@ fn bitset_cover_detects_large_offset() {
     assert!(!report.same_segment);
     assert!(report.similarity < 0.9);
 }
+
+#[test]
+fn bitset_cover_metrics_follow_known_overlap() {
+    // Reference stroke is 20x4 px; candidate repeats it and adds a second, far-away stroke of the
+    // same size, so the tolerant intersection is 80 px with |A| = 80 and |B| = 160.
+    let mut reference = vec![0u8; 64 * 12];
+    let mut candidate = vec![0u8; 64 * 12];
+    for y in 4..8 {
+        for x in 2..22 {
+            reference[y * 64 + x] = 220;
+            candidate[y * 64 + x] = 220;
+        }
+        for x in 40..60 {
+            candidate[y * 64 + x] = 220;
+        }
+    }
+    let roi = full_roi();
+    let frame_a = frame_from_pixels(64, 12, &reference);
+    let frame_b = frame_from_pixels(64, 12, &candidate);
+    let preprocess = PreprocessSettings {
+        target: 220,
+        delta: 10,
+        mode: PreprocessMode::default(),
+    };
+
+    let expected = [
+        (CoverMetric::Jaccard, 0.5),
+        (CoverMetric::Dice, 2.0 / 3.0),
+        (CoverMetric::Overlap, 1.0),
+    ];
+    for (metric, value) in expected {
+        let comparator =
+            BitsetCoverComparator::with_settings(preprocess, BitsetCoverSettings { metric });
+        let feat_a = comparator.extract(&frame_a, &roi).unwrap();
+        let feat_b = comparator.extract(&frame_b, &roi).unwrap();
+        let report = comparator.compare(&feat_a, &feat_b);
+        assert!(
+            (report.similarity - value).abs() < 1e-4,
+            "{} similarity {} != {value}",
+            metric.as_str(),
+            report.similarity
+        );
+        let reported = report.metric(metric.as_str()).expect("metric reported");
+        assert!((reported - value).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn bitset_cover_default_metric_is_miss_fraction() {
+    assert_eq!(
+        BitsetCoverSettings::default().metric,
+        CoverMetric::MissFraction
+    );
+}
+
+fn chamfer_then_bitset() -> FallbackComparator {
+    let preprocess = PreprocessSettings {
+        target: 210,
+        delta: 20,
+        mode: PreprocessMode::default(),
+    };
+    FallbackComparator::new(vec![
+        Arc::new(SparseChamferComparator::new(preprocess)),
+        Arc::new(BitsetCoverComparator::new(preprocess)),
+    ])
+}
+
+/// 16x16 frame with a vertical stroke and a wide bar; the 3x4 ROI around the stroke is too
+/// small for sparse-chamfer's default minimum.
+fn fallback_fixture() -> (VideoFrame, RoiConfig) {
+    let mut data = vec![30u8; 16 * 16];
+    for y in 4..8 {
+        data[y * 16 + 5] = 210;
+    }
+    for y in 10..14 {
+        for x in 2..14 {
+            data[y * 16 + x] = 210;
+        }
+    }
+    let sparse_roi = RoiConfig {
+        x: 0.25,
+        y: 0.25,
+        width: 0.1875,
+        height: 0.25,
+    };
+    (frame_from_pixels(16, 16, &data), sparse_roi)
+}
+
+#[test]
+fn fallback_uses_next_comparator_when_primary_extract_fails() {
+    let comparator = chamfer_then_bitset();
+    let (frame, sparse_roi) = fallback_fixture();
+
+    let features = comparator
+        .extract(&frame, &sparse_roi)
+        .expect("bitset-cover picks up the region");
+    assert_eq!(comparator.producer(&features), Some("bitset-cover"));
+    assert!(comparator.compare(&features, &features).same_segment);
+}
+
+#[test]
+fn fallback_prefers_primary_comparator() {
+    let comparator = chamfer_then_bitset();
+    let (frame, _) = fallback_fixture();
+
+    let features = comparator.extract(&frame, &full_roi()).unwrap();
+    assert_eq!(comparator.producer(&features), Some("sparse-chamfer"));
+    assert!(comparator.compare(&features, &features).same_segment);
+}
+
+#[test]
+fn fallback_never_compares_blobs_from_different_producers() {
+    let comparator = chamfer_then_bitset();
+    let (frame, sparse_roi) = fallback_fixture();
+
+    let primary = comparator.extract(&frame, &full_roi()).unwrap();
+    let fallback = comparator.extract(&frame, &sparse_roi).unwrap();
+    let report = comparator.compare(&primary, &fallback);
+    assert!(!report.same_segment);
+    assert_eq!(report.similarity, 0.0);
+}
+
+#[test]
+fn components_mode_separates_two_glyphs() {
+    // Two bright 3x5 strokes with a dark gap between them.
+    let mut pixels = vec![5u8; 16 * 12];
+    for y in 3..8 {
+        for x in (2..5).chain(9..12) {
+            pixels[y * 16 + x] = 205;
+        }
+    }
+    let frame = frame_from_pixels(16, 12, &pixels);
+    let threshold = PreprocessSettings {
+        target: 200,
+        delta: 15,
+        mode: PreprocessMode::Threshold,
+    };
+    let components = PreprocessSettings {
+        mode: PreprocessMode::Components,
+        ..threshold
+    };
+
+    let plain = extract_masked_patch(&frame, &full_roi(), threshold).unwrap();
+    assert!(plain.components.is_empty());
+
+    let labeled = extract_masked_patch(&frame, &full_roi(), components).unwrap();
+    assert_eq!(labeled.mask, plain.mask);
+    let mut boxes: Vec<_> = labeled
+        .components
+        .iter()
+        .map(|c| (c.min_x, c.min_y, c.width(), c.height(), c.area))
+        .collect();
+    boxes.sort_unstable();
+    assert_eq!(boxes, vec![(2, 3, 3, 5, 15), (9, 3, 3, 5, 15)]);
+}
+
+#[test]
+fn masked_patch_skips_stride_padding() {
+    let settings = PreprocessSettings {
+        target: 200,
+        delta: 15,
+        mode: PreprocessMode::default(),
+    };
+    let pixels = left_bar_pixels();
+    let tight = extract_masked_patch(&frame_from_pixels(16, 12, &pixels), &full_roi(), settings)
+        .expect("tight patch");
+    // Padding inside the luma band would show up in the mask if rows were indexed by width.
+    let padded = extract_masked_patch(
+        &padded_frame(16, 12, &pixels, 16, 205),
+        &full_roi(),
+        settings,
+    )
+    .expect("padded patch");
+
+    assert_eq!((padded.width, padded.height), (16, 12));
+    assert_eq!(padded.original, tight.original);
+    assert_eq!(padded.mask, tight.mask);
+}
+
+#[test]
+fn bitset_cover_skips_stride_padding() {
+    let comparator = BitsetCoverComparator::new(PreprocessSettings {
+        target: 200,
+        delta: 15,
+        mode: PreprocessMode::default(),
+    });
+    let pixels = left_bar_pixels();
+    let roi = full_roi();
+    let tight = comparator
+        .extract(&frame_from_pixels(16, 12, &pixels), &roi)
+        .unwrap();
+    let padded = comparator
+        .extract(&padded_frame(16, 12, &pixels, 16, 205), &roi)
+        .unwrap();
+
+    let report = comparator.compare(&tight, &padded);
+    assert!(report.same_segment);
+    assert!(report.similarity > 0.95);
+}
+
+#[test]
+fn region_hash_tracks_only_pixels_inside_the_roi() {
+    let pixels = left_bar_pixels();
+    let left_half = RoiConfig {
+        x: 0.0,
+        y: 0.0,
+        width: 0.5,
+        height: 1.0,
+    };
+    let base = region_hash(&frame_from_pixels(16, 12, &pixels), &left_half).unwrap();
+    assert_eq!(
+        region_hash(&padded_frame(16, 12, &pixels, 16, 99), &left_half),
+        Some(base)
+    );
+
+    let mut outside = pixels.clone();
+    outside[5 * 16 + 12] = 205;
+    assert_eq!(
+        region_hash(&frame_from_pixels(16, 12, &outside), &left_half),
+        Some(base)
+    );
+
+    let mut inside = pixels;
+    inside[5 * 16 + 2] = 5;
+    assert_ne!(
+        region_hash(&frame_from_pixels(16, 12, &inside), &left_half),
+        Some(base)
+    );
+}
+
+/// 64x24 frame with "glyphs": columns from `glyph` lit between rows 8 and 16, moved by `dx`.
+fn glyph_frame(glyph: impl Fn(usize) -> bool, dx: usize) -> VideoFrame {
+    let mut pixels = vec![12u8; 64 * 24];
+    for y in 8..16 {
+        for x in 4..56 {
+            if glyph(x) {
+                pixels[y * 64 + x + dx] = 220;
+            }
+        }
+    }
+    frame_from_pixels(64, 24, &pixels)
+}
+
+fn phash() -> PHashComparator {
+    PHashComparator::new(PreprocessSettings {
+        target: 220,
+        delta: 20,
+        mode: PreprocessMode::default(),
+    })
+}
+
+fn phash_hamming(report: &crate::ComparisonReport) -> f32 {
+    report.metric("hamming").unwrap()
+}
+
+#[test]
+fn phash_matches_identical_and_shifted_patches() {
+    let comparator = phash();
+    let roi = full_roi();
+    let word = |x: usize| (x / 3) % 4 != 3 && !x.is_multiple_of(11);
+    let base = comparator.extract(&glyph_frame(word, 0), &roi).unwrap();
+    let shifted = comparator.extract(&glyph_frame(word, 1), &roi).unwrap();
+
+    let identical = comparator.compare(&base, &base);
+    assert!(identical.same_segment);
+    assert_eq!(identical.similarity, 1.0);
+    assert_eq!(phash_hamming(&identical), 0.0);
+
+    let report = comparator.compare(&base, &shifted);
+    assert!(report.same_segment, "hamming {}", phash_hamming(&report));
+}
+
+#[test]
+fn phash_separates_unrelated_patches() {
+    let comparator = phash();
+    let roi = full_roi();
+    let word = comparator
+        .extract(
+            &glyph_frame(|x| (x / 3) % 4 != 3 && !x.is_multiple_of(11), 0),
+            &roi,
+        )
+        .unwrap();
+    let other = comparator
+        .extract(
+            &glyph_frame(|x| x < 22 || (x > 40 && x.is_multiple_of(2)), 0),
+            &roi,
+        )
+        .unwrap();
+
+    let report = comparator.compare(&word, &other);
+    assert!(!report.same_segment, "hamming {}", phash_hamming(&report));
+    assert!(report.similarity < 0.85);
+}
+
+fn ssim() -> SsimComparator {
+    SsimComparator::new(PreprocessSettings {
+        target: 220,
+        delta: 20,
+        mode: PreprocessMode::default(),
+    })
+}
+
+fn metric(report: &crate::ComparisonReport, name: &str) -> f32 {
+    report.metric(name).unwrap()
+}
+
+#[test]
+fn ssim_matches_identical_and_flat_patches() {
+    let comparator = ssim();
+    let roi = full_roi();
+    let word = |x: usize| (x / 3) % 4 != 3 && !x.is_multiple_of(11);
+    let textured = comparator.extract(&glyph_frame(word, 0), &roi).unwrap();
+    let flat = comparator
+        .extract(&frame_from_pixels(64, 24, &[12u8; 64 * 24]), &roi)
+        .unwrap();
+
+    let identical = comparator.compare(&textured, &textured);
+    assert!(identical.same_segment);
+    assert!((identical.similarity - 1.0).abs() < 1e-4);
+    assert!(metric(&identical, "variance_reference") > 0.0);
+    assert_eq!(
+        metric(&identical, "variance_reference"),
+        metric(&identical, "variance_candidate")
+    );
+
+    let empty = comparator.compare(&flat, &flat);
+    assert!(empty.same_segment);
+    assert_eq!(metric(&empty, "mean_reference"), 0.0);
+    assert_eq!(metric(&empty, "variance_reference"), 0.0);
+}
+
+#[test]
+fn ssim_tolerates_anti_aliasing_but_separates_flat_from_textured() {
+    let comparator = ssim();
+    let roi = full_roi();
+    let word = |x: usize| (x / 3) % 4 != 3 && !x.is_multiple_of(11);
+    let textured = comparator.extract(&glyph_frame(word, 0), &roi).unwrap();
+
+    // Same glyphs with softer top and bottom rows, still inside the luma band.
+    let mut pixels = vec![12u8; 64 * 24];
+    for y in 8..16 {
+        for x in (4..56).filter(|&x| word(x)) {
+            pixels[y * 64 + x] = if y == 8 || y == 15 { 204 } else { 220 };
+        }
+    }
+    let softened = comparator
+        .extract(&frame_from_pixels(64, 24, &pixels), &roi)
+        .unwrap();
+    let report = comparator.compare(&textured, &softened);
+    assert!(report.same_segment, "ssim {}", metric(&report, "ssim"));
+    assert!(report.similarity < 1.0);
+
+    let flat = comparator
+        .extract(&frame_from_pixels(64, 24, &[12u8; 64 * 24]), &roi)
+        .unwrap();
+    let report = comparator.compare(&textured, &flat);
+    assert!(!report.same_segment, "ssim {}", metric(&report, "ssim"));
+    assert_eq!(metric(&report, "variance_candidate"), 0.0);
+    assert_eq!(
+        metric(&report, "threshold"),
+        SsimSettings::default().threshold
+    );
+}
+
+fn histogram() -> HistogramComparator {
+    HistogramComparator::new(PreprocessSettings {
+        target: 220,
+        delta: 20,
+        mode: PreprocessMode::default(),
+    })
+}
+
+fn uniform_frame(luma: u8) -> VideoFrame {
+    frame_from_pixels(64, 24, &[luma; 64 * 24])
+}
+
+#[test]
+fn histogram_matches_identical_and_separates_different_patches() {
+    let comparator = histogram();
+    let roi = full_roi();
+    let word = comparator
+        .extract(&glyph_frame(|x| (x / 3) % 4 != 3, 0), &roi)
+        .unwrap();
+
+    let identical = comparator.compare(&word, &word);
+    assert!(identical.same_segment);
+    assert!((identical.similarity - 1.0).abs() < 1e-5);
+    assert!(metric(&identical, "bhattacharyya_distance") < 1e-2);
+
+    // A cut to a bright background shares no luma bins with the dark one.
+    let dark = comparator.extract(&uniform_frame(12), &roi).unwrap();
+    let bright = comparator.extract(&uniform_frame(200), &roi).unwrap();
+    let report = comparator.compare(&dark, &bright);
+    assert!(!report.same_segment);
+    assert_eq!(report.similarity, 0.0);
+    assert_eq!(metric(&report, "bhattacharyya_distance"), 1.0);
+}
+
+/// Inner comparator that counts how often the composite consults it.
+#[derive(Default)]
+struct CountingComparator {
+    compares: std::sync::atomic::AtomicUsize,
+}
+
+impl SubtitleComparator for CountingComparator {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn extract(&self, _frame: &VideoFrame, _roi: &RoiConfig) -> Option<crate::FeatureBlob> {
+        Some(crate::FeatureBlob::new("counting", ()))
+    }
+
+    fn compare(
+        &self,
+        _reference: &crate::FeatureBlob,
+        _candidate: &crate::FeatureBlob,
+    ) -> crate::ComparisonReport {
+        self.compares
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        crate::ComparisonReport::new(0.75, true)
+    }
+}
+
+/// Prefilter scoring two uniform frames by how close their luma is: `1 - |a - b| / 255`.
+struct LumaDistanceComparator;
+
+impl SubtitleComparator for LumaDistanceComparator {
+    fn name(&self) -> &'static str {
+        "luma-distance"
+    }
+
+    fn extract(&self, frame: &VideoFrame, _roi: &RoiConfig) -> Option<crate::FeatureBlob> {
+        Some(crate::FeatureBlob::new("luma-distance", frame.data()[0]))
+    }
+
+    fn compare(
+        &self,
+        reference: &crate::FeatureBlob,
+        candidate: &crate::FeatureBlob,
+    ) -> crate::ComparisonReport {
+        let reference = reference.downcast::<u8>("luma-distance").unwrap();
+        let candidate = candidate.downcast::<u8>("luma-distance").unwrap();
+        let similarity = 1.0 - reference.abs_diff(*candidate) as f32 / 255.0;
+        crate::ComparisonReport::new(similarity, similarity > 0.5)
+    }
+}
+
+/// Composite of [`LumaDistanceComparator`] and a [`CountingComparator`] that escalates prefilter
+/// similarities within `[0.5, 0.9]`.
+fn banded_composite() -> (CompositeComparator, Arc<CountingComparator>) {
+    let precise = Arc::new(CountingComparator::default());
+    let comparator =
+        CompositeComparator::new(Arc::new(LumaDistanceComparator), precise.clone(), 0.5, 0.9);
+    (comparator, precise)
+}
+
+fn compare_uniform(comparator: &CompositeComparator, a: u8, b: u8) -> crate::ComparisonReport {
+    let roi = full_roi();
+    let reference = comparator.extract(&uniform_frame(a), &roi).unwrap();
+    let candidate = comparator.extract(&uniform_frame(b), &roi).unwrap();
+    comparator.compare(&reference, &candidate)
+}
+
+#[test]
+fn composite_accepts_clear_matches_in_the_prefilter() {
+    let (comparator, precise) = banded_composite();
+    let report = compare_uniform(&comparator, 12, 20);
+    assert!(report.same_segment);
+    assert!(report.similarity > 0.9);
+    assert_eq!(metric(&report, "decided_by_prefilter"), 1.0);
+    assert_eq!(
+        precise.compares.load(std::sync::atomic::Ordering::SeqCst),
+        0
+    );
+}
+
+#[test]
+fn composite_rejects_clear_mismatches_in_the_prefilter() {
+    let (comparator, precise) = banded_composite();
+    let report = compare_uniform(&comparator, 12, 200);
+    assert!(!report.same_segment);
+    assert!(report.similarity < 0.5);
+    assert_eq!(metric(&report, "decided_by_prefilter"), 1.0);
+    assert_eq!(
+        precise.compares.load(std::sync::atomic::Ordering::SeqCst),
+        0
+    );
+}
+
+#[test]
+fn composite_escalates_uncertain_pairs_to_the_precise_comparator() {
+    let (comparator, precise) = banded_composite();
+    let report = compare_uniform(&comparator, 12, 100);
+    assert_eq!(
+        precise.compares.load(std::sync::atomic::Ordering::SeqCst),
+        1
+    );
+    assert!(report.same_segment);
+    assert_eq!(report.similarity, 0.75);
+    assert_eq!(metric(&report, "decided_by_prefilter"), 0.0);
+    let prefilter = metric(&report, "prefilter_similarity");
+    assert!((0.5..=0.9).contains(&prefilter), "{prefilter}");
+}
+
+#[test]
+fn histogram_kinds_parse_and_build() {
+    use std::str::FromStr;
+
+    for name in ["histogram", "histogram-chamfer"] {
+        let kind = ComparatorKind::from_str(name).unwrap();
+        assert_eq!(kind.as_str(), name);
+        let comparator = crate::ComparatorFactory::new(crate::ComparatorSettings {
+            kind,
+            target: 220,
+            delta: 20,
+            bitset_cover: BitsetCoverSettings::default(),
+            min_edge_points: crate::DEFAULT_MIN_EDGE_POINTS,
+            sparse_chamfer: crate::SparseChamferSettings::default(),
+            ssim: SsimSettings::default(),
+            fallback: None,
+        })
+        .build();
+        let expected = match kind {
+            ComparatorKind::Histogram => "histogram",
+            _ => "composite",
+        };
+        assert_eq!(comparator.name(), expected);
+    }
+}
@@ -1,7 +1,7 @@
-#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 use std::env;
 
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 use std::sync::Once;
 
 #[cfg(target_os = "macos")]
@@ -89,9 +89,75 @@ fn build_mft_bridge() {}
 #[cfg(not(target_os = "windows"))]
 fn build_dxva_bridge() {}
 
+#[cfg(target_os = "linux")]
+fn build_vaapi_bridge() {
+    if env::var("CARGO_FEATURE_BACKEND_VAAPI").is_err() {
+        return;
+    }
+
+    println!("cargo:rerun-if-changed=src/backends/vaapi/vaapi_bridge.c");
+
+    let mut build = cc::Build::new();
+    build.file("src/backends/vaapi/vaapi_bridge.c");
+    include_ffmpeg_headers(&mut build);
+    build.compile("vaapi_bridge");
+
+    link_ffmpeg_libs();
+    for lib in ["va", "va-drm"] {
+        println!("cargo:rustc-link-lib={lib}");
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn build_vaapi_bridge() {}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn build_nvdec_bridge() {
+    if env::var("CARGO_FEATURE_BACKEND_NVDEC").is_err() {
+        return;
+    }
+
+    println!("cargo:rerun-if-changed=src/backends/nvdec/nvdec_bridge.c");
+
+    let mut build = cc::Build::new();
+    build.file("src/backends/nvdec/nvdec_bridge.c");
+    include_ffmpeg_headers(&mut build);
+    build.compile("nvdec_bridge");
+
+    // FFmpeg loads the CUDA driver API at runtime, so only its own libraries are linked.
+    link_ffmpeg_libs();
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn build_nvdec_bridge() {}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn include_ffmpeg_headers(build: &mut cc::Build) {
+    println!("cargo:rerun-if-env-changed=FFMPEG_DIR");
+    if let Ok(dir) = env::var("FFMPEG_DIR") {
+        build.include(std::path::Path::new(&dir).join("include"));
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn link_ffmpeg_libs() {
+    // With the FFmpeg backend enabled, ffmpeg-sys-next already links these libraries.
+    if env::var("CARGO_FEATURE_BACKEND_FFMPEG").is_ok() {
+        return;
+    }
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        for lib in ["avformat", "avcodec", "avutil"] {
+            println!("cargo:rustc-link-lib={lib}");
+        }
+    });
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     build_videotoolbox_bridge();
     build_mft_bridge();
     build_dxva_bridge();
+    build_vaapi_bridge();
+    build_nvdec_bridge();
 }
@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 use indicatif::{ProgressBar, ProgressStyle};
-use subtitle_fast_decoder::{Backend, Configuration, OutputFormat};
+use subtitle_fast_decoder::{Backend, Configuration, OutputFormat, RawFormat};
 use tokio_stream::StreamExt;
 
 const INPUT_VIDEO: &str = "./demo/video1_30s.mp4";
@@ -75,6 +75,16 @@ async fn run_backend_bench(
         channel_capacity: None,
         output_format: OutputFormat::Nv12,
         start_frame: None,
+        end_frame: None,
+        frame_rate: None,
+        raw_width: None,
+        raw_height: None,
+        raw_format: RawFormat::Nv12,
+        video_stream_index: None,
+        fps_override: None,
+        queue_full_warning: None,
+        sample_timestamps: None,
+        reverse: false,
     };
 
     let provider = config.create_provider()?;
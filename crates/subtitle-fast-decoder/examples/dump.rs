@@ -5,7 +5,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use indicatif::{ProgressBar, ProgressStyle};
 use png::{BitDepth, ColorType, Encoder};
-use subtitle_fast_decoder::{Backend, Configuration, OutputFormat, VideoFrame};
+use subtitle_fast_decoder::{Backend, Configuration, OutputFormat, RawFormat, VideoFrame};
 use tokio_stream::StreamExt;
 
 const SAMPLE_FREQUENCY: usize = 7; // frames per second
@@ -51,6 +51,16 @@ async fn main() -> io::Result<()> {
         channel_capacity: None,
         output_format: OutputFormat::Nv12,
         start_frame: None,
+        end_frame: None,
+        frame_rate: None,
+        raw_width: None,
+        raw_height: None,
+        raw_format: RawFormat::Nv12,
+        video_stream_index: None,
+        fps_override: None,
+        queue_full_warning: None,
+        sample_timestamps: None,
+        reverse: false,
     };
     let provider = config.create_provider().map_err(io::Error::other)?;
     let metadata = provider.metadata();
@@ -1,5 +1,5 @@
 use std::path::PathBuf;
-use subtitle_fast_decoder::{Backend, Configuration, OutputFormat};
+use subtitle_fast_decoder::{Backend, Configuration, OutputFormat, RawFormat};
 
 const VIDEO_FILE: &str = "demo/video1_30s.mp4";
 const BACKEND: Backend = Backend::FFmpeg;
@@ -14,6 +14,16 @@ async fn main() {
         channel_capacity: None,
         output_format: OutputFormat::Nv12,
         start_frame: None,
+        end_frame: None,
+        frame_rate: None,
+        raw_width: None,
+        raw_height: None,
+        raw_format: RawFormat::Nv12,
+        video_stream_index: None,
+        fps_override: None,
+        queue_full_warning: None,
+        sample_timestamps: None,
+        reverse: false,
     };
 
     match config.create_provider() {
@@ -26,6 +36,10 @@ async fn main() {
             println!("Width: {:?}", metadata.width);
             println!("Height: {:?}", metadata.height);
             println!("Total Frames: {:?}", metadata.total_frames);
+            println!("Pixel Format: {:?}", metadata.pixel_format);
+            println!("Bit Depth: {:?}", metadata.bit_depth);
+            println!("Color Range: {:?}", metadata.color_range);
+            println!("Color Primaries: {:?}", metadata.color_primaries);
         }
         Err(err) => {
             eprintln!("Failed to create provider: {}", err);
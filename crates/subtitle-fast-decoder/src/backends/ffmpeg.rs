@@ -10,9 +10,11 @@ use ffmpeg::util::error::{EAGAIN, EWOULDBLOCK};
 use ffmpeg_next as ffmpeg;
 use tokio::sync::mpsc::Sender;
 
+use crate::config::OutputFormat;
 use crate::core::{
-    DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream, SeekInfo,
-    SeekMode, SeekReceiver, VideoFrame, spawn_stream_from_channel,
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DropUntil, FrameStream,
+    SeekDropper, SeekInfo, SeekMode, SeekReceiver, StreamInfo, VideoFrame, display_rotation,
+    filter_stale_frames, past_end_frame, rotate_frames, spawn_stream_from_channel,
 };
 
 const BACKEND_NAME: &str = "ffmpeg";
@@ -21,8 +23,14 @@ const DEFAULT_CHANNEL_CAPACITY: usize = 8;
 pub struct FFmpegProvider {
     input: PathBuf,
     metadata: crate::core::VideoMetadata,
+    streams: Vec<StreamInfo>,
+    video_stream_index: Option<usize>,
+    fps_override: Option<f64>,
     channel_capacity: usize,
+    queue_full_warning: Option<Duration>,
     start_frame: Option<u64>,
+    end_frame: Option<u64>,
+    output_format: OutputFormat,
 }
 
 impl DecoderProvider for FFmpegProvider {
@@ -39,7 +47,24 @@ impl DecoderProvider for FFmpegProvider {
         }
         ffmpeg::init()
             .map_err(|err| DecoderError::backend_failure(BACKEND_NAME, err.to_string()))?;
-        let metadata = probe_metadata(path)?;
+        let streams = probe_video_streams(path)?;
+        if let Some(requested) = config.video_stream_index
+            && requested >= streams.len()
+        {
+            return Err(DecoderError::configuration(format!(
+                "video stream {requested} is out of range; {} has {} video stream(s)",
+                path.display(),
+                streams.len()
+            )));
+        }
+        let mut metadata = probe_metadata(path, config.video_stream_index)?;
+        metadata.video_track_count = Some(streams.len());
+        if let Some(fps) = config.fps_override {
+            // The probed frame count was derived from the wrong rate as well.
+            metadata.fps = Some(fps);
+            metadata.total_frames = None;
+            metadata.total_frames = metadata.calculate_total_frames();
+        }
         let capacity = config
             .channel_capacity
             .map(|n| n.get())
@@ -48,26 +73,46 @@ impl DecoderProvider for FFmpegProvider {
         Ok(Self {
             input: path.to_path_buf(),
             metadata,
+            streams,
+            video_stream_index: config.video_stream_index,
+            fps_override: config.fps_override,
             channel_capacity: capacity,
+            queue_full_warning: config.queue_full_warning,
             start_frame: config.start_frame,
+            end_frame: config.end_frame,
+            output_format: config.output_format,
         })
     }
 
     fn metadata(&self) -> crate::core::VideoMetadata {
-        self.metadata
+        self.metadata.clone()
+    }
+
+    fn list_video_streams(&self) -> Vec<StreamInfo> {
+        self.streams.clone()
     }
 
     fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
         let provider = *self;
         let capacity = provider.channel_capacity;
+        let queue_full_warning = provider.queue_full_warning;
         let start_frame = provider.start_frame;
+        let end_frame = provider.end_frame;
+        let output_format = provider.output_format;
+        let video_stream_index = provider.video_stream_index;
+        let fps_override = provider.fps_override;
+        let rotation = provider.metadata.rotation.unwrap_or(0);
         let controller = DecoderController::new();
         let seek_rx = controller.seek_receiver();
         let serial = controller.serial_handle();
-        let stream = spawn_stream_from_channel(capacity, move |tx| {
+        let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
             if let Err(err) = decode_ffmpeg(
                 provider.input.clone(),
+                video_stream_index,
+                fps_override,
                 start_frame,
+                end_frame,
+                output_format,
                 tx.clone(),
                 seek_rx,
                 serial,
@@ -75,6 +120,8 @@ impl DecoderProvider for FFmpegProvider {
                 let _ = tx.blocking_send(Err(err));
             }
         });
+        let stream = filter_stale_frames(stream, controller.serial_handle());
+        let stream = rotate_frames(stream, rotation);
         Ok((controller, stream))
     }
 }
@@ -84,18 +131,14 @@ struct DecodeState {
     time_base: ffmpeg::Rational,
     frame_rate: Option<(i32, i32)>,
     next_index: u64,
-    pending_drop: Option<DropUntil>,
+    end_frame: Option<u64>,
+    output_format: OutputFormat,
+    dropper: SeekDropper,
     scaler: Option<Scaler>,
     source_format: Option<Pixel>,
     converted: ffmpeg::util::frame::Video,
 }
 
-#[derive(Clone, Copy)]
-enum DropUntil {
-    Timestamp(i64),
-    Frame(u64),
-}
-
 #[derive(Clone, Copy)]
 enum DrainOutcome {
     Continue,
@@ -103,22 +146,29 @@ enum DrainOutcome {
     Closed,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn decode_ffmpeg(
     input: PathBuf,
+    video_stream_index: Option<usize>,
+    fps_override: Option<f64>,
     start_frame: Option<u64>,
+    end_frame: Option<u64>,
+    output_format: OutputFormat,
     tx: Sender<DecoderResult<VideoFrame>>,
     mut seek_rx: SeekReceiver,
     serial: Arc<AtomicU64>,
 ) -> DecoderResult<()> {
     let mut ictx = ffmpeg::format::input(&input)
         .map_err(|err| DecoderError::backend_failure(BACKEND_NAME, err.to_string()))?;
-    let stream = ictx
-        .streams()
-        .best(Type::Video)
-        .ok_or_else(|| DecoderError::backend_failure(BACKEND_NAME, "no video stream found"))?;
+    let stream = select_video_stream(&ictx, video_stream_index)?;
     let stream_index = stream.index();
     let time_base = stream.time_base();
-    let frame_rate = stream_frame_rate(&stream);
+    let frame_rate = fps_override
+        .map(|fps| {
+            let rate = ffmpeg::Rational::from(fps);
+            (rate.numerator(), rate.denominator())
+        })
+        .or_else(|| stream_frame_rate(&stream));
 
     let mut context = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
         .map_err(|err| DecoderError::backend_failure(BACKEND_NAME, err.to_string()))?;
@@ -135,7 +185,9 @@ fn decode_ffmpeg(
         time_base,
         frame_rate,
         next_index: 0,
-        pending_drop: None,
+        end_frame,
+        output_format,
+        dropper: SeekDropper::default(),
         scaler: None,
         source_format: None,
         converted: ffmpeg::util::frame::Video::empty(),
@@ -164,7 +216,8 @@ fn decode_ffmpeg(
             return Ok(());
         }
         if let Some(info) = take_seek(&mut seek_rx) {
-            state.pending_drop = perform_seek(info, &mut ictx, &mut decoder, &mut state)?;
+            let drop_until = perform_seek(info, &mut ictx, &mut decoder, &mut state)?;
+            state.dropper.arm(drop_until);
             current_serial = serial.load(Ordering::SeqCst);
             continue;
         }
@@ -234,7 +287,8 @@ fn drain_decoder(
 ) -> DecoderResult<DrainOutcome> {
     loop {
         if let Some(info) = take_seek(seek_rx) {
-            state.pending_drop = perform_seek(info, ictx, decoder, state)?;
+            let drop_until = perform_seek(info, ictx, decoder, state)?;
+            state.dropper.arm(drop_until);
             return Ok(DrainOutcome::Seeked);
         }
         match decoder.receive_frame(decoded) {
@@ -250,21 +304,9 @@ fn drain_decoder(
                     &mut state.next_index,
                 );
 
-                if let Some(drop) = state.pending_drop {
-                    let keep = match drop {
-                        DropUntil::Timestamp(target) => {
-                            pts_value.map(|value| value >= target).unwrap_or(true)
-                        }
-                        DropUntil::Frame(target) => {
-                            frame_index.map(|value| value >= target).unwrap_or(true)
-                        }
-                    };
-                    if keep {
-                        state.pending_drop = None;
-                    } else {
-                        unsafe { ffmpeg::ffi::av_frame_unref(decoded.as_mut_ptr()) };
-                        continue;
-                    }
+                if state.dropper.should_drop(frame_index, pts) {
+                    unsafe { ffmpeg::ffi::av_frame_unref(decoded.as_mut_ptr()) };
+                    continue;
                 }
 
                 if let (Some(start_frame), Some(index)) = (start_frame, frame_index)
@@ -273,9 +315,20 @@ fn drain_decoder(
                     unsafe { ffmpeg::ffi::av_frame_unref(decoded.as_mut_ptr()) };
                     continue;
                 }
+                if past_end_frame(state.end_frame, frame_index) {
+                    unsafe { ffmpeg::ffi::av_frame_unref(decoded.as_mut_ptr()) };
+                    return Ok(DrainOutcome::Closed);
+                }
 
                 ensure_scaler(state, decoded)?;
-                let frame = build_frame(&state.converted, pts, dts, frame_index, current_serial)?;
+                let frame = build_frame(
+                    &state.converted,
+                    state.output_format,
+                    pts,
+                    dts,
+                    frame_index,
+                    current_serial,
+                )?;
                 unsafe { ffmpeg::ffi::av_frame_unref(decoded.as_mut_ptr()) };
                 if tx.blocking_send(Ok(frame)).is_err() {
                     return Ok(DrainOutcome::Closed);
@@ -299,6 +352,10 @@ fn ensure_scaler(
     let width = decoded.width();
     let height = decoded.height();
     let format = decoded.format();
+    let target = match state.output_format {
+        OutputFormat::I420 => Pixel::YUV420P,
+        _ => Pixel::NV12,
+    };
     let needs_rebuild = state.scaler.is_none()
         || state.source_format != Some(format)
         || state.converted.width() != width
@@ -310,7 +367,7 @@ fn ensure_scaler(
                 format,
                 width,
                 height,
-                Pixel::NV12,
+                target,
                 width,
                 height,
                 ScaleFlags::BILINEAR,
@@ -319,7 +376,7 @@ fn ensure_scaler(
         );
         state.source_format = Some(format);
         unsafe {
-            state.converted.alloc(Pixel::NV12, width, height);
+            state.converted.alloc(target, width, height);
         }
     }
 
@@ -333,6 +390,7 @@ fn ensure_scaler(
 
 fn build_frame(
     converted: &ffmpeg::util::frame::Video,
+    output_format: OutputFormat,
     pts: Option<Duration>,
     dts: Option<Duration>,
     frame_index: Option<u64>,
@@ -340,15 +398,26 @@ fn build_frame(
 ) -> DecoderResult<VideoFrame> {
     let width = converted.width();
     let height = converted.height();
+    let chroma_rows = (height as usize).div_ceil(2);
     let y_stride = converted.stride(0);
-    let uv_stride = converted.stride(1);
-    let y_plane = copy_plane(converted.data(0), y_stride, height as usize, "Y")?;
-    let uv_rows = (height as usize).div_ceil(2);
-    let uv_plane = copy_plane(converted.data(1), uv_stride, uv_rows, "UV")?;
-    VideoFrame::from_nv12_owned(
-        width, height, y_stride, uv_stride, pts, dts, y_plane, uv_plane,
-    )
-    .map(|frame| frame.with_serial(serial).with_index(frame_index))
+    let frame = if output_format == OutputFormat::I420 {
+        let u_stride = converted.stride(1);
+        let v_stride = converted.stride(2);
+        let y_plane = copy_plane(converted.data(0), y_stride, height as usize, "I420 Y")?;
+        let u_plane = copy_plane(converted.data(1), u_stride, chroma_rows, "I420 U")?;
+        let v_plane = copy_plane(converted.data(2), v_stride, chroma_rows, "I420 V")?;
+        VideoFrame::from_i420_owned(
+            width, height, y_stride, u_stride, v_stride, pts, dts, y_plane, u_plane, v_plane,
+        )
+    } else {
+        let uv_stride = converted.stride(1);
+        let y_plane = copy_plane(converted.data(0), y_stride, height as usize, "NV12 Y")?;
+        let uv_plane = copy_plane(converted.data(1), uv_stride, chroma_rows, "NV12 UV")?;
+        VideoFrame::from_nv12_owned(
+            width, height, y_stride, uv_stride, pts, dts, y_plane, uv_plane,
+        )
+    };
+    frame.map(|frame| frame.with_serial(serial).with_index(frame_index))
 }
 
 fn perform_seek(
@@ -358,10 +427,6 @@ fn perform_seek(
     state: &mut DecodeState,
 ) -> DecoderResult<Option<DropUntil>> {
     let target = seek_target(info, state.time_base, state.frame_rate)?;
-    let flags = match target.mode {
-        SeekMode::Fast => ffmpeg::ffi::AVSEEK_FLAG_ANY,
-        SeekMode::Accurate => 0,
-    };
 
     unsafe {
         let fmt_ctx = ictx.as_mut_ptr();
@@ -373,12 +438,16 @@ fn perform_seek(
                 stream_index,
                 i64::MIN,
                 target.timestamp,
-                i64::MAX,
-                flags,
+                target.timestamp,
+                0,
             );
             if result < 0 {
-                let fallback =
-                    ffmpeg::ffi::av_seek_frame(fmt_ctx, stream_index, target.timestamp, flags);
+                let fallback = ffmpeg::ffi::av_seek_frame(
+                    fmt_ctx,
+                    stream_index,
+                    target.timestamp,
+                    ffmpeg::ffi::AVSEEK_FLAG_BACKWARD,
+                );
                 if fallback < 0 {
                     return Err(DecoderError::backend_failure(
                         BACKEND_NAME,
@@ -409,14 +478,14 @@ fn perform_seek(
         }
     }
 
-    let drop = match target.mode {
-        SeekMode::Fast => None,
-        SeekMode::Accurate => match target.frame {
-            Some(frame) => Some(DropUntil::Frame(frame)),
-            None => Some(DropUntil::Timestamp(target.timestamp)),
-        },
+    let drop_until = match target.frame {
+        Some(frame) => DropUntil::Frame(frame),
+        None => DropUntil::Timestamp(
+            timestamp_from_pts(target.timestamp, state.time_base)
+                .unwrap_or_else(|| Duration::from_secs_f64(target.seconds)),
+        ),
     };
-    Ok(drop)
+    Ok(target.mode.drop_until(drop_until))
 }
 
 struct SeekTarget {
@@ -543,18 +612,18 @@ fn time_base_seconds(value: ffmpeg::Rational) -> Option<f64> {
 fn copy_plane(plane: &[u8], stride: usize, rows: usize, label: &str) -> DecoderResult<Vec<u8>> {
     if stride == 0 && rows > 0 {
         return Err(DecoderError::InvalidFrame {
-            reason: format!("NV12 {label} plane stride is zero"),
+            reason: format!("{label} plane stride is zero"),
         });
     }
     let required = stride
         .checked_mul(rows)
         .ok_or_else(|| DecoderError::InvalidFrame {
-            reason: format!("calculated NV12 {label} plane length overflowed"),
+            reason: format!("calculated {label} plane length overflowed"),
         })?;
     if plane.len() < required {
         return Err(DecoderError::InvalidFrame {
             reason: format!(
-                "insufficient NV12 {label} plane bytes: got {} expected at least {}",
+                "insufficient {label} plane bytes: got {} expected at least {}",
                 plane.len(),
                 required
             ),
@@ -595,15 +664,86 @@ fn stream_frame_rate(stream: &ffmpeg::format::stream::Stream) -> Option<(i32, i3
     }
 }
 
-fn probe_metadata(path: &Path) -> DecoderResult<crate::core::VideoMetadata> {
-    use crate::core::VideoMetadata;
+/// Picks the `requested`-th video stream, or FFmpeg's best video stream when none is requested.
+fn select_video_stream(
+    ictx: &ffmpeg::format::context::Input,
+    requested: Option<usize>,
+) -> DecoderResult<ffmpeg::format::stream::Stream<'_>> {
+    let Some(requested) = requested else {
+        return ictx
+            .streams()
+            .best(Type::Video)
+            .ok_or_else(|| DecoderError::backend_failure(BACKEND_NAME, "no video stream found"));
+    };
+    let count = video_streams(ictx).count();
+    video_streams(ictx).nth(requested).ok_or_else(|| {
+        DecoderError::configuration(format!(
+            "video stream {requested} is out of range; input has {count} video stream(s)"
+        ))
+    })
+}
 
+fn video_streams(
+    ictx: &ffmpeg::format::context::Input,
+) -> impl Iterator<Item = ffmpeg::format::stream::Stream<'_>> {
+    ictx.streams()
+        .filter(|stream| stream.parameters().medium() == Type::Video)
+}
+
+fn probe_video_streams(path: &Path) -> DecoderResult<Vec<StreamInfo>> {
     let ictx = ffmpeg::format::input(path)
         .map_err(|err| DecoderError::backend_failure(BACKEND_NAME, err.to_string()))?;
-    let stream = ictx
-        .streams()
-        .best(Type::Video)
-        .ok_or_else(|| DecoderError::backend_failure(BACKEND_NAME, "no video stream found"))?;
+    let streams = video_streams(&ictx)
+        .enumerate()
+        .map(|(index, stream)| {
+            let parameters = stream.parameters();
+            let codec = parameters.id().name().to_string();
+            let dimensions = ffmpeg::codec::context::Context::from_parameters(parameters)
+                .ok()
+                .and_then(|context| context.decoder().video().ok())
+                .map(|decoder| (decoder.width(), decoder.height()));
+            StreamInfo {
+                index,
+                codec,
+                width: dimensions.map(|(width, _)| width).filter(|&w| w > 0),
+                height: dimensions.map(|(_, height)| height).filter(|&h| h > 0),
+                fps: stream_frame_rate(&stream).map(|(num, den)| num as f64 / den as f64),
+            }
+        })
+        .collect();
+    Ok(streams)
+}
+
+/// Clockwise rotation from the stream's display matrix side data, as phones write it for
+/// portrait recordings; 0 when there is none.
+fn stream_rotation(stream: &ffmpeg::format::stream::Stream) -> u32 {
+    use ffmpeg::codec::packet::side_data::Type as SideDataType;
+
+    stream
+        .side_data()
+        .find(|side_data| side_data.kind() == SideDataType::DisplayMatrix)
+        .and_then(|side_data| {
+            let matrix: Vec<f64> = side_data
+                .data()
+                .chunks_exact(4)
+                .take(9)
+                .map(|bytes| i32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64)
+                .collect();
+            (matrix.len() == 9)
+                .then(|| display_rotation(matrix[0], matrix[1], matrix[3], matrix[4]))
+        })
+        .unwrap_or(0)
+}
+
+fn probe_metadata(
+    path: &Path,
+    video_stream_index: Option<usize>,
+) -> DecoderResult<crate::core::VideoMetadata> {
+    use crate::core::{ColorPrimaries, ColorRange, VideoMetadata};
+
+    let ictx = ffmpeg::format::input(path)
+        .map_err(|err| DecoderError::backend_failure(BACKEND_NAME, err.to_string()))?;
+    let stream = select_video_stream(&ictx, video_stream_index)?;
     let time_base = stream.time_base();
     let duration = match (stream.duration(), time_base_seconds(time_base)) {
         (ticks, Some(seconds)) if ticks > 0 => {
@@ -625,10 +765,14 @@ fn probe_metadata(path: &Path) -> DecoderResult<crate::core::VideoMetadata> {
         .decoder()
         .video()
         .map_err(|err| DecoderError::backend_failure(BACKEND_NAME, err.to_string()))?;
-    let width = decoder.width() as u32;
-    let height = decoder.height() as u32;
+    let rotation = stream_rotation(&stream);
+    let (width, height) = match rotation {
+        90 | 270 => (decoder.height() as u32, decoder.width() as u32),
+        _ => (decoder.width() as u32, decoder.height() as u32),
+    };
 
     let mut metadata = VideoMetadata::new();
+    metadata.rotation = Some(rotation);
     if width > 0 {
         metadata.width = Some(width);
     }
@@ -638,6 +782,27 @@ fn probe_metadata(path: &Path) -> DecoderResult<crate::core::VideoMetadata> {
     metadata.duration = duration;
     metadata.fps = fps;
     metadata.total_frames = metadata.calculate_total_frames();
+    if let Some(descriptor) = decoder.format().descriptor() {
+        metadata.pixel_format = Some(descriptor.name().to_string());
+        // SAFETY: descriptors point into FFmpeg's static pixel format table.
+        let depth = unsafe { (*descriptor.as_ptr()).comp[0].depth };
+        metadata.bit_depth = u8::try_from(depth).ok().filter(|&depth| depth > 0);
+    }
+    metadata.color_range = match decoder.color_range() {
+        ffmpeg::color::Range::MPEG => Some(ColorRange::Limited),
+        ffmpeg::color::Range::JPEG => Some(ColorRange::Full),
+        ffmpeg::color::Range::Unspecified => None,
+    };
+    metadata.color_primaries = match decoder.color_primaries() {
+        ffmpeg::color::Primaries::BT709 => Some(ColorPrimaries::Bt709),
+        ffmpeg::color::Primaries::BT470BG => Some(ColorPrimaries::Bt470bg),
+        ffmpeg::color::Primaries::SMPTE170M => Some(ColorPrimaries::Smpte170m),
+        ffmpeg::color::Primaries::BT2020 => Some(ColorPrimaries::Bt2020),
+        ffmpeg::color::Primaries::Unspecified
+        | ffmpeg::color::Primaries::Reserved
+        | ffmpeg::color::Primaries::Reserved0 => None,
+        _ => Some(ColorPrimaries::Other),
+    };
 
     Ok(metadata)
 }
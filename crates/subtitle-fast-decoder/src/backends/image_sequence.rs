@@ -0,0 +1,337 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use png::{ColorType, Decoder, Transformations};
+use tokio::sync::mpsc::Sender;
+
+use crate::core::{
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream, SeekInfo,
+    SeekReceiver, VideoFrame, VideoMetadata, filter_stale_frames, past_end_frame,
+    spawn_stream_from_channel,
+};
+
+/// Treats a directory of PNG frames as a video: files are played back in name order at a fixed
+/// frame rate, with luma taken from the image and neutral chroma.
+pub struct ImageSequenceProvider {
+    frames: Vec<PathBuf>,
+    width: u32,
+    height: u32,
+    fps: f64,
+    channel_capacity: usize,
+    queue_full_warning: Option<Duration>,
+    start_frame: u64,
+    end_frame: Option<u64>,
+}
+
+impl ImageSequenceProvider {
+    const DEFAULT_CHANNEL_CAPACITY: usize = 8;
+    pub const DEFAULT_FPS: f64 = 25.0;
+
+    /// Whether `path` is a directory that contains at least one PNG frame.
+    pub fn accepts(path: &Path) -> bool {
+        path.is_dir() && list_frames(path).is_ok_and(|frames| !frames.is_empty())
+    }
+
+    fn frame_pts(&self, index: u64) -> Duration {
+        Duration::from_secs_f64(index as f64 / self.fps)
+    }
+
+    fn emit_frames(
+        &self,
+        tx: Sender<DecoderResult<VideoFrame>>,
+        mut seek_rx: SeekReceiver,
+        serial: Arc<AtomicU64>,
+    ) {
+        let total = self.frames.len() as u64;
+        let mut index = self.start_frame.min(total);
+        let mut current_serial = serial.load(Ordering::SeqCst);
+        while index < total {
+            if seek_rx.has_changed().unwrap_or(false)
+                && let Some(info) = *seek_rx.borrow_and_update()
+            {
+                current_serial = serial.load(Ordering::SeqCst);
+                index = self.seek_target(info).min(total);
+                continue;
+            }
+            if tx.is_closed() || past_end_frame(self.end_frame, Some(index)) {
+                break;
+            }
+            let frame = self
+                .load_frame(index)
+                .map(|frame| frame.with_index(Some(index)).with_serial(current_serial));
+            if tx.blocking_send(frame).is_err() {
+                break;
+            }
+            index += 1;
+        }
+    }
+
+    fn seek_target(&self, info: SeekInfo) -> u64 {
+        match info {
+            SeekInfo::Frame { frame, .. } => frame,
+            SeekInfo::Time { position, .. } => (position.as_secs_f64() * self.fps).floor() as u64,
+        }
+    }
+
+    fn load_frame(&self, index: u64) -> DecoderResult<VideoFrame> {
+        let path = &self.frames[index as usize];
+        let (width, height, y_plane) = read_luma(path)?;
+        if width != self.width || height != self.height {
+            return Err(DecoderError::InvalidFrame {
+                reason: format!(
+                    "{} is {width}x{height}, expected {}x{} like the first frame",
+                    path.display(),
+                    self.width,
+                    self.height
+                ),
+            });
+        }
+        let stride = width as usize;
+        let uv_plane = vec![128u8; stride * (height as usize).div_ceil(2)];
+        VideoFrame::from_nv12_owned(
+            width,
+            height,
+            stride,
+            stride,
+            Some(self.frame_pts(index)),
+            None,
+            y_plane,
+            uv_plane,
+        )
+    }
+}
+
+impl DecoderProvider for ImageSequenceProvider {
+    fn new(config: &crate::config::Configuration) -> DecoderResult<Self> {
+        let dir = config.input.as_ref().ok_or_else(|| {
+            DecoderError::configuration("image-sequence backend requires an input directory")
+        })?;
+        if !dir.is_dir() {
+            return Err(DecoderError::configuration(format!(
+                "image-sequence input '{}' is not a directory",
+                dir.display()
+            )));
+        }
+        let fps = config
+            .fps_override
+            .or(config.frame_rate)
+            .unwrap_or(Self::DEFAULT_FPS);
+        if !fps.is_finite() || fps <= 0.0 {
+            return Err(DecoderError::configuration(format!(
+                "image-sequence frame rate must be positive (got {fps})"
+            )));
+        }
+        let frames = list_frames(dir)?;
+        let Some(first) = frames.first() else {
+            return Err(DecoderError::configuration(format!(
+                "no PNG frames found in '{}'",
+                dir.display()
+            )));
+        };
+        let (width, height) = read_dimensions(first)?;
+        let capacity = config
+            .channel_capacity
+            .map(|n| n.get())
+            .unwrap_or(Self::DEFAULT_CHANNEL_CAPACITY);
+        Ok(Self {
+            frames,
+            width,
+            height,
+            fps,
+            channel_capacity: capacity.max(1),
+            queue_full_warning: config.queue_full_warning,
+            start_frame: config.start_frame.unwrap_or(0),
+            end_frame: config.end_frame,
+        })
+    }
+
+    fn metadata(&self) -> VideoMetadata {
+        let total_frames = self.frames.len() as u64;
+        VideoMetadata {
+            duration: Some(self.frame_pts(total_frames)),
+            fps: Some(self.fps),
+            width: Some(self.width),
+            height: Some(self.height),
+            total_frames: Some(total_frames),
+            ..VideoMetadata::default()
+        }
+    }
+
+    fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+        let provider = *self;
+        let capacity = provider.channel_capacity;
+        let queue_full_warning = provider.queue_full_warning;
+        let controller = DecoderController::new();
+        let seek_rx = controller.seek_receiver();
+        let serial = controller.serial_handle();
+        let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
+            provider.emit_frames(tx, seek_rx, serial);
+        });
+        let stream = filter_stale_frames(stream, controller.serial_handle());
+        Ok((controller, stream))
+    }
+}
+
+fn list_frames(dir: &Path) -> DecoderResult<Vec<PathBuf>> {
+    let mut frames = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_png = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        if is_png && path.is_file() {
+            frames.push(path);
+        }
+    }
+    frames.sort();
+    Ok(frames)
+}
+
+fn open_png(path: &Path) -> DecoderResult<png::Reader<BufReader<File>>> {
+    let mut decoder = Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    decoder
+        .read_info()
+        .map_err(|err| png_error(path, err.to_string()))
+}
+
+fn read_dimensions(path: &Path) -> DecoderResult<(u32, u32)> {
+    let reader = open_png(path)?;
+    let info = reader.info();
+    Ok((info.width, info.height))
+}
+
+/// Decodes one PNG into a tightly packed luma plane (BT.601 weights for color images).
+fn read_luma(path: &Path) -> DecoderResult<(u32, u32, Vec<u8>)> {
+    let mut reader = open_png(path)?;
+    let size = reader
+        .output_buffer_size()
+        .ok_or_else(|| png_error(path, "image too large"))?;
+    let mut buf = vec![0u8; size];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|err| png_error(path, err.to_string()))?;
+    let channels = match info.color_type {
+        ColorType::Grayscale => 1,
+        ColorType::GrayscaleAlpha => 2,
+        ColorType::Rgb => 3,
+        ColorType::Rgba => 4,
+        ColorType::Indexed => return Err(png_error(path, "unexpanded palette image")),
+    };
+    let width = info.width as usize;
+    let mut luma = Vec::with_capacity(width * info.height as usize);
+    for row in buf.chunks(info.line_size).take(info.height as usize) {
+        for pixel in row[..width * channels].chunks_exact(channels) {
+            let value = if channels >= 3 {
+                let weighted =
+                    299 * pixel[0] as u32 + 587 * pixel[1] as u32 + 114 * pixel[2] as u32;
+                (weighted / 1000) as u8
+            } else {
+                pixel[0]
+            };
+            luma.push(value);
+        }
+    }
+    Ok((info.width, info.height, luma))
+}
+
+fn png_error(path: &Path, message: impl std::fmt::Display) -> DecoderError {
+    DecoderError::InvalidFrame {
+        reason: format!("failed to decode {}: {message}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynDecoderProvider;
+    use tokio_stream::StreamExt;
+
+    fn write_png(path: &Path, width: u32, height: u32, color: ColorType, data: &[u8]) {
+        let file = File::create(path).unwrap();
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+    }
+
+    fn config(dir: &Path) -> crate::config::Configuration {
+        crate::config::Configuration {
+            backend: crate::config::Backend::ImageSequence,
+            input: Some(dir.to_path_buf()),
+            channel_capacity: None,
+            output_format: crate::config::OutputFormat::Nv12,
+            start_frame: None,
+            end_frame: None,
+            frame_rate: Some(10.0),
+            raw_width: None,
+            raw_height: None,
+            raw_format: crate::config::RawFormat::Nv12,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn image_sequence_emits_sorted_frames_with_synthesized_timestamps() {
+        let dir = tempfile::tempdir().unwrap();
+        // Written out of order; playback must follow file names.
+        write_png(
+            &dir.path().join("frame_002.png"),
+            4,
+            2,
+            ColorType::Grayscale,
+            &[200; 8],
+        );
+        write_png(
+            &dir.path().join("frame_000.png"),
+            4,
+            2,
+            ColorType::Grayscale,
+            &[10; 8],
+        );
+        let rgb: Vec<u8> = [255u8, 0, 0].repeat(8);
+        write_png(
+            &dir.path().join("frame_001.png"),
+            4,
+            2,
+            ColorType::Rgb,
+            &rgb,
+        );
+        fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+
+        let provider = Box::new(ImageSequenceProvider::new(&config(dir.path())).unwrap())
+            as DynDecoderProvider;
+        let metadata = provider.metadata();
+        assert_eq!(metadata.total_frames, Some(3));
+        assert_eq!(metadata.width, Some(4));
+        assert_eq!(metadata.height, Some(2));
+
+        let (_controller, stream) = provider.open().unwrap();
+        let frames: Vec<VideoFrame> = stream.map(|frame| frame.unwrap()).collect().await;
+        assert_eq!(frames.len(), 3);
+        let lumas: Vec<u8> = frames.iter().map(|frame| frame.y_plane()[0]).collect();
+        assert_eq!(lumas, vec![10, 76, 200]);
+        for (index, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.index(), Some(index as u64));
+            assert_eq!(frame.pts(), Some(Duration::from_millis(index as u64 * 100)));
+            assert_eq!(frame.uv_plane().len(), 4);
+        }
+    }
+
+    #[test]
+    fn image_sequence_rejects_directory_without_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!ImageSequenceProvider::accepts(dir.path()));
+        assert!(ImageSequenceProvider::new(&config(dir.path())).is_err());
+    }
+}
@@ -1,11 +1,11 @@
 #[cfg(all(target_os = "windows", feature = "backend-mft"))]
 use crate::core::{
-    DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream, SeekInfo,
-    SeekMode, SeekReceiver,
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DropUntil, FrameStream,
+    SeekDropper, SeekInfo, SeekMode, SeekReceiver, past_end_frame,
 };
 
 #[cfg(all(target_os = "windows", feature = "backend-mft"))]
-use crate::core::{VideoFrame, spawn_stream_from_channel};
+use crate::core::{FramePool, VideoFrame, filter_stale_frames, spawn_stream_from_channel};
 
 #[cfg(all(target_os = "windows", feature = "backend-mft"))]
 #[allow(unexpected_cfgs)]
@@ -82,7 +82,9 @@ mod platform {
         input: PathBuf,
         metadata: crate::core::VideoMetadata,
         channel_capacity: usize,
+        queue_full_warning: Option<Duration>,
         start_frame: Option<u64>,
+        end_frame: Option<u64>,
     }
 
     impl MftProvider {}
@@ -98,7 +100,10 @@ mod platform {
                     format!("input file {} does not exist", path.display()),
                 )));
             }
-            let metadata = probe_video_metadata(path)?;
+            let mut metadata = probe_video_metadata(path)?;
+            if let Some(fps) = config.fps_override {
+                metadata.fps = Some(fps);
+            }
             let capacity = config
                 .channel_capacity
                 .map(|n| n.get())
@@ -108,27 +113,32 @@ mod platform {
                 input: path.to_path_buf(),
                 metadata,
                 channel_capacity: capacity,
+                queue_full_warning: config.queue_full_warning,
                 start_frame: config.start_frame,
+                end_frame: config.end_frame,
             })
         }
 
         fn metadata(&self) -> crate::core::VideoMetadata {
-            self.metadata
+            self.metadata.clone()
         }
 
         fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
             let provider = *self;
             let capacity = provider.channel_capacity;
+            let queue_full_warning = provider.queue_full_warning;
             let start_frame = provider.start_frame;
+            let end_frame = provider.end_frame;
             let fps = provider.metadata.fps;
             let controller = DecoderController::new();
             let seek_rx = controller.seek_receiver();
             let serial = controller.serial_handle();
-            let stream = spawn_stream_from_channel(capacity, move |tx| {
+            let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
                 if let Err(err) = decode_mft(
                     provider.input.clone(),
                     tx.clone(),
                     start_frame,
+                    end_frame,
                     seek_rx,
                     serial,
                     fps,
@@ -136,6 +146,7 @@ mod platform {
                     let _ = tx.blocking_send(Err(err));
                 }
             });
+            let stream = filter_stale_frames(stream, controller.serial_handle());
             Ok((controller, stream))
         }
     }
@@ -144,12 +155,13 @@ mod platform {
         path: PathBuf,
         tx: Sender<DecoderResult<VideoFrame>>,
         start_frame: Option<u64>,
+        end_frame: Option<u64>,
         seek_rx: SeekReceiver,
         serial: Arc<AtomicU64>,
         fps: Option<f64>,
     ) -> DecoderResult<()> {
         let c_path = cstring_from_path(&path)?;
-        let mut context = DecodeContext::new(tx, seek_rx, serial, fps);
+        let mut context = DecodeContext::new(tx, seek_rx, serial, fps, end_frame);
         let mut error_ptr: *mut c_char = ptr::null_mut();
         let (has_start_frame, start_frame) = match start_frame {
             Some(value) => (true, value),
@@ -250,10 +262,12 @@ mod platform {
         seek_rx: SeekReceiver,
         serial: Arc<AtomicU64>,
         current_serial: u64,
-        pending_drop: Option<DropUntil>,
+        dropper: SeekDropper,
         seek_error: Option<DecoderError>,
         closed: bool,
         fps: Option<f64>,
+        end_frame: Option<u64>,
+        pool: FramePool,
     }
 
     impl DecodeContext {
@@ -262,6 +276,7 @@ mod platform {
             seek_rx: SeekReceiver,
             serial: Arc<AtomicU64>,
             fps: Option<f64>,
+            end_frame: Option<u64>,
         ) -> Self {
             let current_serial = serial.load(Ordering::SeqCst);
             Self {
@@ -269,10 +284,12 @@ mod platform {
                 seek_rx,
                 serial,
                 current_serial,
-                pending_drop: None,
+                dropper: SeekDropper::default(),
                 seek_error: None,
                 closed: false,
                 fps,
+                end_frame,
+                pool: FramePool::new(),
             }
         }
 
@@ -281,7 +298,7 @@ mod platform {
         }
 
         fn apply_drop(&mut self, drop_until: Option<DropUntil>) {
-            self.pending_drop = drop_until;
+            self.dropper.arm(drop_until);
         }
 
         fn take_seek_error(&mut self) -> Option<DecoderError> {
@@ -303,19 +320,15 @@ mod platform {
         }
 
         fn should_skip_frame(&mut self, index: u64, pts: Option<Duration>) -> bool {
-            let Some(drop_until) = self.pending_drop else {
-                return false;
-            };
-            let keep = match drop_until {
-                DropUntil::Frame(target) => index >= target,
-                DropUntil::Timestamp(target) => pts.map(|value| value >= target).unwrap_or(true),
-            };
-            if keep {
-                self.pending_drop = None;
-                false
-            } else {
-                true
+            self.dropper.should_drop(Some(index), pts)
+        }
+
+        /// Closes the decode once a frame lies past the configured end frame.
+        fn reached_end(&mut self, index: Option<u64>) -> bool {
+            if past_end_frame(self.end_frame, index) {
+                self.closed = true;
             }
+            self.closed
         }
     }
 
@@ -353,15 +366,18 @@ mod platform {
         if context.should_skip_frame(index.unwrap_or(frame.index), pts) {
             return true;
         }
-        match VideoFrame::from_nv12_owned(
+        if context.reached_end(index) {
+            return false;
+        }
+        match context.pool.nv12_frame(
             frame.width,
             frame.height,
             frame.y_stride,
             frame.uv_stride,
             pts,
             dts,
-            y_data.to_vec(),
-            uv_data.to_vec(),
+            context.pool.copy_of(y_data),
+            context.pool.copy_of(uv_data),
         ) {
             Ok(frame_value) => {
                 let frame_value = frame_value
@@ -409,12 +425,6 @@ mod platform {
         }
     }
 
-    #[derive(Clone, Copy)]
-    enum DropUntil {
-        Frame(u64),
-        Timestamp(Duration),
-    }
-
     #[derive(Clone, Copy)]
     struct SeekPlan {
         request: CMftSeekRequest,
@@ -443,10 +453,7 @@ mod platform {
                         position_seconds: seconds,
                         start_frame: frame,
                     },
-                    drop_until: match mode {
-                        SeekMode::Fast => None,
-                        SeekMode::Accurate => Some(DropUntil::Frame(frame)),
-                    },
+                    drop_until: mode.drop_until(DropUntil::Frame(frame)),
                 })
             }
             SeekInfo::Time { position, mode } => {
@@ -480,10 +487,7 @@ mod platform {
                         position_seconds: seconds,
                         start_frame: frame as u64,
                     },
-                    drop_until: match mode {
-                        SeekMode::Fast => None,
-                        SeekMode::Accurate => Some(DropUntil::Timestamp(position)),
-                    },
+                    drop_until: mode.drop_until(DropUntil::Timestamp(position)),
                 })
             }
         }
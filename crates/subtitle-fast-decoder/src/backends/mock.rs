@@ -7,8 +7,9 @@ use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 
 use crate::core::{
-    DecoderController, DecoderProvider, DecoderResult, FrameStream, SeekInfo, SeekMode,
-    SeekReceiver, VideoFrame, spawn_stream_from_channel,
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DropUntil, FramePool,
+    FrameStream, SeekDropper, SeekInfo, SeekMode, SeekReceiver, StreamInfo, VideoFrame,
+    filter_stale_frames, past_end_frame, spawn_stream_from_channel,
 };
 
 pub struct MockProvider {
@@ -18,13 +19,19 @@ pub struct MockProvider {
     stride: usize,
     frame_count: usize,
     frame_interval: Duration,
+    fps: f64,
     channel_capacity: usize,
+    queue_full_warning: Option<Duration>,
     start_frame: u64,
+    end_frame: Option<u64>,
+    pool: FramePool,
 }
 
 impl MockProvider {
     const DEFAULT_CHANNEL_CAPACITY: usize = 8;
     const FPS: f64 = 60.0;
+    /// Seeks land on multiples of this, like a real stream's group of pictures.
+    const KEYFRAME_INTERVAL: u64 = 12;
 
     fn emit_frames(
         &self,
@@ -34,47 +41,55 @@ impl MockProvider {
     ) {
         let mut index = self.start_frame.min(self.frame_count as u64) as usize;
         let mut current_serial = serial.load(Ordering::SeqCst);
-        let mut pending_drop: Option<DropUntil> = None;
+        let mut dropper = SeekDropper::default();
         while index < self.frame_count {
             if let Some(plan) = drain_seek_requests(&mut seek_rx, &serial, &mut current_serial) {
-                index = plan
-                    .start_frame
+                let keyframe = plan.start_frame - plan.start_frame % Self::KEYFRAME_INTERVAL;
+                index = keyframe
                     .min(self.frame_count as u64)
                     .try_into()
                     .unwrap_or(self.frame_count);
-                pending_drop = plan.drop_until;
+                dropper.arm(plan.drop_until);
             }
             if tx.is_closed() {
                 break;
             }
-            let mut buffer = vec![0u8; self.stride * self.height as usize];
+            let y_len = self.stride * self.height as usize;
+            let mut buffer = self.pool.take(y_len);
+            buffer.resize(y_len, 0);
             for (row, chunk) in buffer.chunks_mut(self.stride).enumerate() {
                 let value = ((row + index) % 256) as u8;
                 chunk.fill(value);
             }
             let uv_rows = (self.height as usize).div_ceil(2);
             let uv_stride = self.stride;
-            let uv_plane = vec![128u8; uv_stride * uv_rows];
+            let mut uv_plane = self.pool.take(uv_stride * uv_rows);
+            uv_plane.resize(uv_stride * uv_rows, 128);
             let pts = Some(Duration::from_millis((index * 16) as u64));
-            if should_skip_frame(&mut pending_drop, index as u64, pts) {
+            if dropper.should_drop(Some(index as u64), pts) {
                 index += 1;
                 continue;
             }
-            let frame = VideoFrame::from_nv12_owned(
-                self.width,
-                self.height,
-                self.stride,
-                uv_stride,
-                pts,
-                None,
-                buffer,
-                uv_plane,
-            )
-            .map(|frame| {
-                frame
-                    .with_index(Some(index as u64))
-                    .with_serial(current_serial)
-            });
+            if past_end_frame(self.end_frame, Some(index as u64)) {
+                break;
+            }
+            let frame = self
+                .pool
+                .nv12_frame(
+                    self.width,
+                    self.height,
+                    self.stride,
+                    uv_stride,
+                    pts,
+                    None,
+                    buffer,
+                    uv_plane,
+                )
+                .map(|frame| {
+                    frame
+                        .with_index(Some(index as u64))
+                        .with_serial(current_serial)
+                });
             if tx.blocking_send(frame).is_err() {
                 break;
             }
@@ -92,6 +107,13 @@ impl DecoderProvider for MockProvider {
             .channel_capacity
             .map(|n| n.get())
             .unwrap_or(Self::DEFAULT_CHANNEL_CAPACITY);
+        if let Some(requested) = config.video_stream_index
+            && requested > 0
+        {
+            return Err(DecoderError::configuration(format!(
+                "video stream {requested} is out of range; mock input has 1 video stream(s)"
+            )));
+        }
         Ok(Self {
             _input: config.input.clone(),
             width: 640,
@@ -99,32 +121,54 @@ impl DecoderProvider for MockProvider {
             stride: 640,
             frame_count: 120,
             frame_interval: Duration::from_millis(4),
+            fps: config.fps_override.unwrap_or(Self::FPS),
             channel_capacity: capacity.max(1),
+            queue_full_warning: config.queue_full_warning,
             start_frame: config.start_frame.unwrap_or(0),
+            end_frame: config.end_frame,
+            pool: FramePool::new(),
         })
     }
 
     fn metadata(&self) -> crate::core::VideoMetadata {
-        use crate::core::VideoMetadata;
+        use crate::core::{ColorPrimaries, ColorRange, VideoMetadata};
 
         VideoMetadata {
             duration: Some(Duration::from_secs_f64((self.frame_count as f64) * 0.016)),
-            fps: Some(60.0),
+            fps: Some(self.fps),
             width: Some(self.width),
             height: Some(self.height),
             total_frames: Some(self.frame_count as u64),
+            pixel_format: Some("nv12".to_string()),
+            bit_depth: Some(8),
+            color_range: Some(ColorRange::Limited),
+            color_primaries: Some(ColorPrimaries::Bt709),
+            video_track_count: Some(1),
+            rotation: Some(0),
         }
     }
 
+    fn list_video_streams(&self) -> Vec<StreamInfo> {
+        vec![StreamInfo {
+            index: 0,
+            codec: "rawvideo".to_string(),
+            width: Some(self.width),
+            height: Some(self.height),
+            fps: Some(Self::FPS),
+        }]
+    }
+
     fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
         let provider = *self;
         let capacity = provider.channel_capacity;
+        let queue_full_warning = provider.queue_full_warning;
         let controller = DecoderController::new();
         let seek_rx = controller.seek_receiver();
         let serial = controller.serial_handle();
-        let stream = spawn_stream_from_channel(capacity, move |tx| {
+        let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
             provider.emit_frames(tx, seek_rx, serial);
         });
+        let stream = filter_stale_frames(stream, controller.serial_handle());
         Ok((controller, stream))
     }
 }
@@ -144,32 +188,6 @@ fn drain_seek_requests(
     None
 }
 
-fn should_skip_frame(
-    pending_drop: &mut Option<DropUntil>,
-    frame_index: u64,
-    pts: Option<Duration>,
-) -> bool {
-    let Some(drop_until) = *pending_drop else {
-        return false;
-    };
-    let keep = match drop_until {
-        DropUntil::Frame(target) => frame_index >= target,
-        DropUntil::Timestamp(target) => pts.map(|value| value >= target).unwrap_or(true),
-    };
-    if keep {
-        *pending_drop = None;
-        false
-    } else {
-        true
-    }
-}
-
-#[derive(Clone, Copy)]
-enum DropUntil {
-    Frame(u64),
-    Timestamp(Duration),
-}
-
 #[derive(Clone, Copy)]
 struct SeekPlan {
     start_frame: u64,
@@ -180,10 +198,7 @@ fn compute_seek_plan(info: SeekInfo) -> Option<SeekPlan> {
     match info {
         SeekInfo::Frame { frame, mode } => Some(SeekPlan {
             start_frame: frame,
-            drop_until: match mode {
-                SeekMode::Fast => None,
-                SeekMode::Accurate => Some(DropUntil::Frame(frame)),
-            },
+            drop_until: mode.drop_until(DropUntil::Frame(frame)),
         }),
         SeekInfo::Time { position, mode } => {
             let seconds = position.as_secs_f64();
@@ -203,10 +218,7 @@ fn compute_seek_plan(info: SeekInfo) -> Option<SeekPlan> {
             }
             Some(SeekPlan {
                 start_frame: frame as u64,
-                drop_until: match mode {
-                    SeekMode::Fast => None,
-                    SeekMode::Accurate => Some(DropUntil::Timestamp(position)),
-                },
+                drop_until: mode.drop_until(DropUntil::Timestamp(position)),
             })
         }
     }
@@ -226,6 +238,16 @@ mod tests {
             channel_capacity: None,
             output_format: crate::config::OutputFormat::Nv12,
             start_frame: None,
+            end_frame: None,
+            frame_rate: None,
+            raw_width: None,
+            raw_height: None,
+            raw_format: crate::config::RawFormat::Nv12,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
         };
         let decoder = Box::new(MockProvider::new(&config).unwrap()) as DynDecoderProvider;
         let metadata = decoder.metadata();
@@ -238,6 +260,96 @@ mod tests {
         assert_eq!(frame.uv_plane().len(), 640 * 180);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mock_backend_recycles_planes_of_consumed_frames() {
+        let config = crate::config::Configuration {
+            backend: crate::config::Backend::Mock,
+            ..Default::default()
+        };
+        let provider = MockProvider::new(&config).unwrap();
+        let pool = provider.pool.clone();
+        let (_controller, stream) = (Box::new(provider) as DynDecoderProvider).open().unwrap();
+        let decoded = stream
+            .fold(0, |count, frame| count + frame.map(|_| 1).unwrap())
+            .await;
+
+        assert_eq!(decoded, 120);
+        let stats = pool.stats();
+        assert_eq!(stats.allocated + stats.reused, 240);
+        // Only the frames queued in the channel at once need their own planes.
+        assert!(stats.allocated <= 40, "{stats:?}");
+    }
+
+    #[test]
+    fn mock_backend_reports_pixel_format_and_color_metadata() {
+        use crate::core::{ColorPrimaries, ColorRange};
+
+        let config = crate::config::Configuration {
+            backend: crate::config::Backend::Mock,
+            ..Default::default()
+        };
+        let metadata = MockProvider::new(&config).unwrap().metadata();
+        assert_eq!(metadata.pixel_format.as_deref(), Some("nv12"));
+        assert_eq!(metadata.bit_depth, Some(8));
+        assert_eq!(metadata.color_range, Some(ColorRange::Limited));
+        assert_eq!(metadata.color_primaries, Some(ColorPrimaries::Bt709));
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["pixel_format"], "nv12");
+        assert_eq!(json["bit_depth"], 8);
+        assert_eq!(json["color_range"], "limited");
+        assert_eq!(json["color_primaries"], "bt709");
+        assert_eq!(json["total_frames"], 120);
+    }
+
+    #[test]
+    fn mock_backend_reports_fps_override() {
+        let config = crate::config::Configuration {
+            backend: crate::config::Backend::Mock,
+            fps_override: Some(23.976),
+            ..Default::default()
+        };
+        let provider = MockProvider::new(&config).unwrap();
+        assert_eq!(provider.metadata().fps, Some(23.976));
+        assert_eq!(provider.metadata().total_frames, Some(120));
+    }
+
+    #[test]
+    fn mock_backend_lists_its_stream_and_rejects_others() {
+        let mut config = crate::config::Configuration {
+            backend: crate::config::Backend::Mock,
+            input: None,
+            channel_capacity: None,
+            output_format: crate::config::OutputFormat::Nv12,
+            start_frame: None,
+            end_frame: None,
+            frame_rate: None,
+            raw_width: None,
+            raw_height: None,
+            raw_format: crate::config::RawFormat::Nv12,
+            video_stream_index: Some(0),
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
+        };
+        let decoder = MockProvider::new(&config).unwrap();
+        assert_eq!(decoder.metadata().video_track_count, Some(1));
+        let streams = decoder.list_video_streams();
+        assert_eq!(streams.len(), 1);
+        assert_eq!(streams[0].index, 0);
+        assert_eq!(
+            (streams[0].width, streams[0].height),
+            (Some(640), Some(360))
+        );
+
+        config.video_stream_index = Some(1);
+        let err = MockProvider::new(&config)
+            .err()
+            .expect("index out of range");
+        assert!(err.to_string().contains("out of range"));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn mock_backend_honors_start_frame() {
         let config = crate::config::Configuration {
@@ -246,6 +358,16 @@ mod tests {
             channel_capacity: None,
             output_format: crate::config::OutputFormat::Nv12,
             start_frame: Some(10),
+            end_frame: None,
+            frame_rate: None,
+            raw_width: None,
+            raw_height: None,
+            raw_format: crate::config::RawFormat::Nv12,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
         };
         let decoder = Box::new(MockProvider::new(&config).unwrap()) as DynDecoderProvider;
         let (_controller, mut stream) = decoder.open().unwrap();
@@ -253,6 +375,31 @@ mod tests {
         assert_eq!(frame.index(), Some(10));
     }
 
+    async fn indices_in_range(start_frame: Option<u64>, end_frame: Option<u64>) -> Vec<u64> {
+        let config = crate::config::Configuration {
+            backend: crate::config::Backend::Mock,
+            start_frame,
+            end_frame,
+            ..crate::config::Configuration::default()
+        };
+        let decoder = Box::new(MockProvider::new(&config).unwrap()) as DynDecoderProvider;
+        let (_controller, stream) = decoder.open().unwrap();
+        stream
+            .map(|frame| frame.unwrap().index().unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mock_backend_stops_after_end_frame() {
+        assert_eq!(
+            indices_in_range(Some(10), Some(19)).await,
+            (10..=19).collect::<Vec<_>>()
+        );
+        // An end past the clip is no bound at all.
+        assert_eq!(indices_in_range(Some(110), Some(500)).await.len(), 10);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn mock_backend_seek_by_frame_updates_serial() {
         let config = crate::config::Configuration {
@@ -261,6 +408,16 @@ mod tests {
             channel_capacity: None,
             output_format: crate::config::OutputFormat::Nv12,
             start_frame: None,
+            end_frame: None,
+            frame_rate: None,
+            raw_width: None,
+            raw_height: None,
+            raw_format: crate::config::RawFormat::Nv12,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
         };
         let decoder = Box::new(MockProvider::new(&config).unwrap()) as DynDecoderProvider;
         let (controller, mut stream) = decoder.open().unwrap();
@@ -291,6 +448,16 @@ mod tests {
             channel_capacity: None,
             output_format: crate::config::OutputFormat::Nv12,
             start_frame: None,
+            end_frame: None,
+            frame_rate: None,
+            raw_width: None,
+            raw_height: None,
+            raw_format: crate::config::RawFormat::Nv12,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
         };
         let decoder = Box::new(MockProvider::new(&config).unwrap()) as DynDecoderProvider;
         let (controller, mut stream) = decoder.open().unwrap();
@@ -313,4 +480,32 @@ mod tests {
         assert!(frame.index().unwrap_or(0) >= 60);
         assert!(frame.pts().unwrap_or_default() >= Duration::from_secs(1));
     }
+
+    async fn first_index_after_seek(frame: u64, mode: SeekMode) -> Option<u64> {
+        let config = crate::config::Configuration {
+            backend: crate::config::Backend::Mock,
+            ..Default::default()
+        };
+        let decoder = Box::new(MockProvider::new(&config).unwrap()) as DynDecoderProvider;
+        let (controller, mut stream) = decoder.open().unwrap();
+        let _ = stream.next().await.unwrap().unwrap();
+        let serial = controller.seek(SeekInfo::Frame { frame, mode }).unwrap();
+        for _ in 0..200 {
+            let frame = stream.next().await.unwrap().unwrap();
+            if frame.serial() == serial {
+                return frame.index();
+            }
+        }
+        None
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exact_seek_lands_on_the_requested_frame_and_fast_on_its_keyframe() {
+        assert_eq!(
+            first_index_after_seek(41, SeekMode::Accurate).await,
+            Some(41)
+        );
+        assert_eq!(first_index_after_seek(41, SeekMode::Fast).await, Some(36));
+        assert_eq!(first_index_after_seek(48, SeekMode::Fast).await, Some(48));
+    }
 }
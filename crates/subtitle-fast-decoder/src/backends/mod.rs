@@ -1,4 +1,6 @@
+pub mod image_sequence;
 pub mod mock;
+pub mod raw_yuv;
 
 #[cfg(feature = "backend-ffmpeg")]
 pub mod ffmpeg;
@@ -9,5 +11,11 @@ pub mod dxva;
 #[cfg(all(target_os = "windows", feature = "backend-mft"))]
 pub mod mft;
 
+#[cfg(feature = "backend-nvdec")]
+pub mod nvdec;
+
+#[cfg(feature = "backend-vaapi")]
+pub mod vaapi;
+
 #[cfg(all(target_os = "macos", feature = "backend-videotoolbox"))]
 pub mod videotoolbox;
@@ -0,0 +1,699 @@
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::core::{
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DropUntil, FrameStream,
+    SeekDropper, SeekInfo, SeekMode, SeekReceiver, past_end_frame,
+};
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::config::OutputFormat;
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::core::{FramePool, VideoFrame, filter_stale_frames, spawn_stream_from_channel};
+
+/// `NativeBuffer::pixel_format` of NVDEC surfaces: the `NV12` FourCC.
+pub const NVDEC_PIXEL_FORMAT_NV12: u32 = u32::from_be_bytes(*b"NV12");
+
+/// What the `NativeBuffer::handle` of an NVDEC frame points to when decoding with
+/// `OutputFormat::CudaDevice`. The NV12 planes stay in GPU memory, allocated in the primary
+/// CUDA context of `device`; they remain valid until the last clone of the frame is dropped.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CudaSurface {
+    /// `CUdeviceptr` of the luma plane.
+    pub luma: u64,
+    /// `CUdeviceptr` of the interleaved chroma plane.
+    pub chroma: u64,
+    pub luma_pitch: usize,
+    pub chroma_pitch: usize,
+    /// CUDA device ordinal.
+    pub device: i32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+mod platform {
+    use super::*;
+    use std::ffi::{CStr, CString, c_char, c_void};
+    use std::path::{Path, PathBuf};
+    use std::ptr;
+    use std::slice;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+    use tokio::sync::mpsc::Sender;
+
+    const BACKEND_NAME: &str = "nvdec";
+    const DEFAULT_CHANNEL_CAPACITY: usize = 16;
+
+    #[repr(C)]
+    struct CNvdecProbeResult {
+        has_value: bool,
+        value: u64,
+        duration_seconds: f64,
+        fps: f64,
+        width: u32,
+        height: u32,
+        error: *mut c_char,
+    }
+
+    #[repr(C)]
+    struct CNvdecFrame {
+        surface: *mut c_void,
+        y_data: *const u8,
+        y_len: usize,
+        y_stride: usize,
+        uv_data: *const u8,
+        uv_len: usize,
+        uv_stride: usize,
+        width: u32,
+        height: u32,
+        pts_seconds: f64,
+        dts_seconds: f64,
+        index: u64,
+    }
+
+    type CNvdecFrameCallback = unsafe extern "C" fn(*const CNvdecFrame, *mut c_void) -> bool;
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct CNvdecSeekRequest {
+        position_seconds: f64,
+        start_frame: u64,
+    }
+
+    type CNvdecSeekCallback = unsafe extern "C" fn(*mut c_void, *mut CNvdecSeekRequest) -> i32;
+
+    const SEEK_ACTION_CONTINUE: i32 = 0;
+    const SEEK_ACTION_STOP: i32 = 1;
+    const SEEK_ACTION_SEEK: i32 = 2;
+
+    #[allow(improper_ctypes)]
+    unsafe extern "C" {
+        fn nvdec_probe_total_frames(path: *const c_char, result: *mut CNvdecProbeResult) -> bool;
+        fn nvdec_decode(
+            path: *const c_char,
+            has_start_frame: bool,
+            start_frame: u64,
+            native_surfaces: bool,
+            extra_surfaces: i32,
+            callback: CNvdecFrameCallback,
+            context: *mut c_void,
+            seek_callback: CNvdecSeekCallback,
+            out_error: *mut *mut c_char,
+        ) -> bool;
+        fn nvdec_string_free(ptr: *mut c_char);
+        fn nvdec_surface_release(handle: *mut c_void);
+    }
+
+    pub struct NvdecProvider {
+        input: PathBuf,
+        metadata: crate::core::VideoMetadata,
+        channel_capacity: usize,
+        queue_full_warning: Option<Duration>,
+        output_format: OutputFormat,
+        start_frame: Option<u64>,
+        end_frame: Option<u64>,
+    }
+
+    impl DecoderProvider for NvdecProvider {
+        fn new(config: &crate::config::Configuration) -> DecoderResult<Self> {
+            let path = config.input.as_ref().ok_or_else(|| {
+                DecoderError::configuration("NVDEC backend requires SUBFAST_INPUT to be set")
+            })?;
+            if !path.exists() {
+                return Err(DecoderError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("input file {} does not exist", path.display()),
+                )));
+            }
+            let mut metadata = probe_video_metadata(path)?;
+            if let Some(fps) = config.fps_override {
+                metadata.fps = Some(fps);
+            }
+            let capacity = config
+                .channel_capacity
+                .map(|n| n.get())
+                .unwrap_or(DEFAULT_CHANNEL_CAPACITY)
+                .max(1);
+            Ok(Self {
+                input: path.to_path_buf(),
+                metadata,
+                channel_capacity: capacity,
+                queue_full_warning: config.queue_full_warning,
+                output_format: config.output_format,
+                start_frame: config.start_frame,
+                end_frame: config.end_frame,
+            })
+        }
+
+        fn metadata(&self) -> crate::core::VideoMetadata {
+            self.metadata.clone()
+        }
+
+        fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+            let provider = *self;
+            let capacity = provider.channel_capacity;
+            let queue_full_warning = provider.queue_full_warning;
+            let native_surfaces = provider.output_format == OutputFormat::CudaDevice;
+            let start_frame = provider.start_frame;
+            let end_frame = provider.end_frame;
+            let fps = provider.metadata.fps;
+            let controller = DecoderController::new();
+            let seek_rx = controller.seek_receiver();
+            let serial = controller.serial_handle();
+            let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
+                if let Err(err) = decode_nvdec(
+                    provider.input.clone(),
+                    tx.clone(),
+                    native_surfaces,
+                    // Every queued frame holds a decoder surface while in the channel.
+                    capacity,
+                    start_frame,
+                    end_frame,
+                    seek_rx,
+                    serial,
+                    fps,
+                ) {
+                    let _ = tx.blocking_send(Err(err));
+                }
+            });
+            let stream = filter_stale_frames(stream, controller.serial_handle());
+            Ok((controller, stream))
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn decode_nvdec(
+        path: PathBuf,
+        tx: Sender<DecoderResult<VideoFrame>>,
+        native_surfaces: bool,
+        extra_surfaces: usize,
+        start_frame: Option<u64>,
+        end_frame: Option<u64>,
+        seek_rx: SeekReceiver,
+        serial: Arc<AtomicU64>,
+        fps: Option<f64>,
+    ) -> DecoderResult<()> {
+        let c_path = cstring_from_path(&path)?;
+        let mut context = DecodeContext::new(tx, seek_rx, serial, fps, end_frame);
+        let mut error_ptr: *mut c_char = ptr::null_mut();
+        let (has_start_frame, start_frame) = match start_frame {
+            Some(value) => (true, value),
+            None => (false, 0),
+        };
+        let ok = unsafe {
+            nvdec_decode(
+                c_path.as_ptr(),
+                has_start_frame,
+                start_frame,
+                native_surfaces,
+                i32::try_from(extra_surfaces).unwrap_or(i32::MAX),
+                handle_frame,
+                &mut context as *mut _ as *mut c_void,
+                poll_seek_requests,
+                &mut error_ptr,
+            )
+        };
+        let bridge_error = take_bridge_string(error_ptr);
+        if let Some(err) = context.take_seek_error() {
+            return Err(err);
+        }
+        if context.is_closed() {
+            return Ok(());
+        }
+        if !ok {
+            let message = bridge_error.unwrap_or_else(|| "decode failed".to_string());
+            return Err(DecoderError::backend_failure(BACKEND_NAME, message));
+        }
+        if let Some(message) = bridge_error
+            && !message.is_empty()
+        {
+            return Err(DecoderError::backend_failure(BACKEND_NAME, message));
+        }
+        Ok(())
+    }
+
+    fn probe_video_metadata(path: &Path) -> DecoderResult<crate::core::VideoMetadata> {
+        use crate::core::VideoMetadata;
+
+        let c_path = cstring_from_path(path)?;
+        let mut result = CNvdecProbeResult {
+            has_value: false,
+            value: 0,
+            duration_seconds: 0.0,
+            fps: 0.0,
+            width: 0,
+            height: 0,
+            error: ptr::null_mut(),
+        };
+        let ok = unsafe { nvdec_probe_total_frames(c_path.as_ptr(), &mut result) };
+        let bridge_error = take_bridge_string(result.error);
+        if !ok {
+            let message = bridge_error.unwrap_or_else(|| "probe failed".to_string());
+            return Err(DecoderError::backend_failure(BACKEND_NAME, message));
+        }
+        if let Some(message) = bridge_error
+            && !message.is_empty()
+        {
+            return Err(DecoderError::backend_failure(BACKEND_NAME, message));
+        }
+
+        let mut metadata = VideoMetadata::new();
+        if result.has_value {
+            metadata.total_frames = Some(result.value);
+        }
+        if result.duration_seconds.is_finite() && result.duration_seconds > 0.0 {
+            metadata.duration = Some(Duration::from_secs_f64(result.duration_seconds));
+        }
+        if result.fps.is_finite() && result.fps > 0.0 {
+            metadata.fps = Some(result.fps);
+        }
+        if result.width > 0 {
+            metadata.width = Some(result.width);
+        }
+        if result.height > 0 {
+            metadata.height = Some(result.height);
+        }
+
+        Ok(metadata)
+    }
+
+    fn cstring_from_path(path: &Path) -> DecoderResult<CString> {
+        CString::new(path.to_string_lossy().as_bytes()).map_err(|err| {
+            DecoderError::backend_failure(BACKEND_NAME, format!("invalid path encoding: {err}"))
+        })
+    }
+
+    fn take_bridge_string(ptr: *mut c_char) -> Option<String> {
+        if ptr.is_null() {
+            return None;
+        }
+        let message = unsafe { CStr::from_ptr(ptr).to_string_lossy().into_owned() };
+        unsafe { nvdec_string_free(ptr) };
+        Some(message)
+    }
+
+    struct DecodeContext {
+        tx: Sender<DecoderResult<VideoFrame>>,
+        seek_rx: SeekReceiver,
+        serial: Arc<AtomicU64>,
+        current_serial: u64,
+        dropper: SeekDropper,
+        seek_error: Option<DecoderError>,
+        closed: bool,
+        fps: Option<f64>,
+        end_frame: Option<u64>,
+        pool: FramePool,
+    }
+
+    impl DecodeContext {
+        fn new(
+            tx: Sender<DecoderResult<VideoFrame>>,
+            seek_rx: SeekReceiver,
+            serial: Arc<AtomicU64>,
+            fps: Option<f64>,
+            end_frame: Option<u64>,
+        ) -> Self {
+            let current_serial = serial.load(Ordering::SeqCst);
+            Self {
+                tx,
+                seek_rx,
+                serial,
+                current_serial,
+                dropper: SeekDropper::default(),
+                seek_error: None,
+                closed: false,
+                fps,
+                end_frame,
+                pool: FramePool::new(),
+            }
+        }
+
+        fn is_closed(&self) -> bool {
+            self.closed || self.tx.is_closed()
+        }
+
+        fn apply_drop(&mut self, drop_until: Option<DropUntil>) {
+            self.dropper.arm(drop_until);
+        }
+
+        fn take_seek_error(&mut self) -> Option<DecoderError> {
+            self.seek_error.take()
+        }
+
+        fn send_frame(&mut self, frame: VideoFrame) -> bool {
+            if self.tx.blocking_send(Ok(frame)).is_ok() {
+                true
+            } else {
+                self.closed = true;
+                false
+            }
+        }
+
+        fn send_error(&mut self, error: DecoderError) {
+            let _ = self.tx.blocking_send(Err(error));
+            self.closed = true;
+        }
+
+        fn should_skip_frame(&mut self, index: u64, pts: Option<Duration>) -> bool {
+            self.dropper.should_drop(Some(index), pts)
+        }
+
+        /// Closes the decode once a frame lies past the configured end frame.
+        fn reached_end(&mut self, index: Option<u64>) -> bool {
+            if past_end_frame(self.end_frame, index) {
+                self.closed = true;
+            }
+            self.closed
+        }
+    }
+
+    unsafe extern "C" fn handle_frame(frame: *const CNvdecFrame, context: *mut c_void) -> bool {
+        if frame.is_null() {
+            return false;
+        }
+        let frame = unsafe { &*frame };
+        if context.is_null() {
+            if !frame.surface.is_null() {
+                unsafe { release_native_handle(frame.surface) };
+            }
+            return false;
+        }
+        let context = unsafe { &mut *(context as *mut DecodeContext) };
+        if !frame.surface.is_null() {
+            return handle_native_frame(frame, context);
+        }
+        if context.is_closed() {
+            return false;
+        }
+        if frame.y_data.is_null() || frame.uv_data.is_null() {
+            context.send_error(DecoderError::backend_failure(
+                BACKEND_NAME,
+                "NV12 plane pointer is null",
+            ));
+            return false;
+        }
+        let y_data = unsafe { slice::from_raw_parts(frame.y_data, frame.y_len) };
+        let uv_data = unsafe { slice::from_raw_parts(frame.uv_data, frame.uv_len) };
+        let pts = if frame.pts_seconds.is_finite() && frame.pts_seconds >= 0.0 {
+            Some(Duration::from_secs_f64(frame.pts_seconds))
+        } else {
+            None
+        };
+        let dts = if frame.dts_seconds.is_finite() && frame.dts_seconds >= 0.0 {
+            Some(Duration::from_secs_f64(frame.dts_seconds))
+        } else {
+            None
+        };
+        let index = pts
+            .and_then(|pts| index_from_pts(pts, context.fps))
+            .or(Some(frame.index));
+        if context.should_skip_frame(index.unwrap_or(frame.index), pts) {
+            return true;
+        }
+        if context.reached_end(index) {
+            return false;
+        }
+        match context.pool.nv12_frame(
+            frame.width,
+            frame.height,
+            frame.y_stride,
+            frame.uv_stride,
+            pts,
+            dts,
+            context.pool.copy_of(y_data),
+            context.pool.copy_of(uv_data),
+        ) {
+            Ok(frame_value) => {
+                let frame_value = frame_value
+                    .with_index(index)
+                    .with_serial(context.current_serial);
+                context.send_frame(frame_value)
+            }
+            Err(err) => {
+                context.send_error(err);
+                false
+            }
+        }
+    }
+
+    unsafe extern "C" fn release_native_handle(handle: *mut c_void) {
+        unsafe { nvdec_surface_release(handle) };
+    }
+
+    /// Wraps a device surface into a native frame. The bridge hands over ownership of the
+    /// surface, so every path that does not emit it must release it.
+    fn handle_native_frame(frame: &CNvdecFrame, context: &mut DecodeContext) -> bool {
+        if context.is_closed() {
+            unsafe { release_native_handle(frame.surface) };
+            return false;
+        }
+        let pts = if frame.pts_seconds.is_finite() && frame.pts_seconds >= 0.0 {
+            Some(Duration::from_secs_f64(frame.pts_seconds))
+        } else {
+            None
+        };
+        let dts = if frame.dts_seconds.is_finite() && frame.dts_seconds >= 0.0 {
+            Some(Duration::from_secs_f64(frame.dts_seconds))
+        } else {
+            None
+        };
+        let index = pts
+            .and_then(|pts| index_from_pts(pts, context.fps))
+            .or(Some(frame.index));
+        if context.should_skip_frame(index.unwrap_or(frame.index), pts) {
+            unsafe { release_native_handle(frame.surface) };
+            return true;
+        }
+        if context.reached_end(index) {
+            unsafe { release_native_handle(frame.surface) };
+            return false;
+        }
+        match VideoFrame::from_native_handle(
+            frame.width,
+            frame.height,
+            pts,
+            dts,
+            index,
+            BACKEND_NAME,
+            NVDEC_PIXEL_FORMAT_NV12,
+            frame.surface,
+            release_native_handle,
+        ) {
+            Ok(native_frame) => {
+                context.send_frame(native_frame.with_serial(context.current_serial))
+            }
+            Err(err) => {
+                unsafe { release_native_handle(frame.surface) };
+                context.send_error(err);
+                false
+            }
+        }
+    }
+
+    unsafe extern "C" fn poll_seek_requests(
+        context: *mut c_void,
+        out_request: *mut CNvdecSeekRequest,
+    ) -> i32 {
+        if context.is_null() {
+            return SEEK_ACTION_STOP;
+        }
+        let context = unsafe { &mut *(context as *mut DecodeContext) };
+        if context.is_closed() {
+            return SEEK_ACTION_STOP;
+        }
+        if !context.seek_rx.has_changed().unwrap_or(false) {
+            return SEEK_ACTION_CONTINUE;
+        }
+        let Some(info) = *context.seek_rx.borrow_and_update() else {
+            return SEEK_ACTION_CONTINUE;
+        };
+        context.current_serial = context.serial.load(Ordering::SeqCst);
+        match compute_seek_plan(info, context.fps) {
+            Ok(plan) => {
+                context.apply_drop(plan.drop_until);
+                if !out_request.is_null() {
+                    unsafe { *out_request = plan.request };
+                }
+                SEEK_ACTION_SEEK
+            }
+            Err(err) => {
+                context.seek_error = Some(err);
+                SEEK_ACTION_STOP
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    struct SeekPlan {
+        request: CNvdecSeekRequest,
+        drop_until: Option<DropUntil>,
+    }
+
+    fn compute_seek_plan(info: SeekInfo, fps: Option<f64>) -> DecoderResult<SeekPlan> {
+        match info {
+            SeekInfo::Frame { frame, mode } => {
+                let fps = fps.ok_or_else(|| {
+                    DecoderError::configuration(
+                        "nvdec backend requires frame rate metadata to seek by frame",
+                    )
+                })?;
+                if !(fps.is_finite() && fps > 0.0) {
+                    return Err(DecoderError::configuration(
+                        "nvdec backend requires frame rate metadata to seek by frame",
+                    ));
+                }
+                let seconds = frame as f64 / fps;
+                if !seconds.is_finite() || seconds.is_sign_negative() {
+                    return Err(DecoderError::configuration("invalid seek timestamp"));
+                }
+                Ok(SeekPlan {
+                    request: CNvdecSeekRequest {
+                        position_seconds: seconds,
+                        start_frame: frame,
+                    },
+                    drop_until: mode.drop_until(DropUntil::Frame(frame)),
+                })
+            }
+            SeekInfo::Time { position, mode } => {
+                let fps = fps.ok_or_else(|| {
+                    DecoderError::configuration(
+                        "nvdec backend requires frame rate metadata to seek by time",
+                    )
+                })?;
+                if !(fps.is_finite() && fps > 0.0) {
+                    return Err(DecoderError::configuration(
+                        "nvdec backend requires frame rate metadata to seek by time",
+                    ));
+                }
+                let seconds = position.as_secs_f64();
+                if !seconds.is_finite() || seconds.is_sign_negative() {
+                    return Err(DecoderError::configuration("invalid seek timestamp"));
+                }
+                let raw_frame = seconds * fps;
+                if !raw_frame.is_finite() || raw_frame.is_sign_negative() {
+                    return Err(DecoderError::configuration("invalid seek timestamp"));
+                }
+                let frame = match mode {
+                    SeekMode::Fast => raw_frame.round(),
+                    SeekMode::Accurate => raw_frame.floor(),
+                };
+                if frame < 0.0 || frame > u64::MAX as f64 {
+                    return Err(DecoderError::configuration("seek frame is out of range"));
+                }
+                Ok(SeekPlan {
+                    request: CNvdecSeekRequest {
+                        position_seconds: seconds,
+                        start_frame: frame as u64,
+                    },
+                    drop_until: mode.drop_until(DropUntil::Timestamp(position)),
+                })
+            }
+        }
+    }
+
+    fn index_from_pts(pts: Duration, fps: Option<f64>) -> Option<u64> {
+        let fps = fps?;
+        if !(fps.is_finite() && fps > 0.0) {
+            return None;
+        }
+        let seconds = pts.as_secs_f64();
+        if !seconds.is_finite() || seconds.is_sign_negative() {
+            return None;
+        }
+        let index = (seconds * fps).round();
+        if index.is_finite() && index >= 0.0 && index <= u64::MAX as f64 {
+            Some(index as u64)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use crate::core::{
+        DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream,
+    };
+
+    /// NVDEC needs the NVIDIA driver on Linux or Windows; elsewhere the provider always reports
+    /// the backend as unsupported.
+    pub struct NvdecProvider;
+
+    impl DecoderProvider for NvdecProvider {
+        fn new(_config: &crate::config::Configuration) -> DecoderResult<Self> {
+            Err(DecoderError::unsupported("nvdec"))
+        }
+
+        fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+            Err(DecoderError::unsupported("nvdec"))
+        }
+    }
+}
+
+pub use platform::NvdecProvider;
+
+#[cfg(test)]
+mod tests {
+    use super::NvdecProvider;
+    use crate::config::Configuration;
+    use crate::core::{DecoderError, DecoderProvider};
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    #[test]
+    fn nvdec_is_unsupported_without_cuda() {
+        let result = NvdecProvider::new(&Configuration::default());
+        assert!(matches!(
+            result,
+            Err(DecoderError::Unsupported { backend: "nvdec" })
+        ));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    #[test]
+    fn nvdec_rejects_missing_input_before_touching_the_device() {
+        let config = Configuration {
+            backend: crate::config::Backend::Nvdec,
+            input: Some("/nonexistent/subtitle-fast/clip.mp4".into()),
+            ..Configuration::default()
+        };
+        match NvdecProvider::new(&config) {
+            Err(DecoderError::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+            Err(other) => panic!("expected a missing-file error, got {other}"),
+            Ok(_) => panic!("expected a missing-file error"),
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "needs an NVIDIA GPU and SUBFAST_NVDEC_SAMPLE pointing at a video"]
+    async fn nvdec_emits_device_surfaces() {
+        use crate::config::OutputFormat;
+        use crate::core::FrameBuffer;
+        use tokio_stream::StreamExt;
+
+        let input = std::env::var("SUBFAST_NVDEC_SAMPLE").expect("SUBFAST_NVDEC_SAMPLE");
+        let config = Configuration {
+            backend: crate::config::Backend::Nvdec,
+            input: Some(input.into()),
+            output_format: OutputFormat::CudaDevice,
+            end_frame: Some(2),
+            ..Configuration::default()
+        };
+        let provider = NvdecProvider::new(&config).expect("probe NVDEC input");
+        let (_controller, stream) = Box::new(provider).open().expect("open NVDEC decoder");
+        let frames: Vec<_> = stream
+            .map(|frame| frame.expect("decode frame"))
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 3);
+        for frame in &frames {
+            let FrameBuffer::Native(native) = frame.buffer() else {
+                panic!("expected a native CUDA surface");
+            };
+            assert_eq!(native.backend(), "nvdec");
+            assert_eq!(native.pixel_format(), super::NVDEC_PIXEL_FORMAT_NV12);
+            let surface = unsafe { *(native.handle() as *const super::CudaSurface) };
+            assert_ne!(surface.luma, 0);
+            assert!(surface.luma_pitch >= frame.width() as usize);
+        }
+    }
+}
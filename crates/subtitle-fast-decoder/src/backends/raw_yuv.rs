@@ -0,0 +1,410 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::sync::mpsc::Sender;
+
+use crate::config::{OutputFormat, RawFormat};
+use crate::core::{
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream, SeekInfo,
+    SeekReceiver, VideoFrame, VideoMetadata, filter_stale_frames, i420_to_nv12, nv12_to_i420,
+    past_end_frame, spawn_stream_from_channel,
+};
+
+/// Reads headerless 8-bit NV12 or I420 frames of a fixed size back to back, from a file or from
+/// stdin when the input is `-`. Suits V4L2 capture dumps and pipes from embedded camera tools.
+pub struct RawYuvProvider {
+    input: PathBuf,
+    width: u32,
+    height: u32,
+    format: RawFormat,
+    fps: f64,
+    output_format: OutputFormat,
+    total_frames: Option<u64>,
+    channel_capacity: usize,
+    queue_full_warning: Option<Duration>,
+    start_frame: u64,
+    end_frame: Option<u64>,
+}
+
+impl RawYuvProvider {
+    const DEFAULT_CHANNEL_CAPACITY: usize = 8;
+
+    fn is_stdin(path: &Path) -> bool {
+        path.as_os_str() == "-"
+    }
+
+    fn chroma_size(&self) -> (usize, usize) {
+        (
+            (self.width as usize).div_ceil(2),
+            (self.height as usize).div_ceil(2),
+        )
+    }
+
+    fn frame_size(&self) -> usize {
+        let (chroma_width, chroma_rows) = self.chroma_size();
+        self.width as usize * self.height as usize + 2 * chroma_width * chroma_rows
+    }
+
+    fn frame_pts(&self, index: u64) -> Duration {
+        Duration::from_secs_f64(index as f64 / self.fps)
+    }
+
+    fn open_source(&self) -> DecoderResult<Box<dyn RawSource>> {
+        if Self::is_stdin(&self.input) {
+            Ok(Box::new(StdinSource {
+                stdin: io::stdin(),
+                position: 0,
+            }))
+        } else {
+            Ok(Box::new(File::open(&self.input)?))
+        }
+    }
+
+    fn emit_frames(
+        &self,
+        tx: Sender<DecoderResult<VideoFrame>>,
+        mut seek_rx: SeekReceiver,
+        serial: Arc<AtomicU64>,
+    ) {
+        let mut source = match self.open_source() {
+            Ok(source) => source,
+            Err(err) => {
+                let _ = tx.blocking_send(Err(err));
+                return;
+            }
+        };
+        let frame_size = self.frame_size();
+        let mut buf = vec![0u8; frame_size];
+        let mut index = self.start_frame;
+        let mut current_serial = serial.load(Ordering::SeqCst);
+        let mut positioned = false;
+        loop {
+            if seek_rx.has_changed().unwrap_or(false)
+                && let Some(info) = *seek_rx.borrow_and_update()
+            {
+                current_serial = serial.load(Ordering::SeqCst);
+                index = self.seek_target(info);
+                positioned = false;
+                continue;
+            }
+            if tx.is_closed() || past_end_frame(self.end_frame, Some(index)) {
+                break;
+            }
+            if !positioned {
+                if let Err(err) = source.seek_to(index * frame_size as u64) {
+                    let _ = tx.blocking_send(Err(err));
+                    break;
+                }
+                positioned = true;
+            }
+            let frame = match read_frame(source.as_mut(), &mut buf) {
+                Ok(true) => self
+                    .build_frame(&buf, index)
+                    .map(|frame| frame.with_index(Some(index)).with_serial(current_serial)),
+                Ok(false) => break,
+                Err(err) => Err(err),
+            };
+            let failed = frame.is_err();
+            if tx.blocking_send(frame).is_err() || failed {
+                break;
+            }
+            index += 1;
+        }
+    }
+
+    fn seek_target(&self, info: SeekInfo) -> u64 {
+        match info {
+            SeekInfo::Frame { frame, .. } => frame,
+            SeekInfo::Time { position, .. } => (position.as_secs_f64() * self.fps).floor() as u64,
+        }
+    }
+
+    fn build_frame(&self, data: &[u8], index: u64) -> DecoderResult<VideoFrame> {
+        let width = self.width as usize;
+        let luma_len = width * self.height as usize;
+        let (chroma_width, chroma_rows) = self.chroma_size();
+        let chroma_len = chroma_width * chroma_rows;
+        let (y_plane, chroma) = data.split_at(luma_len);
+        let pts = Some(self.frame_pts(index));
+        match (self.format, self.output_format) {
+            (RawFormat::Nv12, OutputFormat::I420) => nv12_to_i420(&VideoFrame::from_nv12_owned(
+                self.width,
+                self.height,
+                width,
+                chroma_width * 2,
+                pts,
+                None,
+                y_plane.to_vec(),
+                chroma.to_vec(),
+            )?),
+            (RawFormat::Nv12, _) => VideoFrame::from_nv12_owned(
+                self.width,
+                self.height,
+                width,
+                chroma_width * 2,
+                pts,
+                None,
+                y_plane.to_vec(),
+                chroma.to_vec(),
+            ),
+            (RawFormat::I420, output) => {
+                let (u_plane, v_plane) = chroma.split_at(chroma_len);
+                let frame = VideoFrame::from_i420_owned(
+                    self.width,
+                    self.height,
+                    width,
+                    chroma_width,
+                    chroma_width,
+                    pts,
+                    None,
+                    y_plane.to_vec(),
+                    u_plane.to_vec(),
+                    v_plane.to_vec(),
+                )?;
+                if output == OutputFormat::I420 {
+                    Ok(frame)
+                } else {
+                    i420_to_nv12(&frame)
+                }
+            }
+        }
+    }
+}
+
+impl DecoderProvider for RawYuvProvider {
+    fn new(config: &crate::config::Configuration) -> DecoderResult<Self> {
+        let input = config.input.clone().ok_or_else(|| {
+            DecoderError::configuration("raw-yuv backend requires an input file or '-' for stdin")
+        })?;
+        let (Some(width), Some(height)) = (config.raw_width, config.raw_height) else {
+            return Err(DecoderError::configuration(
+                "raw-yuv backend requires raw_width and raw_height",
+            ));
+        };
+        if width == 0 || height == 0 {
+            return Err(DecoderError::configuration(format!(
+                "raw-yuv frame size must be non-zero (got {width}x{height})"
+            )));
+        }
+        let Some(fps) = config.fps_override.or(config.frame_rate) else {
+            return Err(DecoderError::configuration(
+                "raw-yuv backend requires a frame rate",
+            ));
+        };
+        if !fps.is_finite() || fps <= 0.0 {
+            return Err(DecoderError::configuration(format!(
+                "raw-yuv frame rate must be positive (got {fps})"
+            )));
+        }
+        let capacity = config
+            .channel_capacity
+            .map(|n| n.get())
+            .unwrap_or(Self::DEFAULT_CHANNEL_CAPACITY);
+        let mut provider = Self {
+            input,
+            width,
+            height,
+            format: config.raw_format,
+            fps,
+            output_format: config.output_format,
+            total_frames: None,
+            channel_capacity: capacity.max(1),
+            queue_full_warning: config.queue_full_warning,
+            start_frame: config.start_frame.unwrap_or(0),
+            end_frame: config.end_frame,
+        };
+        if !Self::is_stdin(&provider.input) {
+            let len = std::fs::metadata(&provider.input)?.len();
+            provider.total_frames = Some(len / provider.frame_size() as u64);
+        }
+        Ok(provider)
+    }
+
+    fn metadata(&self) -> VideoMetadata {
+        VideoMetadata {
+            duration: self.total_frames.map(|total| self.frame_pts(total)),
+            fps: Some(self.fps),
+            width: Some(self.width),
+            height: Some(self.height),
+            total_frames: self.total_frames,
+            pixel_format: Some(self.format.as_str().to_string()),
+            bit_depth: Some(8),
+            ..VideoMetadata::default()
+        }
+    }
+
+    fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+        let provider = *self;
+        let capacity = provider.channel_capacity;
+        let queue_full_warning = provider.queue_full_warning;
+        let controller = DecoderController::new();
+        let seek_rx = controller.seek_receiver();
+        let serial = controller.serial_handle();
+        let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
+            provider.emit_frames(tx, seek_rx, serial);
+        });
+        let stream = filter_stale_frames(stream, controller.serial_handle());
+        Ok((controller, stream))
+    }
+}
+
+/// Byte source that can be repositioned to a frame boundary.
+trait RawSource: Read + Send {
+    fn seek_to(&mut self, offset: u64) -> DecoderResult<()>;
+}
+
+impl RawSource for File {
+    fn seek_to(&mut self, offset: u64) -> DecoderResult<()> {
+        self.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+}
+
+/// Stdin cannot rewind, so seeking only moves forward by discarding input.
+struct StdinSource {
+    stdin: io::Stdin,
+    position: u64,
+}
+
+impl Read for StdinSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.stdin.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl RawSource for StdinSource {
+    fn seek_to(&mut self, offset: u64) -> DecoderResult<()> {
+        if offset < self.position {
+            return Err(DecoderError::configuration(
+                "raw-yuv input from stdin cannot seek backwards",
+            ));
+        }
+        let skip = offset - self.position;
+        let skipped = io::copy(&mut self.by_ref().take(skip), &mut io::sink())?;
+        if skipped < skip {
+            return Err(DecoderError::InvalidFrame {
+                reason: "raw-yuv input ended before the seek target".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fills `buf` with one frame. Returns `false` on a clean end of input and an error when the
+/// input stops partway through a frame.
+fn read_frame(source: &mut dyn RawSource, buf: &mut [u8]) -> DecoderResult<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match source.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if filled == 0 {
+        return Ok(false);
+    }
+    if filled < buf.len() {
+        return Err(DecoderError::InvalidFrame {
+            reason: format!(
+                "raw-yuv input ended {filled} bytes into a {}-byte frame",
+                buf.len()
+            ),
+        });
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynDecoderProvider;
+    use tokio_stream::StreamExt;
+
+    fn config(path: &Path, format: RawFormat) -> crate::config::Configuration {
+        crate::config::Configuration {
+            backend: crate::config::Backend::RawYuv,
+            input: Some(path.to_path_buf()),
+            channel_capacity: None,
+            output_format: OutputFormat::Nv12,
+            start_frame: None,
+            end_frame: None,
+            frame_rate: Some(10.0),
+            raw_width: Some(4),
+            raw_height: Some(2),
+            raw_format: format,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn raw_yuv_reads_nv12_frames_back_to_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.nv12");
+        // Three 4x2 frames: 8 luma bytes then 4 interleaved chroma bytes each.
+        let mut data = Vec::new();
+        for frame in 0..3u8 {
+            data.extend_from_slice(&[frame * 10; 8]);
+            data.extend_from_slice(&[100, 150, 100, 150]);
+        }
+        std::fs::write(&path, &data).unwrap();
+
+        let provider = Box::new(RawYuvProvider::new(&config(&path, RawFormat::Nv12)).unwrap())
+            as DynDecoderProvider;
+        let metadata = provider.metadata();
+        assert_eq!(metadata.total_frames, Some(3));
+        assert_eq!(metadata.width, Some(4));
+        assert_eq!(metadata.height, Some(2));
+
+        let (_controller, stream) = provider.open().unwrap();
+        let frames: Vec<VideoFrame> = stream.map(|frame| frame.unwrap()).collect().await;
+        assert_eq!(frames.len(), 3);
+        for (index, frame) in frames.iter().enumerate() {
+            assert_eq!((frame.width(), frame.height()), (4, 2));
+            assert_eq!(frame.index(), Some(index as u64));
+            assert_eq!(frame.pts(), Some(Duration::from_millis(index as u64 * 100)));
+            assert_eq!(frame.y_plane()[0], index as u8 * 10);
+            assert_eq!(frame.uv_plane(), &[100, 150, 100, 150]);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn raw_yuv_interleaves_i420_input_and_rejects_truncated_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.yuv");
+        let mut data = vec![50u8; 8];
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        data.extend_from_slice(&[50; 5]);
+        std::fs::write(&path, &data).unwrap();
+
+        let provider = Box::new(RawYuvProvider::new(&config(&path, RawFormat::I420)).unwrap())
+            as DynDecoderProvider;
+        let (_controller, stream) = provider.open().unwrap();
+        let frames: Vec<DecoderResult<VideoFrame>> = stream.collect().await;
+        assert_eq!(frames.len(), 2);
+        let first = frames[0].as_ref().unwrap();
+        assert_eq!(first.uv_plane(), &[1, 3, 2, 4]);
+        assert!(matches!(frames[1], Err(DecoderError::InvalidFrame { .. })));
+    }
+
+    #[test]
+    fn raw_yuv_requires_frame_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.nv12");
+        std::fs::write(&path, [0u8; 12]).unwrap();
+        let mut config = config(&path, RawFormat::Nv12);
+        config.raw_width = None;
+        assert!(RawYuvProvider::new(&config).is_err());
+    }
+}
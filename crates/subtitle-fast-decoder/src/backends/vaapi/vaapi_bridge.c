@@ -0,0 +1,444 @@
+#include <libavcodec/avcodec.h>
+#include <libavformat/avformat.h>
+#include <libavutil/buffer.h>
+#include <libavutil/error.h>
+#include <libavutil/hwcontext.h>
+#include <libavutil/pixdesc.h>
+
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct {
+    bool has_value;
+    uint64_t value;
+    double duration_seconds;
+    double fps;
+    uint32_t width;
+    uint32_t height;
+    char *error;
+} VaapiProbeResult;
+
+typedef struct {
+    const uint8_t *y_data;
+    size_t y_len;
+    size_t y_stride;
+    const uint8_t *uv_data;
+    size_t uv_len;
+    size_t uv_stride;
+    uint32_t width;
+    uint32_t height;
+    double pts_seconds;
+    double dts_seconds;
+    uint64_t index;
+} VaapiFrame;
+
+typedef bool (*VaapiFrameCallback)(const VaapiFrame *frame, void *context);
+
+typedef struct {
+    double position_seconds;
+    uint64_t start_frame;
+} VaapiSeekRequest;
+
+typedef int (*VaapiSeekCallback)(void *context, VaapiSeekRequest *request);
+
+enum {
+    VAAPI_SEEK_CONTINUE = 0,
+    VAAPI_SEEK_STOP = 1,
+    VAAPI_SEEK_SEEK = 2,
+};
+
+static char *vaapi_copy_c_string(const char *message) {
+    if (message == NULL) {
+        return NULL;
+    }
+    size_t len = strlen(message);
+    char *copy = malloc(len + 1);
+    if (copy == NULL) {
+        return NULL;
+    }
+    memcpy(copy, message, len);
+    copy[len] = '\0';
+    return copy;
+}
+
+static char *vaapi_format_error(const char *what, int code) {
+    char reason[AV_ERROR_MAX_STRING_SIZE] = {0};
+    av_strerror(code, reason, sizeof(reason));
+    char message[512];
+    snprintf(message, sizeof(message), "%s: %s", what, reason);
+    return vaapi_copy_c_string(message);
+}
+
+// Opens the VA display named by SUBFAST_VAAPI_DEVICE, or libva's default DRM render node.
+static int vaapi_open_device(AVBufferRef **device) {
+    const char *node = getenv("SUBFAST_VAAPI_DEVICE");
+    if (node != NULL && node[0] == '\0') {
+        node = NULL;
+    }
+    return av_hwdevice_ctx_create(device, AV_HWDEVICE_TYPE_VAAPI, node, NULL, 0);
+}
+
+typedef struct {
+    AVFormatContext *format;
+    AVCodecContext *codec;
+    AVBufferRef *device;
+    int stream_index;
+    double fps;
+    double time_base;
+    int64_t start_pts;
+} VaapiInput;
+
+static void vaapi_input_close(VaapiInput *input) {
+    if (input->codec != NULL) {
+        avcodec_free_context(&input->codec);
+    }
+    if (input->format != NULL) {
+        avformat_close_input(&input->format);
+    }
+    if (input->device != NULL) {
+        av_buffer_unref(&input->device);
+    }
+}
+
+static double vaapi_stream_fps(const AVStream *stream) {
+    AVRational rate = stream->avg_frame_rate;
+    if (rate.num <= 0 || rate.den <= 0) {
+        rate = stream->r_frame_rate;
+    }
+    if (rate.num <= 0 || rate.den <= 0) {
+        return NAN;
+    }
+    return av_q2d(rate);
+}
+
+static bool vaapi_open_format(const char *path, VaapiInput *input, char **out_error) {
+    int err = avformat_open_input(&input->format, path, NULL, NULL);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to open input", err);
+        return false;
+    }
+    err = avformat_find_stream_info(input->format, NULL);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to read stream info", err);
+        return false;
+    }
+    err = av_find_best_stream(input->format, AVMEDIA_TYPE_VIDEO, -1, -1, NULL, 0);
+    if (err < 0) {
+        *out_error = vaapi_format_error("no video stream", err);
+        return false;
+    }
+    input->stream_index = err;
+    const AVStream *stream = input->format->streams[input->stream_index];
+    input->fps = vaapi_stream_fps(stream);
+    input->time_base = av_q2d(stream->time_base);
+    input->start_pts = stream->start_time != AV_NOPTS_VALUE ? stream->start_time : 0;
+    return true;
+}
+
+static enum AVPixelFormat vaapi_get_format(AVCodecContext *codec, const enum AVPixelFormat *formats) {
+    (void)codec;
+    for (const enum AVPixelFormat *format = formats; *format != AV_PIX_FMT_NONE; format++) {
+        if (*format == AV_PIX_FMT_VAAPI) {
+            return *format;
+        }
+    }
+    return AV_PIX_FMT_NONE;
+}
+
+static bool vaapi_open_decoder(VaapiInput *input, char **out_error) {
+    const AVStream *stream = input->format->streams[input->stream_index];
+    const AVCodec *decoder = avcodec_find_decoder(stream->codecpar->codec_id);
+    if (decoder == NULL) {
+        *out_error = vaapi_copy_c_string("no decoder for the video codec");
+        return false;
+    }
+    bool has_vaapi = false;
+    for (int i = 0;; i++) {
+        const AVCodecHWConfig *config = avcodec_get_hw_config(decoder, i);
+        if (config == NULL) {
+            break;
+        }
+        if (config->device_type == AV_HWDEVICE_TYPE_VAAPI &&
+            (config->methods & AV_CODEC_HW_CONFIG_METHOD_HW_DEVICE_CTX)) {
+            has_vaapi = true;
+            break;
+        }
+    }
+    if (!has_vaapi) {
+        *out_error = vaapi_copy_c_string("codec has no VAAPI hardware decoder");
+        return false;
+    }
+    int err = vaapi_open_device(&input->device);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to open VAAPI device", err);
+        return false;
+    }
+    input->codec = avcodec_alloc_context3(decoder);
+    if (input->codec == NULL) {
+        *out_error = vaapi_copy_c_string("failed to allocate decoder context");
+        return false;
+    }
+    err = avcodec_parameters_to_context(input->codec, stream->codecpar);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to copy codec parameters", err);
+        return false;
+    }
+    input->codec->hw_device_ctx = av_buffer_ref(input->device);
+    input->codec->get_format = vaapi_get_format;
+    input->codec->pkt_timebase = stream->time_base;
+    err = avcodec_open2(input->codec, decoder, NULL);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to open decoder", err);
+        return false;
+    }
+    return true;
+}
+
+bool vaapi_probe_total_frames(const char *path, VaapiProbeResult *result) {
+    if (result == NULL) {
+        return false;
+    }
+    result->has_value = false;
+    result->value = 0;
+    result->duration_seconds = NAN;
+    result->fps = NAN;
+    result->width = 0;
+    result->height = 0;
+    result->error = NULL;
+    if (path == NULL) {
+        result->error = vaapi_copy_c_string("path is null");
+        return false;
+    }
+
+    VaapiInput input = {0};
+    if (!vaapi_open_format(path, &input, &result->error)) {
+        vaapi_input_close(&input);
+        return false;
+    }
+    // Fail here rather than on the first frame so callers can fall back to another backend.
+    if (!vaapi_open_decoder(&input, &result->error)) {
+        vaapi_input_close(&input);
+        return false;
+    }
+
+    const AVStream *stream = input.format->streams[input.stream_index];
+    result->fps = input.fps;
+    result->width = (uint32_t)(stream->codecpar->width > 0 ? stream->codecpar->width : 0);
+    result->height = (uint32_t)(stream->codecpar->height > 0 ? stream->codecpar->height : 0);
+    if (stream->duration != AV_NOPTS_VALUE && stream->duration > 0) {
+        result->duration_seconds = (double)stream->duration * input.time_base;
+    } else if (input.format->duration != AV_NOPTS_VALUE && input.format->duration > 0) {
+        result->duration_seconds = (double)input.format->duration / AV_TIME_BASE;
+    }
+    if (stream->nb_frames > 0) {
+        result->has_value = true;
+        result->value = (uint64_t)stream->nb_frames;
+    } else if (isfinite(result->duration_seconds) && isfinite(result->fps) && result->fps > 0.0) {
+        result->has_value = true;
+        result->value = (uint64_t)llround(result->duration_seconds * result->fps);
+    }
+
+    vaapi_input_close(&input);
+    return true;
+}
+
+typedef struct {
+    VaapiInput *input;
+    AVFrame *download;
+    VaapiFrameCallback callback;
+    void *context;
+    uint64_t next_index;
+    uint64_t skip_before;
+    bool stopped;
+} VaapiDecodeState;
+
+static double vaapi_seconds(const VaapiInput *input, int64_t ts) {
+    if (ts == AV_NOPTS_VALUE) {
+        return NAN;
+    }
+    return (double)(ts - input->start_pts) * input->time_base;
+}
+
+// Downloads one decoded surface to NV12 and hands it to the callback.
+static bool vaapi_emit_frame(VaapiDecodeState *state, AVFrame *frame, char **out_error) {
+    VaapiInput *input = state->input;
+    double pts_seconds = vaapi_seconds(input, frame->best_effort_timestamp);
+    double dts_seconds = vaapi_seconds(input, frame->pkt_dts);
+    uint64_t index = state->next_index++;
+    if (isfinite(pts_seconds) && pts_seconds >= 0.0 && isfinite(input->fps) && input->fps > 0.0) {
+        index = (uint64_t)llround(pts_seconds * input->fps);
+        state->next_index = index + 1;
+    }
+    if (index < state->skip_before) {
+        return true;
+    }
+
+    av_frame_unref(state->download);
+    state->download->format = AV_PIX_FMT_NV12;
+    int err = av_hwframe_transfer_data(state->download, frame, 0);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to download VAAPI surface as NV12", err);
+        return false;
+    }
+    const AVFrame *nv12 = state->download;
+    size_t height = (size_t)nv12->height;
+    VaapiFrame out = {
+        .y_data = nv12->data[0],
+        .y_len = (size_t)nv12->linesize[0] * height,
+        .y_stride = (size_t)nv12->linesize[0],
+        .uv_data = nv12->data[1],
+        .uv_len = (size_t)nv12->linesize[1] * ((height + 1) / 2),
+        .uv_stride = (size_t)nv12->linesize[1],
+        .width = (uint32_t)nv12->width,
+        .height = (uint32_t)nv12->height,
+        .pts_seconds = pts_seconds,
+        .dts_seconds = dts_seconds,
+        .index = index,
+    };
+    if (!state->callback(&out, state->context)) {
+        state->stopped = true;
+    }
+    return true;
+}
+
+static bool vaapi_drain_decoder(VaapiDecodeState *state, AVFrame *frame, char **out_error) {
+    while (!state->stopped) {
+        int err = avcodec_receive_frame(state->input->codec, frame);
+        if (err == AVERROR(EAGAIN) || err == AVERROR_EOF) {
+            return true;
+        }
+        if (err < 0) {
+            *out_error = vaapi_format_error("failed to decode frame", err);
+            return false;
+        }
+        bool ok = vaapi_emit_frame(state, frame, out_error);
+        av_frame_unref(frame);
+        if (!ok) {
+            return false;
+        }
+    }
+    return true;
+}
+
+static bool vaapi_seek(VaapiDecodeState *state, double position_seconds, char **out_error) {
+    VaapiInput *input = state->input;
+    int64_t target = input->start_pts;
+    if (input->time_base > 0.0) {
+        target += (int64_t)llround(position_seconds / input->time_base);
+    }
+    int err = av_seek_frame(input->format, input->stream_index, target, AVSEEK_FLAG_BACKWARD);
+    if (err < 0) {
+        *out_error = vaapi_format_error("failed to seek", err);
+        return false;
+    }
+    avcodec_flush_buffers(input->codec);
+    return true;
+}
+
+bool vaapi_decode(
+    const char *path,
+    bool has_start_frame,
+    uint64_t start_frame,
+    VaapiFrameCallback callback,
+    void *context,
+    VaapiSeekCallback seek_callback,
+    char **out_error) {
+    if (out_error != NULL) {
+        *out_error = NULL;
+    }
+    if (path == NULL || callback == NULL || seek_callback == NULL || out_error == NULL) {
+        return false;
+    }
+
+    VaapiInput input = {0};
+    AVPacket *packet = NULL;
+    AVFrame *frame = NULL;
+    AVFrame *download = NULL;
+    bool ok = vaapi_open_format(path, &input, out_error) && vaapi_open_decoder(&input, out_error);
+    if (ok) {
+        packet = av_packet_alloc();
+        frame = av_frame_alloc();
+        download = av_frame_alloc();
+        if (packet == NULL || frame == NULL || download == NULL) {
+            *out_error = vaapi_copy_c_string("failed to allocate decode buffers");
+            ok = false;
+        }
+    }
+
+    VaapiDecodeState state = {
+        .input = &input,
+        .download = download,
+        .callback = callback,
+        .context = context,
+        .next_index = 0,
+        .skip_before = 0,
+        .stopped = false,
+    };
+    if (ok && has_start_frame && start_frame > 0) {
+        if (!isfinite(input.fps) || input.fps <= 0.0) {
+            *out_error = vaapi_copy_c_string("stream has no frame rate to start at a frame");
+            ok = false;
+        } else {
+            ok = vaapi_seek(&state, (double)start_frame / input.fps, out_error);
+            state.next_index = start_frame;
+            state.skip_before = start_frame;
+        }
+    }
+
+    while (ok && !state.stopped) {
+        VaapiSeekRequest request = {0};
+        int action = seek_callback(context, &request);
+        if (action == VAAPI_SEEK_STOP) {
+            break;
+        }
+        if (action == VAAPI_SEEK_SEEK) {
+            ok = vaapi_seek(&state, request.position_seconds, out_error);
+            state.next_index = request.start_frame;
+            state.skip_before = 0;
+            continue;
+        }
+
+        int err = av_read_frame(input.format, packet);
+        if (err == AVERROR_EOF) {
+            err = avcodec_send_packet(input.codec, NULL);
+            if (err < 0 && err != AVERROR_EOF) {
+                *out_error = vaapi_format_error("failed to flush decoder", err);
+                ok = false;
+                break;
+            }
+            ok = vaapi_drain_decoder(&state, frame, out_error);
+            break;
+        }
+        if (err < 0) {
+            *out_error = vaapi_format_error("failed to read packet", err);
+            ok = false;
+            break;
+        }
+        if (packet->stream_index == input.stream_index) {
+            err = avcodec_send_packet(input.codec, packet);
+            if (err < 0 && err != AVERROR(EAGAIN)) {
+                *out_error = vaapi_format_error("failed to submit packet", err);
+                ok = false;
+            }
+        }
+        av_packet_unref(packet);
+        if (ok) {
+            ok = vaapi_drain_decoder(&state, frame, out_error);
+        }
+    }
+
+    av_frame_free(&download);
+    av_frame_free(&frame);
+    av_packet_free(&packet);
+    vaapi_input_close(&input);
+    return ok;
+}
+
+void vaapi_string_free(char *ptr) {
+    free(ptr);
+}
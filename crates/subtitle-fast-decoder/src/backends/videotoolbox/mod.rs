@@ -1,12 +1,13 @@
 #[cfg(all(target_os = "macos", feature = "backend-videotoolbox"))]
 use crate::core::{
-    DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream, SeekInfo,
-    SeekMode, SeekReceiver,
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DropUntil, FramePool,
+    FrameStream, SeekDropper, SeekInfo, SeekMode, SeekReceiver, display_rotation, past_end_frame,
+    repack_i420, rotate_frames,
 };
 
 use crate::config::OutputFormat;
 #[cfg(target_os = "macos")]
-use crate::core::{VideoFrame, spawn_stream_from_channel};
+use crate::core::{VideoFrame, filter_stale_frames, spawn_stream_from_channel};
 
 #[cfg(target_os = "macos")]
 #[allow(unexpected_cfgs)]
@@ -30,6 +31,8 @@ mod platform {
         fps: f64,
         width: u32,
         height: u32,
+        /// `a`, `b`, `c`, `d` of the track's preferred transform.
+        transform: [f64; 4],
         error: *mut c_char,
     }
 
@@ -94,8 +97,10 @@ mod platform {
         input: PathBuf,
         metadata: crate::core::VideoMetadata,
         channel_capacity: usize,
+        queue_full_warning: Option<Duration>,
         output_format: OutputFormat,
         start_frame: Option<u64>,
+        end_frame: Option<u64>,
     }
 
     impl VideoToolboxProvider {}
@@ -115,6 +120,7 @@ mod platform {
             fps: f64::NAN,
             width: 0,
             height: 0,
+            transform: [1.0, 0.0, 0.0, 1.0],
             error: ptr::null_mut(),
         };
         let ok = unsafe { videotoolbox_probe_total_frames(c_path.as_ptr(), &mut result) };
@@ -145,6 +151,12 @@ mod platform {
         if result.height > 0 {
             metadata.height = Some(result.height);
         }
+        let [a, b, c, d] = result.transform;
+        let rotation = display_rotation(a, b, c, d);
+        if !rotation.is_multiple_of(180) {
+            std::mem::swap(&mut metadata.width, &mut metadata.height);
+        }
+        metadata.rotation = Some(rotation);
 
         Ok(metadata)
     }
@@ -185,7 +197,18 @@ mod platform {
                     format!("input file {} does not exist", path.display()),
                 )));
             }
-            let metadata = probe_video_metadata(path)?;
+            let mut metadata = probe_video_metadata(path)?;
+            if let Some(fps) = config.fps_override {
+                metadata.fps = Some(fps);
+            }
+            // Pixel buffers are handed out as stored, so their size is the unrotated one.
+            if config.output_format == OutputFormat::CVPixelBuffer
+                && metadata
+                    .rotation
+                    .is_some_and(|rotation| !rotation.is_multiple_of(180))
+            {
+                std::mem::swap(&mut metadata.width, &mut metadata.height);
+            }
             let capacity = config
                 .channel_capacity
                 .map(|n| n.get())
@@ -195,30 +218,36 @@ mod platform {
                 input: path.to_path_buf(),
                 metadata,
                 channel_capacity: capacity,
+                queue_full_warning: config.queue_full_warning,
                 output_format: config.output_format,
                 start_frame: config.start_frame,
+                end_frame: config.end_frame,
             })
         }
 
         fn metadata(&self) -> crate::core::VideoMetadata {
-            self.metadata
+            self.metadata.clone()
         }
 
         fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
             let path = self.input.clone();
             let capacity = self.channel_capacity;
+            let queue_full_warning = self.queue_full_warning;
             let output_format = self.output_format;
             let start_frame = self.start_frame;
+            let end_frame = self.end_frame;
             let fps = self.metadata.fps;
+            let rotation = self.metadata.rotation.unwrap_or(0);
             let controller = DecoderController::new();
             let seek_rx = controller.seek_receiver();
             let serial = controller.serial_handle();
-            let stream = spawn_stream_from_channel(capacity, move |tx| {
+            let stream = spawn_stream_from_channel(capacity, queue_full_warning, move |tx| {
                 let result = match output_format {
-                    OutputFormat::Nv12 => decode_videotoolbox_nv12(
+                    OutputFormat::Nv12 | OutputFormat::I420 => decode_videotoolbox_nv12(
                         path.clone(),
                         tx.clone(),
                         start_frame,
+                        end_frame,
                         seek_rx,
                         serial.clone(),
                         fps,
@@ -227,6 +256,7 @@ mod platform {
                         path.clone(),
                         tx.clone(),
                         start_frame,
+                        end_frame,
                         seek_rx,
                         serial.clone(),
                         fps,
@@ -236,6 +266,13 @@ mod platform {
                     let _ = tx.blocking_send(Err(err));
                 }
             });
+            let stream = filter_stale_frames(stream, controller.serial_handle());
+            // VideoToolbox hands back biplanar buffers; I420 is repacked from them.
+            let stream = match output_format {
+                OutputFormat::I420 => rotate_frames(repack_i420(stream), rotation),
+                OutputFormat::Nv12 => rotate_frames(stream, rotation),
+                _ => stream,
+            };
             Ok((controller, stream))
         }
     }
@@ -244,12 +281,13 @@ mod platform {
         path: PathBuf,
         tx: mpsc::Sender<DecoderResult<VideoFrame>>,
         start_frame: Option<u64>,
+        end_frame: Option<u64>,
         seek_rx: SeekReceiver,
         serial: Arc<AtomicU64>,
         fps: Option<f64>,
     ) -> DecoderResult<()> {
         let c_path = cstring_from_path(&path)?;
-        let mut context = Box::new(DecodeContext::new(tx, seek_rx, serial, fps));
+        let mut context = Box::new(DecodeContext::new(tx, seek_rx, serial, fps, end_frame));
         let mut next_start_frame = start_frame;
 
         loop {
@@ -302,12 +340,13 @@ mod platform {
         path: PathBuf,
         tx: mpsc::Sender<DecoderResult<VideoFrame>>,
         start_frame: Option<u64>,
+        end_frame: Option<u64>,
         seek_rx: SeekReceiver,
         serial: Arc<AtomicU64>,
         fps: Option<f64>,
     ) -> DecoderResult<()> {
         let c_path = cstring_from_path(&path)?;
-        let mut context = Box::new(DecodeContext::new(tx, seek_rx, serial, fps));
+        let mut context = Box::new(DecodeContext::new(tx, seek_rx, serial, fps, end_frame));
         let mut next_start_frame = start_frame;
 
         loop {
@@ -363,10 +402,12 @@ mod platform {
         serial: Arc<AtomicU64>,
         current_serial: u64,
         pending_seek: Option<SeekPlan>,
-        pending_drop: Option<DropUntil>,
+        dropper: SeekDropper,
         seek_error: Option<DecoderError>,
         closed: bool,
         fps: Option<f64>,
+        end_frame: Option<u64>,
+        pool: FramePool,
     }
 
     impl DecodeContext {
@@ -375,6 +416,7 @@ mod platform {
             seek_rx: SeekReceiver,
             serial: Arc<AtomicU64>,
             fps: Option<f64>,
+            end_frame: Option<u64>,
         ) -> Self {
             let current_serial = serial.load(Ordering::SeqCst);
             Self {
@@ -383,10 +425,12 @@ mod platform {
                 serial,
                 current_serial,
                 pending_seek: None,
-                pending_drop: None,
+                dropper: SeekDropper::default(),
                 seek_error: None,
                 closed: false,
                 fps,
+                end_frame,
+                pool: FramePool::new(),
             }
         }
 
@@ -395,7 +439,7 @@ mod platform {
         }
 
         fn apply_drop(&mut self, drop_until: Option<DropUntil>) {
-            self.pending_drop = drop_until;
+            self.dropper.arm(drop_until);
         }
 
         fn take_pending_seek(&mut self) -> Option<SeekPlan> {
@@ -435,19 +479,15 @@ mod platform {
         }
 
         fn should_skip_frame(&mut self, index: u64, pts: Option<Duration>) -> bool {
-            let Some(drop_until) = self.pending_drop else {
-                return false;
-            };
-            let keep = match drop_until {
-                DropUntil::Frame(target) => index >= target,
-                DropUntil::Timestamp(target) => pts.map(|value| value >= target).unwrap_or(true),
-            };
-            if keep {
-                self.pending_drop = None;
-                false
-            } else {
-                true
+            self.dropper.should_drop(Some(index), pts)
+        }
+
+        /// Closes the decode once a frame lies past the configured end frame.
+        fn reached_end(&mut self, index: Option<u64>) -> bool {
+            if past_end_frame(self.end_frame, index) {
+                self.closed = true;
             }
+            self.closed
         }
     }
 
@@ -491,16 +531,19 @@ mod platform {
         if context.should_skip_frame(index.unwrap_or(frame.index), pts) {
             return true;
         }
+        if context.reached_end(index) {
+            return false;
+        }
 
-        let y_frame = match VideoFrame::from_nv12_owned(
+        let y_frame = match context.pool.nv12_frame(
             frame.width,
             frame.height,
             frame.y_stride,
             frame.uv_stride,
             pts,
             dts,
-            y_data.to_vec(),
-            uv_data.to_vec(),
+            context.pool.copy_of(y_data),
+            context.pool.copy_of(uv_data),
         ) {
             Ok(value) => value.with_index(index).with_serial(context.current_serial),
             Err(err) => {
@@ -564,6 +607,10 @@ mod platform {
             unsafe { release_native_handle(frame.pixel_buffer) };
             return true;
         }
+        if context.reached_end(index) {
+            unsafe { release_native_handle(frame.pixel_buffer) };
+            return false;
+        }
 
         let native_frame = match VideoFrame::from_native_handle(
             frame.width,
@@ -590,12 +637,6 @@ mod platform {
         true
     }
 
-    #[derive(Clone, Copy)]
-    enum DropUntil {
-        Frame(u64),
-        Timestamp(Duration),
-    }
-
     #[derive(Clone, Copy)]
     struct SeekPlan {
         start_frame: u64,
@@ -606,10 +647,7 @@ mod platform {
         match info {
             SeekInfo::Frame { frame, mode } => Ok(SeekPlan {
                 start_frame: frame,
-                drop_until: match mode {
-                    SeekMode::Fast => None,
-                    SeekMode::Accurate => Some(DropUntil::Frame(frame)),
-                },
+                drop_until: mode.drop_until(DropUntil::Frame(frame)),
             }),
             SeekInfo::Time { position, mode } => {
                 let fps = fps.ok_or_else(|| {
@@ -639,10 +677,7 @@ mod platform {
                 }
                 Ok(SeekPlan {
                     start_frame: frame as u64,
-                    drop_until: match mode {
-                        SeekMode::Fast => None,
-                        SeekMode::Accurate => Some(DropUntil::Timestamp(position)),
-                    },
+                    drop_until: mode.drop_until(DropUntil::Timestamp(position)),
                 })
             }
         }
@@ -22,6 +22,7 @@ typedef struct {
     double fps;
     uint32_t width;
     uint32_t height;
+    double transform[4];
     char *error;
 } VideoToolboxProbeResult;
 
@@ -271,6 +272,10 @@ bool videotoolbox_probe_total_frames(const char *path, VideoToolboxProbeResult *
     out_result->fps = NAN;
     out_result->width = 0;
     out_result->height = 0;
+    out_result->transform[0] = 1.0;
+    out_result->transform[1] = 0.0;
+    out_result->transform[2] = 0.0;
+    out_result->transform[3] = 1.0;
     out_result->error = NULL;
 
     @autoreleasepool {
@@ -326,6 +331,12 @@ bool videotoolbox_probe_total_frames(const char *path, VideoToolboxProbeResult *
             out_result->height = (uint32_t)llround(height);
         }
 
+        CGAffineTransform transform = track.preferredTransform;
+        out_result->transform[0] = transform.a;
+        out_result->transform[1] = transform.b;
+        out_result->transform[2] = transform.c;
+        out_result->transform[3] = transform.d;
+
         if (isfinite(duration_seconds) && duration_seconds > 0.0 && isfinite(fps) && fps > 0.0) {
             Float64 total = round(duration_seconds * fps);
             if (isfinite(total) && total > 0.0) {
@@ -3,15 +3,22 @@ use std::fmt;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 #[cfg(feature = "backend-ffmpeg")]
 use std::sync::OnceLock;
 
-use crate::core::{DecoderError, DecoderProvider, DecoderResult, DynDecoderProvider};
+use crate::core::{
+    DEFAULT_QUEUE_FULL_WARNING, DecoderError, DecoderProvider, DecoderResult, DynDecoderProvider,
+};
+use crate::reverse::ReverseReader;
+use crate::timestamps::TimestampSampler;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Backend {
     Mock,
+    ImageSequence,
+    RawYuv,
     #[cfg(feature = "backend-ffmpeg")]
     FFmpeg,
     #[cfg(all(feature = "backend-videotoolbox", target_os = "macos"))]
@@ -20,20 +27,64 @@ pub enum Backend {
     Dxva,
     #[cfg(all(feature = "backend-mft", target_os = "windows"))]
     Mft,
+    #[cfg(all(feature = "backend-vaapi", target_os = "linux"))]
+    Vaapi,
+    #[cfg(all(
+        feature = "backend-nvdec",
+        any(target_os = "linux", target_os = "windows")
+    ))]
+    Nvdec,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum OutputFormat {
     #[default]
     Nv12,
+    /// Planar Y, U and V; emitted by the FFmpeg, VideoToolbox and raw-yuv backends.
+    I420,
     CVPixelBuffer,
+    /// NV12 left in GPU memory as a native `CudaSurface` handle; NVDEC backend only.
+    CudaDevice,
 }
 
 impl OutputFormat {
     pub fn as_str(&self) -> &'static str {
         match self {
             OutputFormat::Nv12 => "nv12",
+            OutputFormat::I420 => "i420",
             OutputFormat::CVPixelBuffer => "cvpixelbuffer",
+            OutputFormat::CudaDevice => "cuda",
+        }
+    }
+}
+
+/// Pixel layout of headerless frames read by the raw-yuv backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RawFormat {
+    #[default]
+    Nv12,
+    I420,
+}
+
+impl RawFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RawFormat::Nv12 => "nv12",
+            RawFormat::I420 => "i420",
+        }
+    }
+}
+
+impl FromStr for RawFormat {
+    type Err = DecoderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nv12" => Ok(RawFormat::Nv12),
+            "i420" => Ok(RawFormat::I420),
+            other => Err(DecoderError::configuration(format!(
+                "unknown raw format '{other}'"
+            ))),
         }
     }
 }
@@ -44,6 +95,8 @@ impl FromStr for Backend {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_ascii_lowercase().as_str() {
             "mock" => Ok(Backend::Mock),
+            "image-sequence" => Ok(Backend::ImageSequence),
+            "raw-yuv" => Ok(Backend::RawYuv),
             #[cfg(feature = "backend-ffmpeg")]
             "ffmpeg" => Ok(Backend::FFmpeg),
             #[cfg(all(feature = "backend-videotoolbox", target_os = "macos"))]
@@ -52,6 +105,13 @@ impl FromStr for Backend {
             "dxva" => Ok(Backend::Dxva),
             #[cfg(all(feature = "backend-mft", target_os = "windows"))]
             "mft" => Ok(Backend::Mft),
+            #[cfg(all(feature = "backend-vaapi", target_os = "linux"))]
+            "vaapi" => Ok(Backend::Vaapi),
+            #[cfg(all(
+                feature = "backend-nvdec",
+                any(target_os = "linux", target_os = "windows")
+            ))]
+            "nvdec" => Ok(Backend::Nvdec),
             other => Err(DecoderError::configuration(format!(
                 "unknown backend '{other}'"
             ))),
@@ -63,6 +123,8 @@ impl Backend {
     pub fn as_str(&self) -> &'static str {
         match self {
             Backend::Mock => "mock",
+            Backend::ImageSequence => "image-sequence",
+            Backend::RawYuv => "raw-yuv",
             #[cfg(feature = "backend-ffmpeg")]
             Backend::FFmpeg => "ffmpeg",
             #[cfg(all(feature = "backend-videotoolbox", target_os = "macos"))]
@@ -71,12 +133,54 @@ impl Backend {
             Backend::Dxva => "dxva",
             #[cfg(all(feature = "backend-mft", target_os = "windows"))]
             Backend::Mft => "mft",
+            #[cfg(all(feature = "backend-vaapi", target_os = "linux"))]
+            Backend::Vaapi => "vaapi",
+            #[cfg(all(
+                feature = "backend-nvdec",
+                any(target_os = "linux", target_os = "windows")
+            ))]
+            Backend::Nvdec => "nvdec",
             #[allow(unreachable_patterns)]
             _ => "unsupported",
         }
     }
 }
 
+impl Backend {
+    /// Whether the backend decodes on a GPU or media engine rather than the CPU.
+    pub fn is_hardware(&self) -> bool {
+        match self {
+            #[cfg(all(feature = "backend-videotoolbox", target_os = "macos"))]
+            Backend::VideoToolbox => true,
+            #[cfg(all(feature = "backend-dxva", target_os = "windows"))]
+            Backend::Dxva => true,
+            #[cfg(all(feature = "backend-mft", target_os = "windows"))]
+            Backend::Mft => true,
+            #[cfg(all(feature = "backend-vaapi", target_os = "linux"))]
+            Backend::Vaapi => true,
+            #[cfg(all(
+                feature = "backend-nvdec",
+                any(target_os = "linux", target_os = "windows")
+            ))]
+            Backend::Nvdec => true,
+            _ => false,
+        }
+    }
+
+    /// Position in the default selection order; lower ranks are tried first.
+    fn default_rank(&self) -> u8 {
+        match self {
+            // Only compiled in on CI, where it stands in for every real decoder.
+            Backend::Mock => 0,
+            Backend::ImageSequence => 3,
+            #[allow(unreachable_patterns)]
+            backend if backend.is_hardware() => 1,
+            #[allow(unreachable_patterns)]
+            _ => 2,
+        }
+    }
+}
+
 impl fmt::Display for Backend {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.as_str())
@@ -89,6 +193,9 @@ fn compiled_backends() -> Vec<Backend> {
         backends.push(Backend::Mock);
     }
     append_platform_backends(&mut backends);
+    backends.push(Backend::ImageSequence);
+    // `Backend::RawYuv` is left out: it needs `raw_width`/`raw_height`, so it only runs when
+    // selected explicitly.
     backends
 }
 
@@ -108,6 +215,14 @@ fn append_platform_backends(_backends: &mut Vec<Backend>) {
 
 #[cfg(not(target_os = "macos"))]
 fn append_platform_backends(backends: &mut Vec<Backend>) {
+    // Opt-in, so a build that enables it prefers it over the other hardware decoders.
+    #[cfg(all(
+        feature = "backend-nvdec",
+        any(target_os = "linux", target_os = "windows")
+    ))]
+    {
+        backends.push(Backend::Nvdec);
+    }
     #[cfg(all(feature = "backend-mft", target_os = "windows"))]
     {
         backends.push(Backend::Mft);
@@ -116,6 +231,10 @@ fn append_platform_backends(backends: &mut Vec<Backend>) {
     {
         backends.push(Backend::Dxva);
     }
+    #[cfg(all(feature = "backend-vaapi", target_os = "linux"))]
+    {
+        backends.push(Backend::Vaapi);
+    }
     #[cfg(feature = "backend-ffmpeg")]
     {
         if ffmpeg_runtime_available() {
@@ -143,6 +262,31 @@ pub struct Configuration {
     pub channel_capacity: Option<NonZeroUsize>,
     pub output_format: OutputFormat,
     pub start_frame: Option<u64>,
+    /// Last frame index to decode, inclusive; the stream ends after it. `None` decodes to the end.
+    pub end_frame: Option<u64>,
+    /// Playback rate for inputs without their own timing, such as image sequences and raw
+    /// YUV.
+    pub frame_rate: Option<f64>,
+    /// Frame width of raw-yuv input, which has no header to probe.
+    pub raw_width: Option<u32>,
+    /// Frame height of raw-yuv input.
+    pub raw_height: Option<u32>,
+    /// Pixel layout of raw-yuv input.
+    pub raw_format: RawFormat,
+    /// Which video stream to decode, counting video streams only; `None` picks the default one.
+    pub video_stream_index: Option<usize>,
+    /// Replaces the frame rate probed from the container, for files whose metadata is wrong.
+    pub fps_override: Option<f64>,
+    /// Log a warning each time the decoded-frame queue stays full this long, which means the
+    /// consumer rather than the decoder limits throughput; `None` disables the check.
+    pub queue_full_warning: Option<Duration>,
+    /// Emit only the first frame at or after each of these times, seeking to each in order,
+    /// instead of decoding the whole input; see [`TimestampSampler`].
+    pub sample_timestamps: Option<Vec<Duration>>,
+    /// Emit frames in descending order from `start_frame` down to frame 0, decoding forward in
+    /// windows and replaying each backwards; see [`ReverseReader`]. Needs `start_frame` and
+    /// excludes `end_frame` and `sample_timestamps`.
+    pub reverse: bool,
 }
 
 impl Default for Configuration {
@@ -157,6 +301,16 @@ impl Default for Configuration {
             channel_capacity: None,
             output_format: OutputFormat::Nv12,
             start_frame: None,
+            end_frame: None,
+            frame_rate: None,
+            raw_width: None,
+            raw_height: None,
+            raw_format: RawFormat::default(),
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: Some(DEFAULT_QUEUE_FULL_WARNING),
+            sample_timestamps: None,
+            reverse: false,
         }
     }
 }
@@ -191,6 +345,57 @@ impl Configuration {
             })?;
             config.start_frame = Some(parsed);
         }
+        if let Ok(end_frame) = env::var("SUBFAST_END_FRAME") {
+            let parsed: u64 = end_frame.parse().map_err(|_| {
+                DecoderError::configuration(format!(
+                    "failed to parse SUBFAST_END_FRAME='{end_frame}' as a non-negative integer"
+                ))
+            })?;
+            config.end_frame = Some(parsed);
+        }
+        if let Ok(frame_rate) = env::var("SUBFAST_FRAME_RATE") {
+            let parsed: f64 = frame_rate.parse().map_err(|_| {
+                DecoderError::configuration(format!(
+                    "failed to parse SUBFAST_FRAME_RATE='{frame_rate}' as a number"
+                ))
+            })?;
+            config.frame_rate = Some(parsed);
+        }
+        if let Ok(width) = env::var("SUBFAST_RAW_WIDTH") {
+            let parsed: u32 = width.parse().map_err(|_| {
+                DecoderError::configuration(format!(
+                    "failed to parse SUBFAST_RAW_WIDTH='{width}' as a non-negative integer"
+                ))
+            })?;
+            config.raw_width = Some(parsed);
+        }
+        if let Ok(height) = env::var("SUBFAST_RAW_HEIGHT") {
+            let parsed: u32 = height.parse().map_err(|_| {
+                DecoderError::configuration(format!(
+                    "failed to parse SUBFAST_RAW_HEIGHT='{height}' as a non-negative integer"
+                ))
+            })?;
+            config.raw_height = Some(parsed);
+        }
+        if let Ok(format) = env::var("SUBFAST_RAW_FORMAT") {
+            config.raw_format = RawFormat::from_str(&format)?;
+        }
+        if let Ok(fps) = env::var("SUBFAST_FPS") {
+            let parsed: f64 = fps.parse().map_err(|_| {
+                DecoderError::configuration(format!(
+                    "failed to parse SUBFAST_FPS='{fps}' as a number"
+                ))
+            })?;
+            config.fps_override = Some(parsed);
+        }
+        if let Ok(stream) = env::var("SUBFAST_VIDEO_STREAM") {
+            let parsed: usize = stream.parse().map_err(|_| {
+                DecoderError::configuration(format!(
+                    "failed to parse SUBFAST_VIDEO_STREAM='{stream}' as a non-negative integer"
+                ))
+            })?;
+            config.video_stream_index = Some(parsed);
+        }
         Ok(config)
     }
 
@@ -198,9 +403,38 @@ impl Configuration {
         compiled_backends()
     }
 
+    /// Available backends in the order automatic selection tries them. By default hardware
+    /// decoders come first, then FFmpeg, then image sequences, each group in compiled order;
+    /// backends listed in `priority` move to the front in the listed order, and listed
+    /// backends that are not available are ignored.
+    pub fn backend_priority(priority: &[Backend]) -> Vec<Backend> {
+        prioritize(&compiled_backends(), priority, Backend::default_rank)
+    }
+
     pub fn create_provider(&self) -> DecoderResult<DynDecoderProvider> {
         self.validate_output_format()?;
+        self.validate_fps_override()?;
+        self.validate_frame_range()?;
+
+        if self.reverse {
+            let start_frame = self.validate_reverse()?;
+            // The reader seeks the backend itself, so the backend decodes without a range.
+            let backend_config = Configuration {
+                start_frame: None,
+                end_frame: None,
+                ..self.clone()
+            };
+            let provider = backend_config.create_backend_provider()?;
+            return Ok(Box::new(ReverseReader::wrap(provider, start_frame)));
+        }
+        let provider = self.create_backend_provider()?;
+        Ok(match self.sample_timestamps.clone() {
+            Some(timestamps) => Box::new(TimestampSampler::wrap(provider, timestamps)),
+            None => provider,
+        })
+    }
 
+    fn create_backend_provider(&self) -> DecoderResult<DynDecoderProvider> {
         match self.backend {
             Backend::Mock => {
                 if !github_ci_active() {
@@ -209,6 +443,12 @@ impl Configuration {
                     Ok(Box::new(crate::backends::mock::MockProvider::new(self)?))
                 }
             }
+            Backend::ImageSequence => Ok(Box::new(
+                crate::backends::image_sequence::ImageSequenceProvider::new(self)?,
+            )),
+            Backend::RawYuv => Ok(Box::new(crate::backends::raw_yuv::RawYuvProvider::new(
+                self,
+            )?)),
             #[cfg(feature = "backend-ffmpeg")]
             Backend::FFmpeg => Ok(Box::new(crate::backends::ffmpeg::FFmpegProvider::new(
                 self,
@@ -221,6 +461,13 @@ impl Configuration {
             Backend::Dxva => Ok(Box::new(crate::backends::dxva::DxvaProvider::new(self)?)),
             #[cfg(all(feature = "backend-mft", target_os = "windows"))]
             Backend::Mft => Ok(Box::new(crate::backends::mft::MftProvider::new(self)?)),
+            #[cfg(all(feature = "backend-vaapi", target_os = "linux"))]
+            Backend::Vaapi => Ok(Box::new(crate::backends::vaapi::VaapiProvider::new(self)?)),
+            #[cfg(all(
+                feature = "backend-nvdec",
+                any(target_os = "linux", target_os = "windows")
+            ))]
+            Backend::Nvdec => Ok(Box::new(crate::backends::nvdec::NvdecProvider::new(self)?)),
             #[allow(unreachable_patterns)]
             other => Err(DecoderError::unsupported(other.as_str())),
         }
@@ -228,9 +475,67 @@ impl Configuration {
 }
 
 impl Configuration {
+    fn validate_fps_override(&self) -> DecoderResult<()> {
+        match self.fps_override {
+            Some(fps) if !fps.is_finite() || fps <= 0.0 => Err(DecoderError::configuration(
+                format!("fps override must be positive and finite (got {fps})"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_frame_range(&self) -> DecoderResult<()> {
+        match (self.start_frame, self.end_frame) {
+            (Some(start), Some(end)) if end < start => Err(DecoderError::configuration(format!(
+                "end frame {end} is before start frame {start}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_reverse(&self) -> DecoderResult<u64> {
+        let start_frame = self.start_frame.ok_or_else(|| {
+            DecoderError::configuration("reverse decoding needs a start frame to read back from")
+        })?;
+        if self.end_frame.is_some() {
+            return Err(DecoderError::configuration(
+                "reverse decoding always ends at frame 0 and does not take an end frame",
+            ));
+        }
+        if self.sample_timestamps.is_some() {
+            return Err(DecoderError::configuration(
+                "reverse decoding cannot be combined with sample timestamps",
+            ));
+        }
+        Ok(start_frame)
+    }
+
     fn validate_output_format(&self) -> DecoderResult<()> {
         match self.output_format {
             OutputFormat::Nv12 => Ok(()),
+            OutputFormat::I420 => {
+                if self.backend == Backend::RawYuv {
+                    return Ok(());
+                }
+                #[cfg(feature = "backend-ffmpeg")]
+                {
+                    if self.backend == Backend::FFmpeg {
+                        return Ok(());
+                    }
+                }
+                #[cfg(all(feature = "backend-videotoolbox", target_os = "macos"))]
+                {
+                    if self.backend == Backend::VideoToolbox {
+                        return Ok(());
+                    }
+                }
+
+                Err(DecoderError::configuration(format!(
+                    "output format '{}' is only supported by ffmpeg, videotoolbox and raw-yuv backends (selected: {})",
+                    self.output_format.as_str(),
+                    self.backend.as_str()
+                )))
+            }
             OutputFormat::CVPixelBuffer => {
                 #[cfg(all(feature = "backend-videotoolbox", target_os = "macos"))]
                 {
@@ -245,8 +550,46 @@ impl Configuration {
                     self.backend.as_str()
                 )))
             }
+            OutputFormat::CudaDevice => {
+                #[cfg(all(
+                    feature = "backend-nvdec",
+                    any(target_os = "linux", target_os = "windows")
+                ))]
+                {
+                    if self.backend == Backend::Nvdec {
+                        return Ok(());
+                    }
+                }
+
+                Err(DecoderError::configuration(format!(
+                    "output format '{}' is only supported by nvdec backend (selected: {})",
+                    self.output_format.as_str(),
+                    self.backend.as_str()
+                )))
+            }
+        }
+    }
+}
+
+fn prioritize<T: Copy + PartialEq>(
+    available: &[T],
+    priority: &[T],
+    rank: impl Fn(&T) -> u8,
+) -> Vec<T> {
+    let mut ordered: Vec<T> = Vec::with_capacity(available.len());
+    for candidate in priority {
+        if available.contains(candidate) && !ordered.contains(candidate) {
+            ordered.push(*candidate);
         }
     }
+    let mut rest: Vec<T> = available
+        .iter()
+        .copied()
+        .filter(|candidate| !ordered.contains(candidate))
+        .collect();
+    rest.sort_by_key(|candidate| rank(candidate));
+    ordered.extend(rest);
+    ordered
 }
 
 fn default_backend() -> Backend {
@@ -265,3 +608,86 @@ fn github_ci_active() -> bool {
         .map(|value| !value.is_empty() && value != "false")
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in backends: (name, decodes in hardware).
+    const STUBS: [(&str, bool); 4] = [
+        ("software-a", false),
+        ("hardware-a", true),
+        ("software-b", false),
+        ("hardware-b", true),
+    ];
+
+    fn stub_rank(name: &&str) -> u8 {
+        let hardware = STUBS.iter().any(|(stub, hw)| stub == name && *hw);
+        if hardware { 0 } else { 1 }
+    }
+
+    fn names() -> Vec<&'static str> {
+        STUBS.iter().map(|(name, _)| *name).collect()
+    }
+
+    #[test]
+    fn hardware_backends_come_first_in_a_stable_order() {
+        assert_eq!(
+            prioritize(&names(), &[], stub_rank),
+            ["hardware-a", "hardware-b", "software-a", "software-b"]
+        );
+    }
+
+    #[test]
+    fn priority_override_leads_and_skips_unavailable_backends() {
+        assert_eq!(
+            prioritize(
+                &names(),
+                &["software-b", "missing", "hardware-b", "software-b"],
+                stub_rank
+            ),
+            ["software-b", "hardware-b", "hardware-a", "software-a"]
+        );
+    }
+
+    #[test]
+    fn image_sequences_are_tried_last_unless_listed() {
+        assert_eq!(
+            Configuration::backend_priority(&[]).last(),
+            Some(&Backend::ImageSequence)
+        );
+        assert_eq!(
+            Configuration::backend_priority(&[Backend::ImageSequence]).first(),
+            Some(&Backend::ImageSequence)
+        );
+    }
+
+    #[test]
+    fn end_frame_before_start_frame_is_rejected() {
+        let config = Configuration {
+            start_frame: Some(10),
+            end_frame: Some(9),
+            ..Configuration::default()
+        };
+        assert!(matches!(
+            config.create_provider(),
+            Err(DecoderError::Configuration { .. })
+        ));
+    }
+
+    #[test]
+    fn reverse_needs_a_start_frame_and_no_end_frame() {
+        for (start_frame, end_frame) in [(None, None), (Some(10), Some(20))] {
+            let config = Configuration {
+                reverse: true,
+                start_frame,
+                end_frame,
+                ..Configuration::default()
+            };
+            assert!(matches!(
+                config.create_provider(),
+                Err(DecoderError::Configuration { .. })
+            ));
+        }
+    }
+}
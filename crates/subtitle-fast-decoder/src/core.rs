@@ -1,27 +1,94 @@
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures_core::Stream;
+use futures_util::StreamExt;
+use futures_util::future::ready;
 use futures_util::stream::unfold;
-use tokio::sync::mpsc::{self, Sender};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::mpsc::{self, Sender, WeakSender};
 use tokio::sync::watch;
 
 pub use subtitle_fast_types::{
-    DecoderError, DecoderResult, FrameBuffer, NativeBuffer, Nv12Buffer, VideoFrame,
+    ColorPrimaries, ColorRange, ColorSpace, DecoderError, DecoderResult, FrameBuffer, I420Buffer,
+    NativeBuffer, Nv12Buffer, P010Buffer, PlaneRecycler, VideoFrame,
 };
 
 pub type FrameStream = Pin<Box<dyn Stream<Item = DecoderResult<VideoFrame>> + Send>>;
 
 pub type DynDecoderProvider = Box<dyn DecoderProvider>;
 
+/// How long the decode channel may stay full before a backpressure warning is logged.
+pub const DEFAULT_QUEUE_FULL_WARNING: Duration = Duration::from_secs(10);
+
+/// How far a seek goes past the keyframe it lands on.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SeekMode {
+    /// Emit from the nearest keyframe at or before the target.
     Fast,
+    /// Decode from that keyframe and drop frames until the target index or timestamp.
     Accurate,
 }
 
+impl SeekMode {
+    /// Drop target for a seek towards `target`; `None` for [`SeekMode::Fast`].
+    pub fn drop_until(self, target: DropUntil) -> Option<DropUntil> {
+        match self {
+            SeekMode::Fast => None,
+            SeekMode::Accurate => Some(target),
+        }
+    }
+}
+
+/// Position an accurate seek decodes up to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropUntil {
+    Frame(u64),
+    Timestamp(Duration),
+}
+
+/// Drops the frames decoded between a seek's keyframe and its target, shared by the backends
+/// so every one of them lands accurate seeks on the same frame.
+#[derive(Debug, Default)]
+pub struct SeekDropper {
+    pending: Option<DropUntil>,
+    dropped: u64,
+}
+
+impl SeekDropper {
+    /// Starts dropping towards `target` after a seek; `None` emits the next frame as is.
+    pub fn arm(&mut self, target: Option<DropUntil>) {
+        self.pending = target;
+    }
+
+    /// Whether the decoded frame precedes the pending target. Frames missing the index or
+    /// timestamp the target is expressed in are kept, and the first kept frame disarms it.
+    pub fn should_drop(&mut self, index: Option<u64>, pts: Option<Duration>) -> bool {
+        let Some(target) = self.pending else {
+            return false;
+        };
+        let keep = match target {
+            DropUntil::Frame(target) => index.is_none_or(|index| index >= target),
+            DropUntil::Timestamp(target) => pts.is_none_or(|pts| pts >= target),
+        };
+        if keep {
+            self.pending = None;
+        } else {
+            self.dropped += 1;
+        }
+        !keep
+    }
+
+    /// Frames dropped by every seek so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SeekInfo {
     Time { position: Duration, mode: SeekMode },
@@ -71,13 +138,27 @@ impl DecoderController {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
 pub struct VideoMetadata {
     pub duration: Option<Duration>,
     pub fps: Option<f64>,
     pub width: Option<u32>,
     pub height: Option<u32>,
     pub total_frames: Option<u64>,
+    /// Pixel format of the source stream as the backend names it, e.g. `yuv420p10le` for FFmpeg.
+    pub pixel_format: Option<String>,
+    /// Bits per luma sample in the source stream.
+    pub bit_depth: Option<u8>,
+    /// `None` when the backend cannot probe it or the source leaves it unspecified.
+    pub color_range: Option<ColorRange>,
+    /// `None` when the backend cannot probe it or the source leaves it unspecified.
+    pub color_primaries: Option<ColorPrimaries>,
+    /// Number of video streams in the input; `None` when the backend cannot enumerate them.
+    pub video_track_count: Option<usize>,
+    /// Clockwise rotation (0, 90, 180 or 270 degrees) the container asks for on display.
+    /// Backends that read it emit NV12/I420 frames already turned upright, and `width`/`height`
+    /// describe the upright frames; native-handle output stays as stored.
+    pub rotation: Option<u32>,
 }
 
 impl VideoMetadata {
@@ -114,6 +195,17 @@ impl VideoMetadata {
     }
 }
 
+/// One video stream in the input container, as reported by [`DecoderProvider::list_video_streams`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    /// Position among the video streams; this is what `Configuration::video_stream_index` selects.
+    pub index: usize,
+    pub codec: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+}
+
 pub trait DecoderProvider: Send + 'static {
     fn new(config: &crate::config::Configuration) -> crate::core::DecoderResult<Self>
     where
@@ -123,14 +215,33 @@ pub trait DecoderProvider: Send + 'static {
         VideoMetadata::default()
     }
 
+    /// Video streams available in the input. Backends that cannot enumerate streams return an
+    /// empty list and always decode the default one.
+    fn list_video_streams(&self) -> Vec<StreamInfo> {
+        Vec::new()
+    }
+
     fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)>;
 }
 
+/// Runs `task` on a blocking thread and streams the frames it sends. When `queue_full_warning`
+/// is set, a warning is logged each time the channel has stayed full that long.
 pub fn spawn_stream_from_channel(
     capacity: usize,
+    queue_full_warning: Option<Duration>,
     task: impl FnOnce(Sender<DecoderResult<VideoFrame>>) + Send + 'static,
 ) -> FrameStream {
     let (tx, rx) = mpsc::channel(capacity);
+    if let Some(threshold) = queue_full_warning {
+        watch_queue(&tx, threshold, |full_for, capacity| {
+            eprintln!(
+                "decoder queue has been full ({capacity} frames) for {:.1}s; decoding is \
+                 waiting on the stages consuming frames (sampling and detection, or OCR further \
+                 downstream)",
+                full_for.as_secs_f64()
+            );
+        });
+    }
     tokio::task::spawn_blocking(move || task(tx));
     let stream = unfold(rx, |mut receiver| async {
         receiver.recv().await.map(|item| (item, receiver))
@@ -138,6 +249,309 @@ pub fn spawn_stream_from_channel(
     Box::pin(stream)
 }
 
+/// Tracks how long a channel has stayed full and decides when to warn: once the queue has
+/// been full for `threshold`, then again after each further `threshold` it stays full.
+#[derive(Debug)]
+pub(crate) struct QueueMonitor {
+    threshold: Duration,
+    full_since: Option<Instant>,
+    last_warning: Option<Instant>,
+}
+
+impl QueueMonitor {
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            full_since: None,
+            last_warning: None,
+        }
+    }
+
+    /// Records the channel occupancy at `now`; returns how long it has been full when a
+    /// warning is due.
+    pub(crate) fn observe(
+        &mut self,
+        occupancy: usize,
+        capacity: usize,
+        now: Instant,
+    ) -> Option<Duration> {
+        if occupancy < capacity {
+            self.full_since = None;
+            self.last_warning = None;
+            return None;
+        }
+        let full_since = *self.full_since.get_or_insert(now);
+        let quiet_since = self.last_warning.unwrap_or(full_since);
+        if now.duration_since(quiet_since) < self.threshold {
+            return None;
+        }
+        self.last_warning = Some(now);
+        Some(now.duration_since(full_since))
+    }
+}
+
+/// Polls the occupancy of the channel behind `sender` until every strong sender is gone,
+/// calling `warn` with the time spent full and the capacity whenever [`QueueMonitor`] says so.
+pub(crate) fn watch_queue<T: Send + 'static>(
+    sender: &Sender<T>,
+    threshold: Duration,
+    warn: impl Fn(Duration, usize) + Send + 'static,
+) {
+    let weak: WeakSender<T> = sender.downgrade();
+    let poll = (threshold / 4).max(Duration::from_millis(10));
+    tokio::spawn(async move {
+        let mut monitor = QueueMonitor::new(threshold);
+        let mut ticks = tokio::time::interval(poll);
+        loop {
+            ticks.tick().await;
+            let Some(sender) = weak.upgrade() else {
+                break;
+            };
+            let capacity = sender.max_capacity();
+            let occupancy = capacity - sender.capacity();
+            drop(sender);
+            if let Some(full_for) = monitor.observe(occupancy, capacity, Instant::now()) {
+                warn(full_for, capacity);
+            }
+        }
+    });
+}
+
+/// Drops frames stamped with a serial older than the controller's current one, so frames decoded
+/// from the previous position cannot leak through after a seek.
+pub fn filter_stale_frames(stream: FrameStream, serial: Arc<AtomicU64>) -> FrameStream {
+    Box::pin(stream.filter(move |item| {
+        let keep = match item {
+            Ok(frame) => frame.serial() >= serial.load(Ordering::SeqCst),
+            Err(_) => true,
+        };
+        ready(keep)
+    }))
+}
+
+/// Repacks an NV12 frame's interleaved UV plane into separate U and V planes. The Y plane is
+/// copied as is; the chroma planes come out without row padding.
+pub fn nv12_to_i420(frame: &VideoFrame) -> DecoderResult<VideoFrame> {
+    let FrameBuffer::Nv12(buffer) = frame.buffer() else {
+        return Err(DecoderError::InvalidFrame {
+            reason: "only NV12 frames can be repacked as I420".into(),
+        });
+    };
+    let (chroma_width, chroma_rows) = chroma_size(frame);
+    let uv_stride = buffer.uv_stride();
+    if uv_stride < chroma_width * 2 {
+        return Err(DecoderError::InvalidFrame {
+            reason: format!("NV12 UV stride {uv_stride} is narrower than the frame"),
+        });
+    }
+    let mut u_plane = Vec::with_capacity(chroma_width * chroma_rows);
+    let mut v_plane = Vec::with_capacity(chroma_width * chroma_rows);
+    for row in 0..chroma_rows {
+        let start = row * uv_stride;
+        for pair in buffer.uv_plane()[start..start + chroma_width * 2].chunks_exact(2) {
+            u_plane.push(pair[0]);
+            v_plane.push(pair[1]);
+        }
+    }
+    VideoFrame::from_i420_owned(
+        frame.width(),
+        frame.height(),
+        buffer.y_stride(),
+        chroma_width,
+        chroma_width,
+        frame.pts(),
+        frame.dts(),
+        buffer.y_plane().to_vec(),
+        u_plane,
+        v_plane,
+    )
+    .map(|converted| {
+        converted
+            .with_serial(frame.serial())
+            .with_index(frame.index())
+    })
+}
+
+/// Interleaves an I420 frame's U and V planes back into NV12's single UV plane.
+pub fn i420_to_nv12(frame: &VideoFrame) -> DecoderResult<VideoFrame> {
+    let Some(buffer) = frame.i420() else {
+        return Err(DecoderError::InvalidFrame {
+            reason: "only I420 frames can be repacked as NV12".into(),
+        });
+    };
+    let (chroma_width, chroma_rows) = chroma_size(frame);
+    let (u_stride, v_stride) = (buffer.u_stride(), buffer.v_stride());
+    if u_stride < chroma_width || v_stride < chroma_width {
+        return Err(DecoderError::InvalidFrame {
+            reason: format!(
+                "I420 chroma strides {u_stride}/{v_stride} are narrower than the frame"
+            ),
+        });
+    }
+    let mut uv_plane = Vec::with_capacity(chroma_width * 2 * chroma_rows);
+    for row in 0..chroma_rows {
+        let u_row = &buffer.u_plane()[row * u_stride..][..chroma_width];
+        let v_row = &buffer.v_plane()[row * v_stride..][..chroma_width];
+        for (&u, &v) in u_row.iter().zip(v_row) {
+            uv_plane.extend_from_slice(&[u, v]);
+        }
+    }
+    VideoFrame::from_nv12_owned(
+        frame.width(),
+        frame.height(),
+        buffer.y_stride(),
+        chroma_width * 2,
+        frame.pts(),
+        frame.dts(),
+        buffer.y_plane().to_vec(),
+        uv_plane,
+    )
+    .map(|converted| {
+        converted
+            .with_serial(frame.serial())
+            .with_index(frame.index())
+    })
+}
+
+/// Repacks every NV12 frame of `stream` as I420, for backends whose decoder only emits NV12.
+pub fn repack_i420(stream: FrameStream) -> FrameStream {
+    Box::pin(stream.map(|item| item.and_then(|frame| nv12_to_i420(&frame))))
+}
+
+/// Rotates every frame of `stream` clockwise by `degrees`, for backends whose source is stored
+/// sideways and carries its display rotation as metadata.
+pub fn rotate_frames(stream: FrameStream, degrees: u32) -> FrameStream {
+    if degrees == 0 {
+        return stream;
+    }
+    Box::pin(stream.map(move |item| item.and_then(|frame| frame.rotate(degrees))))
+}
+
+/// Clockwise display rotation encoded by the linear part of a display matrix, laid out as in
+/// MP4 (`a b` on the first row, `c d` on the second), snapped to the nearest quarter turn.
+/// Mirroring and scaling are ignored.
+pub fn display_rotation(a: f64, b: f64, c: f64, d: f64) -> u32 {
+    let scale_x = a.hypot(c);
+    let scale_y = b.hypot(d);
+    if scale_x == 0.0 || scale_y == 0.0 {
+        return 0;
+    }
+    let degrees = (b / scale_y).atan2(a / scale_x).to_degrees();
+    let quarter_turns = (degrees / 90.0).round() as i64;
+    (quarter_turns.rem_euclid(4) * 90) as u32
+}
+
+/// Width and height of a 4:2:0 chroma plane in samples.
+fn chroma_size(frame: &VideoFrame) -> (usize, usize) {
+    (
+        (frame.width() as usize).div_ceil(2),
+        (frame.height() as usize).div_ceil(2),
+    )
+}
+
+/// Idle buffers a [`FramePool`] keeps per plane size; further returned buffers are freed.
+const MAX_POOLED_PER_SIZE: usize = 32;
+
+/// Recycles NV12 plane allocations between decoded frames. Backends fill planes taken from the
+/// pool and build frames with [`FramePool::nv12_frame`]; when the pipeline drops the last clone
+/// of a frame, its planes come back here. A stream in steady state therefore allocates about
+/// as many planes as it has frames in flight (decoder queue plus sampler history) rather than
+/// two per decoded frame. Clones share the same buffers.
+#[derive(Clone, Default)]
+pub struct FramePool {
+    inner: Arc<PoolInner>,
+}
+
+/// Allocation counters of a [`FramePool`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FramePoolStats {
+    /// Buffers allocated because none of the requested size was idle.
+    pub allocated: u64,
+    /// Buffers handed out again after a frame returned them.
+    pub reused: u64,
+}
+
+#[derive(Default)]
+struct PoolInner {
+    idle: Mutex<HashMap<usize, Vec<Vec<u8>>>>,
+    allocated: AtomicU64,
+    reused: AtomicU64,
+}
+
+impl PlaneRecycler for PoolInner {
+    fn recycle(&self, mut plane: Vec<u8>) {
+        plane.clear();
+        let mut idle = self.idle.lock();
+        let bucket = idle.entry(plane.capacity()).or_default();
+        if bucket.len() < MAX_POOLED_PER_SIZE {
+            bucket.push(plane);
+        }
+    }
+}
+
+impl FramePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty buffer with capacity for exactly `len` bytes.
+    pub fn take(&self, len: usize) -> Vec<u8> {
+        if let Some(plane) = self.inner.idle.lock().get_mut(&len).and_then(Vec::pop) {
+            self.inner.reused.fetch_add(1, Ordering::Relaxed);
+            return plane;
+        }
+        self.inner.allocated.fetch_add(1, Ordering::Relaxed);
+        Vec::with_capacity(len)
+    }
+
+    /// A pooled copy of `data`, in place of `data.to_vec()`.
+    pub fn copy_of(&self, data: &[u8]) -> Vec<u8> {
+        let mut plane = self.take(data.len());
+        plane.extend_from_slice(data);
+        plane
+    }
+
+    /// Builds an NV12 frame like [`VideoFrame::from_nv12_owned`] whose planes return to this
+    /// pool once the frame is dropped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn nv12_frame(
+        &self,
+        width: u32,
+        height: u32,
+        y_stride: usize,
+        uv_stride: usize,
+        pts: Option<Duration>,
+        dts: Option<Duration>,
+        y_plane: Vec<u8>,
+        uv_plane: Vec<u8>,
+    ) -> DecoderResult<VideoFrame> {
+        VideoFrame::from_nv12_recycled(
+            width,
+            height,
+            y_stride,
+            uv_stride,
+            pts,
+            dts,
+            y_plane,
+            uv_plane,
+            self.inner.clone(),
+        )
+    }
+
+    pub fn stats(&self) -> FramePoolStats {
+        FramePoolStats {
+            allocated: self.inner.allocated.load(Ordering::Relaxed),
+            reused: self.inner.reused.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Whether a frame lies after the configured end of the decode range, at which point backends
+/// stop decoding and close the channel. Frames without an index never end the range.
+pub fn past_end_frame(end_frame: Option<u64>, index: Option<u64>) -> bool {
+    matches!((end_frame, index), (Some(end), Some(index)) if index > end)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,9 +581,133 @@ mod tests {
         assert_eq!(frame.index(), None);
     }
 
+    #[test]
+    fn frame_pool_reuses_planes_of_dropped_frames() {
+        let pool = FramePool::new();
+        let frame = pool
+            .nv12_frame(4, 2, 4, 4, None, None, pool.copy_of(&[7; 8]), pool.take(4))
+            .unwrap_err();
+        // The UV plane was left empty, so the frame is rejected and its planes are not pooled.
+        assert!(frame.to_string().contains("NV12 UV"));
+
+        let frame = pool
+            .nv12_frame(
+                4,
+                2,
+                4,
+                4,
+                None,
+                None,
+                pool.copy_of(&[7; 8]),
+                pool.copy_of(&[128; 4]),
+            )
+            .unwrap();
+        let y_address = frame.y_plane().as_ptr();
+        let clone = frame.clone();
+        drop(frame);
+        assert_eq!(pool.take(8).capacity(), 8);
+        assert_eq!(
+            pool.stats().reused,
+            0,
+            "a live clone keeps the planes out of the pool"
+        );
+
+        drop(clone);
+        let reused = pool.take(8);
+        assert_eq!(reused.as_ptr(), y_address);
+        assert!(reused.is_empty());
+        assert_eq!(
+            pool.stats(),
+            FramePoolStats {
+                allocated: 5,
+                reused: 1
+            }
+        );
+    }
+
+    #[test]
+    fn p010_frames_validate_planes_and_expose_an_8_bit_luma_view() {
+        // 10-bit values 940 and 64 sit in the high bits: 940 << 6 and 64 << 6.
+        let luma = [940u16 << 6, 64 << 6, 0, 0];
+        let frame =
+            VideoFrame::from_p010_owned(2, 2, 2, 2, None, None, luma.to_vec(), vec![512 << 6; 2])
+                .unwrap();
+        let buffer = frame.p010().expect("p010 buffer");
+        assert_eq!(buffer.y_plane().len(), 4);
+        assert_eq!(buffer.uv_plane().len(), 2);
+        assert_eq!(frame.y_plane(), &[235, 16, 0, 0]);
+        assert_eq!(frame.stride(), 2);
+        assert!(matches!(frame.buffer(), FrameBuffer::P010(_)));
+
+        let short = VideoFrame::from_p010_owned(2, 2, 2, 2, None, None, vec![0; 3], vec![0; 2]);
+        let err = short.expect_err("Y plane too short");
+        assert!(
+            err.to_string()
+                .contains("insufficient P010 Y plane samples")
+        );
+        let short = VideoFrame::from_p010_owned(4, 2, 4, 4, None, None, vec![0; 8], vec![0; 3]);
+        assert!(short.is_err());
+    }
+
+    #[test]
+    fn queue_monitor_warns_once_per_threshold_while_full() {
+        let threshold = Duration::from_secs(2);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut monitor = QueueMonitor::new(threshold);
+
+        assert_eq!(monitor.observe(3, 4, at(0)), None);
+        assert_eq!(monitor.observe(4, 4, at(1)), None);
+        assert_eq!(monitor.observe(4, 4, at(2)), None);
+        assert_eq!(monitor.observe(4, 4, at(3)), Some(Duration::from_secs(2)));
+        assert_eq!(monitor.observe(4, 4, at(4)), None);
+        assert_eq!(monitor.observe(4, 4, at(5)), Some(Duration::from_secs(4)));
+        // Draining the queue resets the clock.
+        assert_eq!(monitor.observe(1, 4, at(6)), None);
+        assert_eq!(monitor.observe(4, 4, at(7)), None);
+        assert_eq!(monitor.observe(4, 4, at(9)), Some(threshold));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blocked_consumer_triggers_queue_full_warning() {
+        let threshold = Duration::from_millis(100);
+        let (tx, mut rx) = mpsc::channel::<u32>(2);
+        let (warn_tx, mut warnings) = mpsc::unbounded_channel();
+        let started = Instant::now();
+        watch_queue(&tx, threshold, move |full_for, capacity| {
+            let _ = warn_tx.send((full_for, capacity));
+        });
+        // Fast producer: fills the queue at once, then stays blocked on the consumer.
+        let producer = tokio::task::spawn_blocking(move || {
+            for value in 0.. {
+                if tx.blocking_send(value).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (full_for, capacity) = tokio::time::timeout(Duration::from_secs(5), warnings.recv())
+            .await
+            .expect("warning within the timeout")
+            .expect("watcher still running");
+        assert!(started.elapsed() >= threshold);
+        assert!(full_for >= threshold);
+        assert_eq!(capacity, 2);
+
+        // Closing the receiver ends the producer, which drops the last sender and stops the
+        // watcher.
+        rx.close();
+        producer.await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while warnings.recv().await.is_some() {}
+        })
+        .await
+        .expect("watcher exits once the senders are gone");
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn spawn_stream_from_channel_pushes_values() {
-        let stream = spawn_stream_from_channel(2, move |tx| {
+        let stream = spawn_stream_from_channel(2, None, move |tx| {
             tx.blocking_send(Ok(VideoFrame::from_nv12_owned(
                 2,
                 2,
@@ -187,4 +725,271 @@ mod tests {
         let frame = stream.next().await.unwrap().unwrap();
         assert_eq!(frame.data(), &[1, 2, 3, 4]);
     }
+
+    #[test]
+    fn seek_dropper_counts_frames_before_the_target() {
+        let mut dropper = SeekDropper::default();
+        dropper.arm(SeekMode::Accurate.drop_until(DropUntil::Frame(14)));
+        let dropped: Vec<u64> = (12..17)
+            .filter(|&index| dropper.should_drop(Some(index), None))
+            .collect();
+        assert_eq!(dropped, [12, 13]);
+
+        let ms = |value: u64| Some(Duration::from_millis(value));
+        dropper.arm(SeekMode::Accurate.drop_until(DropUntil::Timestamp(Duration::from_millis(40))));
+        assert!(dropper.should_drop(None, ms(20)));
+        assert!(!dropper.should_drop(None, ms(40)));
+        assert!(!dropper.should_drop(None, ms(0)));
+
+        dropper.arm(SeekMode::Fast.drop_until(DropUntil::Frame(14)));
+        assert!(!dropper.should_drop(Some(12), None));
+        assert_eq!(dropper.dropped(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stale_serial_frames_are_filtered_after_seek() {
+        let controller = DecoderController::new();
+        let _seek_rx = controller.seek_receiver();
+        let stream = spawn_stream_from_channel(4, None, move |tx| {
+            for (index, serial) in [(0u64, 0u64), (1, 0), (40, 1), (41, 1)] {
+                let frame =
+                    VideoFrame::from_nv12_owned(2, 2, 2, 2, None, None, vec![0; 4], vec![128; 2])
+                        .unwrap()
+                        .with_index(Some(index))
+                        .with_serial(serial);
+                tx.blocking_send(Ok(frame)).unwrap();
+            }
+        });
+        let seek_serial = controller
+            .seek(SeekInfo::Frame {
+                frame: 40,
+                mode: SeekMode::Accurate,
+            })
+            .unwrap();
+        assert_eq!(seek_serial, 1);
+
+        let frames: Vec<VideoFrame> = filter_stale_frames(stream, controller.serial_handle())
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+        let indices: Vec<Option<u64>> = frames.iter().map(VideoFrame::index).collect();
+        assert_eq!(indices, vec![Some(40), Some(41)]);
+        assert!(frames.iter().all(|frame| frame.serial() == seek_serial));
+    }
+
+    #[test]
+    fn nv12_round_trips_through_i420() {
+        // 3x3 pixels: 2x2 chroma samples, with padding after every row of both planes.
+        let y_plane: Vec<u8> = (0..12).collect();
+        let uv_plane = vec![10, 20, 11, 21, 0, 0, 12, 22, 13, 23, 0, 0];
+        let nv12 = VideoFrame::from_nv12_owned(
+            3,
+            3,
+            4,
+            6,
+            Some(Duration::from_millis(40)),
+            None,
+            y_plane.clone(),
+            uv_plane,
+        )
+        .unwrap()
+        .with_index(Some(7))
+        .with_serial(2);
+
+        let i420 = nv12_to_i420(&nv12).unwrap();
+        let planes = i420.i420().expect("I420 output");
+        assert_eq!(planes.u_plane(), &[10, 11, 12, 13]);
+        assert_eq!(planes.v_plane(), &[20, 21, 22, 23]);
+        assert_eq!((planes.u_stride(), planes.v_stride()), (2, 2));
+        assert_eq!(i420.y_plane(), y_plane.as_slice());
+        assert_eq!(i420.y_stride(), 4);
+        assert_eq!(i420.index(), Some(7));
+        assert_eq!(i420.serial(), 2);
+        assert_eq!(i420.pts(), Some(Duration::from_millis(40)));
+
+        let back = i420_to_nv12(&i420).unwrap();
+        assert_eq!(back.uv_plane(), &[10, 20, 11, 21, 12, 22, 13, 23]);
+        assert_eq!(back.uv_stride(), 4);
+        assert_eq!(back.y_plane(), nv12.y_plane());
+        assert!(nv12_to_i420(&i420).is_err());
+    }
+
+    fn rotation_source() -> VideoFrame {
+        // 4x2 luma numbered in raster order; two chroma pairs (U, V) = (1, 2) and (3, 4).
+        VideoFrame::from_nv12_owned(
+            4,
+            2,
+            4,
+            4,
+            Some(Duration::from_millis(40)),
+            None,
+            vec![0, 1, 2, 3, 4, 5, 6, 7],
+            vec![1, 2, 3, 4],
+        )
+        .unwrap()
+        .with_index(Some(3))
+        .with_serial(5)
+    }
+
+    #[test]
+    fn quarter_turns_swap_dimensions_and_move_luma() {
+        let frame = rotation_source();
+
+        let clockwise = frame.rotate(90).unwrap();
+        assert_eq!((clockwise.width(), clockwise.height()), (2, 4));
+        assert_eq!(clockwise.y_plane(), &[4, 0, 5, 1, 6, 2, 7, 3]);
+        assert_eq!(clockwise.uv_plane(), &[1, 2, 3, 4]);
+        assert_eq!((clockwise.y_stride(), clockwise.uv_stride()), (2, 2));
+        assert_eq!(clockwise.index(), Some(3));
+        assert_eq!(clockwise.serial(), 5);
+        assert_eq!(clockwise.pts(), Some(Duration::from_millis(40)));
+
+        let counter = frame.rotate(270).unwrap();
+        assert_eq!((counter.width(), counter.height()), (2, 4));
+        assert_eq!(counter.y_plane(), &[3, 7, 2, 6, 1, 5, 0, 4]);
+        assert_eq!(counter.uv_plane(), &[3, 4, 1, 2]);
+
+        let upside_down = frame.rotate(180).unwrap();
+        assert_eq!((upside_down.width(), upside_down.height()), (4, 2));
+        assert_eq!(upside_down.y_plane(), &[7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(upside_down.uv_plane(), &[3, 4, 1, 2]);
+
+        let planar = nv12_to_i420(&frame).unwrap().rotate(90).unwrap();
+        let planes = planar.i420().expect("rotation keeps I420");
+        assert_eq!(planar.y_plane(), clockwise.y_plane());
+        assert_eq!(
+            (planes.u_plane(), planes.v_plane()),
+            (&[1, 3][..], &[2, 4][..])
+        );
+
+        assert!(frame.rotate(45).is_err());
+    }
+
+    #[test]
+    fn display_matrix_rotation_snaps_to_quarter_turns() {
+        assert_eq!(display_rotation(1.0, 0.0, 0.0, 1.0), 0);
+        // What phones write for portrait recordings.
+        assert_eq!(display_rotation(0.0, 1.0, -1.0, 0.0), 90);
+        assert_eq!(display_rotation(-1.0, 0.0, 0.0, -1.0), 180);
+        assert_eq!(display_rotation(0.0, -1.0, 1.0, 0.0), 270);
+        // Fixed-point matrices scale uniformly and round to the nearest turn.
+        assert_eq!(display_rotation(1.0, 65536.0, -65536.0, 1.0), 90);
+        assert_eq!(display_rotation(0.0, 0.0, 0.0, 0.0), 0);
+    }
+
+    #[test]
+    fn solid_gray_converts_to_equal_rgb_channels() {
+        // Limited-range luma 126 is mid-gray: 1.164 * (126 - 16) ~= 128.
+        let frame = VideoFrame::from_nv12_owned(4, 2, 4, 4, None, None, vec![126; 8], vec![128; 4])
+            .unwrap();
+        for color_space in [ColorSpace::Bt601, ColorSpace::Bt709] {
+            let rgb = frame.to_rgb24(color_space).unwrap();
+            assert_eq!(rgb.len(), 4 * 2 * 3);
+            assert!(rgb.iter().all(|&value| value.abs_diff(128) <= 1), "{rgb:?}");
+
+            let bgra = frame.to_bgra(color_space).unwrap();
+            assert_eq!(bgra.len(), 4 * 2 * 4);
+            assert!(bgra.chunks_exact(4).all(|pixel| pixel[3] == u8::MAX));
+        }
+        let planar = nv12_to_i420(&frame).unwrap();
+        assert_eq!(
+            planar.to_rgb24(ColorSpace::Bt601).unwrap(),
+            frame.to_rgb24(ColorSpace::Bt601).unwrap()
+        );
+    }
+
+    #[test]
+    fn native_frames_refuse_rgb_conversion() {
+        unsafe extern "C" fn release(_: *mut std::ffi::c_void) {}
+
+        let frame = VideoFrame::from_native_handle(
+            2,
+            2,
+            None,
+            None,
+            None,
+            "test",
+            0,
+            std::ptr::NonNull::<u8>::dangling().as_ptr().cast(),
+            release,
+        )
+        .unwrap();
+        assert!(matches!(
+            frame.to_rgb24(ColorSpace::Bt709),
+            Err(DecoderError::InvalidFrame { .. })
+        ));
+    }
+
+    /// A `width` x `height` frame whose luma is `row * 16 + column` and whose chroma pairs are
+    /// `(column, row)` in chroma coordinates.
+    fn numbered_frame(width: u32, height: u32) -> VideoFrame {
+        let (w, h) = (width as usize, height as usize);
+        let y_plane = (0..h)
+            .flat_map(|row| (0..w).map(move |column| (row * 16 + column) as u8))
+            .collect();
+        let uv_plane = (0..h.div_ceil(2))
+            .flat_map(|row| (0..w.div_ceil(2)).flat_map(move |column| [column as u8, row as u8]))
+            .collect();
+        VideoFrame::from_nv12_owned(
+            width,
+            height,
+            w,
+            w.div_ceil(2) * 2,
+            Some(Duration::from_millis(500)),
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap()
+        .with_index(Some(30))
+    }
+
+    fn roi(x: f32, y: f32, width: f32, height: f32) -> subtitle_fast_types::RoiConfig {
+        subtitle_fast_types::RoiConfig {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn crop_widens_odd_edges_to_whole_chroma_samples() {
+        let frame = numbered_frame(6, 6);
+        // Pixels 1..5 horizontally and 3..4 vertically widen to 0..6 and 2..4.
+        let crop = frame.crop_nv12(&roi(1.0 / 6.0, 0.5, 0.6, 0.1)).unwrap();
+        assert_eq!((crop.width(), crop.height()), (6, 2));
+        assert_eq!(&crop.y_plane()[..6], &[32, 33, 34, 35, 36, 37]);
+        assert_eq!(crop.uv_plane(), &[0, 1, 1, 1, 2, 1]);
+        assert_eq!(crop.pts(), Some(Duration::from_millis(500)));
+        assert_eq!(crop.index(), Some(30));
+    }
+
+    #[test]
+    fn crop_keeps_the_last_column_and_row_of_odd_sized_frames() {
+        let frame = numbered_frame(5, 5);
+        let crop = frame.crop_nv12(&roi(0.5, 0.5, 0.5, 0.5)).unwrap();
+        // Starts round down to pixel 2; the ends stop at the frame's odd edge.
+        assert_eq!((crop.width(), crop.height()), (3, 3));
+        assert_eq!(crop.y_stride(), 3);
+        assert_eq!(&crop.y_plane()[..3], &[34, 35, 36]);
+        assert_eq!(&crop.y_plane()[6..], &[66, 67, 68]);
+        assert_eq!(crop.uv_stride(), 4);
+        assert_eq!(crop.uv_plane(), &[1, 1, 2, 1, 1, 2, 2, 2]);
+    }
+
+    #[test]
+    fn crop_rejects_rois_without_area() {
+        let frame = numbered_frame(8, 8);
+        for empty in [
+            roi(0.25, 0.25, 0.0, 0.5),
+            roi(0.25, 0.25, 0.5, -0.1),
+            roi(1.5, 0.0, 0.5, 1.0),
+        ] {
+            assert!(matches!(
+                frame.crop_nv12(&empty),
+                Err(DecoderError::InvalidFrame { .. })
+            ));
+        }
+    }
 }
@@ -0,0 +1,91 @@
+use futures_util::stream::unfold;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::core::{
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, FrameStream, VideoFrame,
+    VideoMetadata,
+};
+
+const EXTERNAL_BACKEND: &str = "external";
+
+/// Decoder provider whose frames are pushed by the caller instead of decoded from a file, for
+/// applications that already capture or decode NV12 buffers themselves.
+///
+/// Frames reach the pipeline in the order they are pushed; the pipeline's sorter still
+/// reorders them by index or timestamp, so frames should carry a pts or an index. The channel
+/// holds at most `capacity` frames: once the pipeline falls behind, [`ExternalFrameSender::push`]
+/// blocks until a slot frees up, which throttles the producer to the pipeline's pace. The
+/// stream ends after every sender has been closed or dropped.
+pub struct ExternalFrameProvider {
+    receiver: Receiver<DecoderResult<VideoFrame>>,
+    metadata: VideoMetadata,
+}
+
+/// Producer side of an [`ExternalFrameProvider`]; clone it to push from several threads.
+#[derive(Clone)]
+pub struct ExternalFrameSender {
+    sender: Sender<DecoderResult<VideoFrame>>,
+}
+
+impl ExternalFrameProvider {
+    /// Creates a provider and the sender feeding it, buffering up to `capacity` frames.
+    pub fn channel(capacity: usize) -> (Self, ExternalFrameSender) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let provider = Self {
+            receiver,
+            metadata: VideoMetadata::default(),
+        };
+        (provider, ExternalFrameSender { sender })
+    }
+
+    /// Reports `metadata` (frame rate, expected frame count) to the pipeline, which otherwise
+    /// estimates the frame rate from timestamps.
+    pub fn with_metadata(mut self, metadata: VideoMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+impl ExternalFrameSender {
+    /// Queues a frame, blocking while the channel is full. Call it from a plain thread; async
+    /// callers use [`ExternalFrameSender::push_async`]. Fails once the pipeline has stopped.
+    pub fn push(&self, frame: VideoFrame) -> DecoderResult<()> {
+        self.sender
+            .blocking_send(Ok(frame))
+            .map_err(|_| pipeline_closed())
+    }
+
+    /// Queues a frame, waiting while the channel is full.
+    pub async fn push_async(&self, frame: VideoFrame) -> DecoderResult<()> {
+        self.sender
+            .send(Ok(frame))
+            .await
+            .map_err(|_| pipeline_closed())
+    }
+
+    /// Ends the stream once the last sender is closed; dropping a sender does the same.
+    pub fn close(self) {}
+}
+
+impl DecoderProvider for ExternalFrameProvider {
+    fn new(_config: &crate::config::Configuration) -> DecoderResult<Self> {
+        Err(DecoderError::configuration(
+            "external frame providers are created with ExternalFrameProvider::channel",
+        ))
+    }
+
+    fn metadata(&self) -> VideoMetadata {
+        self.metadata.clone()
+    }
+
+    fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+        let stream = unfold(self.receiver, |mut receiver| async {
+            receiver.recv().await.map(|item| (item, receiver))
+        });
+        Ok((DecoderController::new(), Box::pin(stream)))
+    }
+}
+
+fn pipeline_closed() -> DecoderError {
+    DecoderError::backend_failure(EXTERNAL_BACKEND, "the pipeline stopped receiving frames")
+}
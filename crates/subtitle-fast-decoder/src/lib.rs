@@ -1,10 +1,17 @@
 pub mod backends;
 pub mod config;
 pub mod core;
+pub mod external;
+pub mod reverse;
+pub mod timestamps;
 
-pub use config::{Backend, Configuration, OutputFormat};
+pub use config::{Backend, Configuration, OutputFormat, RawFormat};
 pub use core::{
-    DecoderController, DecoderError, DecoderProvider, DecoderResult, DynDecoderProvider,
-    FrameBuffer, FrameStream, NativeBuffer, Nv12Buffer, SeekInfo, SeekMode, VideoFrame,
-    VideoMetadata,
+    ColorPrimaries, ColorRange, ColorSpace, DEFAULT_QUEUE_FULL_WARNING, DecoderController,
+    DecoderError, DecoderProvider, DecoderResult, DynDecoderProvider, FrameBuffer, FramePool,
+    FramePoolStats, FrameStream, I420Buffer, NativeBuffer, Nv12Buffer, P010Buffer, PlaneRecycler,
+    SeekInfo, SeekMode, StreamInfo, VideoFrame, VideoMetadata,
 };
+pub use external::{ExternalFrameProvider, ExternalFrameSender};
+pub use reverse::ReverseReader;
+pub use timestamps::TimestampSampler;
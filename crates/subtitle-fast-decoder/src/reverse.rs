@@ -0,0 +1,158 @@
+use futures_util::StreamExt;
+use futures_util::stream::unfold;
+
+use crate::core::{
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DynDecoderProvider,
+    FrameStream, SeekInfo, SeekMode, StreamInfo, VideoFrame, VideoMetadata,
+};
+
+/// Frames decoded per backward step. Each window costs one accurate seek, that is a decode
+/// from the preceding keyframe, and holds this many frames in memory.
+pub const REVERSE_WINDOW: u64 = 32;
+
+/// Emits frames in descending index order from a start frame down to frame 0, for
+/// [`Configuration::reverse`](crate::config::Configuration::reverse).
+///
+/// Backends only decode forward, so the input is read in windows of [`REVERSE_WINDOW`]
+/// frames: the reader seeks accurately to the first frame of the window, decodes forward up
+/// to its last frame, and replays the window backwards before stepping to the previous one.
+/// Frames keep the backend's index and pts. The stream ends after frame 0, or early if the
+/// backend's stream ends. The returned controller is not connected to the backend.
+pub struct ReverseReader {
+    inner: DynDecoderProvider,
+    start_frame: u64,
+}
+
+struct Window {
+    controller: DecoderController,
+    stream: FrameStream,
+    /// Decoded frames of the current window in ascending order, emitted by popping.
+    buffered: Vec<VideoFrame>,
+    /// Last frame of the next window to decode; `None` once frame 0 has been read.
+    next_high: Option<u64>,
+}
+
+impl ReverseReader {
+    /// Reads `inner` backwards from `start_frame`, clamped to the last frame when the
+    /// backend reports a frame count.
+    pub fn wrap(inner: DynDecoderProvider, start_frame: u64) -> Self {
+        let start_frame = match inner.metadata().total_frames {
+            Some(total) => start_frame.min(total.saturating_sub(1)),
+            None => start_frame,
+        };
+        Self { inner, start_frame }
+    }
+}
+
+impl DecoderProvider for ReverseReader {
+    fn new(_config: &crate::config::Configuration) -> DecoderResult<Self> {
+        Err(DecoderError::configuration(
+            "reverse readers are created by Configuration::create_provider",
+        ))
+    }
+
+    fn metadata(&self) -> VideoMetadata {
+        VideoMetadata {
+            total_frames: Some(self.start_frame + 1),
+            ..self.inner.metadata()
+        }
+    }
+
+    fn list_video_streams(&self) -> Vec<StreamInfo> {
+        self.inner.list_video_streams()
+    }
+
+    fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+        let (controller, stream) = self.inner.open()?;
+        let window = Window {
+            controller,
+            stream,
+            buffered: Vec::new(),
+            next_high: Some(self.start_frame),
+        };
+        let stream = unfold(window, |mut window| async move {
+            loop {
+                if let Some(frame) = window.buffered.pop() {
+                    return Some((Ok(frame), window));
+                }
+                let high = window.next_high?;
+                if let Err(err) = window.fill(high).await {
+                    window.next_high = None;
+                    return Some((Err(err), window));
+                }
+            }
+        });
+        Ok((DecoderController::new(), Box::pin(stream)))
+    }
+}
+
+impl Window {
+    /// Decodes frames `high - REVERSE_WINDOW + 1 ..= high` into `buffered`.
+    async fn fill(&mut self, high: u64) -> DecoderResult<()> {
+        let low = high.saturating_sub(REVERSE_WINDOW - 1);
+        self.next_high = low.checked_sub(1);
+        let serial = self.controller.seek(SeekInfo::Frame {
+            frame: low,
+            mode: SeekMode::Accurate,
+        })?;
+        while let Some(frame) = self.stream.next().await {
+            let frame = frame?;
+            if frame.serial() < serial {
+                continue;
+            }
+            let index = frame.index().ok_or_else(|| {
+                DecoderError::configuration("reverse decoding needs frames with an index")
+            })?;
+            if index > high {
+                return Ok(());
+            }
+            if index >= low {
+                self.buffered.push(frame);
+            }
+            if index == high {
+                return Ok(());
+            }
+        }
+        // The backend ran out of frames and cannot seek any more.
+        self.next_high = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::mock::MockProvider;
+    use crate::config::{Backend, Configuration};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mock_frames_come_out_in_descending_index_order() {
+        let config = Configuration {
+            backend: Backend::Mock,
+            ..Default::default()
+        };
+        let mock = Box::new(MockProvider::new(&config).unwrap());
+        // 70 spans three windows, the last of them partial.
+        let provider = Box::new(ReverseReader::wrap(mock, 70)) as DynDecoderProvider;
+        assert_eq!(provider.metadata().total_frames, Some(71));
+
+        let (_controller, stream) = provider.open().unwrap();
+        let indices: Vec<u64> = stream
+            .map(|frame| frame.unwrap().index().unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(indices, (0..=70).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn start_frame_is_clamped_to_the_last_frame() {
+        let config = Configuration {
+            backend: Backend::Mock,
+            ..Default::default()
+        };
+        let mock = Box::new(MockProvider::new(&config).unwrap());
+        let provider = ReverseReader::wrap(mock, 10_000);
+        assert_eq!(provider.metadata().total_frames, Some(120));
+    }
+}
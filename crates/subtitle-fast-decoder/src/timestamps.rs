@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use futures_util::stream::unfold;
+
+use crate::core::{
+    DecoderController, DecoderError, DecoderProvider, DecoderResult, DynDecoderProvider,
+    FrameStream, SeekInfo, SeekMode, StreamInfo, VideoMetadata,
+};
+
+/// Decodes one frame per requested timestamp instead of the whole input, for
+/// [`Configuration::sample_timestamps`](crate::config::Configuration::sample_timestamps).
+///
+/// Each timestamp is an accurate time seek on the wrapped backend, and the first frame decoded
+/// at or after it is emitted with the backend's own index and pts; the rest of the backend's
+/// output is discarded. Frames come out in the order the timestamps were given, and the
+/// stream ends after the last one or when a seek runs past the end of the input. The returned
+/// controller is not connected to the backend, so the caller cannot seek it.
+pub struct TimestampSampler {
+    inner: DynDecoderProvider,
+    timestamps: Vec<Duration>,
+}
+
+impl TimestampSampler {
+    pub fn wrap(inner: DynDecoderProvider, timestamps: Vec<Duration>) -> Self {
+        Self { inner, timestamps }
+    }
+}
+
+impl DecoderProvider for TimestampSampler {
+    fn new(_config: &crate::config::Configuration) -> DecoderResult<Self> {
+        Err(DecoderError::configuration(
+            "timestamp samplers are created by Configuration::create_provider",
+        ))
+    }
+
+    fn metadata(&self) -> VideoMetadata {
+        VideoMetadata {
+            total_frames: Some(self.timestamps.len() as u64),
+            ..self.inner.metadata()
+        }
+    }
+
+    fn list_video_streams(&self) -> Vec<StreamInfo> {
+        self.inner.list_video_streams()
+    }
+
+    fn open(self: Box<Self>) -> DecoderResult<(DecoderController, FrameStream)> {
+        let (controller, stream) = self.inner.open()?;
+        let pending: VecDeque<Duration> = self.timestamps.into();
+        let stream = unfold(
+            (controller, stream, pending),
+            |(controller, mut stream, mut pending)| async move {
+                let position = pending.pop_front()?;
+                let serial = match controller.seek(SeekInfo::Time {
+                    position,
+                    mode: SeekMode::Accurate,
+                }) {
+                    Ok(serial) => serial,
+                    Err(err) => return Some((Err(err), (controller, stream, VecDeque::new()))),
+                };
+                loop {
+                    match stream.next().await? {
+                        Ok(frame) if frame.serial() < serial => continue,
+                        Ok(frame) => return Some((Ok(frame), (controller, stream, pending))),
+                        Err(err) => return Some((Err(err), (controller, stream, VecDeque::new()))),
+                    }
+                }
+            },
+        );
+        Ok((DecoderController::new(), Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::mock::MockProvider;
+    use crate::config::{Backend, Configuration};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn requested_timestamps_yield_one_frame_each_in_order() {
+        let requested = vec![
+            Duration::from_millis(500),
+            Duration::from_millis(1_000),
+            Duration::from_millis(1_500),
+        ];
+        let config = Configuration {
+            backend: Backend::Mock,
+            sample_timestamps: Some(requested.clone()),
+            ..Default::default()
+        };
+        let mock = Box::new(MockProvider::new(&config).unwrap());
+        let provider = Box::new(TimestampSampler::wrap(
+            mock,
+            config.sample_timestamps.clone().unwrap(),
+        )) as DynDecoderProvider;
+        assert_eq!(provider.metadata().total_frames, Some(3));
+
+        let (_controller, stream) = provider.open().unwrap();
+        let frames: Vec<_> = stream.map(|frame| frame.unwrap()).collect().await;
+
+        assert_eq!(frames.len(), 3);
+        for (frame, target) in frames.iter().zip(&requested) {
+            let pts = frame.pts().expect("mock frames carry a pts");
+            assert!(pts >= *target && pts - *target < Duration::from_millis(20));
+            // The mock stamps frame N at N * 16 ms.
+            assert_eq!(frame.index(), Some(pts.as_millis() as u64 / 16));
+        }
+    }
+}
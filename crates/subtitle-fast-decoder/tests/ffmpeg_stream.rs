@@ -45,3 +45,44 @@ async fn ffmpeg_backend_requires_asset() {
         );
     }
 }
+
+#[test]
+fn ffmpeg_backend_probes_video_streams() {
+    let asset = match env::var("SUBFAST_TEST_ASSET") {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => {
+            eprintln!("skipping ffmpeg stream probe test - SUBFAST_TEST_ASSET not set");
+            return;
+        }
+    };
+
+    let config = Configuration {
+        backend: Backend::FFmpeg,
+        input: Some(asset.clone()),
+        ..Configuration::default()
+    };
+    let provider = config
+        .create_provider()
+        .expect("failed to initialize ffmpeg backend");
+    let streams = provider.list_video_streams();
+    assert!(!streams.is_empty(), "asset should have a video stream");
+    assert_eq!(provider.metadata().video_track_count, Some(streams.len()));
+    for stream in &streams {
+        eprintln!(
+            "video stream {}: {} {:?}x{:?} @ {:?} fps",
+            stream.index, stream.codec, stream.width, stream.height, stream.fps
+        );
+    }
+
+    let out_of_range = Configuration {
+        backend: Backend::FFmpeg,
+        input: Some(asset),
+        video_stream_index: Some(streams.len()),
+        ..Configuration::default()
+    };
+    let err = out_of_range
+        .create_provider()
+        .err()
+        .expect("out-of-range stream index should be rejected");
+    assert!(err.to_string().contains("out of range"));
+}
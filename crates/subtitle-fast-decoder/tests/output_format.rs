@@ -1,4 +1,4 @@
-use subtitle_fast_decoder::{Backend, Configuration, DecoderError, OutputFormat};
+use subtitle_fast_decoder::{Backend, Configuration, DecoderError, OutputFormat, RawFormat};
 
 #[test]
 fn handle_output_rejects_non_videotoolbox_backend() {
@@ -8,6 +8,16 @@ fn handle_output_rejects_non_videotoolbox_backend() {
         channel_capacity: None,
         output_format: OutputFormat::CVPixelBuffer,
         start_frame: None,
+        end_frame: None,
+        frame_rate: None,
+        raw_width: None,
+        raw_height: None,
+        raw_format: RawFormat::Nv12,
+        video_stream_index: None,
+        fps_override: None,
+        queue_full_warning: None,
+        sample_timestamps: None,
+        reverse: false,
     };
 
     let err = match config.create_provider() {
@@ -23,3 +33,61 @@ fn handle_output_rejects_non_videotoolbox_backend() {
         other => panic!("unexpected error: {other:?}"),
     }
 }
+
+#[test]
+fn i420_output_rejects_backends_without_a_planar_path() {
+    let config = Configuration {
+        backend: Backend::ImageSequence,
+        output_format: OutputFormat::I420,
+        ..Configuration::default()
+    };
+
+    match config.create_provider() {
+        Err(DecoderError::Configuration { message }) => {
+            assert!(message.contains(OutputFormat::I420.as_str()), "{message}");
+            assert!(message.contains("image-sequence"), "{message}");
+        }
+        Err(other) => panic!("unexpected error: {other:?}"),
+        Ok(_) => panic!("image sequences should not accept I420 output"),
+    }
+}
+
+#[test]
+fn cuda_output_rejects_backends_other_than_nvdec() {
+    let config = Configuration {
+        backend: Backend::ImageSequence,
+        output_format: OutputFormat::CudaDevice,
+        ..Configuration::default()
+    };
+
+    match config.create_provider() {
+        Err(DecoderError::Configuration { message }) => {
+            assert!(
+                message.contains(OutputFormat::CudaDevice.as_str()),
+                "{message}"
+            );
+            assert!(message.contains("nvdec"), "{message}");
+        }
+        Err(other) => panic!("unexpected error: {other:?}"),
+        Ok(_) => panic!("image sequences should not accept CUDA output"),
+    }
+}
+
+#[test]
+fn fps_override_must_be_positive_and_finite() {
+    for fps in [0.0, -25.0, f64::NAN, f64::INFINITY] {
+        let config = Configuration {
+            backend: Backend::ImageSequence,
+            fps_override: Some(fps),
+            ..Configuration::default()
+        };
+
+        match config.create_provider() {
+            Err(DecoderError::Configuration { message }) => {
+                assert!(message.contains("fps override"), "{message}");
+            }
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("fps override {fps} should be rejected"),
+        }
+    }
+}
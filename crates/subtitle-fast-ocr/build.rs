@@ -24,7 +24,20 @@ fn build_vision_bridge() {
 #[cfg(not(target_os = "macos"))]
 fn build_vision_bridge() {}
 
+fn link_tesseract() {
+    if std::env::var("CARGO_FEATURE_ENGINE_TESSERACT").is_err() {
+        return;
+    }
+
+    println!("cargo:rerun-if-env-changed=TESSERACT_LIB_DIR");
+    if let Ok(dir) = std::env::var("TESSERACT_LIB_DIR") {
+        println!("cargo:rustc-link-search=native={dir}");
+    }
+    println!("cargo:rustc-link-lib=tesseract");
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     build_vision_bridge();
+    link_tesseract();
 }
@@ -0,0 +1,180 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use subtitle_fast_types::VideoFrame;
+
+use crate::engine::OcrEngine;
+use crate::error::OcrError;
+use crate::plane::LumaPlane;
+use crate::region::OcrRegion;
+use crate::request::OcrRequest;
+use crate::response::OcrResponse;
+
+/// Boxed future returned by [`AsyncOcrEngine`] methods.
+pub type OcrFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, OcrError>> + Send + 'a>>;
+
+/// OCR request that owns its frame, so it can move to another task or thread.
+#[derive(Debug, Clone)]
+pub struct OwnedOcrRequest {
+    frame: Arc<VideoFrame>,
+    regions: Vec<OcrRegion>,
+}
+
+impl OwnedOcrRequest {
+    pub fn new(frame: Arc<VideoFrame>, regions: Vec<OcrRegion>) -> Self {
+        Self { frame, regions }
+    }
+
+    pub fn frame(&self) -> &Arc<VideoFrame> {
+        &self.frame
+    }
+
+    pub fn regions(&self) -> &[OcrRegion] {
+        &self.regions
+    }
+
+    /// Borrowed view of the frame's luma plane for synchronous engines.
+    pub fn as_request(&self) -> OcrRequest<'_> {
+        OcrRequest::new(LumaPlane::from_frame(&self.frame), &self.regions)
+    }
+}
+
+/// Asynchronous counterpart of [`OcrEngine`] for engines that can serve several requests at
+/// once, for example by keeping a pool of native contexts.
+///
+/// Simple backends keep implementing the synchronous trait and are driven through
+/// [`SyncOcrAdapter`].
+pub trait AsyncOcrEngine: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn warm_up(&self) -> OcrFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn recognize(&self, request: OwnedOcrRequest) -> OcrFuture<'_, OcrResponse>;
+}
+
+const _: () = crate::engine::assert_thread_safe::<dyn AsyncOcrEngine>();
+
+/// Runs a synchronous [`OcrEngine`] on tokio's blocking pool, so slow FFI calls do not hold an
+/// async worker thread. The futures must be polled inside a tokio runtime; a panic in the
+/// wrapped engine is reported as a backend error.
+#[derive(Clone)]
+pub struct SyncOcrAdapter {
+    engine: Arc<dyn OcrEngine>,
+}
+
+impl SyncOcrAdapter {
+    pub fn new(engine: Arc<dyn OcrEngine>) -> Self {
+        Self { engine }
+    }
+
+    pub fn engine(&self) -> &Arc<dyn OcrEngine> {
+        &self.engine
+    }
+
+    async fn run_blocking<T, F>(&self, action: &'static str, call: F) -> Result<T, OcrError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn OcrEngine) -> Result<T, OcrError> + Send + 'static,
+    {
+        let engine = Arc::clone(&self.engine);
+        let name = engine.name();
+        match tokio::task::spawn_blocking(move || call(engine.as_ref())).await {
+            Ok(result) => result,
+            Err(err) if err.is_panic() => Err(OcrError::backend(format!(
+                "{name} engine panicked during {action}"
+            ))),
+            Err(err) => Err(OcrError::backend(format!(
+                "{name} engine {action} was cancelled: {err}"
+            ))),
+        }
+    }
+}
+
+impl AsyncOcrEngine for SyncOcrAdapter {
+    fn name(&self) -> &'static str {
+        self.engine.name()
+    }
+
+    fn warm_up(&self) -> OcrFuture<'_, ()> {
+        Box::pin(self.run_blocking("warm-up", |engine| engine.warm_up()))
+    }
+
+    fn recognize(&self, request: OwnedOcrRequest) -> OcrFuture<'_, OcrResponse> {
+        Box::pin(self.run_blocking("recognition", move |engine| {
+            engine.recognize(&request.as_request())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OcrText;
+
+    struct EchoEngine;
+
+    impl OcrEngine for EchoEngine {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+            match request.regions() {
+                [] => Err(OcrError::transient("no regions")),
+                [region, ..] if region.width < 0.0 => panic!("negative width"),
+                regions => Ok(OcrResponse::new(
+                    regions
+                        .iter()
+                        .map(|region| {
+                            let text = format!("{}x{}", request.plane().width(), region.width);
+                            OcrText::new(*region, text)
+                        })
+                        .collect(),
+                )),
+            }
+        }
+    }
+
+    fn request(regions: Vec<OcrRegion>) -> OwnedOcrRequest {
+        let frame =
+            VideoFrame::from_nv12_owned(8, 4, 8, 8, None, None, vec![0; 8 * 4], vec![128; 8 * 2])
+                .unwrap();
+        OwnedOcrRequest::new(Arc::new(frame), regions)
+    }
+
+    #[tokio::test]
+    async fn adapter_forwards_results_and_errors() {
+        let adapter = SyncOcrAdapter::new(Arc::new(EchoEngine));
+        assert_eq!(adapter.name(), "echo");
+        adapter.warm_up().await.unwrap();
+
+        let region = OcrRegion::new(1.0, 1.0, 3.0, 2.0);
+        let response = adapter.recognize(request(vec![region])).await.unwrap();
+        assert_eq!(response.texts.len(), 1);
+        assert_eq!(response.texts[0].text, "8x3");
+        assert_eq!(response.texts[0].region, region);
+
+        let err = adapter.recognize(request(Vec::new())).await.unwrap_err();
+        assert!(err.is_transient());
+        assert_eq!(err.to_string(), "transient backend error: no regions");
+    }
+
+    #[tokio::test]
+    async fn adapter_reports_engine_panics_as_errors() {
+        let adapter = SyncOcrAdapter::new(Arc::new(EchoEngine));
+
+        let err = adapter
+            .recognize(request(vec![OcrRegion::new(0.0, 0.0, -1.0, 1.0)]))
+            .await
+            .unwrap_err();
+
+        assert!(!err.is_transient());
+        assert!(
+            err.to_string()
+                .contains("echo engine panicked during recognition")
+        );
+    }
+}
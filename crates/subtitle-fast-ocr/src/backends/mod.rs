@@ -1,2 +1,8 @@
 #[cfg(all(feature = "engine-vision", target_os = "macos"))]
 pub mod vision;
+
+#[cfg(feature = "engine-tesseract")]
+pub mod tesseract;
+
+#[cfg(feature = "engine-onnx")]
+pub mod onnx;
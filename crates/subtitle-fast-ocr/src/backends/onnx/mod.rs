@@ -0,0 +1,390 @@
+mod runtime;
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{LumaPlane, OcrEngine, OcrError, OcrRegion, OcrRequest, OcrResponse, OcrText};
+
+use runtime::OrtSession;
+
+/// File name of the recognition model inside the model cache directory.
+pub const PPOCR_REC_MODEL_FILE: &str = "ppocr_rec.onnx";
+/// File name of the character dictionary inside the model cache directory.
+pub const PPOCR_DICTIONARY_FILE: &str = "ppocr_keys.txt";
+
+/// Input height of the PP-OCR recognition networks (`rec_image_shape` 3x48xW).
+const INPUT_HEIGHT: usize = 48;
+const MIN_INPUT_WIDTH: usize = 16;
+/// Keeps the tensor bounded for full-width lines on 4K frames.
+const MAX_INPUT_WIDTH: usize = 3200;
+/// CTC blank class; dictionary entries start at class 1.
+const BLANK: usize = 0;
+
+/// Per-step class scores of one recognized line.
+pub(crate) struct StepScores {
+    data: Vec<f32>,
+    steps: usize,
+    classes: usize,
+}
+
+impl StepScores {
+    pub(crate) fn new(data: Vec<f32>, steps: usize, classes: usize) -> Result<Self, OcrError> {
+        if data.len() != steps * classes {
+            return Err(OcrError::backend(format!(
+                "recognition output holds {} scores, expected {steps} x {classes}",
+                data.len()
+            )));
+        }
+        Ok(Self {
+            data,
+            steps,
+            classes,
+        })
+    }
+
+    fn step(&self, index: usize) -> &[f32] {
+        &self.data[index * self.classes..(index + 1) * self.classes]
+    }
+}
+
+/// A recognition network run on one normalized `1 x 3 x 48 x width` image.
+pub(crate) trait RecognitionModel: Send {
+    fn run(&mut self, input: &mut [f32], shape: [usize; 4]) -> Result<StepScores, OcrError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct OnnxPpocrConfig {
+    /// PP-OCR recognition model exported to ONNX.
+    pub model_path: PathBuf,
+    /// Character dictionary the model was trained with, one entry per line.
+    pub dictionary_path: PathBuf,
+}
+
+impl OnnxPpocrConfig {
+    /// Expects `ppocr_rec.onnx` and `ppocr_keys.txt` in the model cache directory.
+    pub fn from_model_cache(cache_dir: &Path) -> Result<Self, OcrError> {
+        Ok(Self {
+            model_path: OcrError::locate_model(cache_dir, PPOCR_REC_MODEL_FILE)?,
+            dictionary_path: OcrError::locate_model(cache_dir, PPOCR_DICTIONARY_FILE)?,
+        })
+    }
+}
+
+/// PaddleOCR text recognizer running through onnxruntime.
+///
+/// Each region is cropped from the luma plane, scaled to the network height and decoded with
+/// greedy CTC; the line confidence is the mean softmax peak of the emitted characters. The
+/// onnxruntime shared library is loaded at construction, from `ORT_DYLIB_PATH` when set.
+pub struct OnnxPpocrRecognizer {
+    model: Mutex<Box<dyn RecognitionModel>>,
+    dictionary: Vec<String>,
+}
+
+impl OnnxPpocrRecognizer {
+    pub fn new(config: OnnxPpocrConfig) -> Result<Self, OcrError> {
+        let dictionary = load_dictionary(&config.dictionary_path)?;
+        let session = OrtSession::open(&config.model_path)?;
+        Ok(Self::with_model(Box::new(session), dictionary))
+    }
+
+    fn with_model(model: Box<dyn RecognitionModel>, dictionary: Vec<String>) -> Self {
+        Self {
+            model: Mutex::new(model),
+            dictionary,
+        }
+    }
+
+    fn recognize_region(
+        &self,
+        model: &mut dyn RecognitionModel,
+        plane: &LumaPlane<'_>,
+        region: &OcrRegion,
+    ) -> Result<Option<OcrText>, OcrError> {
+        let Some(bounds) = crop_bounds(region, plane.width() as usize, plane.height() as usize)
+        else {
+            return Ok(None);
+        };
+        let (mut input, width) = normalized_input(plane, bounds);
+        let scores = model.run(&mut input, [1, 3, INPUT_HEIGHT, width])?;
+        let Some((text, confidence)) = decode_ctc(&scores, &self.dictionary)? else {
+            return Ok(None);
+        };
+        let (left, top, right, bottom) = bounds;
+        let region = OcrRegion::new(
+            left as f32,
+            top as f32,
+            (right - left) as f32,
+            (bottom - top) as f32,
+        );
+        Ok(Some(OcrText::new(region, text).with_confidence(confidence)))
+    }
+}
+
+const _: () = crate::engine::assert_thread_safe::<OnnxPpocrRecognizer>();
+
+impl OcrEngine for OnnxPpocrRecognizer {
+    fn name(&self) -> &'static str {
+        "onnx-ppocr"
+    }
+
+    fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+        let plane = request.plane();
+        if plane.data().is_empty() || plane.width() == 0 || plane.height() == 0 {
+            return Ok(OcrResponse::empty());
+        }
+        let full_frame = [OcrRegion::new(
+            0.0,
+            0.0,
+            plane.width() as f32,
+            plane.height() as f32,
+        )];
+        let regions = match request.regions() {
+            [] => &full_frame[..],
+            regions => regions,
+        };
+
+        let mut model = self
+            .model
+            .lock()
+            .map_err(|_| OcrError::backend("onnx recognizer poisoned by an earlier panic"))?;
+        let mut texts = Vec::new();
+        for region in regions {
+            if let Some(text) = self.recognize_region(model.as_mut(), plane, region)? {
+                texts.push(text);
+            }
+        }
+        Ok(OcrResponse::new(texts))
+    }
+}
+
+/// Reads a PaddleOCR key file. Like PaddleOCR's `use_space_char`, a space is appended when the
+/// model predicts one more class than the file lists; see [`decode_ctc`].
+fn load_dictionary(path: &Path) -> Result<Vec<String>, OcrError> {
+    let contents = std::fs::read_to_string(path).map_err(|err| {
+        OcrError::backend(format!(
+            "failed to read OCR dictionary {}: {err}",
+            path.display()
+        ))
+    })?;
+    let dictionary: Vec<String> = contents
+        .lines()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if dictionary.is_empty() {
+        return Err(OcrError::backend(format!(
+            "OCR dictionary {} has no entries",
+            path.display()
+        )));
+    }
+    Ok(dictionary)
+}
+
+/// Pixel bounds `(left, top, right, bottom)` of `region` inside the plane, or `None` when
+/// nothing of it is left.
+fn crop_bounds(
+    region: &OcrRegion,
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let clamp = |value: f32, limit: usize| (value.max(0.0) as usize).min(limit);
+    let left = clamp(region.x.floor(), width);
+    let top = clamp(region.y.floor(), height);
+    let right = clamp((region.x + region.width).ceil(), width);
+    let bottom = clamp((region.y + region.height).ceil(), height);
+    (right > left && bottom > top).then_some((left, top, right, bottom))
+}
+
+/// Scales the crop to the network height with bilinear sampling and returns the CHW tensor,
+/// normalized to [-1, 1] and repeated over three channels, with its width.
+fn normalized_input(
+    plane: &LumaPlane<'_>,
+    (left, top, right, bottom): (usize, usize, usize, usize),
+) -> (Vec<f32>, usize) {
+    let crop_width = right - left;
+    let crop_height = bottom - top;
+    let width = (crop_width * INPUT_HEIGHT)
+        .div_ceil(crop_height)
+        .clamp(MIN_INPUT_WIDTH, MAX_INPUT_WIDTH);
+    let data = plane.data();
+    let stride = plane.stride();
+    let sample = |x: usize, y: usize| f32::from(data[(top + y) * stride + left + x]);
+    let scale_x = crop_width as f32 / width as f32;
+    let scale_y = crop_height as f32 / INPUT_HEIGHT as f32;
+
+    let channel_len = INPUT_HEIGHT * width;
+    let mut input = vec![0.0f32; channel_len * 3];
+    for row in 0..INPUT_HEIGHT {
+        let src_y = ((row as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (crop_height - 1) as f32);
+        let y0 = src_y as usize;
+        let y1 = (y0 + 1).min(crop_height - 1);
+        let fy = src_y - y0 as f32;
+        for col in 0..width {
+            let src_x = ((col as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (crop_width - 1) as f32);
+            let x0 = src_x as usize;
+            let x1 = (x0 + 1).min(crop_width - 1);
+            let fx = src_x - x0 as f32;
+            let top_mix = sample(x0, y0) * (1.0 - fx) + sample(x1, y0) * fx;
+            let bottom_mix = sample(x0, y1) * (1.0 - fx) + sample(x1, y1) * fx;
+            let value = top_mix * (1.0 - fy) + bottom_mix * fy;
+            input[row * width + col] = value / 127.5 - 1.0;
+        }
+    }
+    let (luma, rest) = input.split_at_mut(channel_len);
+    rest[..channel_len].copy_from_slice(luma);
+    rest[channel_len..].copy_from_slice(luma);
+    (input, width)
+}
+
+/// Greedy CTC decode: takes the best class per step, collapses repeats and drops blanks.
+/// Returns the text with the mean peak probability of its characters, or `None` when no
+/// character was emitted.
+fn decode_ctc(
+    scores: &StepScores,
+    dictionary: &[String],
+) -> Result<Option<(String, f32)>, OcrError> {
+    let with_space = match scores.classes.checked_sub(dictionary.len()) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => {
+            return Err(OcrError::backend(format!(
+                "recognition model predicts {} classes but the dictionary has {} entries",
+                scores.classes,
+                dictionary.len()
+            )));
+        }
+    };
+
+    let mut text = String::new();
+    let mut peaks = Vec::new();
+    let mut previous = BLANK;
+    for step in 0..scores.steps {
+        let (class, peak) = step_peak(scores.step(step));
+        if class != BLANK && class != previous {
+            match dictionary.get(class - 1) {
+                Some(symbol) => text.push_str(symbol),
+                None if with_space => text.push(' '),
+                None => unreachable!("class count was checked against the dictionary"),
+            }
+            peaks.push(peak);
+        }
+        previous = class;
+    }
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let confidence = peaks.iter().sum::<f32>() / peaks.len() as f32;
+    Ok(Some((text, confidence)))
+}
+
+/// Best class of one step and its probability. Exported PP-OCR heads already end in a
+/// softmax; raw logits are normalized here so either export works.
+fn step_peak(row: &[f32]) -> (usize, f32) {
+    let (class, &max) = row
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap_or((BLANK, &0.0));
+    let sum: f32 = row.iter().sum();
+    let is_distribution =
+        row.iter().all(|value| (0.0..=1.0).contains(value)) && (sum - 1.0).abs() < 1e-3;
+    if is_distribution {
+        return (class, max);
+    }
+    let exp_sum: f32 = row.iter().map(|value| (value - max).exp()).sum();
+    (class, 1.0 / exp_sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 16;
+
+    /// Stands in for the network: checks the tensor layout and replays fixed scores.
+    struct ScriptedModel {
+        scores: Vec<Vec<f32>>,
+    }
+
+    impl RecognitionModel for ScriptedModel {
+        fn run(&mut self, input: &mut [f32], shape: [usize; 4]) -> Result<StepScores, OcrError> {
+            assert_eq!(shape[..3], [1, 3, INPUT_HEIGHT]);
+            assert_eq!(input.len(), shape.iter().product::<usize>());
+            assert!(input.iter().all(|value| (-1.0..=1.0).contains(value)));
+            let classes = self.scores[0].len();
+            StepScores::new(self.scores.concat(), self.scores.len(), classes)
+        }
+    }
+
+    fn dictionary() -> Vec<String> {
+        ["a", "b"].map(String::from).to_vec()
+    }
+
+    fn recognizer(scores: Vec<Vec<f32>>) -> OnnxPpocrRecognizer {
+        OnnxPpocrRecognizer::with_model(Box::new(ScriptedModel { scores }), dictionary())
+    }
+
+    fn recognize(engine: &OnnxPpocrRecognizer, regions: &[OcrRegion]) -> OcrResponse {
+        let data = vec![200u8; (WIDTH * HEIGHT) as usize];
+        let plane = LumaPlane::from_parts(WIDTH, HEIGHT, WIDTH as usize, &data).unwrap();
+        engine
+            .recognize(&OcrRequest::new(plane, regions))
+            .expect("recognition succeeds")
+    }
+
+    #[test]
+    fn ctc_collapses_repeats_and_blanks_and_averages_peaks() {
+        // Classes: blank, "a", "b", space.
+        let engine = recognizer(vec![
+            vec![0.1, 0.8, 0.05, 0.05],
+            vec![0.1, 0.6, 0.2, 0.1],
+            vec![0.9, 0.05, 0.05, 0.0],
+            vec![0.0, 0.1, 0.1, 0.8],
+            vec![0.1, 0.1, 0.7, 0.1],
+        ]);
+        let regions = [OcrRegion::new(4.0, 2.0, 40.0, 10.0)];
+
+        let response = recognize(&engine, &regions);
+
+        assert_eq!(response.texts.len(), 1);
+        let line = &response.texts[0];
+        assert_eq!(line.text, "a b");
+        let confidence = line.confidence.expect("confidence reported");
+        assert!((confidence - (0.8 + 0.8 + 0.7) / 3.0).abs() < 1e-6);
+        assert_eq!(line.region, regions[0]);
+    }
+
+    #[test]
+    fn logits_are_normalized_before_taking_the_peak() {
+        let engine = recognizer(vec![vec![0.0, 3.0, 0.0]]);
+
+        let response = recognize(&engine, &[]);
+
+        let line = &response.texts[0];
+        assert_eq!(line.text, "a");
+        let expected = 3f32.exp() / (3f32.exp() + 2.0);
+        assert!((line.confidence.unwrap() - expected).abs() < 1e-6);
+        assert_eq!(line.region.width, WIDTH as f32);
+    }
+
+    #[test]
+    fn blank_only_output_yields_no_text() {
+        let engine = recognizer(vec![vec![0.9, 0.05, 0.05]; 4]);
+
+        assert!(recognize(&engine, &[]).texts.is_empty());
+    }
+
+    #[test]
+    fn class_count_must_match_the_dictionary() {
+        let engine = recognizer(vec![vec![0.2; 5]]);
+        let data = vec![0u8; (WIDTH * HEIGHT) as usize];
+        let plane = LumaPlane::from_parts(WIDTH, HEIGHT, WIDTH as usize, &data).unwrap();
+
+        let err = engine
+            .recognize(&OcrRequest::new(plane, &[]))
+            .expect_err("five classes do not fit two entries");
+
+        assert!(err.to_string().contains("dictionary has 2 entries"));
+    }
+}
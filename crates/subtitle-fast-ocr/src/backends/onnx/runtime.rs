@@ -0,0 +1,337 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::Path;
+use std::ptr;
+
+use libloading::Library;
+
+use super::{RecognitionModel, StepScores};
+use crate::OcrError;
+
+/// Oldest C API version whose function table covers every entry used below.
+const ORT_API_VERSION: u32 = 11;
+const ORT_LOGGING_LEVEL_WARNING: c_int = 2;
+const ORT_ARENA_ALLOCATOR: c_int = 1;
+const ORT_MEM_TYPE_DEFAULT: c_int = 0;
+const ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT: c_int = 1;
+
+/// Environment variable naming the onnxruntime shared library to load.
+const DYLIB_ENV: &str = "ORT_DYLIB_PATH";
+
+type Status = *mut c_void;
+type Release = unsafe extern "C" fn(*mut c_void);
+type Reserved = *const c_void;
+
+#[cfg(windows)]
+type PathChar = u16;
+#[cfg(not(windows))]
+type PathChar = c_char;
+
+#[repr(C)]
+struct OrtApiBase {
+    get_api: unsafe extern "C" fn(version: u32) -> *const OrtApi,
+    get_version_string: unsafe extern "C" fn() -> *const c_char,
+}
+
+/// Prefix of `OrtApi` from `onnxruntime_c_api.h`. The table is append-only, so entries this
+/// backend does not call are kept as reserved slots to preserve the offsets.
+#[repr(C)]
+struct OrtApi {
+    _reserved_0_1: [Reserved; 2],
+    get_error_message: unsafe extern "C" fn(status: *const c_void) -> *const c_char,
+    create_env:
+        unsafe extern "C" fn(level: c_int, log_id: *const c_char, out: *mut *mut c_void) -> Status,
+    _reserved_4_6: [Reserved; 3],
+    create_session: unsafe extern "C" fn(
+        env: *const c_void,
+        model_path: *const PathChar,
+        options: *const c_void,
+        out: *mut *mut c_void,
+    ) -> Status,
+    _reserved_8: [Reserved; 1],
+    run: unsafe extern "C" fn(
+        session: *mut c_void,
+        run_options: *const c_void,
+        input_names: *const *const c_char,
+        inputs: *const *const c_void,
+        input_len: usize,
+        output_names: *const *const c_char,
+        output_len: usize,
+        outputs: *mut *mut c_void,
+    ) -> Status,
+    create_session_options: unsafe extern "C" fn(out: *mut *mut c_void) -> Status,
+    _reserved_11_35: [Reserved; 25],
+    session_get_input_name: unsafe extern "C" fn(
+        session: *const c_void,
+        index: usize,
+        allocator: *mut c_void,
+        out: *mut *mut c_char,
+    ) -> Status,
+    session_get_output_name: unsafe extern "C" fn(
+        session: *const c_void,
+        index: usize,
+        allocator: *mut c_void,
+        out: *mut *mut c_char,
+    ) -> Status,
+    _reserved_38_48: [Reserved; 11],
+    create_tensor_with_data_as_ort_value: unsafe extern "C" fn(
+        info: *const c_void,
+        data: *mut c_void,
+        data_len: usize,
+        shape: *const i64,
+        shape_len: usize,
+        element_type: c_int,
+        out: *mut *mut c_void,
+    ) -> Status,
+    _reserved_50: [Reserved; 1],
+    get_tensor_mutable_data:
+        unsafe extern "C" fn(value: *mut c_void, out: *mut *mut c_void) -> Status,
+    _reserved_52_60: [Reserved; 9],
+    get_dimensions_count: unsafe extern "C" fn(info: *const c_void, out: *mut usize) -> Status,
+    get_dimensions:
+        unsafe extern "C" fn(info: *const c_void, values: *mut i64, len: usize) -> Status,
+    _reserved_63_64: [Reserved; 2],
+    get_tensor_type_and_shape:
+        unsafe extern "C" fn(value: *const c_void, out: *mut *mut c_void) -> Status,
+    _reserved_66_68: [Reserved; 3],
+    create_cpu_memory_info:
+        unsafe extern "C" fn(allocator: c_int, mem_type: c_int, out: *mut *mut c_void) -> Status,
+    _reserved_70_75: [Reserved; 6],
+    allocator_free: unsafe extern "C" fn(allocator: *mut c_void, ptr: *mut c_void) -> Status,
+    _reserved_77: [Reserved; 1],
+    get_allocator_with_default_options: unsafe extern "C" fn(out: *mut *mut c_void) -> Status,
+    _reserved_79_91: [Reserved; 13],
+    release_env: Release,
+    release_status: Release,
+    release_memory_info: Release,
+    release_session: Release,
+    release_value: Release,
+    _reserved_97_98: [Reserved; 2],
+    release_tensor_type_and_shape_info: Release,
+    release_session_options: Release,
+}
+
+/// An onnxruntime object released through its `Release*` entry when dropped.
+struct Handle {
+    ptr: *mut c_void,
+    release: Release,
+}
+
+impl Handle {
+    fn new(ptr: *mut c_void, release: Release) -> Self {
+        Self { ptr, release }
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            unsafe { (self.release)(self.ptr) };
+        }
+    }
+}
+
+/// A CPU inference session over one model with a single input and output.
+pub(super) struct OrtSession {
+    session: Handle,
+    memory_info: Handle,
+    input_name: CString,
+    output_name: CString,
+    _env: Handle,
+    api: &'static OrtApi,
+    // Dropped last: every handle above points into the library.
+    _library: Library,
+}
+
+// Sessions have no thread affinity; the recognizer only runs one while holding its mutex.
+unsafe impl Send for OrtSession {}
+
+impl OrtSession {
+    pub(super) fn open(model_path: &Path) -> Result<Self, OcrError> {
+        let library_path = std::env::var_os(DYLIB_ENV)
+            .unwrap_or_else(|| libloading::library_filename("onnxruntime"));
+        let library = unsafe { Library::new(&library_path) }.map_err(|err| {
+            OcrError::backend(format!(
+                "failed to load onnxruntime from {}: {err}; install it or set {DYLIB_ENV}",
+                library_path.to_string_lossy()
+            ))
+        })?;
+        let api = unsafe {
+            let get_api_base: libloading::Symbol<unsafe extern "C" fn() -> *const OrtApiBase> =
+                library.get(b"OrtGetApiBase\0").map_err(|err| {
+                    OcrError::backend(format!("onnxruntime is missing OrtGetApiBase: {err}"))
+                })?;
+            let base = get_api_base();
+            if base.is_null() {
+                return Err(OcrError::backend("onnxruntime returned no API base"));
+            }
+            let api = ((*base).get_api)(ORT_API_VERSION);
+            if api.is_null() {
+                let version = CStr::from_ptr(((*base).get_version_string)()).to_string_lossy();
+                return Err(OcrError::backend(format!(
+                    "onnxruntime {version} does not provide C API version {ORT_API_VERSION}"
+                )));
+            }
+            // The table is static data inside the library, which the session keeps loaded.
+            &*api
+        };
+
+        let mut raw = ptr::null_mut();
+        check(api, unsafe {
+            (api.create_env)(
+                ORT_LOGGING_LEVEL_WARNING,
+                c"subtitle-fast".as_ptr(),
+                &mut raw,
+            )
+        })?;
+        let env = Handle::new(raw, api.release_env);
+
+        let mut raw = ptr::null_mut();
+        check(api, unsafe { (api.create_session_options)(&mut raw) })?;
+        let options = Handle::new(raw, api.release_session_options);
+
+        let path = encode_path(model_path)?;
+        let mut raw = ptr::null_mut();
+        check(api, unsafe {
+            (api.create_session)(env.ptr, path.as_ptr(), options.ptr, &mut raw)
+        })?;
+        let session = Handle::new(raw, api.release_session);
+
+        let mut raw = ptr::null_mut();
+        check(api, unsafe {
+            (api.create_cpu_memory_info)(ORT_ARENA_ALLOCATOR, ORT_MEM_TYPE_DEFAULT, &mut raw)
+        })?;
+        let memory_info = Handle::new(raw, api.release_memory_info);
+
+        let input_name = node_name(api, session.ptr, api.session_get_input_name)?;
+        let output_name = node_name(api, session.ptr, api.session_get_output_name)?;
+
+        Ok(Self {
+            session,
+            memory_info,
+            input_name,
+            output_name,
+            _env: env,
+            api,
+            _library: library,
+        })
+    }
+}
+
+impl RecognitionModel for OrtSession {
+    fn run(&mut self, input: &mut [f32], shape: [usize; 4]) -> Result<StepScores, OcrError> {
+        let api = self.api;
+        let dims = shape.map(|value| value as i64);
+        let mut raw = ptr::null_mut();
+        check(api, unsafe {
+            (api.create_tensor_with_data_as_ort_value)(
+                self.memory_info.ptr,
+                input.as_mut_ptr().cast(),
+                std::mem::size_of_val(input),
+                dims.as_ptr(),
+                dims.len(),
+                ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT,
+                &mut raw,
+            )
+        })?;
+        let input_value = Handle::new(raw, api.release_value);
+
+        let input_names = [self.input_name.as_ptr()];
+        let output_names = [self.output_name.as_ptr()];
+        let inputs = [input_value.ptr.cast_const()];
+        let mut raw = ptr::null_mut();
+        check(api, unsafe {
+            (api.run)(
+                self.session.ptr,
+                ptr::null(),
+                input_names.as_ptr(),
+                inputs.as_ptr(),
+                1,
+                output_names.as_ptr(),
+                1,
+                &mut raw,
+            )
+        })?;
+        let output = Handle::new(raw, api.release_value);
+
+        let mut raw = ptr::null_mut();
+        check(api, unsafe {
+            (api.get_tensor_type_and_shape)(output.ptr, &mut raw)
+        })?;
+        let info = Handle::new(raw, api.release_tensor_type_and_shape_info);
+        let mut rank = 0;
+        check(api, unsafe {
+            (api.get_dimensions_count)(info.ptr, &mut rank)
+        })?;
+        let mut dims = vec![0i64; rank];
+        check(api, unsafe {
+            (api.get_dimensions)(info.ptr, dims.as_mut_ptr(), rank)
+        })?;
+
+        // PP-OCR recognition heads emit `[batch, steps, classes]`.
+        let [1, steps, classes] = dims[..] else {
+            return Err(OcrError::backend(format!(
+                "unexpected recognition output shape {dims:?}"
+            )));
+        };
+        let (steps, classes) = (steps.max(0) as usize, classes.max(0) as usize);
+        let mut data = ptr::null_mut();
+        check(api, unsafe {
+            (api.get_tensor_mutable_data)(output.ptr, &mut data)
+        })?;
+        let len = steps * classes;
+        let scores = if len == 0 {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(data.cast::<f32>(), len) }.to_vec()
+        };
+        StepScores::new(scores, steps, classes)
+    }
+}
+
+/// Turns a non-null `OrtStatus` into a backend error and releases it.
+fn check(api: &OrtApi, status: Status) -> Result<(), OcrError> {
+    if status.is_null() {
+        return Ok(());
+    }
+    let message = unsafe { CStr::from_ptr((api.get_error_message)(status)) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { (api.release_status)(status) };
+    Err(OcrError::backend(format!("onnxruntime: {message}")))
+}
+
+type NameGetter =
+    unsafe extern "C" fn(*const c_void, usize, *mut c_void, *mut *mut c_char) -> Status;
+
+/// Copies the name of the model's first input or output, freeing the runtime's copy.
+fn node_name(api: &OrtApi, session: *mut c_void, getter: NameGetter) -> Result<CString, OcrError> {
+    let mut allocator = ptr::null_mut();
+    check(api, unsafe {
+        (api.get_allocator_with_default_options)(&mut allocator)
+    })?;
+    let mut raw = ptr::null_mut();
+    check(api, unsafe { getter(session, 0, allocator, &mut raw) })?;
+    let name = unsafe { CStr::from_ptr(raw) }.to_owned();
+    check(api, unsafe { (api.allocator_free)(allocator, raw.cast()) })?;
+    Ok(name)
+}
+
+#[cfg(windows)]
+fn encode_path(path: &Path) -> Result<Vec<PathChar>, OcrError> {
+    use std::os::windows::ffi::OsStrExt;
+
+    Ok(path.as_os_str().encode_wide().chain([0]).collect())
+}
+
+#[cfg(not(windows))]
+fn encode_path(path: &Path) -> Result<CString, OcrError> {
+    use std::os::unix::ffi::OsStrExt;
+
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+        OcrError::backend(format!(
+            "model path {} contains an interior null byte",
+            path.display()
+        ))
+    })
+}
@@ -0,0 +1,303 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_float, c_int, c_void};
+use std::ptr::{self, NonNull};
+use std::sync::Mutex;
+
+use crate::{OcrEngine, OcrError, OcrRegion, OcrRequest, OcrResponse, OcrText};
+
+/// `RIL_TEXTLINE` from tesseract's `TessPageIteratorLevel`.
+const LEVEL_TEXTLINE: c_int = 2;
+
+#[repr(C)]
+struct TessBaseApi {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct TessResultIterator {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct TessPageIterator {
+    _private: [u8; 0],
+}
+
+unsafe extern "C" {
+    fn TessBaseAPICreate() -> *mut TessBaseApi;
+    fn TessBaseAPIDelete(handle: *mut TessBaseApi);
+    fn TessBaseAPIEnd(handle: *mut TessBaseApi);
+    fn TessBaseAPIInit3(
+        handle: *mut TessBaseApi,
+        datapath: *const c_char,
+        language: *const c_char,
+    ) -> c_int;
+    fn TessBaseAPISetPageSegMode(handle: *mut TessBaseApi, mode: c_int);
+    fn TessBaseAPISetImage(
+        handle: *mut TessBaseApi,
+        imagedata: *const u8,
+        width: c_int,
+        height: c_int,
+        bytes_per_pixel: c_int,
+        bytes_per_line: c_int,
+    );
+    fn TessBaseAPISetRectangle(
+        handle: *mut TessBaseApi,
+        left: c_int,
+        top: c_int,
+        width: c_int,
+        height: c_int,
+    );
+    fn TessBaseAPIRecognize(handle: *mut TessBaseApi, monitor: *mut c_void) -> c_int;
+    fn TessBaseAPIGetIterator(handle: *mut TessBaseApi) -> *mut TessResultIterator;
+    fn TessBaseAPIClear(handle: *mut TessBaseApi);
+    fn TessResultIteratorDelete(handle: *mut TessResultIterator);
+    fn TessResultIteratorNext(handle: *mut TessResultIterator, level: c_int) -> c_int;
+    fn TessResultIteratorGetUTF8Text(
+        handle: *const TessResultIterator,
+        level: c_int,
+    ) -> *mut c_char;
+    fn TessResultIteratorConfidence(handle: *const TessResultIterator, level: c_int) -> c_float;
+    fn TessResultIteratorGetPageIteratorConst(
+        handle: *const TessResultIterator,
+    ) -> *const TessPageIterator;
+    fn TessPageIteratorBoundingBox(
+        handle: *const TessPageIterator,
+        level: c_int,
+        left: *mut c_int,
+        top: *mut c_int,
+        right: *mut c_int,
+        bottom: *mut c_int,
+    ) -> c_int;
+    fn TessDeleteText(text: *const c_char);
+}
+
+/// Owned `TessBaseAPI` handle.
+struct TessApi {
+    handle: NonNull<TessBaseApi>,
+}
+
+// The handle has no thread affinity; the engine only touches it while holding its mutex.
+unsafe impl Send for TessApi {}
+
+impl TessApi {
+    fn init(languages: &CStr, psm: u8) -> Result<Self, OcrError> {
+        let handle = NonNull::new(unsafe { TessBaseAPICreate() })
+            .ok_or_else(|| OcrError::backend("tesseract failed to allocate an API handle"))?;
+        let api = Self { handle };
+        // A null data path defers to TESSDATA_PREFIX and the library's compiled-in default.
+        if unsafe { TessBaseAPIInit3(api.handle.as_ptr(), ptr::null(), languages.as_ptr()) } != 0 {
+            return Err(OcrError::backend(format!(
+                "tesseract failed to load language data '{}'; install it or set TESSDATA_PREFIX",
+                languages.to_string_lossy()
+            )));
+        }
+        unsafe { TessBaseAPISetPageSegMode(api.handle.as_ptr(), c_int::from(psm)) };
+        Ok(api)
+    }
+
+    /// Recognizes the pixels inside `rect` and appends one entry per text line.
+    fn recognize_rect(
+        &mut self,
+        rect: (c_int, c_int, c_int, c_int),
+        texts: &mut Vec<OcrText>,
+    ) -> Result<(), OcrError> {
+        let handle = self.handle.as_ptr();
+        let (left, top, width, height) = rect;
+        unsafe { TessBaseAPISetRectangle(handle, left, top, width, height) };
+        if unsafe { TessBaseAPIRecognize(handle, ptr::null_mut()) } != 0 {
+            return Err(OcrError::backend("tesseract recognition failed"));
+        }
+        let Some(iterator) = NonNull::new(unsafe { TessBaseAPIGetIterator(handle) }) else {
+            return Ok(());
+        };
+        let iterator = ResultIterator(iterator);
+        loop {
+            if let Some(text) = iterator.line() {
+                texts.push(text);
+            }
+            if unsafe { TessResultIteratorNext(iterator.0.as_ptr(), LEVEL_TEXTLINE) } == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TessApi {
+    fn drop(&mut self) {
+        unsafe {
+            TessBaseAPIEnd(self.handle.as_ptr());
+            TessBaseAPIDelete(self.handle.as_ptr());
+        }
+    }
+}
+
+struct ResultIterator(NonNull<TessResultIterator>);
+
+impl ResultIterator {
+    /// The current text line with its bounding box in plane coordinates, if it holds text.
+    fn line(&self) -> Option<OcrText> {
+        let raw = unsafe { TessResultIteratorGetUTF8Text(self.0.as_ptr(), LEVEL_TEXTLINE) };
+        if raw.is_null() {
+            return None;
+        }
+        let bytes = unsafe { CStr::from_ptr(raw) }.to_bytes().to_vec();
+        unsafe { TessDeleteText(raw) };
+
+        let (mut left, mut top, mut right, mut bottom) = (0, 0, 0, 0);
+        let page = unsafe { TessResultIteratorGetPageIteratorConst(self.0.as_ptr()) };
+        let has_box = !page.is_null()
+            && unsafe {
+                TessPageIteratorBoundingBox(
+                    page,
+                    LEVEL_TEXTLINE,
+                    &mut left,
+                    &mut top,
+                    &mut right,
+                    &mut bottom,
+                )
+            } != 0;
+        if !has_box {
+            return None;
+        }
+        let region = OcrRegion::new(
+            left as f32,
+            top as f32,
+            (right - left) as f32,
+            (bottom - top) as f32,
+        );
+        let text = OcrText::from_utf8_lossy(region, &bytes);
+        if text.text.trim().is_empty() {
+            return None;
+        }
+        let confidence = unsafe { TessResultIteratorConfidence(self.0.as_ptr(), LEVEL_TEXTLINE) };
+        // Tesseract reports 0-100 and a negative value when it has no estimate.
+        if confidence.is_finite() && confidence >= 0.0 {
+            Some(text.with_confidence((confidence / 100.0).min(1.0)))
+        } else {
+            Some(text)
+        }
+    }
+}
+
+impl Drop for ResultIterator {
+    fn drop(&mut self) {
+        unsafe { TessResultIteratorDelete(self.0.as_ptr()) };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TesseractOcrConfig {
+    /// Tesseract language codes such as `eng` or `chi_sim`, combined in order.
+    pub languages: Vec<String>,
+    /// Page segmentation mode passed to tesseract (`--psm`).
+    pub psm: u8,
+}
+
+impl Default for TesseractOcrConfig {
+    fn default() -> Self {
+        Self {
+            languages: vec!["eng".to_string()],
+            // A single uniform block, which fits one- and two-line subtitles.
+            psm: 6,
+        }
+    }
+}
+
+pub struct TesseractOcrEngine {
+    api: Mutex<TessApi>,
+}
+
+impl TesseractOcrEngine {
+    pub fn new() -> Result<Self, OcrError> {
+        Self::with_config(TesseractOcrConfig::default())
+    }
+
+    pub fn with_config(config: TesseractOcrConfig) -> Result<Self, OcrError> {
+        let mut languages: Vec<&str> = Vec::with_capacity(config.languages.len());
+        for value in &config.languages {
+            let trimmed = value.trim();
+            if !trimmed.is_empty() && !languages.contains(&trimmed) {
+                languages.push(trimmed);
+            }
+        }
+        if languages.is_empty() {
+            return Err(OcrError::backend(
+                "tesseract OCR needs at least one language",
+            ));
+        }
+        let languages = CString::new(languages.join("+")).map_err(|_| {
+            OcrError::backend(
+                "tesseract OCR language contains interior null byte and cannot be used",
+            )
+        })?;
+        let api = TessApi::init(&languages, config.psm)?;
+        Ok(Self {
+            api: Mutex::new(api),
+        })
+    }
+}
+
+const _: () = crate::engine::assert_thread_safe::<TesseractOcrEngine>();
+
+impl OcrEngine for TesseractOcrEngine {
+    fn name(&self) -> &'static str {
+        "tesseract"
+    }
+
+    fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+        let plane = request.plane();
+        if plane.data().is_empty() || plane.width() == 0 || plane.height() == 0 {
+            return Ok(OcrResponse::empty());
+        }
+        let dimension = |value: usize| {
+            c_int::try_from(value)
+                .map_err(|_| OcrError::backend("plane is too large for tesseract"))
+        };
+        let width = dimension(plane.width() as usize)?;
+        let height = dimension(plane.height() as usize)?;
+        let stride = dimension(plane.stride())?;
+
+        let full_frame = [OcrRegion::new(0.0, 0.0, width as f32, height as f32)];
+        let regions = match request.regions() {
+            [] => &full_frame[..],
+            regions => regions,
+        };
+
+        let mut api = self
+            .api
+            .lock()
+            .map_err(|_| OcrError::backend("tesseract engine poisoned by an earlier panic"))?;
+        let handle = api.handle.as_ptr();
+        // The plane outlives the calls below; each region then only narrows the rectangle.
+        unsafe { TessBaseAPISetImage(handle, plane.data().as_ptr(), width, height, 1, stride) };
+        let mut texts = Vec::new();
+        let mut result = Ok(());
+        for region in regions {
+            let Some(rect) = clamp_region(region, width, height) else {
+                continue;
+            };
+            result = api.recognize_rect(rect, &mut texts);
+            if result.is_err() {
+                break;
+            }
+        }
+        unsafe { TessBaseAPIClear(handle) };
+        result.map(|()| OcrResponse::new(texts))
+    }
+}
+
+/// Integer `(left, top, width, height)` of `region` inside a `width` x `height` plane, or
+/// `None` when nothing of it is left.
+fn clamp_region(
+    region: &OcrRegion,
+    width: c_int,
+    height: c_int,
+) -> Option<(c_int, c_int, c_int, c_int)> {
+    let left = (region.x.floor() as c_int).clamp(0, width);
+    let top = (region.y.floor() as c_int).clamp(0, height);
+    let right = ((region.x + region.width).ceil() as c_int).clamp(left, width);
+    let bottom = ((region.y + region.height).ceil() as c_int).clamp(top, height);
+    (right > left && bottom > top).then_some((left, top, right - left, bottom - top))
+}
@@ -2,7 +2,10 @@ use std::ffi::{CStr, CString};
 use std::ptr;
 use std::slice;
 
-use crate::{OcrEngine, OcrError, OcrRegion, OcrRequest, OcrResponse, OcrText};
+use crate::{LumaPlane, OcrEngine, OcrError, OcrRegion, OcrRequest, OcrResponse, OcrText};
+
+/// Error the bridge reports for an empty or malformed frame; retrying cannot help.
+const INVALID_INPUT_ERROR: &str = "invalid input frame for Vision OCR";
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -18,6 +21,8 @@ struct CVisionOcrText {
     rect: CVisionOcrRect,
     confidence: f32,
     text: *mut std::os::raw::c_char,
+    /// Index into the `regions` passed to the bridge; 0 for the whole-frame fallback.
+    region_index: usize,
 }
 
 #[repr(C)]
@@ -141,39 +146,27 @@ impl VisionOcrEngine {
             auto_detect_language: config.auto_detect_language,
         })
     }
-}
-
-impl OcrEngine for VisionOcrEngine {
-    fn name(&self) -> &'static str {
-        "macos_vision"
-    }
 
-    fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
-        let plane = request.plane();
+    /// Runs one bridge call over `regions` of `plane` and returns each text with the index of
+    /// the region it was found in. An empty `regions` slice covers the whole plane.
+    fn recognize_regions(
+        &self,
+        plane: &LumaPlane<'_>,
+        regions: &[CVisionOcrRect],
+    ) -> Result<Vec<(usize, OcrText)>, OcrError> {
         let width = plane.width() as usize;
         let height = plane.height() as usize;
         let stride = plane.stride();
 
         let data = plane.data();
         if data.is_empty() {
-            return Ok(OcrResponse::empty());
-        }
-
-        let regions = request.regions();
-        let mut ffi_regions = Vec::with_capacity(regions.len());
-        for region in regions {
-            ffi_regions.push(CVisionOcrRect {
-                x: region.x,
-                y: region.y,
-                width: region.width,
-                height: region.height,
-            });
+            return Ok(Vec::new());
         }
 
-        let (regions_ptr, regions_count) = if ffi_regions.is_empty() {
+        let (regions_ptr, regions_count) = if regions.is_empty() {
             (ptr::null(), 0)
         } else {
-            (ffi_regions.as_ptr(), ffi_regions.len())
+            (regions.as_ptr(), regions.len())
         };
 
         let language_ptrs: Vec<*const std::os::raw::c_char> =
@@ -200,7 +193,12 @@ impl OcrEngine for VisionOcrEngine {
 
         let owned = OwnedVisionOcrResult::new(raw);
         if let Some(message) = owned.error_message() {
-            return Err(OcrError::backend(message));
+            // Apart from rejected input, the bridge fails on allocations and on the Vision
+            // request itself, both of which can succeed on a retry once memory frees up.
+            if message == INVALID_INPUT_ERROR {
+                return Err(OcrError::backend(message));
+            }
+            return Err(OcrError::transient(message));
         }
 
         let mut texts = Vec::with_capacity(owned.texts().len());
@@ -208,26 +206,98 @@ impl OcrEngine for VisionOcrEngine {
             if entry.text.is_null() {
                 continue;
             }
-            let text = unsafe { CStr::from_ptr(entry.text) }
-                .to_string_lossy()
-                .into_owned();
-            if text.trim().is_empty() {
-                continue;
-            }
-
+            let bytes = unsafe { CStr::from_ptr(entry.text) }.to_bytes();
             let region = entry.rect;
-            let mut ocr_text = OcrText::new(
+            let mut ocr_text = OcrText::from_utf8_lossy(
                 OcrRegion::new(region.x, region.y, region.width, region.height),
-                text,
+                bytes,
             );
+            if ocr_text.text.trim().is_empty() {
+                continue;
+            }
 
             if entry.confidence.is_finite() && entry.confidence >= 0.0 {
                 ocr_text = ocr_text.with_confidence(entry.confidence);
             }
 
-            texts.push(ocr_text);
+            texts.push((entry.region_index, ocr_text));
         }
 
-        Ok(OcrResponse::new(texts))
+        Ok(texts)
+    }
+}
+
+const _: () = crate::engine::assert_thread_safe::<VisionOcrEngine>();
+
+fn ffi_rect(region: &OcrRegion) -> CVisionOcrRect {
+    CVisionOcrRect {
+        x: region.x,
+        y: region.y,
+        width: region.width,
+        height: region.height,
+    }
+}
+
+/// Whether two requests read the same pixels and can share one bridge call.
+fn same_plane(a: &LumaPlane<'_>, b: &LumaPlane<'_>) -> bool {
+    ptr::eq(a.data(), b.data())
+        && a.width() == b.width()
+        && a.height() == b.height()
+        && a.stride() == b.stride()
+}
+
+impl OcrEngine for VisionOcrEngine {
+    fn name(&self) -> &'static str {
+        "macos_vision"
+    }
+
+    fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+        let regions: Vec<CVisionOcrRect> = request.regions().iter().map(ffi_rect).collect();
+        let texts = self.recognize_regions(request.plane(), &regions)?;
+        Ok(OcrResponse::new(
+            texts.into_iter().map(|(_, text)| text).collect(),
+        ))
+    }
+
+    /// Consecutive requests over the same plane are recognized in a single bridge call and
+    /// their texts routed back by region index.
+    fn recognize_batch(&self, requests: &[OcrRequest<'_>]) -> Result<Vec<OcrResponse>, OcrError> {
+        let mut responses = Vec::with_capacity(requests.len());
+        let mut start = 0;
+        while start < requests.len() {
+            let plane = requests[start].plane();
+            let end = start
+                + requests[start..]
+                    .iter()
+                    .take_while(|request| same_plane(request.plane(), plane))
+                    .count();
+
+            // `owners[i]` is the request that contributed region `i` of the combined call.
+            let full_frame = OcrRegion::new(0.0, 0.0, plane.width() as f32, plane.height() as f32);
+            let mut regions = Vec::new();
+            let mut owners = Vec::new();
+            for (offset, request) in requests[start..end].iter().enumerate() {
+                match request.regions() {
+                    [] => {
+                        regions.push(ffi_rect(&full_frame));
+                        owners.push(offset);
+                    }
+                    request_regions => {
+                        regions.extend(request_regions.iter().map(ffi_rect));
+                        owners.extend(std::iter::repeat_n(offset, request_regions.len()));
+                    }
+                }
+            }
+
+            let mut texts: Vec<Vec<OcrText>> = vec![Vec::new(); end - start];
+            for (region_index, text) in self.recognize_regions(plane, &regions)? {
+                if let Some(&owner) = owners.get(region_index) {
+                    texts[owner].push(text);
+                }
+            }
+            responses.extend(texts.into_iter().map(OcrResponse::new));
+            start = end;
+        }
+        Ok(responses)
     }
 }
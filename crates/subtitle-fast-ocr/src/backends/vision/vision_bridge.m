@@ -21,6 +21,7 @@ typedef struct {
     VisionOcrRect rect;
     float confidence;
     char *text;
+    size_t region_index;
 } VisionOcrText;
 
 typedef struct {
@@ -342,6 +343,7 @@ VisionOcrResult vision_recognize_text(
                 entry.rect.height = obs_height;
                 entry.confidence = (float)best.confidence;
                 entry.text = text;
+                entry.region_index = idx;
 
                 if (!vision_append_text(&texts, &count, &capacity, entry)) {
                     free(text);
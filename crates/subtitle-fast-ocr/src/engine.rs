@@ -3,6 +3,10 @@ use crate::request::OcrRequest;
 use crate::response::OcrResponse;
 
 /// Common interface for all OCR engines.
+///
+/// The pipeline shares one engine as `Arc<dyn OcrEngine>` and calls it from worker tasks on
+/// any thread, so engines must be `Send + Sync`. Engines wrapping non-thread-safe native state
+/// should guard it with a `Mutex` rather than asserting `Send`/`Sync` with `unsafe impl`.
 pub trait OcrEngine: Send + Sync {
     fn name(&self) -> &'static str;
 
@@ -11,8 +15,25 @@ pub trait OcrEngine: Send + Sync {
     }
 
     fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError>;
+
+    /// Recognizes several requests, returning one response per request in order. Backends
+    /// with per-call overhead can override this to submit the requests together.
+    fn recognize_batch(&self, requests: &[OcrRequest<'_>]) -> Result<Vec<OcrResponse>, OcrError> {
+        requests
+            .iter()
+            .map(|request| self.recognize(request))
+            .collect()
+    }
 }
 
+/// Compile-time check that the trait object and bundled engines can be shared across threads.
+pub(crate) const fn assert_thread_safe<T: ?Sized + Send + Sync>() {}
+
+const _: () = {
+    assert_thread_safe::<dyn OcrEngine>();
+    assert_thread_safe::<NoopOcrEngine>();
+};
+
 /// Placeholder OCR engine used while a real backend is not wired.
 #[derive(Debug, Default)]
 pub struct NoopOcrEngine;
@@ -26,3 +47,26 @@ impl OcrEngine for NoopOcrEngine {
         Ok(OcrResponse::empty())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LumaPlane, OcrRegion};
+
+    #[test]
+    fn default_batch_returns_one_response_per_request() {
+        let data = vec![0u8; 16 * 8];
+        let regions = [OcrRegion::new(0.0, 0.0, 8.0, 4.0)];
+        let plane = || LumaPlane::from_parts(16, 8, 16, &data).unwrap();
+        let requests = [
+            OcrRequest::new(plane(), &regions),
+            OcrRequest::new(plane(), &[]),
+            OcrRequest::new(plane(), &regions),
+        ];
+
+        let responses = NoopOcrEngine.recognize_batch(&requests).unwrap();
+
+        assert_eq!(responses.len(), requests.len());
+        assert!(NoopOcrEngine.recognize_batch(&[]).unwrap().is_empty());
+    }
+}
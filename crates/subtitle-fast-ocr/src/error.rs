@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -10,6 +12,12 @@ pub enum OcrError {
     PlaneOverflow { stride: usize, height: u32 },
     #[error("backend error: {message}")]
     Backend { message: String },
+    /// A failure the backend may not repeat, such as an FFI call rejected under memory
+    /// pressure; callers can retry the request.
+    #[error("transient backend error: {message}")]
+    Transient { message: String },
+    #[error("OCR model not found at {}; download it there or pass --model-cache", path.display())]
+    ModelNotFound { path: PathBuf },
 }
 
 impl OcrError {
@@ -18,4 +26,24 @@ impl OcrError {
             message: message.into(),
         }
     }
+
+    pub fn transient(message: impl Into<String>) -> Self {
+        Self::Transient {
+            message: message.into(),
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Transient { .. })
+    }
+
+    /// Resolves `file_name` inside the model cache directory, failing with the full expected path.
+    pub fn locate_model(cache_dir: &Path, file_name: &str) -> Result<PathBuf, Self> {
+        let path = cache_dir.join(file_name);
+        if path.is_file() {
+            Ok(path)
+        } else {
+            Err(Self::ModelNotFound { path })
+        }
+    }
 }
@@ -1,3 +1,4 @@
+mod async_engine;
 mod backends;
 mod engine;
 mod error;
@@ -6,6 +7,13 @@ mod region;
 mod request;
 mod response;
 
+pub use async_engine::{AsyncOcrEngine, OcrFuture, OwnedOcrRequest, SyncOcrAdapter};
+#[cfg(feature = "engine-onnx")]
+pub use backends::onnx::{
+    OnnxPpocrConfig, OnnxPpocrRecognizer, PPOCR_DICTIONARY_FILE, PPOCR_REC_MODEL_FILE,
+};
+#[cfg(feature = "engine-tesseract")]
+pub use backends::tesseract::{TesseractOcrConfig, TesseractOcrEngine};
 #[cfg(all(feature = "engine-vision", target_os = "macos"))]
 pub use backends::vision::{VisionOcrConfig, VisionOcrEngine};
 pub use engine::{NoopOcrEngine, OcrEngine};
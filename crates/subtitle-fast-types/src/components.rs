@@ -0,0 +1,150 @@
+//! 4-connected component labeling over run-length encoded binary masks, shared by the
+//! validator's band detectors and the comparator's component preprocessing.
+
+/// A horizontal run of set pixels in row `y`, covering `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRun {
+    pub y: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Pixel count and inclusive bounding box of one connected component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentBounds {
+    pub area: usize,
+    pub min_x: usize,
+    pub max_x: usize,
+    pub min_y: usize,
+    pub max_y: usize,
+}
+
+impl ComponentBounds {
+    fn new(x: usize, y: usize) -> Self {
+        Self {
+            area: 0,
+            min_x: x,
+            max_x: x,
+            min_y: y,
+            max_y: y,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.max_x - self.min_x + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.max_y - self.min_y + 1
+    }
+}
+
+/// Labels a byte mask where any non-zero value is set; rows are `width` bytes apart.
+pub fn mask_components(mask: &[u8], width: usize, height: usize) -> Vec<ComponentBounds> {
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let mut runs = Vec::new();
+    for (y, row) in mask.chunks_exact(width).take(height).enumerate() {
+        let mut x = 0usize;
+        while x < width {
+            if row[x] == 0 {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < width && row[x] != 0 {
+                x += 1;
+            }
+            runs.push(RowRun { y, start, end: x });
+        }
+    }
+    label_runs(&runs)
+}
+
+/// Groups runs into components, joining runs in adjacent rows whose spans overlap. `runs`
+/// must be ordered by row.
+pub fn label_runs(runs: &[RowRun]) -> Vec<ComponentBounds> {
+    if runs.is_empty() {
+        return Vec::new();
+    }
+
+    let mut dsu = RunDsu::new(runs.len());
+    let mut prev_row = 0..0;
+    let mut row_start = 0;
+    while row_start < runs.len() {
+        let y = runs[row_start].y;
+        let row_end = runs[row_start..]
+            .iter()
+            .position(|run| run.y != y)
+            .map_or(runs.len(), |len| row_start + len);
+        if prev_row.start < prev_row.end && runs[prev_row.start].y + 1 == y {
+            for curr_idx in row_start..row_end {
+                let curr = runs[curr_idx];
+                for prev_idx in prev_row.clone() {
+                    let prev = runs[prev_idx];
+                    if prev.end > curr.start && curr.end > prev.start {
+                        dsu.union(curr_idx as u32, prev_idx as u32);
+                    }
+                }
+            }
+        }
+        prev_row = row_start..row_end;
+        row_start = row_end;
+    }
+
+    let mut stats = vec![None; runs.len()];
+    for (idx, run) in runs.iter().enumerate() {
+        let root = dsu.find(idx as u32) as usize;
+        let entry = stats[root].get_or_insert_with(|| ComponentBounds::new(run.start, run.y));
+        entry.area += run.end - run.start;
+        entry.min_x = entry.min_x.min(run.start);
+        entry.max_x = entry.max_x.max(run.end.saturating_sub(1));
+        entry.min_y = entry.min_y.min(run.y);
+        entry.max_y = entry.max_y.max(run.y);
+    }
+    stats.into_iter().flatten().collect()
+}
+
+/// Union-find over run indices with path compression and union by rank.
+struct RunDsu {
+    parent: Vec<u32>,
+    rank: Vec<u8>,
+}
+
+impl RunDsu {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len as u32).collect(),
+            rank: vec![0; len],
+        }
+    }
+
+    fn find(&mut self, x: u32) -> u32 {
+        let idx = x as usize;
+        let parent = self.parent[idx];
+        if parent == x {
+            return x;
+        }
+        let root = self.find(parent);
+        self.parent[idx] = root;
+        root
+    }
+
+    fn union(&mut self, a: u32, b: u32) {
+        let mut root_a = self.find(a);
+        let mut root_b = self.find(b);
+        if root_a == root_b {
+            return;
+        }
+        let rank_a = self.rank[root_a as usize];
+        let rank_b = self.rank[root_b as usize];
+        if rank_a < rank_b {
+            std::mem::swap(&mut root_a, &mut root_b);
+        }
+        self.parent[root_b as usize] = root_a;
+        if rank_a == rank_b {
+            self.rank[root_a as usize] = rank_a + 1;
+        }
+    }
+}
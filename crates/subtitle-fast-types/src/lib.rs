@@ -14,6 +14,8 @@ use std::time::Duration;
 use serde::Serialize;
 use thiserror::Error;
 
+pub mod components;
+
 pub type DecoderResult<T> = Result<T, DecoderError>;
 
 #[derive(Clone)]
@@ -30,6 +32,8 @@ pub struct VideoFrame {
 #[derive(Clone)]
 pub enum FrameBuffer {
     Nv12(Nv12Buffer),
+    I420(I420Buffer),
+    P010(P010Buffer),
     Native(NativeBuffer),
 }
 
@@ -37,8 +41,119 @@ pub enum FrameBuffer {
 pub struct Nv12Buffer {
     y_stride: usize,
     uv_stride: usize,
+    y_plane: Arc<PlaneData>,
+    uv_plane: Arc<PlaneData>,
+}
+
+/// Takes plane allocations back once no frame references them, so a decoder can fill them
+/// again instead of allocating per frame.
+pub trait PlaneRecycler: Send + Sync {
+    fn recycle(&self, plane: Vec<u8>);
+}
+
+/// An NV12 plane, handed to its recycler (if any) when the last reference drops.
+struct PlaneData {
+    bytes: Vec<u8>,
+    recycler: Option<Arc<dyn PlaneRecycler>>,
+}
+
+impl std::ops::Deref for PlaneData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for PlaneData {
+    fn drop(&mut self) {
+        if let Some(recycler) = self.recycler.take() {
+            recycler.recycle(std::mem::take(&mut self.bytes));
+        }
+    }
+}
+
+/// Planar 4:2:0: a full-resolution Y plane followed by separate quarter-size U and V planes.
+#[derive(Clone)]
+pub struct I420Buffer {
+    y_stride: usize,
+    u_stride: usize,
+    v_stride: usize,
     y_plane: Arc<[u8]>,
-    uv_plane: Arc<[u8]>,
+    u_plane: Arc<[u8]>,
+    v_plane: Arc<[u8]>,
+}
+
+/// 10-bit 4:2:0 laid out like NV12, with one 16-bit sample per component holding the value in
+/// its high 10 bits. Strides count samples rather than bytes. An 8-bit copy of the luma (the
+/// high byte of each sample) is derived on construction so 8-bit consumers such as the
+/// detectors can read [`VideoFrame::y_plane`] unchanged.
+#[derive(Clone)]
+pub struct P010Buffer {
+    y_stride: usize,
+    uv_stride: usize,
+    y_plane: Arc<[u16]>,
+    uv_plane: Arc<[u16]>,
+    luma_8bit: Arc<[u8]>,
+}
+
+/// YCbCr-to-RGB matrix used by [`VideoFrame::to_rgb24`] and [`VideoFrame::to_bgra`]. Both
+/// expect limited-range (16-235) samples, which is what video decoders produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Standard-definition sources.
+    #[default]
+    Bt601,
+    /// High-definition sources.
+    Bt709,
+}
+
+impl ColorSpace {
+    /// Cr weight for red, Cb and Cr weights for green, and Cb weight for blue.
+    fn chroma_weights(self) -> [f32; 4] {
+        match self {
+            ColorSpace::Bt601 => [1.596, 0.392, 0.813, 2.017],
+            ColorSpace::Bt709 => [1.793, 0.213, 0.533, 2.112],
+        }
+    }
+
+    fn to_rgb(self, y: u8, cb: u8, cr: u8) -> [u8; 3] {
+        let [r_cr, g_cb, g_cr, b_cb] = self.chroma_weights();
+        let luma = 1.164 * (y as f32 - 16.0);
+        let cb = cb as f32 - 128.0;
+        let cr = cr as f32 - 128.0;
+        let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+        [
+            channel(luma + r_cr * cr),
+            channel(luma - g_cb * cb - g_cr * cr),
+            channel(luma + b_cb * cb),
+        ]
+    }
+}
+
+/// Sample range a source declares, as probed by a decoder backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorRange {
+    /// 16-235 luma, what [`ColorSpace`] conversions expect.
+    Limited,
+    /// 0-255 luma.
+    Full,
+}
+
+/// Color primaries a source declares, as probed by a decoder backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorPrimaries {
+    Bt709,
+    /// BT.601 625-line (PAL).
+    Bt470bg,
+    /// BT.601 525-line (NTSC).
+    Smpte170m,
+    /// Wide-gamut HDR and UHD sources.
+    Bt2020,
+    /// Declared, but none of the above.
+    Other,
 }
 
 #[derive(Clone)]
@@ -95,6 +210,55 @@ impl Nv12Buffer {
     }
 }
 
+impl P010Buffer {
+    pub fn y_stride(&self) -> usize {
+        self.y_stride
+    }
+
+    pub fn uv_stride(&self) -> usize {
+        self.uv_stride
+    }
+
+    pub fn y_plane(&self) -> &[u16] {
+        &self.y_plane
+    }
+
+    pub fn uv_plane(&self) -> &[u16] {
+        &self.uv_plane
+    }
+
+    /// Luma shifted down to 8 bits, with the same stride as the 16-bit plane.
+    pub fn luma_8bit(&self) -> &[u8] {
+        &self.luma_8bit
+    }
+}
+
+impl I420Buffer {
+    pub fn y_stride(&self) -> usize {
+        self.y_stride
+    }
+
+    pub fn u_stride(&self) -> usize {
+        self.u_stride
+    }
+
+    pub fn v_stride(&self) -> usize {
+        self.v_stride
+    }
+
+    pub fn y_plane(&self) -> &[u8] {
+        &self.y_plane
+    }
+
+    pub fn u_plane(&self) -> &[u8] {
+        &self.u_plane
+    }
+
+    pub fn v_plane(&self) -> &[u8] {
+        &self.v_plane
+    }
+}
+
 impl fmt::Debug for VideoFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.buffer {
@@ -112,6 +276,36 @@ impl fmt::Debug for VideoFrame {
                 .field("serial", &self.serial)
                 .field("index", &self.index)
                 .finish(),
+            FrameBuffer::I420(buffer) => f
+                .debug_struct("VideoFrame")
+                .field("width", &self.width)
+                .field("height", &self.height)
+                .field("format", &"i420")
+                .field("y_stride", &buffer.y_stride)
+                .field("u_stride", &buffer.u_stride)
+                .field("v_stride", &buffer.v_stride)
+                .field("y_bytes", &buffer.y_plane.len())
+                .field("u_bytes", &buffer.u_plane.len())
+                .field("v_bytes", &buffer.v_plane.len())
+                .field("pts", &self.pts)
+                .field("dts", &self.dts)
+                .field("serial", &self.serial)
+                .field("index", &self.index)
+                .finish(),
+            FrameBuffer::P010(buffer) => f
+                .debug_struct("VideoFrame")
+                .field("width", &self.width)
+                .field("height", &self.height)
+                .field("format", &"p010")
+                .field("y_stride", &buffer.y_stride)
+                .field("uv_stride", &buffer.uv_stride)
+                .field("y_samples", &buffer.y_plane.len())
+                .field("uv_samples", &buffer.uv_plane.len())
+                .field("pts", &self.pts)
+                .field("dts", &self.dts)
+                .field("serial", &self.serial)
+                .field("index", &self.index)
+                .finish(),
             FrameBuffer::Native(buffer) => f
                 .debug_struct("VideoFrame")
                 .field("width", &self.width)
@@ -138,44 +332,55 @@ impl VideoFrame {
         uv_stride: usize,
         pts: Option<Duration>,
         dts: Option<Duration>,
-        mut y_plane: Vec<u8>,
-        mut uv_plane: Vec<u8>,
+        y_plane: Vec<u8>,
+        uv_plane: Vec<u8>,
     ) -> DecoderResult<Self> {
-        let y_required =
-            y_stride
-                .checked_mul(height as usize)
-                .ok_or_else(|| DecoderError::InvalidFrame {
-                    reason: "calculated NV12 Y plane length overflowed".into(),
-                })?;
-        let uv_rows = nv12_uv_rows(height);
-        let uv_required =
-            uv_stride
-                .checked_mul(uv_rows)
-                .ok_or_else(|| DecoderError::InvalidFrame {
-                    reason: "calculated NV12 UV plane length overflowed".into(),
-                })?;
-
-        if y_plane.len() < y_required {
-            return Err(DecoderError::InvalidFrame {
-                reason: format!(
-                    "insufficient NV12 Y plane bytes: got {} expected at least {}",
-                    y_plane.len(),
-                    y_required
-                ),
-            });
-        }
-        if uv_plane.len() < uv_required {
-            return Err(DecoderError::InvalidFrame {
-                reason: format!(
-                    "insufficient NV12 UV plane bytes: got {} expected at least {}",
-                    uv_plane.len(),
-                    uv_required
-                ),
-            });
-        }
+        Self::nv12_from_planes(
+            width, height, y_stride, uv_stride, pts, dts, y_plane, uv_plane, None,
+        )
+    }
+
+    /// Like [`VideoFrame::from_nv12_owned`], but both planes go to `recycler` once the last
+    /// clone of the frame drops.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_nv12_recycled(
+        width: u32,
+        height: u32,
+        y_stride: usize,
+        uv_stride: usize,
+        pts: Option<Duration>,
+        dts: Option<Duration>,
+        y_plane: Vec<u8>,
+        uv_plane: Vec<u8>,
+        recycler: Arc<dyn PlaneRecycler>,
+    ) -> DecoderResult<Self> {
+        Self::nv12_from_planes(
+            width,
+            height,
+            y_stride,
+            uv_stride,
+            pts,
+            dts,
+            y_plane,
+            uv_plane,
+            Some(recycler),
+        )
+    }
 
-        y_plane.truncate(y_required);
-        uv_plane.truncate(uv_required);
+    #[allow(clippy::too_many_arguments)]
+    fn nv12_from_planes(
+        width: u32,
+        height: u32,
+        y_stride: usize,
+        uv_stride: usize,
+        pts: Option<Duration>,
+        dts: Option<Duration>,
+        y_plane: Vec<u8>,
+        uv_plane: Vec<u8>,
+        recycler: Option<Arc<dyn PlaneRecycler>>,
+    ) -> DecoderResult<Self> {
+        let y_plane = checked_plane(y_plane, y_stride, height as usize, "NV12 Y")?;
+        let uv_plane = checked_plane(uv_plane, uv_stride, chroma_rows(height), "NV12 UV")?;
 
         Ok(Self {
             width,
@@ -185,10 +390,88 @@ impl VideoFrame {
             serial: 0,
             index: None,
             buffer: FrameBuffer::Nv12(Nv12Buffer {
+                y_stride,
+                uv_stride,
+                y_plane: Arc::new(PlaneData {
+                    bytes: y_plane,
+                    recycler: recycler.clone(),
+                }),
+                uv_plane: Arc::new(PlaneData {
+                    bytes: uv_plane,
+                    recycler,
+                }),
+            }),
+        })
+    }
+
+    /// Builds a planar I420 frame, validating each plane against its stride like
+    /// [`VideoFrame::from_nv12_owned`] does.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_i420_owned(
+        width: u32,
+        height: u32,
+        y_stride: usize,
+        u_stride: usize,
+        v_stride: usize,
+        pts: Option<Duration>,
+        dts: Option<Duration>,
+        y_plane: Vec<u8>,
+        u_plane: Vec<u8>,
+        v_plane: Vec<u8>,
+    ) -> DecoderResult<Self> {
+        let chroma_rows = chroma_rows(height);
+        let y_plane = checked_plane(y_plane, y_stride, height as usize, "I420 Y")?;
+        let u_plane = checked_plane(u_plane, u_stride, chroma_rows, "I420 U")?;
+        let v_plane = checked_plane(v_plane, v_stride, chroma_rows, "I420 V")?;
+
+        Ok(Self {
+            width,
+            height,
+            pts,
+            dts,
+            serial: 0,
+            index: None,
+            buffer: FrameBuffer::I420(I420Buffer {
+                y_stride,
+                u_stride,
+                v_stride,
+                y_plane: Arc::from(y_plane.into_boxed_slice()),
+                u_plane: Arc::from(u_plane.into_boxed_slice()),
+                v_plane: Arc::from(v_plane.into_boxed_slice()),
+            }),
+        })
+    }
+
+    /// Builds a 10-bit P010 frame, validating each plane against its stride (in samples) like
+    /// [`VideoFrame::from_nv12_owned`] does, and derives the 8-bit luma view.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_p010_owned(
+        width: u32,
+        height: u32,
+        y_stride: usize,
+        uv_stride: usize,
+        pts: Option<Duration>,
+        dts: Option<Duration>,
+        y_plane: Vec<u16>,
+        uv_plane: Vec<u16>,
+    ) -> DecoderResult<Self> {
+        let y_plane = checked_plane(y_plane, y_stride, height as usize, "P010 Y")?;
+        let uv_plane = checked_plane(uv_plane, uv_stride, chroma_rows(height), "P010 UV")?;
+        let luma_8bit: Vec<u8> = y_plane.iter().map(|&sample| (sample >> 8) as u8).collect();
+
+        Ok(Self {
+            width,
+            height,
+            pts,
+            dts,
+            serial: 0,
+            index: None,
+            buffer: FrameBuffer::P010(P010Buffer {
                 y_stride,
                 uv_stride,
                 y_plane: Arc::from(y_plane.into_boxed_slice()),
                 uv_plane: Arc::from(uv_plane.into_boxed_slice()),
+                luma_8bit: Arc::from(luma_8bit.into_boxed_slice()),
             }),
         })
     }
@@ -256,6 +539,20 @@ impl VideoFrame {
         self.expect_nv12()
     }
 
+    pub fn i420(&self) -> Option<&I420Buffer> {
+        match &self.buffer {
+            FrameBuffer::I420(buffer) => Some(buffer),
+            _ => None,
+        }
+    }
+
+    pub fn p010(&self) -> Option<&P010Buffer> {
+        match &self.buffer {
+            FrameBuffer::P010(buffer) => Some(buffer),
+            _ => None,
+        }
+    }
+
     pub fn native(&self) -> Option<&NativeBuffer> {
         match &self.buffer {
             FrameBuffer::Native(buffer) => Some(buffer),
@@ -264,11 +561,11 @@ impl VideoFrame {
     }
 
     pub fn stride(&self) -> usize {
-        self.expect_nv12().y_stride
+        self.expect_luma().1
     }
 
     pub fn y_stride(&self) -> usize {
-        self.expect_nv12().y_stride
+        self.expect_luma().1
     }
 
     pub fn uv_stride(&self) -> usize {
@@ -276,11 +573,11 @@ impl VideoFrame {
     }
 
     pub fn data(&self) -> &[u8] {
-        &self.expect_nv12().y_plane
+        self.expect_luma().0
     }
 
     pub fn y_plane(&self) -> &[u8] {
-        &self.expect_nv12().y_plane
+        self.expect_luma().0
     }
 
     pub fn uv_plane(&self) -> &[u8] {
@@ -323,20 +620,393 @@ impl VideoFrame {
         self.dts = dts;
     }
 
+    /// Copies the part of the frame inside `roi` (normalized to the frame size) into a new NV12
+    /// frame. The ROI is clamped to the frame and widened to even pixel edges so each chroma
+    /// sample of the crop lies entirely inside it; timestamps, index and serial carry over.
+    pub fn crop_nv12(&self, roi: &RoiConfig) -> DecoderResult<VideoFrame> {
+        let FrameBuffer::Nv12(buffer) = &self.buffer else {
+            return Err(DecoderError::InvalidFrame {
+                reason: "only NV12 frames can be cropped".into(),
+            });
+        };
+        let (left, top, right, bottom) =
+            crop_bounds(roi, self.width, self.height).ok_or_else(|| {
+                DecoderError::InvalidFrame {
+                    reason: format!(
+                        "crop ROI {}x{} at ({}, {}) has no area inside the frame",
+                        roi.width, roi.height, roi.x, roi.y
+                    ),
+                }
+            })?;
+        let frame_chroma_bytes = (self.width as usize).div_ceil(2) * 2;
+        if buffer.y_stride < self.width as usize || buffer.uv_stride < frame_chroma_bytes {
+            return Err(DecoderError::InvalidFrame {
+                reason: "plane stride is narrower than the frame".into(),
+            });
+        }
+
+        let width = right - left;
+        let height = bottom - top;
+        // `left` is even, so it is also the byte offset of its chroma pair.
+        let chroma_bytes = width.div_ceil(2) * 2;
+        let mut y_plane = Vec::with_capacity(width * height);
+        for row in top..bottom {
+            y_plane.extend_from_slice(&buffer.y_plane[row * buffer.y_stride + left..][..width]);
+        }
+        let chroma_top = top / 2;
+        let mut uv_plane = Vec::with_capacity(chroma_bytes * height.div_ceil(2));
+        for row in chroma_top..chroma_top + height.div_ceil(2) {
+            uv_plane.extend_from_slice(
+                &buffer.uv_plane[row * buffer.uv_stride + left..][..chroma_bytes],
+            );
+        }
+
+        VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            chroma_bytes,
+            self.pts,
+            self.dts,
+            y_plane,
+            uv_plane,
+        )
+        .map(|frame| frame.with_serial(self.serial).with_index(self.index))
+    }
+
+    /// Rotates an NV12 or I420 frame clockwise by `degrees`, which must be 0, 90, 180 or 270.
+    /// Quarter turns swap width and height. The planes of the result have no row padding;
+    /// timestamps, index and serial carry over.
+    pub fn rotate(&self, degrees: u32) -> DecoderResult<VideoFrame> {
+        if !matches!(degrees, 0 | 90 | 180 | 270) {
+            return Err(DecoderError::InvalidFrame {
+                reason: format!("rotation must be a multiple of 90 degrees (got {degrees})"),
+            });
+        }
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (chroma_width, chroma_height) = (width.div_ceil(2), chroma_rows(self.height));
+        let (out_width, out_height) = if degrees.is_multiple_of(180) {
+            (self.width, self.height)
+        } else {
+            (self.height, self.width)
+        };
+        let out_chroma_width = (out_width as usize).div_ceil(2);
+        let frame = match &self.buffer {
+            FrameBuffer::Nv12(buffer) => {
+                if buffer.y_stride < width || buffer.uv_stride < chroma_width * 2 {
+                    return Err(DecoderError::InvalidFrame {
+                        reason: "plane stride is narrower than the frame".into(),
+                    });
+                }
+                let y_plane =
+                    rotate_plane(&buffer.y_plane, buffer.y_stride, width, height, 1, degrees);
+                let uv_plane = rotate_plane(
+                    &buffer.uv_plane,
+                    buffer.uv_stride,
+                    chroma_width,
+                    chroma_height,
+                    2,
+                    degrees,
+                );
+                VideoFrame::from_nv12_owned(
+                    out_width,
+                    out_height,
+                    out_width as usize,
+                    out_chroma_width * 2,
+                    self.pts,
+                    self.dts,
+                    y_plane,
+                    uv_plane,
+                )?
+            }
+            FrameBuffer::I420(buffer) => {
+                if buffer.y_stride < width
+                    || buffer.u_stride < chroma_width
+                    || buffer.v_stride < chroma_width
+                {
+                    return Err(DecoderError::InvalidFrame {
+                        reason: "plane stride is narrower than the frame".into(),
+                    });
+                }
+                let y_plane =
+                    rotate_plane(&buffer.y_plane, buffer.y_stride, width, height, 1, degrees);
+                let u_plane = rotate_plane(
+                    &buffer.u_plane,
+                    buffer.u_stride,
+                    chroma_width,
+                    chroma_height,
+                    1,
+                    degrees,
+                );
+                let v_plane = rotate_plane(
+                    &buffer.v_plane,
+                    buffer.v_stride,
+                    chroma_width,
+                    chroma_height,
+                    1,
+                    degrees,
+                );
+                VideoFrame::from_i420_owned(
+                    out_width,
+                    out_height,
+                    out_width as usize,
+                    out_chroma_width,
+                    out_chroma_width,
+                    self.pts,
+                    self.dts,
+                    y_plane,
+                    u_plane,
+                    v_plane,
+                )?
+            }
+            FrameBuffer::P010(_) | FrameBuffer::Native(_) => {
+                return Err(DecoderError::InvalidFrame {
+                    reason: "only NV12 and I420 frames can be rotated".into(),
+                });
+            }
+        };
+        Ok(frame.with_serial(self.serial).with_index(self.index))
+    }
+
+    /// Converts the frame to packed 8-bit RGB, three bytes per pixel with no row padding.
+    /// Chroma is upsampled by repeating each sample over its 2x2 block. Native-handle frames
+    /// have no planes in memory and return an error.
+    pub fn to_rgb24(&self, color_space: ColorSpace) -> DecoderResult<Vec<u8>> {
+        let mut rgb = Vec::with_capacity(self.width as usize * self.height as usize * 3);
+        self.for_each_rgb(color_space, |[r, g, b]| rgb.extend_from_slice(&[r, g, b]))?;
+        Ok(rgb)
+    }
+
+    /// Like [`VideoFrame::to_rgb24`], but four bytes per pixel in B, G, R, A order with an
+    /// opaque alpha.
+    pub fn to_bgra(&self, color_space: ColorSpace) -> DecoderResult<Vec<u8>> {
+        let mut bgra = Vec::with_capacity(self.width as usize * self.height as usize * 4);
+        self.for_each_rgb(color_space, |[r, g, b]| {
+            bgra.extend_from_slice(&[b, g, r, u8::MAX])
+        })?;
+        Ok(bgra)
+    }
+
+    fn for_each_rgb(
+        &self,
+        color_space: ColorSpace,
+        mut emit: impl FnMut([u8; 3]),
+    ) -> DecoderResult<()> {
+        let width = self.width as usize;
+        let chroma_width = width.div_ceil(2);
+        let (y_plane, y_stride, chroma) = match &self.buffer {
+            FrameBuffer::Nv12(buffer) => (
+                &buffer.y_plane[..],
+                buffer.y_stride,
+                ChromaPlanes::Interleaved {
+                    uv: &buffer.uv_plane,
+                    stride: buffer.uv_stride,
+                },
+            ),
+            FrameBuffer::I420(buffer) => (
+                &buffer.y_plane[..],
+                buffer.y_stride,
+                ChromaPlanes::Planar {
+                    u: &buffer.u_plane,
+                    u_stride: buffer.u_stride,
+                    v: &buffer.v_plane,
+                    v_stride: buffer.v_stride,
+                },
+            ),
+            FrameBuffer::P010(buffer) => (
+                &buffer.luma_8bit[..],
+                buffer.y_stride,
+                ChromaPlanes::Interleaved16 {
+                    uv: &buffer.uv_plane,
+                    stride: buffer.uv_stride,
+                },
+            ),
+            FrameBuffer::Native(buffer) => {
+                return Err(DecoderError::InvalidFrame {
+                    reason: format!(
+                        "{} native handle frames cannot be converted to RGB",
+                        buffer.backend
+                    ),
+                });
+            }
+        };
+        if y_stride < width || !chroma.fits(chroma_width) {
+            return Err(DecoderError::InvalidFrame {
+                reason: "plane stride is narrower than the frame".into(),
+            });
+        }
+        for row in 0..self.height as usize {
+            let luma = &y_plane[row * y_stride..][..width];
+            for (column, &y) in luma.iter().enumerate() {
+                let (cb, cr) = chroma.sample(column / 2, row / 2);
+                emit(color_space.to_rgb(y, cb, cr));
+            }
+        }
+        Ok(())
+    }
+
     fn expect_nv12(&self) -> &Nv12Buffer {
         match &self.buffer {
             FrameBuffer::Nv12(buffer) => buffer,
+            FrameBuffer::I420(_) => {
+                panic!("VideoFrame does not contain NV12 data (I420 output requested)")
+            }
+            FrameBuffer::P010(_) => {
+                panic!("VideoFrame does not contain NV12 data (P010 output requested)")
+            }
             FrameBuffer::Native(_) => {
                 panic!("VideoFrame does not contain NV12 data (native handle output requested)")
             }
         }
     }
+
+    /// The Y plane and its stride, which NV12 and I420 lay out identically; P010 frames give
+    /// their 8-bit luma view.
+    fn expect_luma(&self) -> (&[u8], usize) {
+        match &self.buffer {
+            FrameBuffer::Nv12(buffer) => (&buffer.y_plane, buffer.y_stride),
+            FrameBuffer::I420(buffer) => (&buffer.y_plane, buffer.y_stride),
+            FrameBuffer::P010(buffer) => (&buffer.luma_8bit, buffer.y_stride),
+            FrameBuffer::Native(_) => {
+                panic!("VideoFrame does not contain a Y plane (native handle output requested)")
+            }
+        }
+    }
+}
+
+/// Pixel bounds `(left, top, right, bottom)` of a normalized ROI, clamped to the frame and
+/// widened to even edges except where an odd-sized frame ends. `None` for an ROI without area.
+fn crop_bounds(roi: &RoiConfig, width: u32, height: u32) -> Option<(usize, usize, usize, usize)> {
+    if !(roi.width > 0.0 && roi.height > 0.0) {
+        return None;
+    }
+    let span = |start: f32, length: f32, size: u32| {
+        let size_f = size as f32;
+        let from = (start.clamp(0.0, 1.0) * size_f).floor() as usize;
+        let to = ((start + length).clamp(0.0, 1.0) * size_f).ceil() as usize;
+        (to > from).then(|| (from & !1, to.next_multiple_of(2).min(size as usize)))
+    };
+    let (left, right) = span(roi.x, roi.width, width)?;
+    let (top, bottom) = span(roi.y, roi.height, height)?;
+    Some((left, top, right, bottom))
+}
+
+/// Rotates a plane of `width` x `height` pixels, each `sample_size` bytes wide, clockwise by
+/// `degrees` into a tightly packed buffer.
+fn rotate_plane(
+    plane: &[u8],
+    stride: usize,
+    width: usize,
+    height: usize,
+    sample_size: usize,
+    degrees: u32,
+) -> Vec<u8> {
+    let mut rotated = Vec::with_capacity(width * height * sample_size);
+    let (out_width, out_height) = if degrees.is_multiple_of(180) {
+        (width, height)
+    } else {
+        (height, width)
+    };
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let (src_x, src_y) = match degrees {
+                90 => (y, height - 1 - x),
+                180 => (width - 1 - x, height - 1 - y),
+                270 => (width - 1 - y, x),
+                _ => (x, y),
+            };
+            let start = src_y * stride + src_x * sample_size;
+            rotated.extend_from_slice(&plane[start..start + sample_size]);
+        }
+    }
+    rotated
+}
+
+/// Borrowed chroma planes of an NV12, I420 or P010 frame.
+enum ChromaPlanes<'a> {
+    Interleaved {
+        uv: &'a [u8],
+        stride: usize,
+    },
+    /// P010's interleaved 16-bit samples; strides count samples.
+    Interleaved16 {
+        uv: &'a [u16],
+        stride: usize,
+    },
+    Planar {
+        u: &'a [u8],
+        u_stride: usize,
+        v: &'a [u8],
+        v_stride: usize,
+    },
+}
+
+impl ChromaPlanes<'_> {
+    fn fits(&self, chroma_width: usize) -> bool {
+        match *self {
+            ChromaPlanes::Interleaved { stride, .. }
+            | ChromaPlanes::Interleaved16 { stride, .. } => stride >= chroma_width * 2,
+            ChromaPlanes::Planar {
+                u_stride, v_stride, ..
+            } => u_stride >= chroma_width && v_stride >= chroma_width,
+        }
+    }
+
+    /// Cb and Cr of the chroma sample at (`x`, `y`) in chroma coordinates.
+    fn sample(&self, x: usize, y: usize) -> (u8, u8) {
+        match *self {
+            ChromaPlanes::Interleaved { uv, stride } => {
+                let offset = y * stride + x * 2;
+                (uv[offset], uv[offset + 1])
+            }
+            ChromaPlanes::Interleaved16 { uv, stride } => {
+                let offset = y * stride + x * 2;
+                ((uv[offset] >> 8) as u8, (uv[offset + 1] >> 8) as u8)
+            }
+            ChromaPlanes::Planar {
+                u,
+                u_stride,
+                v,
+                v_stride,
+            } => (u[y * u_stride + x], v[y * v_stride + x]),
+        }
+    }
 }
 
-fn nv12_uv_rows(height: u32) -> usize {
+fn chroma_rows(height: u32) -> usize {
     (height as usize).div_ceil(2)
 }
 
+/// Checks that `plane` holds `rows` rows of `stride` samples and drops any trailing padding.
+fn checked_plane<T>(
+    mut plane: Vec<T>,
+    stride: usize,
+    rows: usize,
+    label: &str,
+) -> DecoderResult<Vec<T>> {
+    let unit = if size_of::<T>() == 1 {
+        "bytes"
+    } else {
+        "samples"
+    };
+    let required = stride
+        .checked_mul(rows)
+        .ok_or_else(|| DecoderError::InvalidFrame {
+            reason: format!("calculated {label} plane length overflowed"),
+        })?;
+    if plane.len() < required {
+        return Err(DecoderError::InvalidFrame {
+            reason: format!(
+                "insufficient {label} plane {unit}: got {} expected at least {}",
+                plane.len(),
+                required
+            ),
+        });
+    }
+    plane.truncate(required);
+    Ok(plane)
+}
+
 #[derive(Debug, Error)]
 pub enum DecoderError {
     #[error("backend {backend} is not supported in this build")]
@@ -375,9 +1045,18 @@ impl DecoderError {
             message: message.into(),
         }
     }
+
+    /// Whether the stream can keep going past this error (a single bad frame) or
+    /// must stop (unsupported backend, bad configuration, missing file).
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            Self::BackendFailure { .. } | Self::InvalidFrame { .. }
+        )
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub struct RoiConfig {
     pub x: f32,
     pub y: f32,
@@ -435,6 +1114,8 @@ pub struct OcrText {
     pub region: OcrRegion,
     pub text: String,
     pub confidence: Option<f32>,
+    /// The engine returned bytes that were not valid UTF-8 and `text` holds replacement characters.
+    pub had_invalid_bytes: bool,
 }
 
 impl OcrText {
@@ -443,6 +1124,18 @@ impl OcrText {
             region,
             text,
             confidence: None,
+            had_invalid_bytes: false,
+        }
+    }
+
+    /// Builds text from raw engine output, recovering invalid UTF-8 lossily and flagging it.
+    pub fn from_utf8_lossy(region: OcrRegion, bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::new(region, text.to_owned()),
+            Err(_) => Self {
+                had_invalid_bytes: true,
+                ..Self::new(region, String::from_utf8_lossy(bytes).into_owned())
+            },
         }
     }
 
@@ -11,7 +11,7 @@ use subtitle_fast_types::VideoFrame;
 use subtitle_fast_validator::subtitle_detection::VisionTextDetector;
 use subtitle_fast_validator::subtitle_detection::projection_band::ProjectionBandDetector;
 use subtitle_fast_validator::subtitle_detection::{
-    IntegralBandDetector, LumaBandConfig, RoiConfig, SubtitleDetectionConfig,
+    IntegralBandDetector, LumaBandConfig, LumaPolarity, RoiConfig, SubtitleDetectionConfig,
     SubtitleDetectionError, SubtitleDetector, SubtitleDetectorKind,
 };
 
@@ -115,6 +115,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 config.luma_band = LumaBandConfig {
                     target: TARGET,
                     delta: DELTA,
+                    polarity: LumaPolarity::Bright,
                 };
 
                 let detector = build_bench_detector(kind, &config)?;
@@ -7,12 +7,13 @@ use image::{Rgb, RgbImage};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use serde_json::json;
 use subtitle_fast_types::VideoFrame;
+use subtitle_fast_validator::overlay::RgbCanvas;
 #[cfg(all(feature = "detector-vision", target_os = "macos"))]
 use subtitle_fast_validator::subtitle_detection::VisionTextDetector;
 use subtitle_fast_validator::subtitle_detection::projection_band::ProjectionBandDetector;
 use subtitle_fast_validator::subtitle_detection::{
-    DetectionRegion, IntegralBandDetector, LumaBandConfig, RoiConfig, SubtitleDetectionConfig,
-    SubtitleDetectionError, SubtitleDetector,
+    DetectionRegion, IntegralBandDetector, LumaBandConfig, LumaPolarity, RoiConfig,
+    SubtitleDetectionConfig, SubtitleDetectionError, SubtitleDetector,
 };
 
 const TARGET: u8 = 235;
@@ -113,6 +114,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 config.luma_band = LumaBandConfig {
                     target: TARGET,
                     delta: DELTA,
+                    polarity: LumaPolarity::Bright,
                 };
                 let roi = config.roi;
                 let detector = build_detector(&detector_name, config)?;
@@ -212,32 +214,13 @@ fn frame_to_image(frame: &VideoFrame) -> RgbImage {
 }
 
 fn overlay_regions(image: &mut RgbImage, regions: &[DetectionRegion]) {
+    let (width, height) = image.dimensions();
+    RgbCanvas::new(image, width, height).draw_regions(regions, [255, 0, 0]);
     for (index, region) in regions.iter().enumerate() {
-        draw_box(image, region);
         draw_label(image, region, index);
     }
 }
 
-fn draw_box(image: &mut RgbImage, region: &DetectionRegion) {
-    let width = image.width() as f32;
-    let height = image.height() as f32;
-    let x0 = region.x.max(0.0).min(width - 1.0) as i32;
-    let y0 = region.y.max(0.0).min(height - 1.0) as i32;
-    let x1 = (region.x + region.width).max(0.0).min(width - 1.0) as i32;
-    let y1 = (region.y + region.height).max(0.0).min(height - 1.0) as i32;
-    if x0 >= x1 || y0 >= y1 {
-        return;
-    }
-    for x in x0..=x1 {
-        set_pixel(image, x, y0);
-        set_pixel(image, x, y1);
-    }
-    for y in y0..=y1 {
-        set_pixel(image, x0, y);
-        set_pixel(image, x1, y);
-    }
-}
-
 fn draw_label(image: &mut RgbImage, region: &DetectionRegion, index: usize) {
     let label = index.to_string();
     let width = image.width() as i32;
@@ -321,16 +304,6 @@ fn fill_rect(image: &mut RgbImage, x: i32, y: i32, width: i32, height: i32, colo
     }
 }
 
-fn set_pixel(image: &mut RgbImage, x: i32, y: i32) {
-    if x < 0 || y < 0 {
-        return;
-    }
-    let (x, y) = (x as u32, y as u32);
-    if x < image.width() && y < image.height() {
-        image.put_pixel(x, y, Rgb([255, 0, 0]));
-    }
-}
-
 fn build_detector(
     name: &str,
     config: SubtitleDetectionConfig,
@@ -1,4 +1,7 @@
-use crate::subtitle_detection::{DEFAULT_DELTA, DEFAULT_TARGET, RoiConfig, SubtitleDetectorKind};
+use crate::subtitle_detection::{
+    DEFAULT_DELTA, DEFAULT_MAX_BRIGHT_FRACTION, DEFAULT_MIN_AREA_RATIO, DEFAULT_TARGET,
+    LumaBandTuning, LumaPolarity, RegionScore, RoiConfig, SimdPath, SubtitleDetectorKind,
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct FrameValidatorConfig {
@@ -11,6 +14,14 @@ pub struct SubtitleDetectionOptions {
     pub roi: Option<RoiConfig>,
     pub detector: SubtitleDetectorKind,
     pub luma_band: LumaBandOptions,
+    pub expected_aspect: Option<f32>,
+    pub min_area_ratio: f32,
+    pub max_bright_fraction: f32,
+    pub edge_exclusion_px: u32,
+    pub region_score: RegionScore,
+    pub detection_downscale: u32,
+    pub simd: SimdPath,
+    pub tuning: LumaBandTuning,
 }
 
 impl Default for SubtitleDetectionOptions {
@@ -18,8 +29,16 @@ impl Default for SubtitleDetectionOptions {
         Self {
             enabled: true,
             roi: None,
-            detector: SubtitleDetectorKind::ProjectionBand,
+            detector: SubtitleDetectorKind::default(),
             luma_band: LumaBandOptions::default(),
+            expected_aspect: None,
+            min_area_ratio: DEFAULT_MIN_AREA_RATIO,
+            max_bright_fraction: DEFAULT_MAX_BRIGHT_FRACTION,
+            edge_exclusion_px: 0,
+            region_score: RegionScore::default(),
+            detection_downscale: 1,
+            simd: SimdPath::Auto,
+            tuning: LumaBandTuning::default(),
         }
     }
 }
@@ -28,6 +47,7 @@ impl Default for SubtitleDetectionOptions {
 pub struct LumaBandOptions {
     pub target: u8,
     pub delta: u8,
+    pub polarity: LumaPolarity,
 }
 
 impl Default for LumaBandOptions {
@@ -35,6 +55,7 @@ impl Default for LumaBandOptions {
         Self {
             target: DEFAULT_TARGET,
             delta: DEFAULT_DELTA,
+            polarity: LumaPolarity::Bright,
         }
     }
 }
@@ -57,6 +57,15 @@ impl SubtitleDetectionPipeline {
         let mut state = self.state.lock().await;
         state.finalize();
     }
+
+    pub async fn reset(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut state = self.state.lock().await;
+        state.reset();
+    }
 }
 
 struct SubtitleDetectionState {
@@ -65,6 +74,7 @@ struct SubtitleDetectionState {
     detector_dims: Option<(usize, usize, usize)>,
     detector_roi: Option<RoiConfig>,
     init_error_logged: bool,
+    roi_clamp_logged: bool,
     options: SubtitleDetectionOptions,
 }
 
@@ -76,6 +86,7 @@ impl SubtitleDetectionState {
             detector_dims: None,
             detector_roi: None,
             init_error_logged: false,
+            roi_clamp_logged: false,
             options,
         }
     }
@@ -94,7 +105,13 @@ impl SubtitleDetectionState {
             frame.height() as usize,
             frame.stride(),
         );
-        let desired_roi = roi_override.or(self.options.roi);
+        let desired_roi = match roi_override.or(self.options.roi) {
+            Some(roi) => match self.clamp_roi(roi) {
+                Some(clamped) => Some(clamped),
+                None => return Ok(SubtitleDetectionResult::empty()),
+            },
+            None => None,
+        };
         let detector_kind = self.options.detector;
         let needs_rebuild = self.detector_dims != Some(dims)
             || self.detector_kind != Some(detector_kind)
@@ -108,7 +125,16 @@ impl SubtitleDetectionState {
             detector_config.luma_band = LumaBandConfig {
                 target: self.options.luma_band.target,
                 delta: self.options.luma_band.delta,
+                polarity: self.options.luma_band.polarity,
             };
+            detector_config.expected_aspect = self.options.expected_aspect;
+            detector_config.min_area_ratio = self.options.min_area_ratio;
+            detector_config.max_bright_fraction = self.options.max_bright_fraction;
+            detector_config.edge_exclusion_px = self.options.edge_exclusion_px;
+            detector_config.region_score = self.options.region_score;
+            detector_config.detection_downscale = self.options.detection_downscale;
+            detector_config.simd = self.options.simd;
+            detector_config.tuning = self.options.tuning;
             if let Some(roi) = desired_roi {
                 detector_config.roi = roi;
             }
@@ -149,6 +175,42 @@ impl SubtitleDetectionState {
         }
     }
 
+    /// Clips an ROI that reaches past the frame edges (e.g. one tracked near a border) and
+    /// returns `None` when nothing of it is left on screen.
+    fn clamp_roi(&mut self, roi: RoiConfig) -> Option<RoiConfig> {
+        let x0 = roi.x.clamp(0.0, 1.0);
+        let y0 = roi.y.clamp(0.0, 1.0);
+        let x1 = (roi.x + roi.width).clamp(0.0, 1.0);
+        let y1 = (roi.y + roi.height).clamp(0.0, 1.0);
+        let clamped = (x1 > x0 && y1 > y0).then_some(RoiConfig {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        });
+        if clamped != Some(roi) && !self.roi_clamp_logged {
+            self.roi_clamp_logged = true;
+            match clamped {
+                Some(clamped) => eprintln!(
+                    "detection ROI ({:.3},{:.3},{:.3},{:.3}) extends past the frame; clamped to ({:.3},{:.3},{:.3},{:.3})",
+                    roi.x,
+                    roi.y,
+                    roi.width,
+                    roi.height,
+                    clamped.x,
+                    clamped.y,
+                    clamped.width,
+                    clamped.height
+                ),
+                None => eprintln!(
+                    "detection ROI ({:.3},{:.3},{:.3},{:.3}) lies outside the frame; reporting no subtitle",
+                    roi.x, roi.y, roi.width, roi.height
+                ),
+            }
+        }
+        clamped
+    }
+
     fn finalize(&mut self) {
         if !self.options.enabled {
             return;
@@ -158,6 +220,13 @@ impl SubtitleDetectionState {
         self.detector_dims = None;
         self.detector_roi = None;
     }
+
+    /// Drops the detector and the one-shot log flags so the next frame starts like a new file.
+    fn reset(&mut self) {
+        self.finalize();
+        self.init_error_logged = false;
+        self.roi_clamp_logged = false;
+    }
 }
 
 fn inflate_regions(
@@ -220,3 +289,105 @@ fn log_init_failure(kind: SubtitleDetectorKind, err: &SubtitleDetectionError) {
         kind.as_str()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 640;
+    const HEIGHT: usize = 360;
+
+    fn subtitle_frame() -> VideoFrame {
+        sized_subtitle_frame(WIDTH, HEIGHT)
+    }
+
+    /// Dark frame with a striped bright band across the middle of its lower fifth.
+    fn sized_subtitle_frame(width: usize, height: usize) -> VideoFrame {
+        let mut y_plane = vec![16u8; width * height];
+        for row in height * 29 / 36..height * 32 / 36 {
+            for col in width * 9 / 32..width * 23 / 32 {
+                if (col / 3) % 2 == 0 {
+                    y_plane[row * width + col] = 230;
+                }
+            }
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap()
+    }
+
+    fn state() -> SubtitleDetectionState {
+        SubtitleDetectionState::new(SubtitleDetectionOptions {
+            detector: SubtitleDetectorKind::IntegralBand,
+            ..SubtitleDetectionOptions::default()
+        })
+    }
+
+    #[test]
+    fn partially_off_frame_roi_is_clamped() {
+        let roi = RoiConfig {
+            x: -0.25,
+            y: 0.6,
+            width: 1.5,
+            height: 0.6,
+        };
+        let mut state = state();
+
+        let result = state
+            .process_frame(&subtitle_frame(), Some(roi))
+            .expect("clamped ROI detects");
+
+        assert!(result.has_subtitle);
+        let clamped = state.detector_roi.expect("detector built with clamped ROI");
+        assert_eq!((clamped.x, clamped.y, clamped.width), (0.0, 0.6, 1.0));
+        assert!((clamped.height - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fully_off_frame_roi_reports_no_subtitle() {
+        let roi = RoiConfig {
+            x: 1.2,
+            y: 0.5,
+            width: 0.3,
+            height: 0.2,
+        };
+        let mut state = state();
+
+        let result = state
+            .process_frame(&subtitle_frame(), Some(roi))
+            .expect("off-frame ROI is not an error");
+
+        assert!(!result.has_subtitle);
+        assert!(result.regions.is_empty());
+    }
+
+    #[test]
+    fn reset_lets_the_next_file_use_its_own_dimensions() {
+        let mut state = state();
+        let first = state
+            .process_frame(&sized_subtitle_frame(WIDTH, HEIGHT), None)
+            .expect("first file detects");
+        assert!(first.has_subtitle);
+
+        state.reset();
+        assert!(state.detector.is_none());
+        assert_eq!(state.detector_dims, None);
+
+        let second = state
+            .process_frame(&sized_subtitle_frame(1280, 720), None)
+            .expect("second file detects");
+        assert!(second.has_subtitle);
+        assert_eq!(state.detector_dims, Some((1280, 720, 1280)));
+        let band = &second.regions[0];
+        assert!(band.y >= 560.0 && band.y + band.height <= 660.0, "{band:?}");
+    }
+}
@@ -1,3 +1,4 @@
+pub mod overlay;
 pub mod subtitle_detection;
 
 mod config;
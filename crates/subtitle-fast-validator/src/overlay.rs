@@ -0,0 +1,62 @@
+//! Outlines detection results on packed RGB8 images, for previews and debug dumps.
+
+use subtitle_fast_types::DetectionRegion;
+
+/// Mutable view of a packed RGB8 image, three bytes per pixel with no row padding.
+pub struct RgbCanvas<'a> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+}
+
+impl<'a> RgbCanvas<'a> {
+    /// `pixels` must hold at least `width * height * 3` bytes; pixels past its end are skipped.
+    pub fn new(pixels: &'a mut [u8], width: u32, height: u32) -> Self {
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    pub fn draw_regions(&mut self, regions: &[DetectionRegion], color: [u8; 3]) {
+        for region in regions {
+            self.draw_box(region.x, region.y, region.width, region.height, color);
+        }
+    }
+
+    /// Draws a one-pixel outline of the box, in pixel coordinates, clamped to the image.
+    /// Boxes with no area inside the image are skipped.
+    pub fn draw_box(&mut self, x: f32, y: f32, width: f32, height: f32, color: [u8; 3]) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let max_x = (self.width - 1) as f32;
+        let max_y = (self.height - 1) as f32;
+        let x0 = x.clamp(0.0, max_x) as u32;
+        let y0 = y.clamp(0.0, max_y) as u32;
+        let x1 = (x + width).clamp(0.0, max_x) as u32;
+        let y1 = (y + height).clamp(0.0, max_y) as u32;
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+        for px in x0..=x1 {
+            self.set_pixel(px, y0, color);
+            self.set_pixel(px, y1, color);
+        }
+        for py in y0..=y1 {
+            self.set_pixel(x0, py, color);
+            self.set_pixel(x1, py, color);
+        }
+    }
+
+    fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 3]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let offset = (y as usize * self.width as usize + x as usize) * 3;
+        if let Some(pixel) = self.pixels.get_mut(offset..offset + 3) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+}
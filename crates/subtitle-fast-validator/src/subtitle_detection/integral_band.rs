@@ -1,27 +1,14 @@
 use std::cmp::{self, Ordering};
 
-#[cfg(target_arch = "aarch64")]
-use std::arch::is_aarch64_feature_detected;
-#[cfg(target_arch = "x86_64")]
-use std::arch::is_x86_feature_detected;
-
 use super::{
-    DetectionRegion, LumaBandConfig, MIN_REGION_HEIGHT_PX, MIN_REGION_WIDTH_PX, RoiConfig,
-    SubtitleDetectionConfig, SubtitleDetectionError, SubtitleDetectionResult, SubtitleDetector,
-    log_region_debug,
+    DetectionRegion, LumaBandConfig, LumaBandTuning, MIN_REGION_HEIGHT_PX, MIN_REGION_WIDTH_PX,
+    RegionScore, RoiConfig, SimdPath, SubtitleDetectionConfig, SubtitleDetectionError,
+    SubtitleDetectionResult, SubtitleDetector, is_solid_fill, log_region_debug, near_frame_edge,
 };
 use subtitle_fast_types::VideoFrame;
+use subtitle_fast_types::components::mask_components;
 
-const RLSA_H_GAP: usize = 200;
-const RLSA_V_GAP: usize = 20;
-const MIN_AREA: usize = 400;
-const MAX_AREA_RATIO: f32 = 0.35;
-const MIN_ASPECT_RATIO: f32 = 2.0;
 const VMR_K: usize = 4;
-const Y_MERGE_TOL: usize = 10;
-const IOU_MERGE: f32 = 0.15;
-const NEAR_GAP: usize = 16;
-const MAX_OUTPUT_REGIONS: usize = 5;
 
 #[derive(Clone, Copy)]
 struct RoiRect {
@@ -33,18 +20,41 @@ struct RoiRect {
 
 pub struct IntegralBandDetector {
     config: SubtitleDetectionConfig,
-    roi: RoiRect,
+    rois: Vec<RoiRect>,
     required_len: usize,
+    downscale: usize,
+    simd: SimdPath,
 }
 
 impl IntegralBandDetector {
     pub fn new(config: SubtitleDetectionConfig) -> Result<Self, SubtitleDetectionError> {
+        let roi = config.roi;
+        Self::with_rois(config, vec![roi])
+    }
+
+    /// Detector that analyses each of `rois` independently in one pass, for example the top
+    /// and bottom lines of a bilingual release; `config.roi` is ignored. Regions from every
+    /// ROI are returned together in frame coordinates, highest score first.
+    pub fn with_rois(
+        config: SubtitleDetectionConfig,
+        rois: Vec<RoiConfig>,
+    ) -> Result<Self, SubtitleDetectionError> {
         let required_len = required_len(&config)?;
-        let roi = compute_roi_rect(config.frame_width, config.frame_height, config.roi)?;
+        if rois.is_empty() {
+            return Err(SubtitleDetectionError::EmptyRoi);
+        }
+        let rois = rois
+            .into_iter()
+            .map(|roi| compute_roi_rect(config.frame_width, config.frame_height, roi))
+            .collect::<Result<Vec<_>, _>>()?;
+        let downscale = config.detection_downscale.max(1) as usize;
+        let simd = config.simd.resolve()?;
         Ok(Self {
             config,
-            roi,
+            rois,
             required_len,
+            downscale,
+            simd,
         })
     }
 }
@@ -53,6 +63,7 @@ impl SubtitleDetector for IntegralBandDetector {
     fn ensure_available(config: &SubtitleDetectionConfig) -> Result<(), SubtitleDetectionError> {
         required_len(config)?;
         let _ = compute_roi_rect(config.frame_width, config.frame_height, config.roi)?;
+        config.simd.resolve()?;
         Ok(())
     }
 
@@ -68,93 +79,138 @@ impl SubtitleDetector for IntegralBandDetector {
             });
         }
 
-        if self.roi.width == 0 || self.roi.height == 0 {
+        let mut result = SubtitleDetectionResult::empty();
+        for &roi in &self.rois {
+            let found = self.detect_in_roi(y_plane, roi);
+            result.max_score = result.max_score.max(found.max_score);
+            result.regions.extend(found.regions);
+        }
+        result
+            .regions
+            .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        result.has_subtitle = !result.regions.is_empty();
+        Ok(result)
+    }
+}
+
+impl IntegralBandDetector {
+    /// Runs the band analysis inside one ROI, reporting regions in frame coordinates.
+    fn detect_in_roi(&self, y_plane: &[u8], roi: RoiRect) -> SubtitleDetectionResult {
+        // Mask coordinates and pixel thresholds below are in decimated units.
+        let scale = self.downscale;
+        let width = roi.width / scale;
+        let height = roi.height / scale;
+        if width == 0 || height == 0 {
             let result = SubtitleDetectionResult {
                 has_subtitle: false,
                 max_score: 0.0,
                 regions: Vec::new(),
             };
-            return Ok(result);
+            return result;
         }
 
-        let mut mask = threshold_mask(y_plane, self.config.stride, self.roi, self.config.luma_band);
-        rlsa_horizontal(&mut mask, self.roi.width, self.roi.height, RLSA_H_GAP);
-        rlsa_vertical(&mut mask, self.roi.width, self.roi.height, RLSA_V_GAP);
-
-        let components = connected_components(&mask, self.roi.width, self.roi.height);
+        let mut mask = threshold_mask(
+            y_plane,
+            self.config.stride,
+            roi,
+            self.config.luma_band,
+            scale,
+            self.simd,
+        );
+        let bright = mask.iter().map(|&value| value as usize).sum();
+        if is_solid_fill(bright, mask.len(), self.config.max_bright_fraction) {
+            return SubtitleDetectionResult::empty();
+        }
+        let tuning = &self.config.tuning;
+        rlsa_horizontal(
+            &mut mask,
+            width,
+            height,
+            scaled(tuning.horizontal_gap, scale),
+        );
+        rlsa_vertical(&mut mask, width, height, scaled(tuning.vertical_gap, scale));
+
+        let components = mask_components(&mask, width, height);
         if components.is_empty() {
             let result = SubtitleDetectionResult {
                 has_subtitle: false,
                 max_score: 0.0,
                 regions: Vec::new(),
             };
-            return Ok(result);
+            return result;
         }
 
-        let integral = integral_image(&mask, self.roi.width, self.roi.height);
+        let integral = integral_image(&mask, width, height);
         let frame_area = self
             .config
             .frame_width
-            .saturating_mul(self.config.frame_height) as f32;
-        let max_rect_area = frame_area * MAX_AREA_RATIO;
+            .saturating_mul(self.config.frame_height) as f32
+            / (scale * scale) as f32;
+        let min_component_area = (tuning.min_area / (scale * scale)).max(1);
+        let min_region_height = scaled(MIN_REGION_HEIGHT_PX, scale);
+        let min_region_width = scaled(MIN_REGION_WIDTH_PX, scale);
+        let max_rect_area = frame_area * tuning.max_area_ratio;
+        let min_area = frame_area * self.config.min_area_ratio;
 
         let mut candidates = Vec::new();
         for comp in components {
-            if comp.area < MIN_AREA {
+            if comp.area < min_component_area || (comp.area as f32) < min_area {
                 continue;
             }
-            let width = comp.max_x - comp.min_x + 1;
-            let height = comp.max_y - comp.min_y + 1;
-            if width == 0 || height == 0 {
+            let comp_width = comp.max_x - comp.min_x + 1;
+            let comp_height = comp.max_y - comp.min_y + 1;
+            if comp_width == 0 || comp_height == 0 {
                 continue;
             }
-            if height < MIN_REGION_HEIGHT_PX {
+            if comp_height < min_region_height {
                 log_region_debug(
                     "integral",
                     "reject_short_component",
                     comp.min_x,
                     comp.min_y,
-                    width,
-                    height,
+                    comp_width,
+                    comp_height,
                     0.0,
                 );
                 continue;
             }
-            if width < MIN_REGION_WIDTH_PX {
+            if comp_width < min_region_width {
                 log_region_debug(
                     "integral",
                     "reject_narrow_component",
                     comp.min_x,
                     comp.min_y,
-                    width,
-                    height,
+                    comp_width,
+                    comp_height,
                     0.0,
                 );
                 continue;
             }
-            let rect_area = (width * height) as f32;
+            let rect_area = (comp_width * comp_height) as f32;
             if rect_area > max_rect_area {
                 continue;
             }
-            let aspect = width as f32 / height.max(1) as f32;
-            if aspect < MIN_ASPECT_RATIO {
+            let aspect = comp_width as f32 / comp_height.max(1) as f32;
+            if aspect < tuning.min_aspect_ratio {
                 continue;
             }
 
             let (fill, vmr, score) = evaluate_region(
                 &integral,
-                self.roi.width,
+                width,
                 comp.min_x,
                 comp.min_y,
-                width,
-                height,
+                comp_width,
+                comp_height,
                 VMR_K,
+                self.config.expected_aspect,
+                self.config.region_score,
             );
             candidates.push(Candidate {
                 x: comp.min_x,
                 y: comp.min_y,
-                width,
-                height,
+                width: comp_width,
+                height: comp_height,
                 _fill: fill,
                 _vmr: vmr,
                 score,
@@ -166,17 +222,46 @@ impl SubtitleDetector for IntegralBandDetector {
                 max_score: 0.0,
                 regions: Vec::new(),
             };
-            return Ok(result);
+            return result;
         }
 
-        let mut merged = merge_line_candidates(candidates, &integral, self.roi.width);
+        let mut merged = merge_line_candidates(
+            candidates,
+            &integral,
+            width,
+            scale,
+            tuning,
+            self.config.expected_aspect,
+            self.config.region_score,
+        );
+        merged.retain(|cand| {
+            let near_edge = near_frame_edge(
+                &self.config,
+                cand.x * scale + roi.x,
+                cand.y * scale + roi.y,
+                cand.width * scale,
+                cand.height * scale,
+            );
+            if near_edge {
+                log_region_debug(
+                    "integral",
+                    "reject_edge_region",
+                    cand.x,
+                    cand.y,
+                    cand.width,
+                    cand.height,
+                    cand.score,
+                );
+            }
+            !near_edge
+        });
         if merged.is_empty() {
             let result = SubtitleDetectionResult {
                 has_subtitle: false,
                 max_score: 0.0,
                 regions: Vec::new(),
             };
-            return Ok(result);
+            return result;
         }
 
         merged.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
@@ -184,7 +269,7 @@ impl SubtitleDetector for IntegralBandDetector {
         let max_score = merged.first().map(|c| c.score).unwrap_or(0.0);
 
         let mut regions = Vec::new();
-        for cand in merged.iter().take(MAX_OUTPUT_REGIONS) {
+        for cand in merged.iter().take(tuning.max_output_regions) {
             log_region_debug(
                 "integral",
                 "accept_region",
@@ -195,20 +280,19 @@ impl SubtitleDetector for IntegralBandDetector {
                 cand.score,
             );
             regions.push(DetectionRegion {
-                x: (cand.x + self.roi.x) as f32,
-                y: (cand.y + self.roi.y) as f32,
-                width: cand.width as f32,
-                height: cand.height as f32,
+                x: (cand.x * scale + roi.x) as f32,
+                y: (cand.y * scale + roi.y) as f32,
+                width: (cand.width * scale) as f32,
+                height: (cand.height * scale) as f32,
                 score: cand.score,
             });
         }
 
-        let result = SubtitleDetectionResult {
+        SubtitleDetectionResult {
             has_subtitle: !regions.is_empty(),
             max_score,
             regions,
-        };
-        Ok(result)
+        }
     }
 }
 
@@ -251,36 +335,60 @@ fn compute_roi_rect(
     })
 }
 
-fn threshold_mask(data: &[u8], stride: usize, roi: RoiRect, params: LumaBandConfig) -> Vec<u8> {
-    let mut mask = vec![0u8; roi.width * roi.height];
-    if mask.is_empty() {
-        return mask;
-    }
-
-    let lo = params.target.saturating_sub(params.delta);
-    let hi = params.target.saturating_add(params.delta);
+/// Full-frame pixel distance in units of a mask decimated by `scale`, at least one.
+fn scaled(px: usize, scale: usize) -> usize {
+    (px / scale).max(1)
+}
 
-    #[cfg(target_arch = "x86_64")]
-    {
-        if is_x86_feature_detected!("sse2") {
-            unsafe {
-                threshold_mask_sse2(data, stride, roi, lo, hi, &mut mask);
-            }
-            return mask;
+/// One byte per ROI pixel, 1 where the luma falls in any of the polarity's ranges.
+fn threshold_mask(
+    data: &[u8],
+    stride: usize,
+    roi: RoiRect,
+    params: LumaBandConfig,
+    scale: usize,
+    simd: SimdPath,
+) -> Vec<u8> {
+    let mut ranges = params.ranges().into_iter();
+    let (lo, hi) = ranges.next().unwrap_or_default();
+    let mut mask = threshold_range(data, stride, roi, lo, hi, scale, simd);
+    for (lo, hi) in ranges {
+        let other = threshold_range(data, stride, roi, lo, hi, scale, simd);
+        for (value, extra) in mask.iter_mut().zip(other) {
+            *value |= extra;
         }
     }
+    mask
+}
 
-    #[cfg(target_arch = "aarch64")]
-    {
-        if is_aarch64_feature_detected!("neon") {
-            unsafe {
-                threshold_mask_neon(data, stride, roi, lo, hi, &mut mask);
-            }
-            return mask;
-        }
+fn threshold_range(
+    data: &[u8],
+    stride: usize,
+    roi: RoiRect,
+    lo: u8,
+    hi: u8,
+    scale: usize,
+    simd: SimdPath,
+) -> Vec<u8> {
+    if scale > 1 {
+        return threshold_mask_decimated(data, stride, roi, scale, lo, hi);
     }
 
-    threshold_mask_scalar(data, stride, roi, lo, hi, &mut mask);
+    let mut mask = vec![0u8; roi.width * roi.height];
+    if mask.is_empty() {
+        return mask;
+    }
+
+    // `simd` comes from `SimdPath::resolve`, which only returns paths the CPU supports.
+    match simd {
+        #[cfg(target_arch = "x86_64")]
+        SimdPath::Avx2 => unsafe { threshold_mask_avx2(data, stride, roi, lo, hi, &mut mask) },
+        #[cfg(target_arch = "x86_64")]
+        SimdPath::Sse2 => unsafe { threshold_mask_sse2(data, stride, roi, lo, hi, &mut mask) },
+        #[cfg(target_arch = "aarch64")]
+        SimdPath::Neon => unsafe { threshold_mask_neon(data, stride, roi, lo, hi, &mut mask) },
+        _ => threshold_mask_scalar(data, stride, roi, lo, hi, &mut mask),
+    }
     mask
 }
 
@@ -301,6 +409,32 @@ fn threshold_mask_scalar(
     }
 }
 
+/// Thresholds every `scale`th pixel of every `scale`th ROI row into a mask of
+/// `roi.width / scale` by `roi.height / scale`.
+fn threshold_mask_decimated(
+    data: &[u8],
+    stride: usize,
+    roi: RoiRect,
+    scale: usize,
+    lo: u8,
+    hi: u8,
+) -> Vec<u8> {
+    let width = roi.width / scale;
+    let height = roi.height / scale;
+    let mut mask = vec![0u8; width * height];
+    if mask.is_empty() {
+        return mask;
+    }
+    for (row, dst) in mask.chunks_exact_mut(width).enumerate() {
+        let src_offset = (roi.y + row * scale) * stride + roi.x;
+        let src = &data[src_offset..src_offset + width * scale];
+        for (value, out) in src.iter().step_by(scale).zip(dst.iter_mut()) {
+            *out = u8::from(*value >= lo && *value <= hi);
+        }
+    }
+    mask
+}
+
 #[inline(always)]
 fn threshold_mask_scalar_row(src: &[u8], dst: &mut [u8], lo: u8, hi: u8) {
     for (value, out) in src.iter().zip(dst.iter_mut()) {
@@ -308,6 +442,49 @@ fn threshold_mask_scalar_row(src: &[u8], dst: &mut [u8], lo: u8, hi: u8) {
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn threshold_mask_avx2(
+    data: &[u8],
+    stride: usize,
+    roi: RoiRect,
+    lo: u8,
+    hi: u8,
+    mask: &mut [u8],
+) {
+    use std::arch::x86_64::{
+        __m256i, _mm256_and_si256, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_max_epu8,
+        _mm256_min_epu8, _mm256_set1_epi8, _mm256_storeu_si256,
+    };
+
+    let lo_vec = _mm256_set1_epi8(lo as i8);
+    let hi_vec = _mm256_set1_epi8(hi as i8);
+    let ones = _mm256_set1_epi8(1);
+    let width = roi.width;
+
+    for row in 0..roi.height {
+        let src_ptr = unsafe { data.as_ptr().add((roi.y + row) * stride + roi.x) };
+        let dst_ptr = unsafe { mask.as_mut_ptr().add(row * width) };
+
+        let mut x = 0usize;
+        while x + 32 <= width {
+            let pixels = unsafe { _mm256_loadu_si256(src_ptr.add(x) as *const __m256i) };
+            let ge_lo = _mm256_cmpeq_epi8(pixels, _mm256_max_epu8(pixels, lo_vec));
+            let le_hi = _mm256_cmpeq_epi8(pixels, _mm256_min_epu8(pixels, hi_vec));
+            let mask_vec = _mm256_and_si256(_mm256_and_si256(ge_lo, le_hi), ones);
+            unsafe { _mm256_storeu_si256(dst_ptr.add(x) as *mut __m256i, mask_vec) };
+            x += 32;
+        }
+
+        if x < width {
+            let remaining = width - x;
+            let src_tail = unsafe { std::slice::from_raw_parts(src_ptr.add(x), remaining) };
+            let dst_tail = unsafe { std::slice::from_raw_parts_mut(dst_ptr.add(x), remaining) };
+            threshold_mask_scalar_row(src_tail, dst_tail, lo, hi);
+        }
+    }
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "sse2")]
 unsafe fn threshold_mask_sse2(
@@ -453,136 +630,6 @@ fn rlsa_vertical(mask: &mut [u8], width: usize, height: usize, gap: usize) {
     }
 }
 
-#[derive(Clone, Copy)]
-struct ComponentStats {
-    area: usize,
-    min_x: usize,
-    max_x: usize,
-    min_y: usize,
-    max_y: usize,
-}
-
-impl ComponentStats {
-    fn new(x: usize, y: usize) -> Self {
-        Self {
-            area: 0,
-            min_x: x,
-            max_x: x,
-            min_y: y,
-            max_y: y,
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-struct RowRun {
-    y: usize,
-    start: usize,
-    end: usize,
-}
-
-#[derive(Default)]
-struct RunDsu {
-    parent: Vec<u32>,
-    rank: Vec<u8>,
-}
-
-impl RunDsu {
-    fn new(len: usize) -> Self {
-        Self {
-            parent: (0..len as u32).collect(),
-            rank: vec![0; len],
-        }
-    }
-
-    fn find(&mut self, x: u32) -> u32 {
-        let idx = x as usize;
-        let parent = self.parent[idx];
-        if parent == x {
-            return x;
-        }
-        let root = self.find(parent);
-        self.parent[idx] = root;
-        root
-    }
-
-    fn union(&mut self, a: u32, b: u32) {
-        let mut root_a = self.find(a);
-        let mut root_b = self.find(b);
-        if root_a == root_b {
-            return;
-        }
-        let rank_a = self.rank[root_a as usize];
-        let rank_b = self.rank[root_b as usize];
-        if rank_a < rank_b {
-            std::mem::swap(&mut root_a, &mut root_b);
-        }
-        self.parent[root_b as usize] = root_a;
-        if rank_a == rank_b {
-            self.rank[root_a as usize] = rank_a + 1;
-        }
-    }
-}
-
-fn connected_components(mask: &[u8], width: usize, height: usize) -> Vec<ComponentStats> {
-    if width == 0 || height == 0 {
-        return Vec::new();
-    }
-
-    let mut runs = Vec::new();
-    let mut row_offsets = vec![0usize; height + 1];
-    for y in 0..height {
-        row_offsets[y] = runs.len();
-        let row = &mask[y * width..(y + 1) * width];
-        let mut x = 0usize;
-        while x < width {
-            if row[x] == 0 {
-                x += 1;
-                continue;
-            }
-            let start = x;
-            while x < width && row[x] != 0 {
-                x += 1;
-            }
-            runs.push(RowRun { y, start, end: x });
-        }
-    }
-    row_offsets[height] = runs.len();
-
-    if runs.is_empty() {
-        return Vec::new();
-    }
-
-    let mut dsu = RunDsu::new(runs.len());
-    for y in 1..height {
-        let prev_start = row_offsets[y - 1];
-        let prev_end = row_offsets[y];
-        let curr_start = row_offsets[y];
-        let curr_end = row_offsets[y + 1];
-        for curr_idx in curr_start..curr_end {
-            let curr = runs[curr_idx];
-            for (prev_idx, prev) in runs.iter().enumerate().take(prev_end).skip(prev_start) {
-                if prev.y + 1 == curr.y && prev.end > curr.start && curr.end > prev.start {
-                    dsu.union(curr_idx as u32, prev_idx as u32);
-                }
-            }
-        }
-    }
-
-    let mut stats = vec![None; runs.len()];
-    for (idx, run) in runs.iter().enumerate() {
-        let root = dsu.find(idx as u32) as usize;
-        let entry = stats[root].get_or_insert_with(|| ComponentStats::new(run.start, run.y));
-        entry.area += run.end - run.start;
-        entry.min_x = entry.min_x.min(run.start);
-        entry.max_x = entry.max_x.max(run.end.saturating_sub(1));
-        entry.min_y = entry.min_y.min(run.y);
-        entry.max_y = entry.max_y.max(run.y);
-    }
-
-    stats.into_iter().flatten().collect()
-}
-
 fn integral_image(mask: &[u8], width: usize, height: usize) -> Vec<u32> {
     let stride = width + 1;
     let mut integral = vec![0u32; stride * (height + 1)];
@@ -607,6 +654,7 @@ fn rect_sum(integral: &[u32], width: usize, x0: usize, y0: usize, x1: usize, y1:
         .wrapping_add(integral[idx(x0, y0)])
 }
 
+#[allow(clippy::too_many_arguments)]
 fn evaluate_region(
     integral: &[u32],
     width: usize,
@@ -615,6 +663,8 @@ fn evaluate_region(
     w: usize,
     h: usize,
     vmr_k: usize,
+    expected_aspect: Option<f32>,
+    region_score: RegionScore,
 ) -> (f32, f32, f32) {
     let x1 = x + w;
     let y1 = y + h;
@@ -623,12 +673,14 @@ fn evaluate_region(
     let fill = hits / area;
 
     let mut vmr = 0.0f32;
+    let mut compactness = 1.0f32;
     if w >= vmr_k && h >= vmr_k {
         let dx = cmp::max(w / vmr_k, 1);
         let dy = cmp::max(h / vmr_k, 1);
         let mut count = 0.0f32;
         let mut mean = 0.0f32;
         let mut m2 = 0.0f32;
+        let mut occupied = 0.0f32;
 
         let mut yy = y;
         while yy < y1 {
@@ -638,6 +690,9 @@ fn evaluate_region(
                 let xb = cmp::min(xx + dx, x1);
                 let value = rect_sum(integral, width, xx, yy, xb, yb) as f32;
                 count += 1.0;
+                if value > 0.0 {
+                    occupied += 1.0;
+                }
                 let delta = value - mean;
                 mean += delta / count;
                 let delta2 = value - mean;
@@ -650,13 +705,25 @@ fn evaluate_region(
         if count > 0.0 {
             let variance = if count <= 1.0 { 0.0 } else { m2 / count };
             vmr = variance / (mean + 1e-6);
+            compactness = occupied / count;
         }
     }
 
-    let score = fill - 0.1 * vmr;
+    let score =
+        region_score.score(fill, vmr, compactness) * aspect_prior_factor(w, h, expected_aspect);
     (fill, vmr, score)
 }
 
+/// 1.0 at the expected aspect, falling off as `1 / (1 + |ln(aspect / expected)|)`.
+fn aspect_prior_factor(w: usize, h: usize, expected_aspect: Option<f32>) -> f32 {
+    let Some(expected) = expected_aspect.filter(|value| value.is_finite() && *value > 0.0) else {
+        return 1.0;
+    };
+    let aspect = w as f32 / h.max(1) as f32;
+    let distance = (aspect / expected).ln().abs();
+    1.0 / (1.0 + distance)
+}
+
 #[derive(Clone)]
 struct Candidate {
     x: usize,
@@ -672,6 +739,10 @@ fn merge_line_candidates(
     mut candidates: Vec<Candidate>,
     integral: &[u32],
     width: usize,
+    scale: usize,
+    tuning: &LumaBandTuning,
+    expected_aspect: Option<f32>,
+    region_score: RegionScore,
 ) -> Vec<Candidate> {
     if candidates.is_empty() {
         return Vec::new();
@@ -682,25 +753,49 @@ fn merge_line_candidates(
     let mut line_group = vec![candidates[0].clone()];
 
     for cand in candidates.into_iter().skip(1) {
-        if same_line(line_group.last().unwrap(), &cand) {
+        if same_line(line_group.last().unwrap(), &cand, scale, tuning) {
             line_group.push(cand);
         } else {
-            merged.extend(merge_group(line_group, integral, width));
+            merged.extend(merge_group(
+                line_group,
+                integral,
+                width,
+                scale,
+                tuning,
+                expected_aspect,
+                region_score,
+            ));
             line_group = vec![cand];
         }
     }
 
-    merged.extend(merge_group(line_group, integral, width));
+    merged.extend(merge_group(
+        line_group,
+        integral,
+        width,
+        scale,
+        tuning,
+        expected_aspect,
+        region_score,
+    ));
     merged
 }
 
-fn same_line(a: &Candidate, b: &Candidate) -> bool {
+fn same_line(a: &Candidate, b: &Candidate, scale: usize, tuning: &LumaBandTuning) -> bool {
     let cy1 = a.y + a.height / 2;
     let cy2 = b.y + b.height / 2;
-    cy1.abs_diff(cy2) <= Y_MERGE_TOL
+    cy1.abs_diff(cy2) <= scaled(tuning.line_merge_tolerance, scale)
 }
 
-fn merge_group(mut group: Vec<Candidate>, integral: &[u32], width: usize) -> Vec<Candidate> {
+fn merge_group(
+    mut group: Vec<Candidate>,
+    integral: &[u32],
+    width: usize,
+    scale: usize,
+    tuning: &LumaBandTuning,
+    expected_aspect: Option<f32>,
+    region_score: RegionScore,
+) -> Vec<Candidate> {
     if group.is_empty() {
         return Vec::new();
     }
@@ -710,8 +805,15 @@ fn merge_group(mut group: Vec<Candidate>, integral: &[u32], width: usize) -> Vec
     let mut current = iter.next().unwrap();
 
     for candidate in iter {
-        if should_merge(&current, &candidate) {
-            current = merge_candidates(&current, &candidate, integral, width);
+        if should_merge(&current, &candidate, scale, tuning) {
+            current = merge_candidates(
+                &current,
+                &candidate,
+                integral,
+                width,
+                expected_aspect,
+                region_score,
+            );
         } else {
             result.push(current);
             current = candidate;
@@ -721,10 +823,10 @@ fn merge_group(mut group: Vec<Candidate>, integral: &[u32], width: usize) -> Vec
     result
 }
 
-fn should_merge(a: &Candidate, b: &Candidate) -> bool {
+fn should_merge(a: &Candidate, b: &Candidate, scale: usize, tuning: &LumaBandTuning) -> bool {
     let overlap = candidate_iou(a, b);
-    let near = b.x <= a.x + a.width + NEAR_GAP;
-    overlap >= IOU_MERGE || near
+    let near = b.x <= a.x + a.width + scaled(tuning.merge_gap, scale);
+    overlap >= tuning.merge_iou || near
 }
 
 fn candidate_iou(a: &Candidate, b: &Candidate) -> f32 {
@@ -744,14 +846,31 @@ fn candidate_iou(a: &Candidate, b: &Candidate) -> f32 {
     intersection / union
 }
 
-fn merge_candidates(a: &Candidate, b: &Candidate, integral: &[u32], width: usize) -> Candidate {
+fn merge_candidates(
+    a: &Candidate,
+    b: &Candidate,
+    integral: &[u32],
+    width: usize,
+    expected_aspect: Option<f32>,
+    region_score: RegionScore,
+) -> Candidate {
     let x0 = cmp::min(a.x, b.x);
     let y0 = cmp::min(a.y, b.y);
     let x1 = cmp::max(a.x + a.width, b.x + b.width);
     let y1 = cmp::max(a.y + a.height, b.y + b.height);
     let new_width = x1.saturating_sub(x0);
     let new_height = y1.saturating_sub(y0);
-    let (fill, vmr, score) = evaluate_region(integral, width, x0, y0, new_width, new_height, VMR_K);
+    let (fill, vmr, score) = evaluate_region(
+        integral,
+        width,
+        x0,
+        y0,
+        new_width,
+        new_height,
+        VMR_K,
+        expected_aspect,
+        region_score,
+    );
     Candidate {
         x: x0,
         y: y0,
@@ -762,3 +881,472 @@ fn merge_candidates(a: &Candidate, b: &Candidate, integral: &[u32], width: usize
         score,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{DEFAULT_MAX_BRIGHT_FRACTION, LumaPolarity};
+    use super::*;
+
+    const WIDTH: usize = 200;
+    const HEIGHT: usize = 60;
+
+    fn filled_integral() -> Vec<u32> {
+        integral_image(&vec![1u8; WIDTH * HEIGHT], WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn region_matching_aspect_prior_outscores_equally_filled_region() {
+        let integral = filled_integral();
+        let prior = Some(8.0);
+        let (wide_fill, _, wide_score) = evaluate_region(
+            &integral,
+            WIDTH,
+            0,
+            0,
+            160,
+            20,
+            VMR_K,
+            prior,
+            RegionScore::default(),
+        );
+        let (boxy_fill, _, boxy_score) = evaluate_region(
+            &integral,
+            WIDTH,
+            0,
+            0,
+            64,
+            32,
+            VMR_K,
+            prior,
+            RegionScore::default(),
+        );
+
+        assert!((wide_fill - boxy_fill).abs() < 1e-4);
+        assert!(wide_score > boxy_score);
+    }
+
+    /// Black frame with one 60x24 px block at the target luma: 1440 px, above the absolute
+    /// `LumaBandTuning::min_area` but below the ratio floor once the frame is 4K.
+    fn frame_with_small_block(width: usize, height: usize) -> VideoFrame {
+        let mut y_plane = vec![0u8; width * height];
+        for row in 100..124 {
+            y_plane[row * width + 100..row * width + 160].fill(230);
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap()
+    }
+
+    fn detect_small_block(width: usize, height: usize) -> SubtitleDetectionResult {
+        let config = SubtitleDetectionConfig::for_frame(width, height, width);
+        let detector = IntegralBandDetector::new(config).unwrap();
+        detector
+            .detect(&frame_with_small_block(width, height))
+            .unwrap()
+    }
+
+    #[test]
+    fn small_region_passes_absolute_floor_but_fails_ratio_at_4k() {
+        assert!(detect_small_block(1920, 1080).has_subtitle);
+        assert!(!detect_small_block(3840, 2160).has_subtitle);
+    }
+
+    /// 640x360 frame holding a thin 60x24 px stroke: a two-row bar with a two-column stem, 164
+    /// pixels the smoothing gaps cannot fill in, under the default `min_area`.
+    fn detect_thin_stroke(tuning: LumaBandTuning) -> SubtitleDetectionResult {
+        let (width, height) = (640, 360);
+        let mut y_plane = vec![0u8; width * height];
+        for row in 200..224 {
+            let span = if row < 202 { 100..160 } else { 100..102 };
+            y_plane[row * width + span.start..row * width + span.end].fill(230);
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        let frame = VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap();
+        let mut config = SubtitleDetectionConfig::for_frame(width, height, width);
+        config.tuning = tuning;
+        IntegralBandDetector::new(config)
+            .unwrap()
+            .detect(&frame)
+            .unwrap()
+    }
+
+    #[test]
+    fn lower_min_area_admits_a_band_the_default_rejects() {
+        assert!(!detect_thin_stroke(LumaBandTuning::default()).has_subtitle);
+
+        let tuning = LumaBandTuning {
+            min_area: 100,
+            ..LumaBandTuning::default()
+        };
+        let result = detect_thin_stroke(tuning);
+        assert!(result.has_subtitle);
+        let region = &result.regions[0];
+        assert_eq!((region.x, region.y), (100.0, 200.0));
+        assert_eq!((region.width, region.height), (60.0, 24.0));
+    }
+
+    /// 640x360 frame at `background` luma with a line of 12x32 px glyphs at `text` luma.
+    fn text_line_frame(background: u8, text: u8) -> VideoFrame {
+        let (width, height) = (640, 360);
+        let mut y_plane = vec![background; width * height];
+        for row in 280..312 {
+            for glyph in 0..16 {
+                let x = 160 + glyph * 20;
+                y_plane[row * width + x..row * width + x + 12].fill(text);
+            }
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap()
+    }
+
+    fn detect_with_polarity(frame: &VideoFrame, polarity: LumaPolarity) -> bool {
+        let mut config = SubtitleDetectionConfig::for_frame(640, 360, 640);
+        config.luma_band.polarity = polarity;
+        let result = IntegralBandDetector::new(config)
+            .unwrap()
+            .detect(frame)
+            .unwrap();
+        if let Some(region) = result.regions.first() {
+            assert_eq!((region.x, region.y), (160.0, 280.0));
+            assert_eq!(region.height, 32.0);
+        }
+        result.has_subtitle
+    }
+
+    #[test]
+    fn polarity_selects_bright_or_dark_text() {
+        let bright_on_dark = text_line_frame(60, 230);
+        let dark_on_light = text_line_frame(200, 25);
+
+        assert!(detect_with_polarity(&bright_on_dark, LumaPolarity::Bright));
+        assert!(!detect_with_polarity(&dark_on_light, LumaPolarity::Bright));
+
+        assert!(detect_with_polarity(&dark_on_light, LumaPolarity::Dark));
+        assert!(!detect_with_polarity(&bright_on_dark, LumaPolarity::Dark));
+
+        assert!(detect_with_polarity(&bright_on_dark, LumaPolarity::Either));
+        assert!(detect_with_polarity(&dark_on_light, LumaPolarity::Either));
+    }
+
+    #[test]
+    fn dark_range_mirrors_the_bright_band() {
+        let band = LumaBandConfig {
+            target: 230,
+            delta: 12,
+            polarity: LumaPolarity::Either,
+        };
+        assert_eq!(band.bright_range(), (218, 242));
+        assert_eq!(band.dark_range(), (13, 37));
+        assert_eq!(band.ranges(), vec![(218, 242), (13, 37)]);
+        assert_eq!("Dark".parse::<LumaPolarity>(), Ok(LumaPolarity::Dark));
+    }
+
+    fn detect_bright_bottom_strip(max_bright_fraction: f32) -> SubtitleDetectionResult {
+        let (width, height) = (640, 360);
+        let mut y_plane = vec![0u8; width * height];
+        y_plane[280 * width..].fill(230);
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        let frame = VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap();
+        let mut config = SubtitleDetectionConfig::for_frame(width, height, width);
+        config.roi = RoiConfig {
+            x: 0.0,
+            y: 0.8,
+            width: 1.0,
+            height: 0.2,
+        };
+        config.max_bright_fraction = max_bright_fraction;
+        IntegralBandDetector::new(config)
+            .unwrap()
+            .detect(&frame)
+            .unwrap()
+    }
+
+    #[test]
+    fn fully_bright_roi_is_suppressed_as_solid_fill() {
+        assert!(detect_bright_bottom_strip(1.0).has_subtitle);
+        assert!(!detect_bright_bottom_strip(DEFAULT_MAX_BRIGHT_FRACTION).has_subtitle);
+    }
+
+    #[test]
+    fn multiple_rois_report_a_region_each_in_frame_coordinates() {
+        let (width, height) = (640, 360);
+        let mut y_plane = vec![0u8; width * height];
+        for row in 30..62 {
+            y_plane[row * width + 120..row * width + 400].fill(230);
+        }
+        for row in 290..322 {
+            y_plane[row * width + 200..row * width + 520].fill(230);
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        let frame = VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap();
+        let band = |y| RoiConfig {
+            x: 0.0,
+            y,
+            width: 1.0,
+            height: 0.3,
+        };
+        let config = SubtitleDetectionConfig::for_frame(width, height, width);
+
+        let result = IntegralBandDetector::with_rois(config.clone(), vec![band(0.0), band(0.7)])
+            .unwrap()
+            .detect(&frame)
+            .unwrap();
+        let mut boxes: Vec<_> = result
+            .regions
+            .iter()
+            .map(|region| (region.x, region.y, region.width, region.height))
+            .collect();
+        boxes.sort_by(|a, b| a.1.total_cmp(&b.1));
+        assert_eq!(
+            boxes,
+            vec![(120.0, 30.0, 280.0, 32.0), (200.0, 290.0, 320.0, 32.0)]
+        );
+        assert!(result.has_subtitle);
+
+        let mut single = config.clone();
+        single.roi = band(0.7);
+        let result = IntegralBandDetector::new(single)
+            .unwrap()
+            .detect(&frame)
+            .unwrap();
+        assert_eq!(result.regions.len(), 1);
+        assert_eq!(result.regions[0].y, 290.0);
+
+        assert!(matches!(
+            IntegralBandDetector::with_rois(config, Vec::new()),
+            Err(SubtitleDetectionError::EmptyRoi)
+        ));
+    }
+
+    /// 1080p frame with a 600x48 px band at the target luma starting on odd coordinates, so
+    /// decimation cannot line up with its edges.
+    fn detect_band(downscale: u32) -> DetectionRegion {
+        let (width, height) = (1920, 1080);
+        let mut y_plane = vec![0u8; width * height];
+        for row in 901..949 {
+            y_plane[row * width + 661..row * width + 1261].fill(230);
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        let frame = VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap();
+        let mut config = SubtitleDetectionConfig::for_frame(width, height, width);
+        config.detection_downscale = downscale;
+        let result = IntegralBandDetector::new(config)
+            .unwrap()
+            .detect(&frame)
+            .unwrap();
+        assert_eq!(result.regions.len(), 1);
+        result.regions[0].clone()
+    }
+
+    #[test]
+    fn decimated_detection_maps_back_to_full_res_coordinates() {
+        let full = detect_band(1);
+        let decimated = detect_band(2);
+
+        assert_eq!(
+            (full.x, full.y, full.width, full.height),
+            (661.0, 901.0, 600.0, 48.0)
+        );
+        for (full, decimated) in [
+            (full.x, decimated.x),
+            (full.y, decimated.y),
+            (full.width, decimated.width),
+            (full.height, decimated.height),
+        ] {
+            assert!((full - decimated).abs() <= 2.0, "{full} vs {decimated}");
+        }
+    }
+
+    fn detect_band_rows(rows: std::ops::Range<usize>, edge_exclusion_px: u32) -> bool {
+        let (width, height) = (1920, 1080);
+        let mut y_plane = vec![0u8; width * height];
+        for row in rows {
+            y_plane[row * width + 661..row * width + 1261].fill(230);
+        }
+        let uv_plane = vec![128u8; width * height.div_ceil(2)];
+        let frame = VideoFrame::from_nv12_owned(
+            width as u32,
+            height as u32,
+            width,
+            width,
+            None,
+            None,
+            y_plane,
+            uv_plane,
+        )
+        .unwrap();
+        let mut config = SubtitleDetectionConfig::for_frame(width, height, width);
+        config.edge_exclusion_px = edge_exclusion_px;
+        IntegralBandDetector::new(config)
+            .unwrap()
+            .detect(&frame)
+            .unwrap()
+            .has_subtitle
+    }
+
+    #[test]
+    fn edge_margin_rejects_flush_regions_and_keeps_inset_ones() {
+        assert!(detect_band_rows(1032..1080, 0));
+        assert!(!detect_band_rows(1032..1080, 16));
+        assert!(detect_band_rows(901..949, 16));
+    }
+
+    #[test]
+    fn no_prior_leaves_scores_unbiased() {
+        let integral = filled_integral();
+        let (_, _, wide_score) = evaluate_region(
+            &integral,
+            WIDTH,
+            0,
+            0,
+            160,
+            20,
+            VMR_K,
+            None,
+            RegionScore::default(),
+        );
+        let (_, _, boxy_score) = evaluate_region(
+            &integral,
+            WIDTH,
+            0,
+            0,
+            64,
+            32,
+            VMR_K,
+            None,
+            RegionScore::default(),
+        );
+
+        assert!((wide_score - boxy_score).abs() < 1e-4);
+    }
+
+    /// 80x16 mask with its left half solid: fill 0.5, but clumped into half the blocks.
+    fn clumped_integral() -> Vec<u32> {
+        let mask: Vec<u8> = (0..80 * 16).map(|idx| u8::from(idx % 80 < 40)).collect();
+        integral_image(&mask, 80, 16)
+    }
+
+    /// 80x16 mask of evenly spaced 2-px strokes every 5 px: fill 0.4, uniform across blocks.
+    fn even_integral() -> Vec<u32> {
+        let mask: Vec<u8> = (0..80 * 16).map(|idx| u8::from(idx % 80 % 5 < 2)).collect();
+        integral_image(&mask, 80, 16)
+    }
+
+    #[test]
+    fn score_functions_rank_candidates_differently() {
+        let score = |integral: &[u32], region_score| {
+            evaluate_region(integral, 80, 0, 0, 80, 16, VMR_K, None, region_score).2
+        };
+        let (clumped, even) = (clumped_integral(), even_integral());
+
+        assert!(score(&clumped, RegionScore::Fill) > score(&even, RegionScore::Fill));
+        assert!(score(&clumped, RegionScore::default()) < score(&even, RegionScore::default()));
+        assert!(
+            score(&clumped, RegionScore::FillTimesCompactness)
+                < score(&even, RegionScore::FillTimesCompactness)
+        );
+    }
+
+    #[test]
+    fn every_supported_simd_path_matches_the_scalar_mask() {
+        // 75 columns leave a tail after both the 16- and 32-byte loops; the stride adds padding.
+        let (width, height, stride) = (75, 9, 80);
+        let data: Vec<u8> = (0..stride * height)
+            .map(|idx| (idx * 37 % 256) as u8)
+            .collect();
+        let roi = RoiRect {
+            x: 3,
+            y: 1,
+            width: width - 3,
+            height: height - 2,
+        };
+        let band = LumaBandConfig {
+            target: 200,
+            delta: 40,
+            polarity: LumaPolarity::Bright,
+        };
+        let scalar = threshold_mask(&data, stride, roi, band, 1, SimdPath::Scalar);
+        assert!(scalar.contains(&1) && scalar.contains(&0));
+
+        for path in [SimdPath::Avx2, SimdPath::Sse2, SimdPath::Neon] {
+            if !path.is_supported() {
+                continue;
+            }
+            let forced = threshold_mask(&data, stride, roi, band, 1, path);
+            assert_eq!(forced, scalar, "{} mask differs", path.as_str());
+        }
+    }
+
+    #[test]
+    fn forced_simd_paths_are_checked_against_the_build() {
+        assert_eq!(SimdPath::Scalar.resolve().unwrap(), SimdPath::Scalar);
+        #[cfg(not(target_arch = "aarch64"))]
+        assert!(matches!(
+            SimdPath::Neon.resolve(),
+            Err(SubtitleDetectionError::UnsupportedSimd { path: "neon" })
+        ));
+        #[cfg(not(target_arch = "x86_64"))]
+        assert!(SimdPath::Avx2.resolve().is_err());
+        assert_eq!("AVX2".parse::<SimdPath>(), Ok(SimdPath::Avx2));
+        assert!("sse4".parse::<SimdPath>().is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::env;
+use std::str::FromStr;
 use subtitle_fast_types::VideoFrame;
 use thiserror::Error;
 
@@ -18,7 +19,16 @@ pub const DEFAULT_TARGET: u8 = 230;
 pub const DEFAULT_DELTA: u8 = 12;
 pub const MIN_REGION_HEIGHT_PX: usize = 24;
 pub const MIN_REGION_WIDTH_PX: usize = 24;
+/// Smallest component area as a fraction of the frame; equals the 400 px floor at 1080p.
+pub const DEFAULT_MIN_AREA_RATIO: f32 = 400.0 / (1920.0 * 1080.0);
+/// ROIs with more of their pixels inside the luma band than this are treated as a solid fill
+/// (fades, flashes) rather than text; glyph strokes cover well under half of a subtitle strip.
+pub const DEFAULT_MAX_BRIGHT_FRACTION: f32 = 0.6;
+/// Weight of the block variance-to-mean ratio subtracted from the fill ratio by default.
+pub const DEFAULT_VMR_WEIGHT: f32 = 0.1;
 const REGION_DEBUG_ENV: &str = "REGION_DEBUG";
+/// Forces the integral-band threshold path when the configured one is [`SimdPath::Auto`].
+pub const SIMD_ENV: &str = "SUBFAST_SIMD";
 
 #[cfg(target_os = "macos")]
 const AUTO_DETECTOR_PRIORITY: &[SubtitleDetectorKind] = &[
@@ -54,6 +64,111 @@ fn backend_for_kind(kind: SubtitleDetectorKind) -> Option<&'static dyn DetectorB
 pub struct LumaBandConfig {
     pub target: u8,
     pub delta: u8,
+    pub polarity: LumaPolarity,
+}
+
+impl LumaBandConfig {
+    /// Inclusive luma range of bright text: `target` plus or minus `delta`.
+    pub(crate) fn bright_range(self) -> (u8, u8) {
+        (
+            self.target.saturating_sub(self.delta),
+            self.target.saturating_add(self.delta),
+        )
+    }
+
+    /// Inclusive luma range of dark text: the bright range mirrored through mid-gray, so the
+    /// default 230 +/- 12 band keeps pixels 13-37.
+    pub(crate) fn dark_range(self) -> (u8, u8) {
+        let (lo, hi) = self.bright_range();
+        (u8::MAX - hi, u8::MAX - lo)
+    }
+
+    /// Ranges whose union forms the text mask; `Either` thresholds both.
+    pub(crate) fn ranges(self) -> Vec<(u8, u8)> {
+        match self.polarity {
+            LumaPolarity::Bright => vec![self.bright_range()],
+            LumaPolarity::Dark => vec![self.dark_range()],
+            LumaPolarity::Either => vec![self.bright_range(), self.dark_range()],
+        }
+    }
+}
+
+/// Whether subtitle text is brighter or darker than what surrounds it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LumaPolarity {
+    /// Bright text on a darker background; the band is taken around `target`.
+    #[default]
+    Bright,
+    /// Dark text on a lighter background, such as a white lower-third; the band is taken on
+    /// inverted luma.
+    Dark,
+    /// Text of either polarity; both masks are unioned before component analysis.
+    Either,
+}
+
+impl LumaPolarity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LumaPolarity::Bright => "bright",
+            LumaPolarity::Dark => "dark",
+            LumaPolarity::Either => "either",
+        }
+    }
+}
+
+impl FromStr for LumaPolarity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "bright" => Ok(LumaPolarity::Bright),
+            "dark" => Ok(LumaPolarity::Dark),
+            "either" => Ok(LumaPolarity::Either),
+            _ => Err(lower),
+        }
+    }
+}
+
+/// Geometry thresholds of the integral-band detector. Pixel values are at full resolution and
+/// scaled with `detection_downscale`; the defaults suit horizontal subtitles at 720p-1080p.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LumaBandTuning {
+    /// Horizontal run-length smoothing gap that joins glyphs into lines.
+    pub horizontal_gap: usize,
+    /// Vertical run-length smoothing gap that joins stacked strokes.
+    pub vertical_gap: usize,
+    /// Smallest connected component, in in-band pixels after smoothing.
+    pub min_area: usize,
+    /// Components whose bounding box covers more than this fraction of the frame are dropped.
+    pub max_area_ratio: f32,
+    /// Components narrower than this width/height ratio are dropped; lower it for vertical
+    /// text.
+    pub min_aspect_ratio: f32,
+    /// Candidates whose vertical centres are this close share a line.
+    pub line_merge_tolerance: usize,
+    /// Candidates on one line merge when their IoU reaches this value.
+    pub merge_iou: f32,
+    /// Candidates on one line also merge when at most this many pixels apart horizontally.
+    pub merge_gap: usize,
+    /// Most regions reported per frame, highest scores first.
+    pub max_output_regions: usize,
+}
+
+impl Default for LumaBandTuning {
+    fn default() -> Self {
+        Self {
+            horizontal_gap: 200,
+            vertical_gap: 20,
+            min_area: 400,
+            max_area_ratio: 0.35,
+            min_aspect_ratio: 2.0,
+            line_merge_tolerance: 10,
+            merge_iou: 0.15,
+            merge_gap: 16,
+            max_output_regions: 5,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -158,6 +273,10 @@ pub enum SubtitleDetectionError {
     Vision(String),
     #[error("{backend} detector is not supported on this platform")]
     Unsupported { backend: &'static str },
+    #[error("{path} SIMD path is not available on this build or CPU")]
+    UnsupportedSimd { path: &'static str },
+    #[error("unknown SIMD path '{0}' (expected auto, avx2, sse2, neon or scalar)")]
+    InvalidSimd(String),
 }
 
 #[derive(Debug, Clone)]
@@ -167,6 +286,136 @@ pub struct SubtitleDetectionConfig {
     pub stride: usize,
     pub roi: RoiConfig,
     pub luma_band: LumaBandConfig,
+    /// Expected width/height of a subtitle band; candidates are scored down by their distance
+    /// from it. `None` leaves scoring unbiased.
+    pub expected_aspect: Option<f32>,
+    /// Components smaller than this fraction of the frame area are ignored.
+    pub min_area_ratio: f32,
+    /// Frames whose ROI has more than this fraction of pixels in the luma band are skipped.
+    pub max_bright_fraction: f32,
+    /// Regions whose bounding box comes within this many pixels of the frame border are
+    /// dropped, which rejects station logos and channel bugs; 0 keeps them.
+    pub edge_exclusion_px: u32,
+    /// How the integral-band detector ranks candidate regions.
+    pub region_score: RegionScore,
+    /// The integral-band detector thresholds every Nth pixel of every Nth row and scales the
+    /// regions it finds back to frame coordinates; 1 scans at full resolution.
+    pub detection_downscale: u32,
+    /// Instruction set the integral-band detector thresholds the luma plane with.
+    pub simd: SimdPath,
+    /// Component and merge thresholds of the integral-band detector.
+    pub tuning: LumaBandTuning,
+}
+
+/// Threshold implementation of the integral-band detector. Forcing one helps benchmarking,
+/// testing a specific path, or working around a CPU that misbehaves on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SimdPath {
+    /// `SUBFAST_SIMD` when set, otherwise the widest path the CPU supports.
+    #[default]
+    Auto,
+    Avx2,
+    Sse2,
+    Neon,
+    Scalar,
+}
+
+impl SimdPath {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SimdPath::Auto => "auto",
+            SimdPath::Avx2 => "avx2",
+            SimdPath::Sse2 => "sse2",
+            SimdPath::Neon => "neon",
+            SimdPath::Scalar => "scalar",
+        }
+    }
+
+    /// Whether this build and CPU can run the path; `Auto` and `Scalar` always can.
+    pub fn is_supported(self) -> bool {
+        match self {
+            SimdPath::Auto | SimdPath::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            SimdPath::Avx2 => std::arch::is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            SimdPath::Sse2 => std::arch::is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "aarch64")]
+            SimdPath::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            _ => false,
+        }
+    }
+
+    /// The concrete path to run: `Auto` reads `SUBFAST_SIMD` and falls back to the widest
+    /// supported path; a forced path the build or CPU lacks is an error.
+    pub fn resolve(self) -> Result<SimdPath, SubtitleDetectionError> {
+        let requested = match self {
+            SimdPath::Auto => match env::var(SIMD_ENV) {
+                Ok(value) if !value.trim().is_empty() => {
+                    value.parse().map_err(SubtitleDetectionError::InvalidSimd)?
+                }
+                _ => SimdPath::Auto,
+            },
+            forced => forced,
+        };
+        match requested {
+            SimdPath::Auto => Ok([SimdPath::Avx2, SimdPath::Sse2, SimdPath::Neon]
+                .into_iter()
+                .find(|path| path.is_supported())
+                .unwrap_or(SimdPath::Scalar)),
+            path if path.is_supported() => Ok(path),
+            path => Err(SubtitleDetectionError::UnsupportedSimd {
+                path: path.as_str(),
+            }),
+        }
+    }
+}
+
+impl FromStr for SimdPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "auto" => Ok(SimdPath::Auto),
+            "avx2" => Ok(SimdPath::Avx2),
+            "sse2" => Ok(SimdPath::Sse2),
+            "neon" => Ok(SimdPath::Neon),
+            "scalar" => Ok(SimdPath::Scalar),
+            _ => Err(lower),
+        }
+    }
+}
+
+/// Candidate score used by the integral-band detector before the aspect prior is applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionScore {
+    /// Fill ratio minus `vmr_weight` times the variance-to-mean ratio of per-block hit counts,
+    /// which penalizes boxes whose text pixels are clumped; dense CJK text tolerates a lower
+    /// weight.
+    FillMinusVmr { vmr_weight: f32 },
+    /// Fill ratio alone.
+    Fill,
+    /// Fill ratio times the fraction of blocks holding any text pixel, favouring boxes the
+    /// text spans evenly over ones padded with empty space.
+    FillTimesCompactness,
+}
+
+impl Default for RegionScore {
+    fn default() -> Self {
+        RegionScore::FillMinusVmr {
+            vmr_weight: DEFAULT_VMR_WEIGHT,
+        }
+    }
+}
+
+impl RegionScore {
+    pub(crate) fn score(self, fill: f32, vmr: f32, compactness: f32) -> f32 {
+        match self {
+            RegionScore::FillMinusVmr { vmr_weight } => fill - vmr_weight * vmr,
+            RegionScore::Fill => fill,
+            RegionScore::FillTimesCompactness => fill * compactness,
+        }
+    }
 }
 
 impl SubtitleDetectionConfig {
@@ -184,7 +433,16 @@ impl SubtitleDetectionConfig {
             luma_band: LumaBandConfig {
                 target: DEFAULT_TARGET,
                 delta: DEFAULT_DELTA,
+                polarity: LumaPolarity::Bright,
             },
+            expected_aspect: None,
+            min_area_ratio: DEFAULT_MIN_AREA_RATIO,
+            max_bright_fraction: DEFAULT_MAX_BRIGHT_FRACTION,
+            edge_exclusion_px: 0,
+            region_score: RegionScore::default(),
+            detection_downscale: 1,
+            simd: SimdPath::Auto,
+            tuning: LumaBandTuning::default(),
         }
     }
 }
@@ -228,11 +486,12 @@ fn preflight_auto(probe_config: &SubtitleDetectionConfig) -> Result<(), Subtitle
     }))
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SubtitleDetectorKind {
     Auto,
     MacVision,
     IntegralBand,
+    #[default]
     ProjectionBand,
 }
 
@@ -247,6 +506,23 @@ impl SubtitleDetectorKind {
     }
 }
 
+impl FromStr for SubtitleDetectorKind {
+    type Err = String;
+
+    /// Accepts the `as_str` names; `luma` is the integral-band detector and `vision` the
+    /// macOS Vision one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "auto" => Ok(SubtitleDetectorKind::Auto),
+            "luma" | "integral-band" => Ok(SubtitleDetectorKind::IntegralBand),
+            "projection-band" => Ok(SubtitleDetectorKind::ProjectionBand),
+            "macos-vision" | "vision" => Ok(SubtitleDetectorKind::MacVision),
+            _ => Err(lower),
+        }
+    }
+}
+
 pub trait SubtitleDetector: Send + Sync {
     fn detect(&self, frame: &VideoFrame)
     -> Result<SubtitleDetectionResult, SubtitleDetectionError>;
@@ -281,6 +557,28 @@ fn region_debug_enabled() -> bool {
     env::var_os(REGION_DEBUG_ENV).is_some()
 }
 
+/// True when `bright` of `area` ROI pixels exceeds `max_fraction`, i.e. the ROI is a
+/// near-uniform bright fill rather than text on a background.
+pub(crate) fn is_solid_fill(bright: usize, area: usize, max_fraction: f32) -> bool {
+    area > 0 && bright as f32 > area as f32 * max_fraction
+}
+
+/// True when the frame-space box lies within `config.edge_exclusion_px` of a frame border.
+pub(crate) fn near_frame_edge(
+    config: &SubtitleDetectionConfig,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> bool {
+    let margin = config.edge_exclusion_px as usize;
+    margin > 0
+        && (x < margin
+            || y < margin
+            || x + width + margin > config.frame_width
+            || y + height + margin > config.frame_height)
+}
+
 pub(crate) fn log_region_debug(
     detector: &str,
     event: &str,
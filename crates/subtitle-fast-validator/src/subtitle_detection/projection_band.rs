@@ -6,9 +6,10 @@ const BYTE_BITS: usize = 8;
 use super::{
     DetectionRegion, LumaBandConfig, MIN_REGION_HEIGHT_PX, MIN_REGION_WIDTH_PX, RoiConfig,
     SubtitleDetectionConfig, SubtitleDetectionError, SubtitleDetectionResult, SubtitleDetector,
-    log_region_debug,
+    is_solid_fill, log_region_debug, near_frame_edge,
 };
 use subtitle_fast_types::VideoFrame;
+use subtitle_fast_types::components::{ComponentBounds, RowRun, label_runs};
 
 const ROW_DENSITY_THRESHOLD: f32 = 0.08;
 const MIN_BAND_HEIGHT: usize = 8;
@@ -279,6 +280,14 @@ impl SubtitleDetector for ProjectionBandDetector {
             });
         }
         let mut mask = self.threshold_mask(data);
+        let bright = (0..mask.height).map(|y| mask.count_ones_row(y)).sum();
+        if is_solid_fill(
+            bright,
+            mask.width * mask.height,
+            self.config.max_bright_fraction,
+        ) {
+            return Ok(SubtitleDetectionResult::empty());
+        }
         gap_bridge_horizontal(&mut mask, H_GAP);
         gap_bridge_vertical(&mut mask, V_GAP);
         let mut local_candidates = self.find_candidates(&mask);
@@ -296,6 +305,24 @@ impl SubtitleDetector for ProjectionBandDetector {
         let mut regions = Vec::new();
         for cand in local_candidates {
             let activation = candidate_mass(&cand);
+            if near_frame_edge(
+                &self.config,
+                cand.x + self.roi.x,
+                cand.y + self.roi.y,
+                cand.width,
+                cand.height,
+            ) {
+                log_region_debug(
+                    "projection",
+                    "reject_edge_region",
+                    cand.x,
+                    cand.y,
+                    cand.width,
+                    cand.height,
+                    activation,
+                );
+                continue;
+            }
             log_region_debug(
                 "projection",
                 "accept_region",
@@ -473,13 +500,25 @@ fn analyze_band(mask: &PackedMask, band: Range<usize>, min_area_px: usize) -> Ve
     candidates
 }
 
+/// Packed ROI mask of the pixels whose luma falls in any of the polarity's ranges.
 fn threshold_mask(roi: RoiRect, data: &[u8], stride: usize, params: LumaBandConfig) -> PackedMask {
+    let mut ranges = params.ranges().into_iter();
+    let (lo, hi) = ranges.next().unwrap_or_default();
+    let mut mask = threshold_range(roi, data, stride, lo, hi);
+    for (lo, hi) in ranges {
+        let other = threshold_range(roi, data, stride, lo, hi);
+        for (byte, extra) in mask.data.iter_mut().zip(other.data) {
+            *byte |= extra;
+        }
+    }
+    mask
+}
+
+fn threshold_range(roi: RoiRect, data: &[u8], stride: usize, lo: u8, hi: u8) -> PackedMask {
     let mut mask = PackedMask::new(roi.width, roi.height);
     if mask.is_empty() {
         return mask;
     }
-    let lo = params.target.saturating_sub(params.delta);
-    let hi = params.target.saturating_add(params.delta);
 
     #[cfg(target_arch = "x86_64")]
     let has_sse2 = std::arch::is_x86_feature_detected!("sse2");
@@ -626,87 +665,12 @@ fn rle_candidates(mask: &PackedMask, min_area_px: usize) -> Vec<RegionCandidate>
     candidates
 }
 
-#[derive(Clone, Copy)]
-struct ComponentStats {
-    area: usize,
-    min_x: usize,
-    max_x: usize,
-    min_y: usize,
-    max_y: usize,
-}
-
-impl ComponentStats {
-    fn new(x: usize, y: usize) -> Self {
-        Self {
-            area: 0,
-            min_x: x,
-            max_x: x,
-            min_y: y,
-            max_y: y,
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-struct RowRun {
-    y: usize,
-    start: usize,
-    end: usize,
-}
-
-#[derive(Default)]
-struct RunDsu {
-    parent: Vec<u32>,
-    rank: Vec<u8>,
-}
-
-impl RunDsu {
-    fn new(len: usize) -> Self {
-        Self {
-            parent: (0..len as u32).collect(),
-            rank: vec![0; len],
-        }
-    }
-
-    fn find(&mut self, x: u32) -> u32 {
-        let idx = x as usize;
-        let parent = self.parent[idx];
-        if parent == x {
-            return x;
-        }
-        let root = self.find(parent);
-        self.parent[idx] = root;
-        root
-    }
-
-    fn union(&mut self, a: u32, b: u32) {
-        let mut root_a = self.find(a);
-        let mut root_b = self.find(b);
-        if root_a == root_b {
-            return;
-        }
-        let rank_a = self.rank[root_a as usize];
-        let rank_b = self.rank[root_b as usize];
-        if rank_a < rank_b {
-            std::mem::swap(&mut root_a, &mut root_b);
-        }
-        self.parent[root_b as usize] = root_a;
-        if rank_a == rank_b {
-            self.rank[root_a as usize] = rank_a + 1;
-        }
-    }
-}
-
-fn connected_components(mask: &PackedMask) -> Vec<ComponentStats> {
-    let width = mask.width;
-    let height = mask.height;
-    if width == 0 || height == 0 {
+fn connected_components(mask: &PackedMask) -> Vec<ComponentBounds> {
+    if mask.width == 0 || mask.height == 0 {
         return Vec::new();
     }
     let mut runs = Vec::new();
-    let mut row_offsets = vec![0usize; height + 1];
-    for (y, row_offset) in row_offsets.iter_mut().enumerate().take(height) {
-        *row_offset = runs.len();
+    for y in 0..mask.height {
         let iter = mask.row_iter(y);
         let mut current: Option<RowRun> = None;
         for x in iter {
@@ -735,38 +699,7 @@ fn connected_components(mask: &PackedMask) -> Vec<ComponentStats> {
             runs.push(run);
         }
     }
-    row_offsets[height] = runs.len();
-    if runs.is_empty() {
-        return Vec::new();
-    }
-
-    let mut dsu = RunDsu::new(runs.len());
-    for y in 1..height {
-        let prev_start = row_offsets[y - 1];
-        let prev_end = row_offsets[y];
-        let curr_start = row_offsets[y];
-        let curr_end = row_offsets[y + 1];
-        for curr_idx in curr_start..curr_end {
-            let curr = runs[curr_idx];
-            for (prev_idx, prev) in runs.iter().enumerate().take(prev_end).skip(prev_start) {
-                if prev.y + 1 == curr.y && prev.end > curr.start && curr.end > prev.start {
-                    dsu.union(curr_idx as u32, prev_idx as u32);
-                }
-            }
-        }
-    }
-
-    let mut stats = vec![None; runs.len()];
-    for (idx, run) in runs.iter().enumerate() {
-        let root = dsu.find(idx as u32) as usize;
-        let entry = stats[root].get_or_insert_with(|| ComponentStats::new(run.start, run.y));
-        entry.area += run.end - run.start;
-        entry.min_x = entry.min_x.min(run.start);
-        entry.max_x = entry.max_x.max(run.end.saturating_sub(1));
-        entry.min_y = entry.min_y.min(run.y);
-        entry.max_y = entry.max_y.max(run.y);
-    }
-    stats.into_iter().flatten().collect()
+    label_runs(&runs)
 }
 
 fn gap_bridge_horizontal(mask: &mut PackedMask, gap: usize) {
@@ -847,6 +780,7 @@ fn compute_roi_rect(
 
 #[cfg(test)]
 mod tests {
+    use super::super::LumaPolarity;
     use super::*;
 
     fn rows_to_mask(rows: &[&[u8]]) -> PackedMask {
@@ -896,4 +830,28 @@ mod tests {
         let rows = mask_to_rows(&mask);
         assert_eq!(rows, vec![vec![1, 0], vec![0, 0], vec![0, 0], vec![1, 0]]);
     }
+
+    #[test]
+    fn threshold_mask_follows_polarity() {
+        // 20 columns so the SSE2 path handles the first 16 and the scalar tail the rest.
+        let row: Vec<u8> = [230, 25, 128, 200].repeat(5);
+        let roi = RoiRect {
+            x: 0,
+            y: 0,
+            width: row.len(),
+            height: 1,
+        };
+        let mask_for = |polarity| {
+            let band = LumaBandConfig {
+                target: 230,
+                delta: 12,
+                polarity,
+            };
+            mask_to_rows(&threshold_mask(roi, &row, row.len(), band)).remove(0)
+        };
+
+        assert_eq!(mask_for(LumaPolarity::Bright), [1, 0, 0, 0].repeat(5));
+        assert_eq!(mask_for(LumaPolarity::Dark), [0, 1, 0, 0].repeat(5));
+        assert_eq!(mask_for(LumaPolarity::Either), [1, 1, 0, 0].repeat(5));
+    }
 }
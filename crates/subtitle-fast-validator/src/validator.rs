@@ -39,6 +39,12 @@ impl FrameValidator {
     pub async fn finalize(&self) {
         self.operations.finalize().await;
     }
+
+    /// Forgets the detector built for the previous input (frame size, ROI, workspace) so the
+    /// validator can be reused for another file; the next frame rebuilds it.
+    pub async fn reset(&self) {
+        self.operations.reset().await;
+    }
 }
 
 struct ProcessingOperations {
@@ -69,4 +75,10 @@ impl ProcessingOperations {
             detection.finalize().await;
         }
     }
+
+    async fn reset(&self) {
+        if let Some(detection) = self.detection.as_ref() {
+            detection.reset().await;
+        }
+    }
 }
@@ -1,31 +1,122 @@
-use std::time::Instant;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-use subtitle_fast_decoder::{Backend, Configuration};
+use serde::Serialize;
+use subtitle_fast_decoder::{Backend, Configuration, DynDecoderProvider};
 use subtitle_fast_types::DecoderError;
 
-use crate::stage;
+use crate::cache::DetectionCache;
+use crate::contact_sheet::{CropLog, write_contact_sheet};
+use crate::settings::OutputSettings;
+use crate::{benchmark, preview, stage};
 
 const COL_AVG: &str = "\x1b[33m"; // yellow-ish for averages
 const COL_COUNT: &str = "\x1b[36m"; // cyan-ish for counts
 const COL_RESET: &str = "\x1b[0m";
 
+/// Shortest gap between two JSON progress lines.
+const JSON_PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Conventional exit status for a process stopped by SIGINT.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+/// Exit status of a completed run without any cue when `--fail-if-empty` is set.
+pub const NO_SUBTITLES_EXIT_CODE: i32 = 3;
+
+/// How a run ended when it did not fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Completed,
+    /// Ran to the end of the input without finding a single cue; an empty file was written.
+    NoSubtitles {
+        processed_frames: u64,
+    },
+    /// Stopped early by an interrupt; the subtitles found so far were still written.
+    Interrupted,
+}
+
+impl RunStatus {
+    /// Process exit status for a run that ended this way.
+    pub fn exit_code(self, fail_if_empty: bool) -> i32 {
+        match self {
+            RunStatus::Completed => 0,
+            RunStatus::NoSubtitles { .. } if fail_if_empty => NO_SUBTITLES_EXIT_CODE,
+            RunStatus::NoSubtitles { .. } => 0,
+            RunStatus::Interrupted => INTERRUPTED_EXIT_CODE,
+        }
+    }
+}
+
+/// How detection progress is reported on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// Interactive progress bar.
+    #[default]
+    Bar,
+    /// One JSON object per line, at most every [`JSON_PROGRESS_INTERVAL`].
+    Json,
+}
+
+impl ProgressFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProgressFormat::Bar => "bar",
+            ProgressFormat::Json => "json",
+        }
+    }
+}
+
+impl std::str::FromStr for ProgressFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "bar" => Ok(ProgressFormat::Bar),
+            "json" => Ok(ProgressFormat::Json),
+            _ => Err(lower),
+        }
+    }
+}
+
+/// Flips to `true` when the run should stop decoding and write what it has.
+pub type InterruptReceiver = tokio::sync::watch::Receiver<bool>;
+
 #[derive(Clone)]
 pub struct ExecutionPlan {
     pub config: Configuration,
     pub backend_locked: bool,
+    /// Order in which backends are tried when the first one fails and none is locked.
+    pub backend_priority: Vec<Backend>,
     pub pipeline: stage::PipelineConfig,
+    pub preview: Option<PathBuf>,
+    pub dump_frame_info: Option<u32>,
+    pub benchmark_detectors: bool,
+    /// Exit with [`NO_SUBTITLES_EXIT_CODE`] when a completed run found no cue.
+    pub fail_if_empty: bool,
+    pub cache: Option<DetectionCache>,
+    pub progress_format: ProgressFormat,
 }
 
-pub async fn run(plan: ExecutionPlan) -> Result<(), DecoderError> {
+pub async fn run(
+    plan: ExecutionPlan,
+    mut interrupt: InterruptReceiver,
+) -> Result<RunStatus, DecoderError> {
     let ExecutionPlan {
         config,
         backend_locked,
+        backend_priority: available,
         pipeline,
+        preview,
+        dump_frame_info,
+        benchmark_detectors,
+        fail_if_empty: _,
+        cache,
+        progress_format,
     } = plan;
 
-    let available = Configuration::available_backends();
     if available.is_empty() {
         return Err(DecoderError::configuration(
             "no decoding backend available; rebuild with a backend feature such as \"backend-ffmpeg\"",
@@ -34,6 +125,23 @@ pub async fn run(plan: ExecutionPlan) -> Result<(), DecoderError> {
     if !available.contains(&config.backend) {
         return Err(DecoderError::unsupported(config.backend.as_str()));
     }
+    if !backend_locked {
+        let order: Vec<&str> = available.iter().map(Backend::as_str).collect();
+        eprintln!("decoder backend priority: {}", order.join(", "));
+    }
+
+    let cache_key = match (cache.as_ref(), config.input.as_deref()) {
+        (Some(cache), Some(input)) => {
+            match DetectionCache::key(input, &pipeline.detection, &config, pipeline.start_seek) {
+                Ok(key) => Some((cache, key)),
+                Err(err) => {
+                    eprintln!("detection cache disabled: {err}");
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
 
     let mut attempt_config = config.clone();
     let mut tried = Vec::new();
@@ -79,15 +187,67 @@ pub async fn run(plan: ExecutionPlan) -> Result<(), DecoderError> {
             }
         };
 
-        let pipeline_result = stage::build_pipeline(provider, &pipeline);
+        if let Some(count) = dump_frame_info {
+            return run_frame_info(provider, count)
+                .await
+                .map(|()| RunStatus::Completed);
+        }
+
+        if benchmark_detectors {
+            return run_detector_benchmark(provider, &pipeline)
+                .await
+                .map(|()| RunStatus::Completed);
+        }
+
+        if let Some(preview_path) = preview.as_deref() {
+            return run_preview(provider, &pipeline, preview_path)
+                .await
+                .map(|()| RunStatus::Completed);
+        }
+
+        let cached = cache_key.as_ref().and_then(|(cache, key)| cache.load(key));
+        let replaying = cached.is_some();
+        let pipeline_result = match cached {
+            Some(intervals) => {
+                eprintln!(
+                    "loaded {} subtitle intervals from the detection cache; skipping detection",
+                    intervals.len()
+                );
+                stage::build_cached_pipeline(provider, &pipeline, intervals)
+            }
+            None => stage::build_pipeline(provider, &pipeline),
+        };
 
+        let mut intervals = None;
         let outcome = match pipeline_result {
-            Ok(pipeline_streams) => drive_pipeline(pipeline_streams, &pipeline.output.path).await,
+            Ok(pipeline_streams) => {
+                intervals = Some(pipeline_streams.intervals.clone());
+                drive_pipeline(
+                    pipeline_streams,
+                    &pipeline.output,
+                    progress_format,
+                    &mut interrupt,
+                )
+                .await
+            }
             Err(err) => Err((err, 0)),
         };
 
         match outcome {
-            Ok(()) => return Ok(()),
+            Ok(status) => {
+                if status != RunStatus::Interrupted
+                    && !replaying
+                    && let Some((cache, key)) = cache_key.as_ref()
+                    && let Some(intervals) = intervals.and_then(|log| log.snapshot())
+                    && let Err(err) = cache.store(key, &intervals)
+                {
+                    eprintln!(
+                        "failed to write detection cache in {}: {err}",
+                        cache.dir().display()
+                    );
+                }
+                return Ok(status);
+            }
             Err((err, seen)) => {
                 if seen == 0
                     && !backend_locked
@@ -109,6 +269,59 @@ pub async fn run(plan: ExecutionPlan) -> Result<(), DecoderError> {
     }
 }
 
+async fn run_preview(
+    provider: DynDecoderProvider,
+    pipeline: &stage::PipelineConfig,
+    path: &std::path::Path,
+) -> Result<(), DecoderError> {
+    let total_frames = provider.metadata().total_frames;
+    let (_, frames) = provider.open()?;
+    let input = stage::StreamBundle::new(frames, total_frames);
+
+    match preview::first_detection(input, &pipeline.detection).await? {
+        Some(found) => {
+            preview::write_preview(path, &found, &pipeline.detection)?;
+            preview::print_preview(path, &found);
+        }
+        None => eprintln!("no subtitle detected; preview not written"),
+    }
+    Ok(())
+}
+
+async fn run_detector_benchmark(
+    provider: DynDecoderProvider,
+    pipeline: &stage::PipelineConfig,
+) -> Result<(), DecoderError> {
+    let kinds = benchmark::available_detectors();
+    if kinds.is_empty() {
+        return Err(DecoderError::configuration(
+            "no subtitle detector passed its preflight; nothing to benchmark",
+        ));
+    }
+    let total_frames = provider.metadata().total_frames;
+    let (_controller, frames) = provider.open()?;
+    let input = stage::StreamBundle::new(frames, total_frames);
+
+    let report = benchmark::benchmark_detectors(input, &pipeline.detection, &kinds).await?;
+    print!("{report}");
+    Ok(())
+}
+
+async fn run_frame_info(provider: DynDecoderProvider, count: u32) -> Result<(), DecoderError> {
+    let (_controller, mut frames) = provider.open()?;
+    let mut printed = 0;
+    while printed < count
+        && let Some(frame) = frames.next().await
+    {
+        println!("frame {printed}: {}", preview::frame_info(&frame?));
+        printed += 1;
+    }
+    if printed < count {
+        eprintln!("stream ended after {printed} frame(s)");
+    }
+    Ok(())
+}
+
 pub fn display_available_backends() {
     let names: Vec<&'static str> = Configuration::available_backends()
         .iter()
@@ -126,6 +339,19 @@ pub fn parse_backend(value: &str) -> Result<Backend, DecoderError> {
     Backend::from_str(value)
 }
 
+/// First frame at or after `start` and last frame starting before `end` at `fps`.
+pub fn frame_bounds(
+    start: Option<Duration>,
+    end: Option<Duration>,
+    fps: f64,
+) -> (Option<u64>, Option<u64>) {
+    // Round away float noise so a time on a frame boundary maps onto that frame.
+    let frames = |time: Duration| (time.as_secs_f64() * fps * 1e6).round() / 1e6;
+    let start_frame = start.map(|time| frames(time).floor() as u64);
+    let end_frame = end.map(|time| (frames(time).ceil() as u64).saturating_sub(1));
+    (start_frame, end_frame)
+}
+
 fn select_next_backend(available: &[Backend], tried: &[Backend]) -> Option<Backend> {
     available
         .iter()
@@ -135,19 +361,74 @@ fn select_next_backend(available: &[Backend], tried: &[Backend]) -> Option<Backe
 
 async fn drive_pipeline(
     pipeline: stage::PipelineOutputs,
-    output_path: &std::path::Path,
-) -> Result<(), (DecoderError, u64)> {
+    output: &stage::OutputPipelineConfig,
+    progress_format: ProgressFormat,
+    interrupt: &mut InterruptReceiver,
+) -> Result<RunStatus, (DecoderError, u64)> {
     let mut processed = 0;
     let mut subtitles: Vec<stage::MergedSubtitle> = Vec::new();
     let mut stream = pipeline.stream;
-    let mut progress = PipelineProgressBar::new("detect", pipeline.total_frames);
+    let handle = pipeline.handle;
+    let crops = pipeline.crops;
+    let timeline = pipeline.timeline;
+    let frame_size = pipeline.frame_size;
+    // Progress goes to stderr, so stdout carries nothing but the streamed cues.
+    let mut progress = match progress_format {
+        ProgressFormat::Bar => {
+            ProgressReporter::Bar(PipelineProgressBar::new("detect", pipeline.total_frames))
+        }
+        ProgressFormat::Json => ProgressReporter::Json(JsonProgress::new(
+            io::stderr(),
+            pipeline.total_frames,
+            pipeline.source_fps,
+        )),
+    };
+    let mut streamed = match output.target {
+        stage::OutputTarget::Stdout => Some(FinalizedCueWriter::subtitles(
+            std::io::stdout(),
+            output.clone(),
+            frame_size,
+        )),
+        stage::OutputTarget::Path(_) => output
+            .ndjson_stdout
+            .then(|| FinalizedCueWriter::ndjson(std::io::stdout())),
+    };
+    let mut interrupted = *interrupt.borrow();
+    let mut listening = !interrupted;
+    if interrupted {
+        handle.stop();
+    }
 
-    while let Some(event) = stream.next().await {
+    loop {
+        let event = tokio::select! {
+            changed = interrupt.changed(), if listening => {
+                if changed.is_err() {
+                    listening = false;
+                } else if *interrupt.borrow() {
+                    // Stop decoding but keep draining so active regions are finalized.
+                    interrupted = true;
+                    listening = false;
+                    handle.stop();
+                }
+                continue;
+            }
+            event = stream.next() => event,
+        };
+        let Some(event) = event else {
+            break;
+        };
         match event {
             Ok(update) => {
                 processed = processed.max(update.progress.samples_seen);
                 progress.update(&update.progress);
                 apply_updates(&mut subtitles, &update.updates);
+                if let Some(writer) = streamed.as_mut()
+                    && let Err(err) = writer.apply(&update.updates)
+                {
+                    let mapped = stdout_error(err);
+                    progress.fail(&mapped.to_string());
+                    return Err((mapped, processed));
+                }
             }
             Err(err) => {
                 let mapped = stage::pipeline_error_to_frame(err);
@@ -157,12 +438,149 @@ async fn drive_pipeline(
         }
     }
 
-    progress.finish(processed);
-    sort_and_write(output_path, &subtitles)
+    let status = if interrupted {
+        progress.fail("interrupted; writing subtitles found so far");
+        RunStatus::Interrupted
+    } else if subtitles.is_empty() {
+        progress.finish(processed);
+        eprintln!("no subtitles found after processing {processed} frames; writing an empty file");
+        RunStatus::NoSubtitles {
+            processed_frames: processed,
+        }
+    } else {
+        progress.finish(processed);
+        RunStatus::Completed
+    };
+    if let Some(writer) = streamed.as_mut() {
+        writer
+            .finish()
+            .map_err(|err| (stdout_error(err), processed))?;
+    }
+    if let (Some(timeline), Some(path)) = (timeline, output.timeline_csv.as_deref())
+        && let Err(err) = timeline.finish()
+    {
+        let mapped = DecoderError::configuration(format!(
+            "failed to write timeline CSV {}: {err}",
+            path.display()
+        ));
+        return Err((mapped, processed));
+    }
+    sort_and_write(output, &subtitles, crops.as_ref(), frame_size)
         .await
+        .map(|()| status)
         .map_err(|err| (err, processed))
 }
 
+enum ProgressReporter {
+    Bar(PipelineProgressBar),
+    Json(JsonProgress<io::Stderr>),
+}
+
+impl ProgressReporter {
+    fn update(&mut self, progress: &stage::PipelineProgress) {
+        match self {
+            ProgressReporter::Bar(bar) => bar.update(progress),
+            ProgressReporter::Json(json) => json.update(progress),
+        }
+    }
+
+    fn fail(&mut self, reason: &str) {
+        match self {
+            ProgressReporter::Bar(bar) => bar.fail(reason),
+            ProgressReporter::Json(json) => json.finish(),
+        }
+    }
+
+    fn finish(&mut self, processed: u64) {
+        match self {
+            ProgressReporter::Bar(bar) => bar.finish(processed),
+            ProgressReporter::Json(json) => {
+                if let Some(total) = json.total_frames {
+                    json.processed = total;
+                }
+                json.finish();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonProgressLine {
+    processed: u64,
+    total: Option<u64>,
+    /// Processing speed relative to real-time playback; `None` without a source frame rate.
+    fps_multiplier: Option<f64>,
+    elapsed_ms: u64,
+}
+
+/// Writes progress as newline-delimited JSON, throttled to [`JSON_PROGRESS_INTERVAL`]; the
+/// final line is always written.
+struct JsonProgress<W> {
+    out: W,
+    total_frames: Option<u64>,
+    source_fps: Option<f64>,
+    started: Instant,
+    last_emitted: Option<Instant>,
+    processed: u64,
+    fps: f64,
+    finished: bool,
+}
+
+impl<W: Write> JsonProgress<W> {
+    fn new(out: W, total_frames: Option<u64>, source_fps: Option<f64>) -> Self {
+        Self {
+            out,
+            total_frames,
+            source_fps,
+            started: Instant::now(),
+            last_emitted: None,
+            processed: 0,
+            fps: 0.0,
+            finished: false,
+        }
+    }
+
+    fn update(&mut self, progress: &stage::PipelineProgress) {
+        let next = progress.latest_frame_index.saturating_add(1);
+        self.processed = match self.total_frames {
+            Some(total) => next.min(total),
+            None => next,
+        };
+        self.fps = progress.fps;
+        let due = self
+            .last_emitted
+            .is_none_or(|last| last.elapsed() >= JSON_PROGRESS_INTERVAL);
+        if due {
+            self.emit();
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.emit();
+    }
+
+    fn emit(&mut self) {
+        let line = JsonProgressLine {
+            processed: self.processed,
+            total: self.total_frames,
+            fps_multiplier: self
+                .source_fps
+                .filter(|fps| *fps > 0.0)
+                .map(|source| self.fps / source),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+        };
+        if let Ok(json) = serde_json::to_string(&line) {
+            // Progress is best effort; a closed stderr must not fail the run.
+            let _ = writeln!(self.out, "{json}");
+        }
+        self.last_emitted = Some(Instant::now());
+    }
+}
+
 struct PipelineProgressBar {
     bar: ProgressBar,
     total_frames: Option<u64>,
@@ -290,15 +708,273 @@ fn apply_updates(subtitles: &mut Vec<stage::MergedSubtitle>, updates: &[stage::S
     }
 }
 
+/// How [`FinalizedCueWriter`] encodes cues.
+enum CueEncoding {
+    /// One JSON object per line.
+    Ndjson,
+    /// The subtitle file `output` describes, post-processed one finalized batch at a time.
+    /// The last cue of each batch is held back with the detected cues it was built from, since
+    /// the next batch may still merge into it, extend it or shorten it.
+    Subtitles {
+        output: Box<stage::OutputPipelineConfig>,
+        frame_size: Option<(u32, u32)>,
+        started: bool,
+        held: Vec<stage::MergedSubtitle>,
+    },
+}
+
+/// Writes each cue once the merge stage can no longer extend it, i.e. once a later cue starts
+/// more than the merge cache window after it ended. Cues are numbered across batches.
+struct FinalizedCueWriter<W> {
+    out: W,
+    encoding: CueEncoding,
+    pending: Vec<stage::MergedSubtitle>,
+    latest_start: Duration,
+    written: u64,
+}
+
+impl<W: Write> FinalizedCueWriter<W> {
+    fn ndjson(out: W) -> Self {
+        Self::new(out, CueEncoding::Ndjson)
+    }
+
+    fn subtitles(
+        out: W,
+        output: stage::OutputPipelineConfig,
+        frame_size: Option<(u32, u32)>,
+    ) -> Self {
+        Self::new(
+            out,
+            CueEncoding::Subtitles {
+                output: Box::new(output),
+                frame_size,
+                started: false,
+                held: Vec::new(),
+            },
+        )
+    }
+
+    fn new(out: W, encoding: CueEncoding) -> Self {
+        Self {
+            out,
+            encoding,
+            pending: Vec::new(),
+            latest_start: Duration::ZERO,
+            written: 0,
+        }
+    }
+
+    fn apply(&mut self, updates: &[stage::SubtitleUpdate]) -> io::Result<()> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+        for update in updates {
+            self.latest_start = self.latest_start.max(update.subtitle.start_time);
+        }
+        apply_updates(&mut self.pending, updates);
+        let Some(cutoff) = self
+            .latest_start
+            .checked_sub(stage::merge::SUBTITLE_CACHE_WINDOW)
+        else {
+            return Ok(());
+        };
+        let (done, open) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|subtitle| subtitle.end_time < cutoff);
+        self.pending = open;
+        self.write(done, false)
+    }
+
+    /// Writes every cue still open at the end of the run, then anything the format closes with.
+    fn finish(&mut self) -> io::Result<()> {
+        let remaining = std::mem::take(&mut self.pending);
+        self.write(remaining, true)?;
+        if let CueEncoding::Subtitles { output, .. } = &self.encoding {
+            self.out
+                .write_all(output.format.footer(self.written).as_bytes())?;
+        }
+        self.out.flush()
+    }
+
+    /// With `last`, nothing is held back because no later batch can change the final cue.
+    fn write(&mut self, mut subtitles: Vec<stage::MergedSubtitle>, last: bool) -> io::Result<()> {
+        match &mut self.encoding {
+            CueEncoding::Ndjson => {
+                if subtitles.is_empty() {
+                    return Ok(());
+                }
+                stage::sort_subtitles(&mut subtitles);
+                for subtitle in &subtitles {
+                    self.written += 1;
+                    writeln!(
+                        self.out,
+                        "{}",
+                        stage::render_ndjson_cue(self.written, subtitle)
+                    )?;
+                }
+            }
+            CueEncoding::Subtitles {
+                output,
+                frame_size,
+                started,
+                held,
+            } => {
+                if !*started {
+                    *started = true;
+                    self.out
+                        .write_all(output.format.header(*frame_size).as_bytes())?;
+                }
+                let mut detected = std::mem::take(held);
+                detected.append(&mut subtitles);
+                let mut arranged = arrange_cues(output, &detected);
+                if !last && let Some(open) = arranged.pop() {
+                    *held = cues_behind(detected, open.start_time, output.multi_region);
+                }
+                stage::retime_cues(&mut arranged, output.offset_ms, output.rate_scale);
+                for subtitle in &arranged {
+                    if let Some(cue) =
+                        output
+                            .format
+                            .render_cue(self.written + 1, subtitle, *frame_size)
+                    {
+                        self.written += 1;
+                        self.out.write_all(cue.as_bytes())?;
+                    }
+                }
+            }
+        }
+        self.out.flush()
+    }
+}
+
+fn stdout_error(err: io::Error) -> DecoderError {
+    DecoderError::configuration(format!("failed to write cues to stdout: {err}"))
+}
+
 async fn sort_and_write(
-    output_path: &std::path::Path,
+    output: &stage::OutputPipelineConfig,
     subtitles: &[stage::MergedSubtitle],
+    crops: Option<&CropLog>,
+    frame_size: Option<(u32, u32)>,
 ) -> Result<(), DecoderError> {
+    let ordered = prepare_cues(output, subtitles);
+    if let (Some(options), Some(crops)) = (output.contact_sheet.as_ref(), crops) {
+        write_contact_sheet(options, &ordered, crops).map_err(|err| {
+            DecoderError::configuration(format!(
+                "failed to write contact sheet {}: {err}",
+                options.path.display()
+            ))
+        })?;
+    }
+
+    let stage::OutputTarget::Path(output_path) = &output.target else {
+        // Stdout cues were streamed while the pipeline ran.
+        return Ok(());
+    };
+    let bands = if output.split_by_position {
+        stage::split_by_band(subtitles)
+    } else {
+        Vec::new()
+    };
+    if bands.len() <= 1 {
+        return write_subtitles(output_path, output.format, &ordered, frame_size).await;
+    }
+    for band in bands {
+        let path = band_output_path(output_path, &band.label, output.format);
+        let cues = prepare_cues(output, &band.subtitles);
+        write_subtitles(&path, output.format, &cues, frame_size).await?;
+    }
+    Ok(())
+}
+
+fn prepare_cues(
+    output: &stage::OutputPipelineConfig,
+    subtitles: &[stage::MergedSubtitle],
+) -> Vec<stage::MergedSubtitle> {
+    let mut ordered = arrange_cues(output, subtitles);
+    stage::retime_cues(&mut ordered, output.offset_ms, output.rate_scale);
+    ordered
+}
+
+/// Every pass of [`prepare_cues`] except retiming, so cue times stay on the detection clock.
+fn arrange_cues(
+    output: &stage::OutputPipelineConfig,
+    subtitles: &[stage::MergedSubtitle],
+) -> Vec<stage::MergedSubtitle> {
     let mut ordered = subtitles.to_vec();
     stage::sort_subtitles(&mut ordered);
-    let contents = stage::render_srt(&ordered);
+    let mut ordered = stage::layout_subtitles(&ordered, output.multi_region);
+    if let Some(min_duration) = output.min_duration {
+        stage::enforce_min_duration(&mut ordered, min_duration, output.short_cue_policy);
+    }
+    stage::merge_identical_cues(&mut ordered, output.merge_gap, output.merge_ignore_case);
+    if let Some(max_cps) = output.max_cps {
+        let flagged = stage::enforce_max_cps(&mut ordered, max_cps, output.extend_fast_cues);
+        if flagged > 0 {
+            eprintln!("{flagged} cue(s) exceed {max_cps:.1} characters per second");
+        }
+    }
+    stage::enforce_min_gap(&mut ordered, output.min_cue_gap);
+    ordered
+}
+
+/// The detected cues an arranged cue starting at `start` can be rebuilt from. Multi-line
+/// slices also draw on cues that started earlier, so those are cut to begin at `start`.
+fn cues_behind(
+    detected: Vec<stage::MergedSubtitle>,
+    start: Duration,
+    mode: stage::MultiRegionMode,
+) -> Vec<stage::MergedSubtitle> {
+    match mode {
+        stage::MultiRegionMode::MultiLine => detected
+            .into_iter()
+            .filter(|cue| cue.end_time > start)
+            .map(|mut cue| {
+                cue.start_time = cue.start_time.max(start);
+                cue
+            })
+            .collect(),
+        stage::MultiRegionMode::Merge | stage::MultiRegionMode::SeparateCues => detected
+            .into_iter()
+            .filter(|cue| cue.start_time >= start)
+            .collect(),
+    }
+}
 
-    if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty())
+/// Orders the cues the GUI exports and applies the configured short-cue handling to them.
+pub fn prepare_export_cues(
+    output: &OutputSettings,
+    subtitles: &[stage::MergedSubtitle],
+) -> Vec<stage::MergedSubtitle> {
+    let mut ordered = subtitles.to_vec();
+    stage::sort_subtitles(&mut ordered);
+    if let Some(min_duration) = output.min_duration {
+        stage::enforce_min_duration(&mut ordered, min_duration, output.short_cue_policy);
+    }
+    ordered
+}
+
+/// `movie.srt` with label `top` becomes `movie.top.srt`.
+fn band_output_path(path: &Path, label: &str, format: stage::SubtitleFormat) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_else(|| format.extension().to_string());
+    path.with_file_name(format!("{stem}.{label}.{extension}"))
+}
+
+async fn write_subtitles(
+    path: &Path,
+    format: stage::SubtitleFormat,
+    subtitles: &[stage::MergedSubtitle],
+    frame_size: Option<(u32, u32)>,
+) -> Result<(), DecoderError> {
+    let contents = format.render(subtitles, frame_size);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty())
         && let Err(err) = tokio::fs::create_dir_all(parent).await
     {
         return Err(DecoderError::configuration(format!(
@@ -307,12 +983,478 @@ async fn sort_and_write(
         )));
     }
 
-    tokio::fs::write(output_path, contents)
+    tokio::fs::write(path, contents).await.map_err(|err| {
+        DecoderError::configuration(format!(
+            "failed to write subtitle file {}: {err}",
+            path.display()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use subtitle_fast_decoder::{
+        DecoderController, DecoderProvider, ExternalFrameProvider, FrameStream, VideoMetadata,
+    };
+    use subtitle_fast_ocr::{OcrEngine, OcrError, OcrRequest, SyncOcrAdapter};
+    use subtitle_fast_types::{OcrRegion, OcrResponse, OcrText, VideoFrame};
+
+    use super::*;
+    use crate::settings::DetectionSettings;
+    use crate::test_support::scripted_frame;
+
+    /// Endless 60 fps stream with a subtitle band that never disappears.
+    struct EndlessProvider;
+
+    impl DecoderProvider for EndlessProvider {
+        fn new(_: &Configuration) -> subtitle_fast_decoder::DecoderResult<Self> {
+            Ok(Self)
+        }
+
+        fn metadata(&self) -> VideoMetadata {
+            VideoMetadata::default()
+        }
+
+        fn open(
+            self: Box<Self>,
+        ) -> subtitle_fast_decoder::DecoderResult<(DecoderController, FrameStream)> {
+            let frames = futures_util::stream::iter(0u64..).then(|index| async move {
+                tokio::task::yield_now().await;
+                Ok(subtitle_frame(index))
+            });
+            Ok((DecoderController::new(), Box::pin(frames)))
+        }
+    }
+
+    fn subtitle_frame(index: u64) -> VideoFrame {
+        scripted_frame(index, true)
+    }
+
+    struct FixedTextEngine;
+
+    impl OcrEngine for FixedTextEngine {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn recognize(&self, _: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+            Ok(OcrResponse::new(vec![OcrText::new(
+                OcrRegion::new(0.0, 0.0, 1.0, 1.0),
+                "still on screen".to_string(),
+            )]))
+        }
+    }
+
+    fn output_config(path: PathBuf) -> stage::OutputPipelineConfig {
+        stage::OutputPipelineConfig {
+            target: stage::OutputTarget::Path(path),
+            multi_region: Default::default(),
+            format: Default::default(),
+            max_cps: None,
+            extend_fast_cues: false,
+            min_cue_gap: Duration::ZERO,
+            min_duration: None,
+            short_cue_policy: Default::default(),
+            merge_gap: Duration::ZERO,
+            merge_ignore_case: false,
+            offset_ms: 0,
+            rate_scale: 1.0,
+            text_post_processor: Arc::new(stage::postprocess::DefaultTextPostProcessor::default()),
+            ndjson_stdout: false,
+            contact_sheet: None,
+            split_by_position: false,
+            timeline_csv: None,
+        }
+    }
+
+    fn pipeline_config(path: PathBuf) -> stage::PipelineConfig {
+        stage::PipelineConfig {
+            sort_key: Default::default(),
+            decode_error_tolerance: 0,
+            missing_frames: Default::default(),
+            fps_override: None,
+            start_seek: None,
+            thread_pool: None,
+            detection_concurrency: 1,
+            detection: DetectionSettings {
+                samples_per_second: 60,
+                ..Default::default()
+            },
+            ocr: stage::OcrPipelineConfig {
+                engine: Arc::new(SyncOcrAdapter::new(Arc::new(FixedTextEngine))),
+                max_consecutive_failures: 0,
+                retries: 0,
+                retry_delay: Duration::ZERO,
+                drop_invalid_text: false,
+                min_confidence: None,
+                require_confidence: false,
+            },
+            output: output_config(path),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn interrupt_flushes_active_cue_to_valid_srt() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("partial.srt");
+        let pipeline = pipeline_config(path.clone());
+        let outputs = stage::build_pipeline(Box::new(EndlessProvider), &pipeline).unwrap();
+        let (interrupt_tx, mut interrupt_rx) = tokio::sync::watch::channel(false);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            let _ = interrupt_tx.send(true);
+        });
+
+        let status = tokio::time::timeout(
+            Duration::from_secs(30),
+            drive_pipeline(
+                outputs,
+                &pipeline.output,
+                ProgressFormat::Bar,
+                &mut interrupt_rx,
+            ),
+        )
         .await
-        .map_err(|err| {
-            DecoderError::configuration(format!(
-                "failed to write subtitle file {}: {err}",
-                output_path.display()
-            ))
-        })
+        .expect("pipeline drained after interrupt")
+        .map_err(|(err, _)| err)
+        .expect("pipeline run");
+
+        assert_eq!(status, RunStatus::Interrupted);
+        let contents = std::fs::read_to_string(&path).expect("partial output written");
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.first(), Some(&"1"));
+        assert!(lines.get(1).is_some_and(|line| line.contains(" --> ")));
+        assert_eq!(lines.get(2), Some(&"still on screen"));
+    }
+
+    /// Pushes 1.5 s of 60 fps frames through an external provider and runs the pipeline.
+    async fn run_pushed_frames(path: &Path, with_subtitle: bool) -> RunStatus {
+        push_frames_through(pipeline_config(path.to_path_buf()), with_subtitle).await
+    }
+
+    async fn push_frames_through(
+        pipeline: stage::PipelineConfig,
+        with_subtitle: bool,
+    ) -> RunStatus {
+        let (provider, sender) = ExternalFrameProvider::channel(4);
+        let producer = std::thread::spawn(move || {
+            for index in 0..90 {
+                sender
+                    .push(scripted_frame(index, with_subtitle))
+                    .expect("pipeline accepts frame");
+            }
+            sender.close();
+        });
+
+        let outputs = stage::build_pipeline(Box::new(provider), &pipeline).unwrap();
+        let (_interrupt_tx, mut interrupt_rx) = tokio::sync::watch::channel(false);
+        let status = tokio::time::timeout(
+            Duration::from_secs(30),
+            drive_pipeline(
+                outputs,
+                &pipeline.output,
+                ProgressFormat::Bar,
+                &mut interrupt_rx,
+            ),
+        )
+        .await
+        .expect("pipeline finished after close")
+        .map_err(|(err, _)| err)
+        .expect("pipeline run");
+        producer.join().expect("producer thread");
+        status
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pushed_external_frames_flow_through_the_pipeline() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("external.srt");
+
+        let status = run_pushed_frames(&path, true).await;
+
+        assert_eq!(status, RunStatus::Completed);
+        let contents = std::fs::read_to_string(&path).expect("output written");
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.first(), Some(&"1"));
+        assert_eq!(lines.get(2), Some(&"still on screen"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn single_thread_pool_matches_the_global_pool() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let default_path = dir.path().join("default.srt");
+        let single_path = dir.path().join("single.srt");
+
+        let default_status = run_pushed_frames(&default_path, true).await;
+        let mut single = pipeline_config(single_path.clone());
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .expect("thread pool");
+        single.thread_pool = Some(Arc::new(pool));
+        let single_status = push_frames_through(single, true).await;
+
+        assert_eq!(default_status, RunStatus::Completed);
+        assert_eq!(single_status, default_status);
+        let default = std::fs::read_to_string(&default_path).expect("default output");
+        assert!(!default.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&single_path).expect("single-thread output"),
+            default
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn run_without_subtitles_reports_no_cues() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("empty.srt");
+
+        let status = run_pushed_frames(&path, false).await;
+
+        let RunStatus::NoSubtitles { processed_frames } = status else {
+            panic!("expected an empty run, got {status:?}");
+        };
+        assert!(processed_frames > 0);
+        assert_eq!(std::fs::read_to_string(&path).expect("output written"), "");
+        assert_eq!(status.exit_code(false), 0);
+        assert_eq!(status.exit_code(true), NO_SUBTITLES_EXIT_CODE);
+        assert_eq!(RunStatus::Completed.exit_code(true), 0);
+    }
+
+    fn update(
+        kind: stage::SubtitleUpdateKind,
+        id: u64,
+        start_ms: u64,
+        end_ms: u64,
+        text: &str,
+    ) -> stage::SubtitleUpdate {
+        stage::SubtitleUpdate {
+            kind,
+            subtitle: stage::MergedSubtitle {
+                id,
+                start_time: Duration::from_millis(start_ms),
+                end_time: Duration::from_millis(end_ms),
+                start_frame: 0,
+                lines: vec![stage::SubtitleLine {
+                    center: 310.0,
+                    text: text.to_string(),
+                    region: OcrRegion::new(180.0, 290.0, 280.0, 40.0),
+                    score: 1.0,
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn ndjson_writer_emits_cues_once_final() {
+        use stage::SubtitleUpdateKind::{New, Updated};
+
+        let mut writer = FinalizedCueWriter::ndjson(Vec::new());
+        writer.apply(&[update(New, 0, 500, 900, "first")]).unwrap();
+        writer
+            .apply(&[update(Updated, 0, 500, 1500, "first")])
+            .unwrap();
+        assert!(writer.out.is_empty(), "cue may still be extended");
+
+        writer
+            .apply(&[update(New, 1, 4000, 5000, "second")])
+            .unwrap();
+        let after_second = writer.out.clone();
+        writer.finish().unwrap();
+
+        let output = String::from_utf8(writer.out).unwrap();
+        let cues: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("one JSON object per line"))
+            .collect();
+        assert_eq!(String::from_utf8(after_second).unwrap().lines().count(), 1);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0]["index"], 1);
+        assert_eq!(cues[0]["start_ms"], 500);
+        assert_eq!(cues[0]["end_ms"], 1500);
+        assert_eq!(cues[0]["text"], "first");
+        assert_eq!(cues[0]["roi"]["y"], 290.0);
+        assert_eq!(cues[0]["roi"]["height"], 40.0);
+        assert_eq!(cues[1]["index"], 2);
+        assert_eq!(cues[1]["text"], "second");
+    }
+
+    #[test]
+    fn stdout_writer_streams_a_sequentially_numbered_file() {
+        use stage::SubtitleUpdateKind::New;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut output = output_config(dir.path().join("unused.srt"));
+        output.target = stage::OutputTarget::Stdout;
+        let mut writer = FinalizedCueWriter::subtitles(Vec::new(), output, None);
+        writer.apply(&[update(New, 0, 500, 1500, "first")]).unwrap();
+        writer.apply(&[update(New, 1, 4000, 5000, "")]).unwrap();
+        writer
+            .apply(&[update(New, 2, 8000, 9000, "second")])
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(writer.out.clone()).unwrap(),
+            "1\n00:00:00,500 --> 00:00:01,500\nfirst\n",
+            "the first cue is final once a later one starts past the merge window"
+        );
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.out).unwrap(),
+            "1\n00:00:00,500 --> 00:00:01,500\nfirst\n\n\
+             2\n00:00:08,000 --> 00:00:09,000\nsecond\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn stdout_matches_file_output_for_cues_merged_across_batches() {
+        use stage::SubtitleUpdateKind::New;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut output = output_config(dir.path().join("cues.srt"));
+        output.merge_gap = Duration::from_secs(3);
+        output.offset_ms = 100;
+        let updates = [
+            update(New, 0, 500, 1500, "same"),
+            update(New, 1, 4000, 5000, "same"),
+            update(New, 2, 9000, 10000, "later"),
+        ];
+
+        let mut streamed = output.clone();
+        streamed.target = stage::OutputTarget::Stdout;
+        let mut writer = FinalizedCueWriter::subtitles(Vec::new(), streamed, None);
+        writer.apply(&updates[..2]).unwrap();
+        assert!(
+            writer.out.is_empty(),
+            "the first cue is final but the next batch merges into it"
+        );
+        writer.apply(&updates[2..]).unwrap();
+        writer.finish().unwrap();
+
+        let detected: Vec<_> = updates
+            .iter()
+            .map(|update| update.subtitle.clone())
+            .collect();
+        sort_and_write(&output, &detected, None, None)
+            .await
+            .unwrap();
+        let file = std::fs::read_to_string(dir.path().join("cues.srt")).unwrap();
+        assert_eq!(String::from_utf8(writer.out).unwrap(), file);
+        assert!(file.starts_with("1\n00:00:00,600 --> 00:00:05,100\nsame\n"));
+    }
+
+    #[test]
+    fn gui_export_drops_or_extends_short_cues() {
+        use stage::SubtitleUpdateKind::New;
+
+        let detected = [
+            update(New, 1, 4000, 4100, "blip").subtitle,
+            update(New, 0, 500, 1500, "first").subtitle,
+        ];
+        let mut output = OutputSettings {
+            min_duration: Some(Duration::from_millis(800)),
+            ..Default::default()
+        };
+        let dropped = prepare_export_cues(&output, &detected);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].lines[0].text, "first");
+
+        output.short_cue_policy = stage::ShortCuePolicy::Extend;
+        let extended = prepare_export_cues(&output, &detected);
+        assert_eq!(extended.len(), 2);
+        assert_eq!(extended[0].lines[0].text, "first");
+        assert_eq!(extended[1].end_time, Duration::from_millis(4800));
+    }
+
+    fn banded_cue(id: u64, start_ms: u64, text: &str, top: f32) -> stage::MergedSubtitle {
+        stage::MergedSubtitle {
+            id,
+            start_time: Duration::from_millis(start_ms),
+            end_time: Duration::from_millis(start_ms + 1500),
+            start_frame: id,
+            lines: vec![stage::SubtitleLine {
+                center: top + 20.0,
+                text: text.to_string(),
+                region: OcrRegion::new(100.0, top, 440.0, 40.0),
+                score: 1.0,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn split_by_position_writes_one_file_per_band() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut output = output_config(dir.path().join("dual.srt"));
+        output.split_by_position = true;
+        let dual = [
+            banded_cue(0, 1000, "Bonjour", 300.0),
+            banded_cue(1, 1000, "Hello", 20.0),
+            banded_cue(2, 3000, "Au revoir", 304.0),
+            banded_cue(3, 3000, "Goodbye", 18.0),
+        ];
+        sort_and_write(&output, &dual, None, None).await.unwrap();
+
+        let top = std::fs::read_to_string(dir.path().join("dual.top.srt")).unwrap();
+        let bottom = std::fs::read_to_string(dir.path().join("dual.bottom.srt")).unwrap();
+        assert!(top.contains("Hello") && top.contains("Goodbye"));
+        assert!(!top.contains("Bonjour"));
+        assert!(bottom.contains("Bonjour") && bottom.contains("Au revoir"));
+        assert!(!bottom.contains("Hello"));
+        assert!(!dir.path().join("dual.srt").exists());
+
+        output.target = stage::OutputTarget::Path(dir.path().join("single.srt"));
+        let single = [
+            banded_cue(0, 1000, "Hello", 300.0),
+            banded_cue(1, 3000, "Goodbye", 306.0),
+        ];
+        sort_and_write(&output, &single, None, None).await.unwrap();
+        let contents = std::fs::read_to_string(dir.path().join("single.srt")).unwrap();
+        assert!(contents.contains("Hello") && contents.contains("Goodbye"));
+        assert!(!dir.path().join("single.top.srt").exists());
+    }
+
+    #[test]
+    fn json_progress_lines_are_throttled_and_parseable() {
+        let mut progress = JsonProgress::new(Vec::new(), Some(100), Some(25.0));
+        let sample = stage::PipelineProgress {
+            latest_frame_index: 49,
+            fps: 50.0,
+            ..Default::default()
+        };
+        progress.update(&sample);
+        // Within the throttle interval, so only the first update is written.
+        progress.update(&sample);
+        progress.processed = 100;
+        progress.finish();
+
+        let output = String::from_utf8(progress.out).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["processed"], 50);
+        assert_eq!(lines[0]["total"], 100);
+        assert_eq!(lines[0]["fps_multiplier"], 2.0);
+        assert!(lines[0]["elapsed_ms"].is_u64());
+        assert_eq!(lines[1]["processed"], 100);
+    }
+
+    #[test]
+    fn time_bounds_map_to_the_frames_they_cover() {
+        let start = Some(Duration::from_secs(1));
+        let end = Some(Duration::from_secs(2));
+        assert_eq!(frame_bounds(start, end, 25.0), (Some(25), Some(49)));
+        assert_eq!(
+            frame_bounds(Some(Duration::from_millis(1_001)), None, 30_000.0 / 1_001.0),
+            (Some(30), None)
+        );
+        assert_eq!(
+            frame_bounds(None, Some(Duration::from_millis(1_010)), 25.0),
+            (None, Some(25))
+        );
+    }
 }
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use subtitle_fast_types::{DecoderError, DecoderResult, VideoFrame};
+use subtitle_fast_validator::subtitle_detection::{SubtitleDetectorKind, preflight_detection};
+use subtitle_fast_validator::{FrameValidator, FrameValidatorConfig};
+
+use crate::settings::DetectionSettings;
+use crate::stage::StreamBundle;
+use crate::stage::detector::detection_options;
+use crate::stage::sampler::FrameSampler;
+use crate::stage::sorter::FrameSorter;
+
+/// Detector kinds the benchmark tries; ones failing their preflight are left out.
+const BENCHMARK_KINDS: [SubtitleDetectorKind; 3] = [
+    SubtitleDetectorKind::IntegralBand,
+    SubtitleDetectorKind::ProjectionBand,
+    SubtitleDetectorKind::MacVision,
+];
+
+/// Per-detector tallies over the benchmarked samples.
+#[derive(Debug, Clone)]
+pub struct DetectorStats {
+    pub kind: SubtitleDetectorKind,
+    pub frames: u64,
+    pub positives: u64,
+    pub errors: u64,
+    pub total_latency: Duration,
+}
+
+impl DetectorStats {
+    fn new(kind: SubtitleDetectorKind) -> Self {
+        Self {
+            kind,
+            frames: 0,
+            positives: 0,
+            errors: 0,
+            total_latency: Duration::ZERO,
+        }
+    }
+
+    pub fn positive_rate(&self) -> f64 {
+        ratio(self.positives, self.frames)
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        match u32::try_from(self.frames) {
+            Ok(frames) if frames > 0 => self.total_latency / frames,
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DetectorBenchmark {
+    /// Sampled frames every detector was run on.
+    pub samples: u64,
+    pub detectors: Vec<DetectorStats>,
+    /// `agreement[i][j]`: samples on which detectors `i` and `j` gave the same verdict.
+    pub agreement: Vec<Vec<u64>>,
+}
+
+impl DetectorBenchmark {
+    pub fn agreement_rate(&self, first: usize, second: usize) -> f64 {
+        ratio(self.agreement[first][second], self.samples)
+    }
+}
+
+impl fmt::Display for DetectorBenchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .detectors
+            .iter()
+            .map(|stats| stats.kind.as_str().len())
+            .max()
+            .unwrap_or(0)
+            .max("detector".len());
+        writeln!(f, "{} sampled frames", self.samples)?;
+        writeln!(
+            f,
+            "{:<name_width$} {:>8} {:>9} {:>10} {:>7}",
+            "detector", "frames", "positive", "mean ms", "errors"
+        )?;
+        for stats in &self.detectors {
+            writeln!(
+                f,
+                "{:<name_width$} {:>8} {:>8.1}% {:>10.3} {:>7}",
+                stats.kind.as_str(),
+                stats.frames,
+                stats.positive_rate() * 100.0,
+                stats.mean_latency().as_secs_f64() * 1000.0,
+                stats.errors
+            )?;
+        }
+        for first in 0..self.detectors.len() {
+            for second in first + 1..self.detectors.len() {
+                writeln!(
+                    f,
+                    "agreement {} / {}: {:.1}%",
+                    self.detectors[first].kind.as_str(),
+                    self.detectors[second].kind.as_str(),
+                    self.agreement_rate(first, second) * 100.0
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Detector kinds whose preflight passes in this build and on this machine.
+pub fn available_detectors() -> Vec<SubtitleDetectorKind> {
+    BENCHMARK_KINDS
+        .into_iter()
+        .filter(|&kind| preflight_detection(kind).is_ok())
+        .collect()
+}
+
+/// Samples the decoded stream once and runs every sampled frame through each of `kinds`,
+/// without segmentation or OCR.
+pub async fn benchmark_detectors(
+    input: StreamBundle<DecoderResult<VideoFrame>>,
+    settings: &DetectionSettings,
+    kinds: &[SubtitleDetectorKind],
+) -> Result<DetectorBenchmark, DecoderError> {
+    let mut validators = Vec::with_capacity(kinds.len());
+    for &kind in kinds {
+        let mut detection = detection_options(settings);
+        detection.detector = kind;
+        let validator = FrameValidator::new(FrameValidatorConfig { detection }).map_err(|err| {
+            DecoderError::configuration(format!("subtitle detection error: {err}"))
+        })?;
+        validators.push(validator);
+    }
+
+    let sorted = FrameSorter::new().attach(input);
+    let mut sampled = FrameSampler::new(settings.samples_per_second)
+        .with_max_history(settings.max_history_frames)
+        .attach(sorted)
+        .stream;
+
+    let mut report = DetectorBenchmark {
+        samples: 0,
+        detectors: kinds.iter().copied().map(DetectorStats::new).collect(),
+        agreement: vec![vec![0; kinds.len()]; kinds.len()],
+    };
+    let mut verdicts = vec![false; kinds.len()];
+    while let Some(sample) = sampled.next().await {
+        let sample = sample?;
+        for ((validator, stats), verdict) in validators
+            .iter()
+            .zip(&mut report.detectors)
+            .zip(&mut verdicts)
+        {
+            let started = Instant::now();
+            let result = validator.process_frame(sample.frame().clone()).await;
+            stats.total_latency += started.elapsed();
+            stats.frames += 1;
+            *verdict = match result {
+                Ok(detection) => detection.has_subtitle,
+                Err(_) => {
+                    stats.errors += 1;
+                    false
+                }
+            };
+            stats.positives += u64::from(*verdict);
+        }
+        for (first, row) in report.agreement.iter_mut().enumerate() {
+            for (second, agreed) in row.iter_mut().enumerate() {
+                *agreed += u64::from(verdicts[first] == verdicts[second]);
+            }
+        }
+        report.samples += 1;
+    }
+
+    for validator in &validators {
+        validator.finalize().await;
+    }
+    Ok(report)
+}
+
+fn ratio(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use subtitle_fast_decoder::backends::mock::MockProvider;
+    use subtitle_fast_decoder::{Backend, Configuration, DecoderProvider, DynDecoderProvider};
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn every_detector_sees_every_sampled_frame() {
+        let config = Configuration {
+            backend: Backend::Mock,
+            ..Configuration::default()
+        };
+        let provider: DynDecoderProvider =
+            Box::new(MockProvider::new(&config).expect("mock provider"));
+        let total_frames = provider.metadata().total_frames;
+        let (_controller, frames) = provider.open().expect("mock stream");
+        let settings = DetectionSettings {
+            samples_per_second: 10,
+            ..Default::default()
+        };
+        let kinds = [
+            SubtitleDetectorKind::IntegralBand,
+            SubtitleDetectorKind::ProjectionBand,
+        ];
+
+        let report =
+            benchmark_detectors(StreamBundle::new(frames, total_frames), &settings, &kinds)
+                .await
+                .expect("benchmark run");
+
+        // 120 mock frames at 60 fps sampled 10 times a second.
+        assert_eq!(report.samples, 20);
+        for stats in &report.detectors {
+            assert_eq!(stats.frames, report.samples, "{}", stats.kind.as_str());
+        }
+        assert_eq!(report.agreement[0][0], report.samples);
+        assert_eq!(report.agreement[0][1], report.agreement[1][0]);
+        assert!(report.to_string().contains("integral-band"));
+    }
+}
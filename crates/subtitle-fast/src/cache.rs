@@ -0,0 +1,436 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use subtitle_fast_decoder::Configuration;
+use subtitle_fast_types::RoiConfig;
+
+use crate::settings::DetectionSettings;
+use crate::stage::lifecycle::CompletedRegion;
+
+const CACHE_VERSION: u32 = 2;
+
+/// On-disk store of detected subtitle intervals so a rerun with unchanged detection settings
+/// can skip straight to OCR.
+#[derive(Debug, Clone)]
+pub struct DetectionCache {
+    dir: PathBuf,
+}
+
+/// Identifies one input file, the frames decoded from it and the detection configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    input: PathBuf,
+    mtime_ns: u128,
+    settings_hash: u64,
+}
+
+/// Interval produced by the lifecycle tracker, minus the frame pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedInterval {
+    pub id: u64,
+    pub label: String,
+    pub start_us: u64,
+    pub end_us: u64,
+    pub start_frame: u64,
+    pub end_frame: u64,
+    pub roi: CachedRoi,
+    /// Index of the frame the tracker picked for OCR.
+    pub frame_index: u64,
+    /// Highest detection score over the interval, reported with each cue.
+    pub max_score: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CachedRoi {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    input: PathBuf,
+    mtime_ns: u128,
+    settings_hash: u64,
+    intervals: Vec<CachedInterval>,
+}
+
+impl DetectionCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// `start_seek` is the timestamp seek `--start-time` falls back to without a frame rate.
+    pub fn key(
+        input: &Path,
+        settings: &DetectionSettings,
+        decoder: &Configuration,
+        start_seek: Option<Duration>,
+    ) -> io::Result<CacheKey> {
+        let input = fs::canonicalize(input)?;
+        let mtime_ns = fs::metadata(&input)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Ok(CacheKey {
+            input,
+            mtime_ns,
+            settings_hash: settings_hash(settings, decoder, start_seek),
+        })
+    }
+
+    /// Returns the cached intervals when the input is unchanged and detection settings match.
+    pub fn load(&self, key: &CacheKey) -> Option<Vec<CachedInterval>> {
+        let bytes = fs::read(self.entry_path(key)).ok()?;
+        let file: CacheFile = serde_json::from_slice(&bytes).ok()?;
+        let valid = file.version == CACHE_VERSION
+            && file.input == key.input
+            && file.mtime_ns == key.mtime_ns
+            && file.settings_hash == key.settings_hash;
+        valid.then_some(file.intervals)
+    }
+
+    pub fn store(&self, key: &CacheKey, intervals: &[CachedInterval]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            input: key.input.clone(),
+            mtime_ns: key.mtime_ns,
+            settings_hash: key.settings_hash,
+            intervals: intervals.to_vec(),
+        };
+        let bytes = serde_json::to_vec(&file).map_err(io::Error::other)?;
+        fs::write(self.entry_path(key), bytes)
+    }
+
+    /// One entry per input; a settings change overwrites it on the next store.
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        let name = fnv1a(key.input.to_string_lossy().as_bytes());
+        self.dir.join(format!("{name:016x}.json"))
+    }
+}
+
+impl CachedInterval {
+    /// `None` when the region's frame carries no index to find it again on replay.
+    pub fn from_region(region: &CompletedRegion) -> Option<Self> {
+        Some(Self {
+            id: region.id,
+            label: region.label.clone(),
+            start_us: region.start_time.as_micros() as u64,
+            end_us: region.end_time.as_micros() as u64,
+            start_frame: region.start_frame,
+            end_frame: region.end_frame,
+            roi: CachedRoi {
+                x: region.roi.x,
+                y: region.roi.y,
+                width: region.roi.width,
+                height: region.roi.height,
+            },
+            frame_index: region.frame.index()?,
+            max_score: region.max_score,
+        })
+    }
+
+    pub fn start_time(&self) -> Duration {
+        Duration::from_micros(self.start_us)
+    }
+
+    pub fn end_time(&self) -> Duration {
+        Duration::from_micros(self.end_us)
+    }
+
+    pub fn roi(&self) -> RoiConfig {
+        RoiConfig {
+            x: self.roi.x,
+            y: self.roi.y,
+            width: self.roi.width,
+            height: self.roi.height,
+        }
+    }
+}
+
+/// Intervals seen during a pipeline run, shared with the caller so they can be cached afterwards.
+#[derive(Clone, Default)]
+pub struct IntervalLog {
+    inner: Arc<Mutex<IntervalLogState>>,
+}
+
+#[derive(Default)]
+struct IntervalLogState {
+    intervals: Vec<CachedInterval>,
+    incomplete: bool,
+}
+
+impl IntervalLog {
+    pub fn from_intervals(intervals: Vec<CachedInterval>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(IntervalLogState {
+                intervals,
+                incomplete: false,
+            })),
+        }
+    }
+
+    pub fn record(&self, region: &CompletedRegion) {
+        let mut state = self.inner.lock();
+        match CachedInterval::from_region(region) {
+            Some(interval) => state.intervals.push(interval),
+            None => state.incomplete = true,
+        }
+    }
+
+    /// `None` if some interval could not be captured, so the run must not be cached.
+    pub fn snapshot(&self) -> Option<Vec<CachedInterval>> {
+        let state = self.inner.lock();
+        (!state.incomplete).then(|| state.intervals.clone())
+    }
+}
+
+fn settings_hash(
+    settings: &DetectionSettings,
+    decoder: &Configuration,
+    start_seek: Option<Duration>,
+) -> u64 {
+    let canonical = format!(
+        "{};{};seek={}",
+        detection_canonical(settings),
+        decode_canonical(decoder),
+        start_seek
+            .map(|seek| seek.as_micros().to_string())
+            .unwrap_or_default(),
+    );
+    fnv1a(canonical.as_bytes())
+}
+
+/// Decoder options that pick which frames are decoded or how they are timed; intervals found
+/// with other values point at frames a replay would never see, or carry the wrong times.
+fn decode_canonical(decoder: &Configuration) -> String {
+    let bound = |frame: Option<u64>| frame.map(|frame| frame.to_string()).unwrap_or_default();
+    format!(
+        "stream={};fps={};frames={}..{}",
+        decoder
+            .video_stream_index
+            .map(|index| index.to_string())
+            .unwrap_or_default(),
+        decoder
+            .fps_override
+            .map(|fps| fps.to_string())
+            .unwrap_or_default(),
+        bound(decoder.start_frame),
+        bound(decoder.end_frame),
+    )
+}
+
+fn detection_canonical(settings: &DetectionSettings) -> String {
+    let roi = settings
+        .roi
+        .map(|roi| format!("{},{},{},{}", roi.x, roi.y, roi.width, roi.height))
+        .unwrap_or_default();
+    let aspect = settings
+        .expected_aspect
+        .map(|aspect| aspect.to_string())
+        .unwrap_or_default();
+    let min_area = settings
+        .min_area_ratio
+        .map(|ratio| ratio.to_string())
+        .unwrap_or_default();
+    let max_bright = settings
+        .max_bright_fraction
+        .map(|fraction| fraction.to_string())
+        .unwrap_or_default();
+    let tuning = format!(
+        "{},{},{}",
+        settings
+            .min_component_area
+            .map(|area| area.to_string())
+            .unwrap_or_default(),
+        settings
+            .min_region_aspect
+            .map(|aspect| aspect.to_string())
+            .unwrap_or_default(),
+        settings
+            .max_region_area_ratio
+            .map(|ratio| ratio.to_string())
+            .unwrap_or_default(),
+    );
+    format!(
+        "sps={};target={};delta={};comparator={};fallback={};roi={roi};aspect={aspect};min_area={min_area};max_bright={max_bright};edge={};tuning={tuning};smoothing={};history={};refresh={};min_consecutive={};best_frame={};detector={}",
+        settings.samples_per_second,
+        settings.target,
+        settings.delta,
+        settings.comparator.map(|kind| kind.as_str()).unwrap_or(""),
+        settings
+            .comparator_fallback
+            .map(|kind| kind.as_str())
+            .unwrap_or(""),
+        settings.edge_exclusion_px,
+        settings.detection_smoothing_window,
+        settings.max_history_frames,
+        settings.template_refresh_interval,
+        settings.min_consecutive_samples,
+        settings.best_frame_policy.as_str(),
+        settings.detector.as_str(),
+    )
+}
+
+/// Stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(target: u8) -> DetectionSettings {
+        DetectionSettings {
+            target,
+            ..Default::default()
+        }
+    }
+
+    fn decoder() -> Configuration {
+        Configuration::default()
+    }
+
+    fn interval() -> CachedInterval {
+        CachedInterval {
+            id: 3,
+            label: "region-3".into(),
+            start_us: 1_000_000,
+            end_us: 2_500_000,
+            start_frame: 30,
+            end_frame: 75,
+            roi: CachedRoi {
+                x: 0.1,
+                y: 0.8,
+                width: 0.8,
+                height: 0.1,
+            },
+            frame_index: 52,
+            max_score: 0.87,
+        }
+    }
+
+    #[test]
+    fn second_run_with_same_settings_loads_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip.mp4");
+        fs::write(&input, b"not really a video").unwrap();
+        let cache = DetectionCache::new(dir.path().join("cache"));
+
+        let first = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        assert!(cache.load(&first).is_none());
+        cache.store(&first, &[interval()]).unwrap();
+
+        let second = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        let loaded = cache.load(&second).expect("cache hit");
+        assert_eq!(loaded, vec![interval()]);
+        assert_eq!(loaded[0].max_score, 0.87);
+    }
+
+    #[test]
+    fn detection_setting_change_invalidates_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip.mp4");
+        fs::write(&input, b"not really a video").unwrap();
+        let cache = DetectionCache::new(dir.path());
+
+        let key = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        cache.store(&key, &[interval()]).unwrap();
+
+        let changed = DetectionCache::key(&input, &settings(200), &decoder(), None).unwrap();
+        assert!(cache.load(&changed).is_none());
+    }
+
+    #[test]
+    fn video_stream_change_invalidates_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip.mkv");
+        fs::write(&input, b"not really a video").unwrap();
+        let cache = DetectionCache::new(dir.path());
+
+        let key = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        cache.store(&key, &[interval()]).unwrap();
+
+        let second_stream = Configuration {
+            video_stream_index: Some(1),
+            ..decoder()
+        };
+        let changed = DetectionCache::key(&input, &settings(230), &second_stream, None).unwrap();
+        assert!(cache.load(&changed).is_none());
+    }
+
+    #[test]
+    fn fps_override_change_invalidates_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip.mp4");
+        fs::write(&input, b"not really a video").unwrap();
+        let cache = DetectionCache::new(dir.path());
+
+        let key = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        cache.store(&key, &[interval()]).unwrap();
+
+        let corrected = Configuration {
+            fps_override: Some(23.976),
+            ..decoder()
+        };
+        let changed = DetectionCache::key(&input, &settings(230), &corrected, None).unwrap();
+        assert!(cache.load(&changed).is_none());
+    }
+
+    #[test]
+    fn truncated_run_does_not_serve_a_full_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip.mp4");
+        fs::write(&input, b"not really a video").unwrap();
+        let cache = DetectionCache::new(dir.path());
+
+        let truncated = Configuration {
+            end_frame: Some(1500),
+            ..decoder()
+        };
+        let key = DetectionCache::key(&input, &settings(230), &truncated, None).unwrap();
+        cache.store(&key, &[interval()]).unwrap();
+
+        let full = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        assert!(cache.load(&full).is_none());
+        let late_start = Configuration {
+            start_frame: Some(300),
+            ..truncated
+        };
+        let later = DetectionCache::key(&input, &settings(230), &late_start, None).unwrap();
+        assert!(cache.load(&later).is_none());
+    }
+
+    #[test]
+    fn start_time_seek_change_invalidates_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip.mp4");
+        fs::write(&input, b"not really a video").unwrap();
+        let cache = DetectionCache::new(dir.path());
+
+        let seek = Some(Duration::from_secs(60));
+        let key = DetectionCache::key(&input, &settings(230), &decoder(), seek).unwrap();
+        cache.store(&key, &[interval()]).unwrap();
+
+        let same = DetectionCache::key(&input, &settings(230), &decoder(), seek).unwrap();
+        assert!(cache.load(&same).is_some());
+        let full = DetectionCache::key(&input, &settings(230), &decoder(), None).unwrap();
+        assert!(cache.load(&full).is_none());
+    }
+}
@@ -1,17 +1,34 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::parser::ValueSource;
-use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use subtitle_fast_types::RoiConfig;
+use subtitle_fast_validator::subtitle_detection::SubtitleDetectorKind;
+
+use crate::backend::ProgressFormat;
+use crate::settings::DEFAULT_SAMPLES_PER_SECOND;
+
+const DEFAULT_DECODE_ERROR_TOLERANCE: &str = "16";
+const DEFAULT_DUMP_FRAME_COUNT: &str = "5";
 
 #[derive(Debug, Default)]
 pub struct CliSources {
     pub detection_sps_from_cli: bool,
     pub decoder_channel_capacity_from_cli: bool,
+    pub decoder_error_tolerance_from_cli: bool,
     pub detector_target_from_cli: bool,
     pub detector_delta_from_cli: bool,
     pub comparator_from_cli: bool,
+    pub comparator_fallback_from_cli: bool,
     pub detector_roi_from_cli: bool,
+    pub expected_aspect_from_cli: bool,
+    pub min_area_ratio_from_cli: bool,
+    pub max_bright_fraction_from_cli: bool,
+    pub detection_smoothing_window_from_cli: bool,
+    pub multi_region_from_cli: bool,
+    pub max_cps_from_cli: bool,
+    pub ocr_max_consecutive_failures_from_cli: bool,
 }
 
 impl CliSources {
@@ -19,10 +36,25 @@ impl CliSources {
         Self {
             detection_sps_from_cli: value_from_cli(matches, "detection_samples_per_second"),
             decoder_channel_capacity_from_cli: value_from_cli(matches, "decoder_channel_capacity"),
+            decoder_error_tolerance_from_cli: value_from_cli(matches, "tolerate_decode_errors"),
             detector_target_from_cli: value_from_cli(matches, "detector_target"),
             detector_delta_from_cli: value_from_cli(matches, "detector_delta"),
             comparator_from_cli: value_from_cli(matches, "comparator"),
+            comparator_fallback_from_cli: value_from_cli(matches, "comparator_fallback"),
             detector_roi_from_cli: value_from_cli(matches, "roi"),
+            expected_aspect_from_cli: value_from_cli(matches, "expected_aspect"),
+            min_area_ratio_from_cli: value_from_cli(matches, "min_area_ratio"),
+            max_bright_fraction_from_cli: value_from_cli(matches, "max_bright_fraction"),
+            detection_smoothing_window_from_cli: value_from_cli(
+                matches,
+                "detection_smoothing_window",
+            ),
+            multi_region_from_cli: value_from_cli(matches, "multi_region"),
+            max_cps_from_cli: value_from_cli(matches, "max_cps"),
+            ocr_max_consecutive_failures_from_cli: value_from_cli(
+                matches,
+                "ocr_max_consecutive_failures",
+            ),
         }
     }
 }
@@ -51,10 +83,17 @@ pub fn parse_cli() -> (CliArgs, CliSources) {
     disable_help_subcommand = true
 )]
 pub struct CliArgs {
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+
     /// Lock decoding to a specific backend implementation
     #[arg(short = 'b', long = "backend")]
     pub backend: Option<String>,
 
+    /// Try these backends first, in order, when none is locked (e.g. ffmpeg,videotoolbox)
+    #[arg(long = "backend-priority", value_name = "A,B,C", value_delimiter = ',')]
+    pub backend_priority: Vec<String>,
+
     /// Override the configuration file path
     #[arg(long = "config")]
     pub config: Option<PathBuf>,
@@ -67,7 +106,7 @@ pub struct CliArgs {
     #[arg(
         long = "detection-samples-per-second",
         alias = "detection-sps",
-        default_value_t = 7,
+        default_value_t = DEFAULT_SAMPLES_PER_SECOND,
         value_parser = parse_positive_u32
     )]
     pub detection_samples_per_second: u32,
@@ -80,6 +119,16 @@ pub struct CliArgs {
     )]
     pub decoder_channel_capacity: Option<usize>,
 
+    /// Skip up to N corrupt frames (logging each) before aborting on decode errors
+    #[arg(
+        long = "tolerate-decode-errors",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_DECODE_ERROR_TOLERANCE,
+        value_parser = clap::value_parser!(u32)
+    )]
+    pub tolerate_decode_errors: Option<u32>,
+
     /// Override the detector target value (0-255)
     #[arg(long = "detector-target", value_parser = parse_u8_byte)]
     pub detector_target: Option<u8>,
@@ -88,22 +137,289 @@ pub struct CliArgs {
     #[arg(long = "detector-delta", value_parser = parse_u8_byte)]
     pub detector_delta: Option<u8>,
 
-    /// Subtitle comparator to use (bitset-cover, sparse-chamfer)
+    /// Subtitle comparator to use (bitset-cover, sparse-chamfer, phash, ssim, histogram,
+    /// histogram-chamfer)
     #[arg(long = "comparator")]
     pub comparator: Option<String>,
 
+    /// Comparator tried when the primary one cannot extract features from a region
+    #[arg(long = "comparator-fallback")]
+    pub comparator_fallback: Option<String>,
+
     /// Normalized detection ROI as x,y,width,height (omit or zero size uses full frame)
     #[arg(long = "roi", value_name = "X,Y,W,H", value_parser = parse_roi)]
     pub roi: Option<RoiConfig>,
 
-    /// Output subtitle file path
+    /// Expected subtitle band aspect (width / height); detection favors regions close to it
+    #[arg(long = "expected-aspect", value_name = "RATIO")]
+    pub expected_aspect: Option<f32>,
+
+    /// Ignore regions smaller than this fraction of the frame area (scales with resolution)
+    #[arg(long = "min-area-ratio", value_name = "RATIO")]
+    pub min_area_ratio: Option<f32>,
+
+    /// Skip frames whose ROI has more than this fraction of pixels in the luma band (solid fills)
+    #[arg(long = "max-bright-fraction", value_name = "RATIO")]
+    pub max_bright_fraction: Option<f32>,
+
+    /// Reject regions within this many pixels of the frame border (logos, channel bugs)
+    #[arg(long = "edge-exclusion-px", value_name = "PX")]
+    pub edge_exclusion_px: Option<u32>,
+
+    /// Ignore components with fewer in-band pixels than this (integral-band detector; default 400)
+    #[arg(long = "min-component-area", value_name = "PX")]
+    pub min_component_area: Option<usize>,
+
+    /// Ignore regions narrower than this width / height (integral-band; default 2; lower for vertical text)
+    #[arg(long = "min-region-aspect", value_name = "RATIO")]
+    pub min_region_aspect: Option<f32>,
+
+    /// Ignore regions covering more than this fraction of the frame (integral-band; default 0.35)
+    #[arg(long = "max-region-area-ratio", value_name = "RATIO")]
+    pub max_region_area_ratio: Option<f32>,
+
+    /// Majority-vote each detection over this many consecutive samples (odd; 1 disables)
+    #[arg(long = "detection-smoothing-window", value_name = "N")]
+    pub detection_smoothing_window: Option<usize>,
+
+    /// Keep at most N recent frames for start/end refinement (default 120, max 240)
+    #[arg(long = "max-history-frames", value_name = "N")]
+    pub max_history_frames: Option<usize>,
+
+    /// Re-base a subtitle's match template every N matched samples (default 1, 0 disables)
+    #[arg(long = "template-refresh-interval", value_name = "N")]
+    pub template_refresh_interval: Option<usize>,
+
+    /// Open a new subtitle only after it is detected in N consecutive samples (default 1)
+    #[arg(long = "min-consecutive-samples", value_name = "N")]
+    pub min_consecutive_samples: Option<usize>,
+
+    /// Frame of each subtitle used for OCR and crops (highest-score, middle, last, first)
+    #[arg(long = "best-frame", value_name = "POLICY")]
+    pub best_frame: Option<String>,
+
+    /// Skip feature extraction for regions whose ROI and pixels match the previous sample
+    #[arg(long = "reuse-static-features")]
+    pub reuse_static_features: bool,
+
+    /// Subtitle detector (auto, luma, projection-band, macos-vision; default projection-band)
+    #[arg(long = "detector", value_name = "KIND", value_parser = parse_detector)]
+    pub detector: Option<SubtitleDetectorKind>,
+
+    /// Worker threads for frame comparison; also sizes the decoder queue (0 = auto)
+    #[arg(long = "threads", value_name = "N")]
+    pub threads: Option<usize>,
+
+    /// Detect up to N sampled frames at once (default: the --threads count, otherwise 1)
+    #[arg(long = "detection-threads", value_name = "N", value_parser = parse_positive_usize)]
+    pub detection_threads: Option<usize>,
+
+    /// Decode the Nth video stream of a multi-track input (0-based, counting video streams only)
+    #[arg(long = "video-stream", alias = "video-track", value_name = "N")]
+    pub video_stream: Option<usize>,
+
+    /// Stop decoding after frame N (inclusive), to process only the start of a long input
+    #[arg(long = "end-frame", value_name = "N")]
+    pub end_frame: Option<u64>,
+
+    /// Start decoding at this time (HH:MM:SS.mmm, MM:SS.mmm or seconds)
+    #[arg(long = "start-time", value_name = "TIME", value_parser = parse_timestamp)]
+    pub start_time: Option<Duration>,
+
+    /// Stop decoding at this time (HH:MM:SS.mmm, MM:SS.mmm or seconds)
+    #[arg(
+        long = "end-time",
+        value_name = "TIME",
+        value_parser = parse_timestamp,
+        conflicts_with = "end_frame"
+    )]
+    pub end_time: Option<Duration>,
+
+    /// Use this frame rate instead of the one probed from the container (for files with wrong metadata)
+    #[arg(long = "fps", value_name = "FPS", value_parser = parse_frame_rate)]
+    pub fps: Option<f64>,
+
+    /// Frame width of headerless raw YUV input, read from a file or from stdin as `-`
+    #[arg(long = "raw-width", value_name = "PIXELS")]
+    pub raw_width: Option<u32>,
+
+    /// Frame height of headerless raw YUV input
+    #[arg(long = "raw-height", value_name = "PIXELS")]
+    pub raw_height: Option<u32>,
+
+    /// Pixel layout of raw YUV input (nv12, i420; default nv12)
+    #[arg(long = "raw-format", value_name = "FORMAT")]
+    pub raw_format: Option<String>,
+
+    /// Frame rate of raw YUV input, which carries no timing of its own
+    #[arg(long = "raw-fps", value_name = "FPS", value_parser = parse_frame_rate)]
+    pub raw_fps: Option<f64>,
+
+    /// React to frames the decoder dropped, detected as gaps in frame indices (ignore, warn, fail)
+    #[arg(long = "missing-frames", value_name = "POLICY")]
+    pub missing_frames: Option<String>,
+
+    /// Warn when decoded frames have waited this long for detection/OCR (default 10, 0 disables)
+    #[arg(long = "queue-full-warning-secs", value_name = "SECS")]
+    pub queue_full_warning_secs: Option<f64>,
+
+    /// Frame rate used to time image-sequence inputs (a directory of PNG frames; default 25)
+    #[arg(long = "frame-rate", value_name = "FPS", value_parser = parse_frame_rate)]
+    pub frame_rate: Option<f64>,
+
+    /// Output subtitle file path; `-` writes to stdout
     #[arg(short = 'o', long = "output")]
     pub output: Option<PathBuf>,
 
+    /// Stream subtitles to stdout as each cue becomes final instead of writing a file
+    #[arg(long = "stdout")]
+    pub stdout: bool,
+
+    /// Consecutive OCR failures tolerated (cues are kept with empty text) before aborting
+    #[arg(long = "ocr-max-consecutive-failures", value_parser = clap::value_parser!(u32))]
+    pub ocr_max_consecutive_failures: Option<u32>,
+
+    /// Retries for a cue whose OCR failed transiently, e.g. under memory pressure (default 2)
+    #[arg(long = "ocr-retries", value_name = "N")]
+    pub ocr_retries: Option<u32>,
+
+    /// Milliseconds to wait before each OCR retry (default 50)
+    #[arg(long = "ocr-retry-delay-ms", value_name = "MS")]
+    pub ocr_retry_delay_ms: Option<u64>,
+
+    /// Drop OCR lines the engine returned as invalid UTF-8 instead of keeping the lossy text
+    #[arg(long = "drop-invalid-text")]
+    pub drop_invalid_text: bool,
+
+    /// Drop OCR lines recognized with less than this confidence (0-1), and cues left empty
+    #[arg(long = "ocr-min-confidence", value_name = "CONFIDENCE")]
+    pub ocr_min_confidence: Option<f32>,
+
+    /// With --ocr-min-confidence, also drop lines whose engine reports no confidence
+    #[arg(long = "ocr-require-confidence")]
+    pub ocr_require_confidence: bool,
+
+    /// Directory used to locate and store downloaded OCR models
+    #[arg(long = "model-cache", value_name = "DIR")]
+    pub model_cache: Option<PathBuf>,
+
+    /// How concurrent subtitle regions are written (merge, separate-cues, multi-line)
+    #[arg(long = "multi-region")]
+    pub multi_region: Option<String>,
+
+    /// Subtitle file format (srt, vtt, ass, json)
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub format: Option<String>,
+
+    /// Warn about cues whose reading speed exceeds this many characters per second
+    #[arg(long = "max-cps", value_name = "CPS", value_parser = clap::value_parser!(f64))]
+    pub max_cps: Option<f64>,
+
+    /// Extend cues over --max-cps up to the next cue's start time
+    #[arg(long = "extend-fast-cues")]
+    pub extend_fast_cues: bool,
+
+    /// Shorten cues so at least MS milliseconds separate each from the next (default 0)
+    #[arg(long = "min-cue-gap", value_name = "MS")]
+    pub min_cue_gap: Option<u64>,
+
+    /// Treat cues shown for less than MS milliseconds as too short (see --short-cue-policy)
+    #[arg(long = "min-duration", value_name = "MS")]
+    pub min_duration: Option<u64>,
+
+    /// What happens to cues under --min-duration (drop, extend; default drop)
+    #[arg(long = "short-cue-policy", value_name = "POLICY")]
+    pub short_cue_policy: Option<String>,
+
+    /// Merge consecutive cues with the same text less than MS milliseconds apart (default 0, off)
+    #[arg(long = "merge-gap", value_name = "MS")]
+    pub merge_gap: Option<u64>,
+
+    /// Ignore case when comparing cue text for --merge-gap
+    #[arg(long = "merge-ignore-case")]
+    pub merge_ignore_case: bool,
+
+    /// Shift every written cue by MS milliseconds (may be negative; times clamp to zero)
+    #[arg(long = "offset-ms", value_name = "MS", allow_negative_numbers = true)]
+    pub offset_ms: Option<i64>,
+
+    /// Multiply every written cue time by FACTOR before --offset-ms is added (default 1)
+    #[arg(long = "rate-scale", value_name = "FACTOR", value_parser = clap::value_parser!(f64))]
+    pub rate_scale: Option<f64>,
+
+    /// Drop cues with fewer visible characters after punctuation-only lines are stripped (default 2)
+    #[arg(long = "min-cue-chars", value_name = "N")]
+    pub min_cue_chars: Option<usize>,
+
+    /// Also print each cue to stdout as newline-delimited JSON as soon as it is final
+    #[arg(long = "ndjson-stdout")]
+    pub ndjson_stdout: bool,
+
+    /// Write a PNG grid with one labeled ROI thumbnail per cue
+    #[arg(long = "contact-sheet", value_name = "PNG")]
+    pub contact_sheet: Option<PathBuf>,
+
+    /// Thumbnails per row in the contact sheet (default 4)
+    #[arg(long = "contact-sheet-columns", value_name = "N")]
+    pub contact_sheet_columns: Option<usize>,
+
+    /// Contact-sheet thumbnail width in pixels (default 320)
+    #[arg(long = "contact-sheet-thumb-width", value_name = "PX")]
+    pub contact_sheet_thumb_width: Option<u32>,
+
+    /// Write cues from each subtitle band to its own file (name.top.srt, name.bottom.srt)
+    #[arg(long = "split-by-position")]
+    pub split_by_position: bool,
+
+    /// Write one CSV row per detection sample (score, regions, open segment ids)
+    #[arg(long = "timeline-csv", value_name = "CSV")]
+    pub timeline_csv: Option<PathBuf>,
+
+    /// Cache detected subtitle intervals here; reruns with unchanged detection settings skip detection
+    #[arg(long = "cache-dir", value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Write the resolved detection settings as JSON, loadable again with --config
+    #[arg(long = "dump-tuning", value_name = "JSON")]
+    pub dump_tuning: Option<PathBuf>,
+
+    /// Write the first frame with a detected subtitle (regions overlaid) as PNG and exit
+    #[arg(long = "first-frame-preview", value_name = "PNG")]
+    pub first_frame_preview: Option<PathBuf>,
+
+    /// Print format, strides, plane sizes and timestamps for the first N decoded frames and exit
+    #[arg(
+        long = "dump-frame-info",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_DUMP_FRAME_COUNT,
+        value_parser = clap::value_parser!(u32)
+    )]
+    pub dump_frame_info: Option<u32>,
+
+    /// Exit with status 3 when the run completes without finding any subtitle
+    #[arg(long = "fail-if-empty")]
+    pub fail_if_empty: bool,
+
+    /// Progress reporting on stderr: bar, or json for one object per line (default bar)
+    #[arg(long = "progress-format", value_name = "FORMAT", value_parser = parse_progress_format)]
+    pub progress_format: Option<ProgressFormat>,
+
     /// Input video path
     pub input: Option<PathBuf>,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Check which decoder backends, detectors and OCR engines work here, using a generated clip
+    SelfTest,
+    /// Run every available subtitle detector over the same sampled frames and compare them
+    BenchmarkDetectors {
+        /// Input video path
+        input: PathBuf,
+    },
+}
+
 fn parse_u8_byte(value: &str) -> Result<u8, String> {
     value
         .parse::<u8>()
@@ -120,6 +436,65 @@ fn parse_positive_u32(value: &str) -> Result<u32, String> {
     Ok(parsed)
 }
 
+fn parse_positive_usize(value: &str) -> Result<usize, String> {
+    let parsed = value
+        .parse::<usize>()
+        .map_err(|_| format!("'{value}' is not a valid number"))?;
+    if parsed == 0 {
+        return Err("value must be at least 1".into());
+    }
+    Ok(parsed)
+}
+
+fn parse_frame_rate(value: &str) -> Result<f64, String> {
+    let parsed = value
+        .parse::<f64>()
+        .map_err(|_| format!("'{value}' is not a valid number"))?;
+    if !parsed.is_finite() || parsed <= 0.0 {
+        return Err("frame rate must be greater than zero".into());
+    }
+    Ok(parsed)
+}
+
+fn parse_detector(value: &str) -> Result<SubtitleDetectorKind, String> {
+    value.parse::<SubtitleDetectorKind>().map_err(|_| {
+        format!("unknown detector '{value}' (expected auto, luma, projection-band or macos-vision)")
+    })
+}
+
+fn parse_progress_format(value: &str) -> Result<ProgressFormat, String> {
+    value
+        .parse::<ProgressFormat>()
+        .map_err(|_| format!("unknown progress format '{value}' (expected bar or json)"))
+}
+
+/// `HH:MM:SS.mmm`, `MM:SS.mmm` or plain seconds; `,` also separates the milliseconds.
+fn parse_timestamp(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{value}' is not a timestamp (HH:MM:SS.mmm or seconds)");
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let (seconds, larger) = parts.split_last().ok_or_else(invalid)?;
+    let seconds = seconds
+        .replace(',', ".")
+        .parse::<f64>()
+        .map_err(|_| invalid())?;
+    if !seconds.is_finite() || seconds < 0.0 || (!larger.is_empty() && seconds >= 60.0) {
+        return Err(invalid());
+    }
+    let mut total = 0u64;
+    for (idx, part) in larger.iter().enumerate() {
+        let unit = part.parse::<u64>().map_err(|_| invalid())?;
+        // Minutes only wrap when hours are given too.
+        if idx > 0 && unit >= 60 {
+            return Err(invalid());
+        }
+        total = total * 60 + unit;
+    }
+    Ok(Duration::from_secs(total * 60) + Duration::from_secs_f64(seconds))
+}
+
 fn parse_roi(value: &str) -> Result<RoiConfig, String> {
     let parts: Vec<_> = value.split([',', ' ']).filter(|s| !s.is_empty()).collect();
     if parts.len() != 4 {
@@ -190,4 +565,51 @@ mod tests {
         assert!(parse_roi("-0.1,0.0,0.5,0.5").is_err());
         assert!(parse_roi("0.0,0.0,-0.5,0.5").is_err());
     }
+
+    #[test]
+    fn parse_detector_maps_names_to_kinds() {
+        assert_eq!(parse_detector("auto").unwrap(), SubtitleDetectorKind::Auto);
+        assert_eq!(
+            parse_detector("Luma").unwrap(),
+            SubtitleDetectorKind::IntegralBand
+        );
+        assert_eq!(
+            parse_detector("projection-band").unwrap(),
+            SubtitleDetectorKind::ProjectionBand
+        );
+        assert_eq!(
+            parse_detector("macos-vision").unwrap(),
+            SubtitleDetectorKind::MacVision
+        );
+        let err = parse_detector("tesseract").unwrap_err();
+        assert!(err.contains("auto, luma, projection-band or macos-vision"));
+    }
+
+    #[test]
+    fn parse_timestamp_accepts_clock_and_second_forms() {
+        assert_eq!(
+            parse_timestamp("01:02:03.500").unwrap(),
+            Duration::from_millis(3_723_500)
+        );
+        assert_eq!(
+            parse_timestamp("00:01:02,250").unwrap(),
+            Duration::from_millis(62_250)
+        );
+        assert_eq!(parse_timestamp("2:05").unwrap(), Duration::from_secs(125));
+        assert_eq!(
+            parse_timestamp("90.5").unwrap(),
+            Duration::from_millis(90_500)
+        );
+        assert_eq!(parse_timestamp("42").unwrap(), Duration::from_secs(42));
+    }
+
+    #[test]
+    fn parse_timestamp_rejects_malformed_values() {
+        assert!(parse_timestamp("").is_err());
+        assert!(parse_timestamp("-3").is_err());
+        assert!(parse_timestamp("1:2:3:4").is_err());
+        assert!(parse_timestamp("00:61:00").is_err());
+        assert!(parse_timestamp("01:75").is_err());
+        assert!(parse_timestamp("aa:10").is_err());
+    }
 }
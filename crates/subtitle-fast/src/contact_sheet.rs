@@ -0,0 +1,212 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use image::GrayImage;
+use image::imageops::{self, FilterType};
+use parking_lot::Mutex;
+use png::{BitDepth, ColorType, Encoder};
+
+use crate::stage::MergedSubtitle;
+use crate::stage::lifecycle::CompletedRegion;
+
+pub const DEFAULT_COLUMNS: usize = 4;
+pub const DEFAULT_THUMB_WIDTH: u32 = 320;
+
+const GAP: u32 = 4;
+const GLYPH_SCALE: u32 = 2;
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+const LABEL_HEIGHT: u32 = GLYPH_HEIGHT * GLYPH_SCALE + 2 * GAP;
+const LABEL_COLOR: u8 = 255;
+
+#[derive(Debug, Clone)]
+pub struct ContactSheetOptions {
+    pub path: PathBuf,
+    pub columns: usize,
+    pub thumb_width: u32,
+}
+
+/// Thumbnails of the subtitle regions handed to OCR, collected while the pipeline runs.
+#[derive(Clone)]
+pub struct CropLog {
+    thumb_width: u32,
+    inner: Arc<Mutex<Vec<RegionCrop>>>,
+}
+
+struct RegionCrop {
+    start_time: Duration,
+    end_time: Duration,
+    thumbnail: GrayImage,
+}
+
+impl CropLog {
+    pub fn new(thumb_width: u32) -> Self {
+        Self {
+            thumb_width: thumb_width.max(1),
+            inner: Arc::default(),
+        }
+    }
+
+    pub fn record(&self, region: &CompletedRegion) {
+        // Native-handle frames cannot be cropped; their cues get an empty cell.
+        let Ok(crop) = region.crop_image() else {
+            return;
+        };
+        let height = (crop.height() as u64 * self.thumb_width as u64 / crop.width().max(1) as u64)
+            .max(1) as u32;
+        let thumbnail = imageops::resize(&crop, self.thumb_width, height, FilterType::Triangle);
+        self.inner.lock().push(RegionCrop {
+            start_time: region.start_time,
+            end_time: region.end_time,
+            thumbnail,
+        });
+    }
+
+    /// Picks, for each cue, the recorded region overlapping it the longest.
+    fn crops_for(&self, subtitles: &[MergedSubtitle]) -> Vec<Option<GrayImage>> {
+        let crops = self.inner.lock();
+        subtitles
+            .iter()
+            .map(|subtitle| {
+                crops
+                    .iter()
+                    .filter(|crop| {
+                        crop.start_time <= subtitle.end_time && crop.end_time >= subtitle.start_time
+                    })
+                    .max_by_key(|crop| {
+                        crop.end_time.min(subtitle.end_time)
+                            - crop.start_time.max(subtitle.start_time)
+                    })
+                    .map(|crop| crop.thumbnail.clone())
+            })
+            .collect()
+    }
+}
+
+/// Columns and rows needed to lay out `count` cells at most `columns` wide.
+pub fn grid_layout(count: usize, columns: usize) -> (usize, usize) {
+    if count == 0 {
+        return (0, 0);
+    }
+    let columns = columns.clamp(1, count);
+    (columns, count.div_ceil(columns))
+}
+
+/// Writes one grayscale PNG with a labeled thumbnail cell per cue, in the order given.
+pub fn write_contact_sheet(
+    options: &ContactSheetOptions,
+    subtitles: &[MergedSubtitle],
+    crops: &CropLog,
+) -> io::Result<()> {
+    let (columns, rows) = grid_layout(subtitles.len(), options.columns);
+    if columns == 0 {
+        return Ok(());
+    }
+    let thumbnails = crops.crops_for(subtitles);
+    let cell_width = crops.thumb_width;
+    let thumb_height = thumbnails
+        .iter()
+        .flatten()
+        .map(GrayImage::height)
+        .max()
+        .unwrap_or(cell_width / 8);
+    let cell_height = LABEL_HEIGHT + thumb_height;
+    let mut canvas = GrayImage::new(
+        GAP + columns as u32 * (cell_width + GAP),
+        GAP + rows as u32 * (cell_height + GAP),
+    );
+
+    for (idx, (subtitle, thumbnail)) in subtitles.iter().zip(&thumbnails).enumerate() {
+        let x = GAP + (idx % columns) as u32 * (cell_width + GAP);
+        let y = GAP + (idx / columns) as u32 * (cell_height + GAP);
+        let label = format!("#{} {}", idx + 1, format_time(subtitle.start_time));
+        draw_label(&mut canvas, x, y + GAP, &label);
+        if let Some(thumbnail) = thumbnail {
+            imageops::overlay(&mut canvas, thumbnail, x as i64, (y + LABEL_HEIGHT) as i64);
+        }
+    }
+
+    write_gray_png(&options.path, &canvas)
+}
+
+fn write_gray_png(path: &Path, image: &GrayImage) -> io::Result<()> {
+    let file = File::create(path)?;
+    let mut encoder = Encoder::new(BufWriter::new(file), image.width(), image.height());
+    encoder.set_color(ColorType::Grayscale);
+    encoder.set_depth(BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(image.as_raw())
+        .map_err(io::Error::other)?;
+    Ok(())
+}
+
+fn format_time(time: Duration) -> String {
+    let total_ms = time.as_millis();
+    let hours = total_ms / 3_600_000;
+    let minutes = (total_ms / 60_000) % 60;
+    let seconds = (total_ms / 1000) % 60;
+    let millis = total_ms % 1000;
+    format!("{hours:02}:{minutes:02}:{seconds:02}.{millis:03}")
+}
+
+fn draw_label(canvas: &mut GrayImage, x: u32, y: u32, text: &str) {
+    for (slot, ch) in text.chars().enumerate() {
+        let origin_x = x + slot as u32 * (GLYPH_WIDTH + 1) * GLYPH_SCALE;
+        for (row, bits) in glyph(ch).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..GLYPH_SCALE {
+                    for dx in 0..GLYPH_SCALE {
+                        let px = origin_x + col * GLYPH_SCALE + dx;
+                        let py = y + row as u32 * GLYPH_SCALE + dy;
+                        if px < canvas.width() && py < canvas.height() {
+                            canvas.put_pixel(px, py, image::Luma([LABEL_COLOR]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 3x5 bitmap rows for the characters labels use; anything else renders blank.
+fn glyph(ch: char) -> [u8; 5] {
+    match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_layout_fills_rows_up_to_the_column_limit() {
+        assert_eq!(grid_layout(0, 4), (0, 0));
+        assert_eq!(grid_layout(1, 4), (1, 1));
+        assert_eq!(grid_layout(4, 4), (4, 1));
+        assert_eq!(grid_layout(5, 4), (4, 2));
+        assert_eq!(grid_layout(9, 3), (3, 3));
+        assert_eq!(grid_layout(10, 3), (3, 4));
+        assert_eq!(grid_layout(3, 0), (1, 3));
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use subtitle_fast_types::{DecoderError, DecoderResult, VideoFrame};
+
+use crate::settings::DetectionSettings;
+use crate::stage::StreamBundle;
+use crate::stage::detector::{Detector, DetectorError};
+use crate::stage::sampler::FrameSampler;
+use crate::stage::sorter::FrameSorter;
+
+/// Decodes and detects the first `calibration_frames` frames of `input`, then extrapolates the
+/// measured throughput over the stream's total frame count. Segmentation and OCR are skipped,
+/// so the projection covers decoding and detection only.
+///
+/// Returns `None` without decoding when the total frame count is unknown, and when the stream
+/// ends before a single frame was measured.
+pub async fn estimate_runtime(
+    input: StreamBundle<DecoderResult<VideoFrame>>,
+    settings: &DetectionSettings,
+    calibration_frames: u64,
+) -> Result<Option<Duration>, DecoderError> {
+    let StreamBundle {
+        stream,
+        total_frames,
+    } = input;
+    let Some(total_frames) = total_frames.filter(|&total| total > 0) else {
+        return Ok(None);
+    };
+
+    let detector = Detector::new(settings)
+        .map_err(|err| DecoderError::configuration(format!("subtitle detection error: {err}")))?;
+    let decoded = Arc::new(AtomicU64::new(0));
+    let counter = Arc::clone(&decoded);
+    let limit = usize::try_from(calibration_frames.max(1)).unwrap_or(usize::MAX);
+    let truncated = stream.take(limit).inspect(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let started = Instant::now();
+    let sorted =
+        FrameSorter::new().attach(StreamBundle::new(Box::pin(truncated), Some(total_frames)));
+    let sampled = FrameSampler::new(settings.samples_per_second)
+        .with_max_history(settings.max_history_frames)
+        .attach(sorted);
+    let mut detected = detector.attach(sampled).stream;
+    while let Some(result) = detected.next().await {
+        match result {
+            Ok(_) => {}
+            Err(DetectorError::Sampler(err)) => return Err(err),
+            Err(DetectorError::Detection(err)) => {
+                return Err(DecoderError::configuration(format!(
+                    "subtitle detection error: {err}"
+                )));
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    let measured = decoded.load(Ordering::Relaxed);
+    if measured == 0 {
+        return Ok(None);
+    }
+    let per_frame = elapsed.as_secs_f64() / measured as f64;
+    Ok(Some(Duration::from_secs_f64(
+        per_frame * total_frames as f64,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use subtitle_fast_decoder::backends::mock::MockProvider;
+    use subtitle_fast_decoder::{Backend, Configuration, DecoderProvider, DynDecoderProvider};
+
+    use super::*;
+
+    fn settings() -> DetectionSettings {
+        DetectionSettings {
+            samples_per_second: 10,
+            ..Default::default()
+        }
+    }
+
+    fn mock_stream() -> StreamBundle<DecoderResult<VideoFrame>> {
+        let config = Configuration {
+            backend: Backend::Mock,
+            ..Configuration::default()
+        };
+        let provider: DynDecoderProvider =
+            Box::new(MockProvider::new(&config).expect("mock provider"));
+        let total_frames = provider.metadata().total_frames;
+        let (_controller, frames) = provider.open().expect("mock stream");
+        StreamBundle::new(frames, total_frames)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn calibration_projects_the_full_run_within_an_order_of_magnitude() {
+        let estimate = estimate_runtime(mock_stream(), &settings(), 30)
+            .await
+            .expect("calibration run")
+            .expect("mock stream has a frame count");
+
+        // Calibrating on every one of the 120 mock frames measures the actual run.
+        let actual = estimate_runtime(mock_stream(), &settings(), u64::MAX)
+            .await
+            .expect("full run")
+            .expect("mock stream has a frame count");
+
+        assert!(estimate > actual / 10, "{estimate:?} vs {actual:?}");
+        assert!(estimate < actual * 10, "{estimate:?} vs {actual:?}");
+    }
+
+    #[tokio::test]
+    async fn unknown_frame_count_has_no_estimate() {
+        let frames = mock_stream().stream;
+        let estimate = estimate_runtime(StreamBundle::new(frames, None), &settings(), 30)
+            .await
+            .expect("no error");
+        assert_eq!(estimate, None);
+    }
+}
@@ -15,6 +15,7 @@ use crate::gui::components::{
 };
 use crate::gui::icons::{Icon, icon_md, icon_sm};
 use crate::gui::session::{SessionHandle, SessionId, VideoSession};
+use crate::settings::OutputSettings;
 
 #[derive(RustEmbed)]
 #[folder = "assets"]
@@ -46,15 +47,19 @@ impl AssetSource for AppAssets {
     }
 }
 
-pub struct SubtitleFastApp;
+pub struct SubtitleFastApp {
+    output_settings: OutputSettings,
+}
 
 impl SubtitleFastApp {
-    pub fn new(_cx: &mut App) -> Self {
-        Self
+    /// `output_settings` come from the config file and shape exported subtitles.
+    pub fn new(_cx: &mut App, output_settings: OutputSettings) -> Self {
+        Self { output_settings }
     }
 
     pub fn open_window(&self, cx: &mut App) -> WindowHandle<MainWindow> {
         let bounds = Bounds::centered(None, size(px(1200.0), px(800.0)), cx);
+        let output_settings = self.output_settings.clone();
 
         let window = cx
             .open_window(
@@ -155,6 +160,7 @@ impl SubtitleFastApp {
                             controls_view,
                             roi_overlay_view,
                             roi_handle,
+                            output_settings,
                         )
                     });
                     let weak_main = main_window.downgrade();
@@ -254,6 +260,7 @@ pub struct MainWindow {
     controls_view: Entity<VideoControls>,
     roi_overlay: Entity<VideoRoiOverlay>,
     roi_handle: VideoRoiHandle,
+    output_settings: OutputSettings,
 }
 
 impl MainWindow {
@@ -272,6 +279,7 @@ impl MainWindow {
         controls_view: Entity<VideoControls>,
         roi_overlay: Entity<VideoRoiOverlay>,
         roi_handle: VideoRoiHandle,
+        output_settings: OutputSettings,
     ) -> Self {
         Self {
             player,
@@ -294,6 +302,7 @@ impl MainWindow {
             controls_view,
             roi_overlay,
             roi_handle,
+            output_settings,
         }
     }
 
@@ -372,6 +381,7 @@ impl MainWindow {
         detection_handle.set_video_path(Some(path.clone()));
         detection_handle.set_luma_handle(Some(self.luma_handle.clone()));
         detection_handle.set_roi_handle(Some(self.roi_handle.clone()));
+        detection_handle.set_output_settings(self.output_settings.clone());
         let session_id = self.sessions.add_session(path, detection_handle);
 
         if select_new || self.active_session.is_none() {
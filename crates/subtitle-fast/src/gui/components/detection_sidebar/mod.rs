@@ -7,9 +7,12 @@ use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
 use futures_util::StreamExt;
 use tokio::sync::{oneshot, watch};
 
+use crate::backend;
 use crate::gui::components::{VideoLumaHandle, VideoRoiHandle};
 use crate::gui::runtime;
-use crate::settings::{DecoderSettings, DetectionSettings, EffectiveSettings, OutputSettings};
+use crate::settings::{
+    DecoderSettings, DetectionSettings, EffectiveSettings, OcrSettings, OutputSettings,
+};
 use crate::stage::{
     self, MergedSubtitle, PipelineConfig, PipelineHandle, PipelineProgress, SubtitleUpdate,
     SubtitleUpdateKind, TimedSubtitle,
@@ -30,8 +33,6 @@ pub use metrics::DetectionMetrics;
 pub use panel::DetectionSidebar;
 pub use subtitles::DetectedSubtitlesList;
 
-const DEFAULT_SAMPLES_PER_SECOND: u32 = 7;
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DetectionRunState {
     Idle,
@@ -74,6 +75,7 @@ impl DetectionHandle {
             video_path: Mutex::new(None),
             luma_handle: Mutex::new(None),
             roi_handle: Mutex::new(None),
+            output_settings: Mutex::new(OutputSettings::default()),
             cancel_tx: Mutex::new(None),
             subtitle_subscribers: Mutex::new(Vec::new()),
             subtitles: Mutex::new(Vec::new()),
@@ -93,6 +95,11 @@ impl DetectionHandle {
         self.inner.set_roi_handle(handle);
     }
 
+    /// Output settings applied to exported subtitles, such as the short-cue handling.
+    pub fn set_output_settings(&self, settings: OutputSettings) {
+        self.inner.set_output_settings(settings);
+    }
+
     pub fn subscribe_state(&self) -> watch::Receiver<DetectionRunState> {
         self.inner.subscribe_state()
     }
@@ -161,6 +168,7 @@ struct DetectionPipelineInner {
     video_path: Mutex<Option<PathBuf>>,
     luma_handle: Mutex<Option<VideoLumaHandle>>,
     roi_handle: Mutex<Option<VideoRoiHandle>>,
+    output_settings: Mutex<OutputSettings>,
     cancel_tx: Mutex<Option<oneshot::Sender<()>>>,
     subtitle_subscribers: Mutex<Vec<UnboundedSender<SubtitleMessage>>>,
     subtitles: Mutex<Vec<MergedSubtitle>>,
@@ -185,6 +193,12 @@ impl DetectionPipelineInner {
         }
     }
 
+    fn set_output_settings(&self, settings: OutputSettings) {
+        if let Ok(mut slot) = self.output_settings.lock() {
+            *slot = settings;
+        }
+    }
+
     fn subscribe_state(&self) -> watch::Receiver<DetectionRunState> {
         self.state_rx.clone()
     }
@@ -228,10 +242,12 @@ impl DetectionPipelineInner {
         let settings = EffectiveSettings {
             detection: detection_settings,
             decoder: DecoderSettings {
-                backend: None,
-                channel_capacity: None,
+                // Pausing detection leaves the decoder queue full on purpose.
+                queue_full_warning: None,
+                ..Default::default()
             },
-            output: OutputSettings { path: None },
+            ocr: OcrSettings::default(),
+            output: OutputSettings::default(),
         };
         let plan = match build_detection_plan(&path, &settings) {
             Ok(plan) => plan,
@@ -391,11 +407,10 @@ impl DetectionPipelineInner {
             .unwrap_or_else(full_frame_roi);
 
         DetectionSettings {
-            samples_per_second: DEFAULT_SAMPLES_PER_SECOND,
             target,
             delta,
-            comparator: None,
             roi: Some(roi),
+            ..Default::default()
         }
     }
 
@@ -470,8 +485,12 @@ impl DetectionPipelineInner {
             return;
         }
 
-        let mut ordered = subtitles;
-        stage::sort_subtitles(&mut ordered);
+        let output = self
+            .output_settings
+            .lock()
+            .map(|slot| slot.clone())
+            .unwrap_or_default();
+        let ordered = backend::prepare_export_cues(&output, &subtitles);
         let contents = stage::render_srt(&ordered);
         let task = runtime::spawn(async move {
             if let Err(err) = tokio::fs::write(&path, contents).await {
@@ -663,6 +682,7 @@ fn build_detection_plan(
     {
         config.channel_capacity = Some(non_zero);
     }
+    config.queue_full_warning = settings.decoder.queue_full_warning;
 
     Ok(DetectionPlan {
         config,
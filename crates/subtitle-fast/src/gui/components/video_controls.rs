@@ -407,7 +407,7 @@ impl Render for VideoControls {
         let mut total_frames = 0u64;
         let snapshot = self.info.as_ref().map(|info| info.snapshot());
 
-        if let Some(snapshot) = snapshot {
+        if let Some(snapshot) = &snapshot {
             self.sync_paused(snapshot.paused);
             if let Some(timestamp) = snapshot.last_timestamp {
                 current_time = timestamp;
@@ -11,8 +11,8 @@ use gpui::{
     Context, Frame, ObjectFit, Render, Task, VideoHandle, Window, div, prelude::*, rgb, video,
 };
 use subtitle_fast_decoder::{
-    Backend, Configuration, DecoderController, FrameStream, OutputFormat, SeekInfo, SeekMode,
-    VideoFrame, VideoMetadata,
+    Backend, Configuration, DecoderController, FrameStream, OutputFormat, RawFormat, SeekInfo,
+    SeekMode, VideoFrame, VideoMetadata,
 };
 use tokio::sync::{
     mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
@@ -178,11 +178,11 @@ impl VideoPlayerInfoHandle {
     }
 
     fn metadata(&self) -> VideoMetadata {
-        *self
-            .inner
+        self.inner
             .metadata
             .lock()
             .expect("video info mutex poisoned")
+            .clone()
     }
 
     fn set_metadata(&self, metadata: VideoMetadata) {
@@ -257,7 +257,7 @@ impl VideoPlayerInfoHandle {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct VideoPlayerInfoSnapshot {
     pub metadata: VideoMetadata,
     pub last_timestamp: Option<Duration>,
@@ -392,6 +392,16 @@ fn open_session(
         channel_capacity: None,
         output_format: OutputFormat::Nv12,
         start_frame,
+        end_frame: None,
+        frame_rate: None,
+        raw_width: None,
+        raw_height: None,
+        raw_format: RawFormat::Nv12,
+        video_stream_index: None,
+        fps_override: None,
+        queue_full_warning: None,
+        sample_timestamps: None,
+        reverse: false,
     };
 
     let provider = match config.create_provider() {
@@ -404,7 +414,7 @@ fn open_session(
     };
 
     let metadata = provider.metadata();
-    info.set_metadata(metadata);
+    info.set_metadata(metadata.clone());
     let frame_duration = metadata
         .fps
         .and_then(|fps| (fps > 0.0).then(|| Duration::from_secs_f64(1.0 / fps)));
@@ -15,8 +15,8 @@ use crate::gui::components::{
 use crate::gui::icons::{Icon, icon_sm};
 use subtitle_fast_types::VideoFrame;
 use subtitle_fast_validator::subtitle_detection::{
-    LumaBandConfig, SubtitleDetectionConfig, SubtitleDetectionResult, SubtitleDetector,
-    SubtitleDetectorKind, build_detector,
+    LumaBandConfig, LumaPolarity, SubtitleDetectionConfig, SubtitleDetectionResult,
+    SubtitleDetector, SubtitleDetectorKind, build_detector,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -707,6 +707,7 @@ impl ValidatorOverlayState {
             config.luma_band = LumaBandConfig {
                 target: luma.target,
                 delta: luma.delta,
+                polarity: LumaPolarity::Bright,
             };
             match build_detector(SubtitleDetectorKind::ProjectionBand, config) {
                 Ok(detector) => {
@@ -1,8 +1,19 @@
 pub mod backend;
+pub mod benchmark;
+pub mod cache;
 pub mod cli;
+pub mod contact_sheet;
+pub mod estimate;
+pub mod overlay;
+pub mod preview;
+pub mod selftest;
 pub mod settings;
 pub mod stage;
 pub mod subtitle;
+pub mod timeline;
+
+#[cfg(test)]
+pub(crate) mod test_support;
 
 #[cfg(feature = "gui")]
 pub mod gui;
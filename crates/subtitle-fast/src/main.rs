@@ -1,13 +1,25 @@
 use std::env;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
 
 use clap::CommandFactory;
-use subtitle_fast::backend::{self, ExecutionPlan};
-use subtitle_fast::cli::{CliArgs, CliSources, parse_cli};
-use subtitle_fast::settings::{ConfigError, resolve_settings};
+use subtitle_fast::backend::{
+    self, ExecutionPlan, INTERRUPTED_EXIT_CODE, InterruptReceiver, RunStatus,
+};
+use subtitle_fast::cache::DetectionCache;
+use subtitle_fast::cli::{CliArgs, CliCommand, CliSources, parse_cli};
+use subtitle_fast::selftest;
+use subtitle_fast::settings::{ConfigError, resolve_settings, write_tuning};
 use subtitle_fast::stage::PipelineConfig;
+use subtitle_fast_decoder::{Backend, Configuration};
 use subtitle_fast_types::DecoderError;
 
+/// Decoded frames queued per worker thread when `--threads` sizes the decoder channel.
+const FRAMES_PER_THREAD: usize = 4;
+const MIN_THREADED_CHANNEL: usize = 4;
+const MAX_THREADED_CHANNEL: usize = 64;
+
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<(), DecoderError> {
     #[allow(unused_variables)]
@@ -25,14 +37,22 @@ async fn main() -> Result<(), DecoderError> {
 
 #[cfg(feature = "gui")]
 fn run_gui() -> Result<(), DecoderError> {
+    use clap::Parser;
     use gpui::*;
     use subtitle_fast::gui::{AppAssets, SubtitleFastApp, runtime};
 
+    // The GUI takes no arguments, but exported cues still follow the config file's output section.
+    let cli_args = CliArgs::parse_from(["subtitle-fast"]);
+    let output_settings = resolve_settings(&cli_args, &CliSources::default())
+        .map_err(map_config_error)?
+        .settings
+        .output;
+
     Application::new()
         .with_assets(AppAssets)
-        .run(|cx: &mut App| {
+        .run(move |cx: &mut App| {
             runtime::init(tokio::runtime::Handle::current());
-            let app = SubtitleFastApp::new(cx);
+            let app = SubtitleFastApp::new(cx, output_settings);
             app.open_window(cx);
             cx.activate(true);
         });
@@ -41,21 +61,56 @@ fn run_gui() -> Result<(), DecoderError> {
 }
 
 async fn run_cli() -> Result<(), DecoderError> {
-    match prepare_execution_plan().await? {
-        Some(plan) => backend::run(plan).await,
-        None => Ok(()),
+    let Some(plan) = prepare_execution_plan().await? else {
+        return Ok(());
+    };
+    let fail_if_empty = plan.fail_if_empty;
+    let status = backend::run(plan, install_interrupt_handler()).await?;
+    if status == RunStatus::Interrupted {
+        eprintln!("interrupted; subtitles detected so far were written");
+    }
+    match status.exit_code(fail_if_empty) {
+        0 => Ok(()),
+        code => std::process::exit(code),
     }
 }
 
+/// First Ctrl-C asks the pipeline to drain and write its output; a second one exits immediately.
+fn install_interrupt_handler() -> InterruptReceiver {
+    let (interrupt_tx, interrupt_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        eprintln!("\ninterrupt received; finishing active cues (press Ctrl-C again to abort)");
+        let _ = interrupt_tx.send(true);
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    });
+    interrupt_rx
+}
+
 async fn prepare_execution_plan() -> Result<Option<ExecutionPlan>, DecoderError> {
     let (cli_args, cli_sources): (CliArgs, CliSources) = parse_cli();
 
+    if let Some(CliCommand::SelfTest) = cli_args.command {
+        run_self_test().await?;
+        return Ok(None);
+    }
+
     if cli_args.list_backends {
         backend::display_available_backends();
         return Ok(None);
     }
 
-    let input = match cli_args.input.clone() {
+    let benchmark_input = match &cli_args.command {
+        Some(CliCommand::BenchmarkDetectors { input }) => Some(input.clone()),
+        _ => None,
+    };
+    let benchmark_detectors = benchmark_input.is_some();
+
+    let input = match benchmark_input.or_else(|| cli_args.input.clone()) {
         Some(path) => path,
         None => {
             usage();
@@ -63,7 +118,8 @@ async fn prepare_execution_plan() -> Result<Option<ExecutionPlan>, DecoderError>
         }
     };
 
-    if !input.exists() {
+    let reads_stdin = input.as_os_str() == "-";
+    if !reads_stdin && !input.exists() {
         return Err(DecoderError::configuration(format!(
             "input file '{}' does not exist",
             input.display()
@@ -72,8 +128,13 @@ async fn prepare_execution_plan() -> Result<Option<ExecutionPlan>, DecoderError>
 
     let resolved = resolve_settings(&cli_args, &cli_sources).map_err(map_config_error)?;
     let settings = resolved.settings;
+    if let Some(path) = cli_args.dump_tuning.as_deref() {
+        write_tuning(path, &settings.detection).map_err(|err| {
+            DecoderError::configuration(format!("failed to write tuning {}: {err}", path.display()))
+        })?;
+    }
 
-    let pipeline = PipelineConfig::from_settings(&settings, &input)?;
+    let mut pipeline = PipelineConfig::from_settings(&settings, &input)?;
 
     let env_backend_present = std::env::var("SUBFAST_BACKEND").is_ok();
     let mut config = subtitle_fast_decoder::Configuration::from_env().unwrap_or_default();
@@ -81,9 +142,52 @@ async fn prepare_execution_plan() -> Result<Option<ExecutionPlan>, DecoderError>
         Some(name) => Some(backend::parse_backend(name)?),
         None => None,
     };
-    let backend_locked = backend_override.is_some() || env_backend_present;
+    let preferred = settings
+        .decoder
+        .backend_priority
+        .iter()
+        .map(|name| backend::parse_backend(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let backend_priority = subtitle_fast_decoder::Configuration::backend_priority(&preferred);
+    let mut backend_locked = backend_override.is_some() || env_backend_present;
     if let Some(backend_value) = backend_override {
         config.backend = backend_value;
+    } else if input.is_dir() {
+        // A folder of extracted frames can only be read by the image-sequence backend.
+        config.backend = Backend::ImageSequence;
+        backend_locked = true;
+    } else if reads_stdin {
+        // Only raw frames can be streamed in; containers need a seekable file.
+        config.backend = Backend::RawYuv;
+        backend_locked = true;
+    } else if let Some(&first) = backend_priority.first()
+        && !backend_locked
+    {
+        config.backend = first;
+    }
+    if let Some(frame_rate) = cli_args.frame_rate {
+        config.frame_rate = Some(frame_rate);
+    }
+    if let Some(fps) = settings.decoder.fps_override {
+        config.fps_override = Some(fps);
+    }
+    if let Some(fps) = settings.decoder.raw_fps {
+        config.frame_rate = Some(fps);
+    }
+    if let Some(width) = settings.decoder.raw_width {
+        config.raw_width = Some(width);
+    }
+    if let Some(height) = settings.decoder.raw_height {
+        config.raw_height = Some(height);
+    }
+    if let Some(format) = settings.decoder.raw_format {
+        config.raw_format = format;
+    }
+    if let Some(stream) = cli_args.video_stream {
+        config.video_stream_index = Some(stream);
+    }
+    if let Some(end_frame) = cli_args.end_frame {
+        config.end_frame = Some(end_frame);
     }
     config.input = Some(input);
     if let Some(capacity) = settings.decoder.channel_capacity
@@ -91,14 +195,120 @@ async fn prepare_execution_plan() -> Result<Option<ExecutionPlan>, DecoderError>
     {
         config.channel_capacity = Some(non_zero);
     }
+    config.queue_full_warning = settings.decoder.queue_full_warning;
+    if let Some(threads) = cli_args.threads.filter(|&threads| threads > 0) {
+        apply_thread_limit(&mut config, &mut pipeline, threads)?;
+    }
+    if let Some(samples) = cli_args.detection_threads {
+        pipeline.detection_concurrency = samples;
+    }
+    if cli_args.start_time.is_some() || cli_args.end_time.is_some() {
+        apply_time_range(
+            &mut config,
+            &mut pipeline,
+            cli_args.start_time,
+            cli_args.end_time,
+        )?;
+    }
 
     Ok(Some(ExecutionPlan {
         config,
         backend_locked,
+        backend_priority,
         pipeline,
+        preview: cli_args.first_frame_preview.clone(),
+        dump_frame_info: cli_args.dump_frame_info,
+        benchmark_detectors,
+        fail_if_empty: cli_args.fail_if_empty,
+        cache: cli_args.cache_dir.clone().map(DetectionCache::new),
+        progress_format: cli_args.progress_format.unwrap_or_default(),
     }))
 }
 
+/// Runs the comparators on a dedicated pool of `threads` workers, detects as many samples at
+/// once, and, unless a capacity was configured, sizes the decoded-frame queue to match.
+fn apply_thread_limit(
+    config: &mut Configuration,
+    pipeline: &mut PipelineConfig,
+    threads: usize,
+) -> Result<(), DecoderError> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|index| format!("subfast-worker-{index}"))
+        .build()
+        .map_err(|err| {
+            DecoderError::configuration(format!("failed to start {threads} worker threads: {err}"))
+        })?;
+    pipeline.thread_pool = Some(Arc::new(pool));
+    pipeline.detection_concurrency = threads;
+    if config.channel_capacity.is_none() {
+        config.channel_capacity = NonZeroUsize::new(
+            (threads * FRAMES_PER_THREAD).clamp(MIN_THREADED_CHANNEL, MAX_THREADED_CHANNEL),
+        );
+    }
+    Ok(())
+}
+
+/// Turns `--start-time`/`--end-time` into frame bounds at the probed frame rate. Without a
+/// frame rate the start becomes a timestamp seek, and an end time cannot be honoured. Stdin
+/// is never opened to probe; its raw frames run at the configured rate.
+fn apply_time_range(
+    config: &mut Configuration,
+    pipeline: &mut PipelineConfig,
+    start: Option<Duration>,
+    end: Option<Duration>,
+) -> Result<(), DecoderError> {
+    if let (Some(start), Some(end)) = (start, end)
+        && start >= end
+    {
+        return Err(DecoderError::configuration(format!(
+            "--start-time ({:.3}s) must be before --end-time ({:.3}s)",
+            start.as_secs_f64(),
+            end.as_secs_f64()
+        )));
+    }
+    let reads_stdin = config
+        .input
+        .as_deref()
+        .is_some_and(|input| input.as_os_str() == "-");
+    let fps = config
+        .fps_override
+        .or_else(|| {
+            if reads_stdin {
+                return config.frame_rate;
+            }
+            config
+                .create_provider()
+                .ok()
+                .and_then(|provider| provider.metadata().fps)
+        })
+        .filter(|fps| fps.is_finite() && *fps > 0.0);
+    match fps {
+        Some(fps) => {
+            let (start_frame, end_frame) = backend::frame_bounds(start, end, fps);
+            config.start_frame = start_frame.or(config.start_frame);
+            config.end_frame = end_frame.or(config.end_frame);
+        }
+        None if end.is_some() => {
+            return Err(DecoderError::configuration(
+                "--end-time needs the video's frame rate, which could not be probed; pass --fps",
+            ));
+        }
+        None => pipeline.start_seek = start,
+    }
+    Ok(())
+}
+
+async fn run_self_test() -> Result<(), DecoderError> {
+    let report = selftest::run_self_test().await?;
+    print!("{report}");
+    if !report.usable() {
+        eprintln!("self-test failed: at least one category has no working entry");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 fn usage() {
     let mut command = CliArgs::command();
     command.print_help().ok();
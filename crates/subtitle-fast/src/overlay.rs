@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use png::{BitDepth, ColorType, Encoder};
+use subtitle_fast_types::{DetectionRegion, RoiConfig, VideoFrame};
+use subtitle_fast_validator::overlay::RgbCanvas;
+
+pub const REGION_COLOR: [u8; 3] = [255, 0, 0];
+pub const ROI_COLOR: [u8; 3] = [0, 200, 255];
+
+/// RGB8 canvas built from the luma plane of a frame so detection results can be drawn on top.
+pub struct OverlayImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl OverlayImage {
+    pub fn from_frame(frame: &VideoFrame) -> Self {
+        let width = frame.width();
+        let height = frame.height();
+        let stride = frame.stride();
+        let data = frame.y_plane();
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 3);
+        for row in 0..height as usize {
+            let start = row * stride;
+            for col in 0..width as usize {
+                let value = data.get(start + col).copied().unwrap_or(0);
+                pixels.extend_from_slice(&[value, value, value]);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn draw_regions(&mut self, regions: &[DetectionRegion]) {
+        self.canvas().draw_regions(regions, REGION_COLOR);
+    }
+
+    pub fn draw_roi(&mut self, roi: &RoiConfig) {
+        let width = self.width as f32;
+        let height = self.height as f32;
+        self.draw_box(
+            roi.x * width,
+            roi.y * height,
+            roi.width * width,
+            roi.height * height,
+            ROI_COLOR,
+        );
+    }
+
+    pub fn draw_box(&mut self, x: f32, y: f32, width: f32, height: f32, color: [u8; 3]) {
+        self.canvas().draw_box(x, y, width, height, color);
+    }
+
+    pub fn write_png(&self, path: &Path) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut encoder = Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn canvas(&mut self) -> RgbCanvas<'_> {
+        RgbCanvas::new(&mut self.pixels, self.width, self.height)
+    }
+}
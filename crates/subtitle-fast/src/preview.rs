@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use subtitle_fast_types::{DecoderError, DecoderResult, SubtitleDetectionResult, VideoFrame};
+
+use crate::overlay::OverlayImage;
+use crate::settings::DetectionSettings;
+use crate::stage::StreamBundle;
+use crate::stage::detector::{Detector, DetectorError};
+use crate::stage::sampler::FrameSampler;
+use crate::stage::sorter::FrameSorter;
+
+/// First sampled frame whose detection reported a subtitle.
+pub struct PreviewFrame {
+    pub frame_index: u64,
+    pub frame: Arc<VideoFrame>,
+    pub detection: SubtitleDetectionResult,
+}
+
+/// Runs only the sort/sample/detect stages and stops at the first positive detection.
+pub async fn first_detection(
+    input: StreamBundle<DecoderResult<VideoFrame>>,
+    settings: &DetectionSettings,
+) -> Result<Option<PreviewFrame>, DecoderError> {
+    let detector = Detector::new(settings)
+        .map_err(|err| DecoderError::configuration(format!("subtitle detection error: {err}")))?;
+    let sorted = FrameSorter::new().attach(input);
+    let sampled = FrameSampler::new(settings.samples_per_second)
+        .with_max_history(settings.max_history_frames)
+        .attach(sorted);
+    let mut detected = detector.attach(sampled).stream;
+
+    while let Some(result) = detected.next().await {
+        match result {
+            Ok(sample) => {
+                if sample.detection.has_subtitle {
+                    return Ok(Some(PreviewFrame {
+                        frame_index: sample.sample.frame_index(),
+                        frame: sample.sample.frame_handle(),
+                        detection: sample.detection,
+                    }));
+                }
+            }
+            Err(DetectorError::Sampler(err)) => return Err(err),
+            Err(DetectorError::Detection(err)) => {
+                return Err(DecoderError::configuration(format!(
+                    "subtitle detection error: {err}"
+                )));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn write_preview(
+    path: &Path,
+    preview: &PreviewFrame,
+    settings: &DetectionSettings,
+) -> Result<(), DecoderError> {
+    let mut image = OverlayImage::from_frame(&preview.frame);
+    if let Some(roi) = settings.roi.as_ref() {
+        image.draw_roi(roi);
+    }
+    image.draw_regions(&preview.detection.regions);
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            DecoderError::configuration(format!(
+                "failed to prepare preview directory {}: {err}",
+                parent.display()
+            ))
+        })?;
+    }
+    image.write_png(path).map_err(|err| {
+        DecoderError::configuration(format!(
+            "failed to write preview image {}: {err}",
+            path.display()
+        ))
+    })
+}
+
+pub fn print_preview(path: &Path, preview: &PreviewFrame) {
+    let time = preview
+        .frame
+        .pts()
+        .map(|pts| format!("{:.3}s", pts.as_secs_f64()))
+        .unwrap_or_else(|| "--".to_string());
+    println!(
+        "first subtitle detected at frame {} ({time}) with max score {:.3}",
+        preview.frame_index, preview.detection.max_score
+    );
+    for (idx, region) in preview.detection.regions.iter().enumerate() {
+        println!(
+            "  region {idx}: x={:.0} y={:.0} w={:.0} h={:.0} score={:.3}",
+            region.x, region.y, region.width, region.height, region.score
+        );
+    }
+    println!("preview written to {}", path.display());
+}
+
+/// One-line decoder diagnostics for a frame: buffer format, dimensions, plane layout and
+/// timing, or the backend and pixel-format FourCC for native buffers.
+pub fn frame_info(frame: &VideoFrame) -> String {
+    let timing = format!(
+        "pts={} dts={} index={} serial={}",
+        format_timestamp(frame.pts()),
+        format_timestamp(frame.dts()),
+        frame
+            .index()
+            .map(|index| index.to_string())
+            .unwrap_or_else(|| "--".to_string()),
+        frame.serial()
+    );
+    match frame.native() {
+        Some(native) => format!(
+            "format=native backend={} pixel_format={} ({:#010x}) {}x{} {timing}",
+            native.backend(),
+            fourcc_name(native.pixel_format()),
+            native.pixel_format(),
+            frame.width(),
+            frame.height()
+        ),
+        None => format!(
+            "format=nv12 {}x{} y_stride={} uv_stride={} y_bytes={} uv_bytes={} {timing}",
+            frame.width(),
+            frame.height(),
+            frame.y_stride(),
+            frame.uv_stride(),
+            frame.y_plane().len(),
+            frame.uv_plane().len()
+        ),
+    }
+}
+
+/// Renders a pixel-format code as its four-character name, most significant byte first as
+/// CoreVideo does; codes that are not printable ASCII come back as `'????'`.
+pub fn fourcc_name(code: u32) -> String {
+    let bytes = code.to_be_bytes();
+    if bytes
+        .iter()
+        .all(|byte| byte.is_ascii_graphic() || *byte == b' ')
+    {
+        format!("'{}'", String::from_utf8_lossy(&bytes))
+    } else {
+        "'????'".to_string()
+    }
+}
+
+fn format_timestamp(value: Option<std::time::Duration>) -> String {
+    value
+        .map(|value| format!("{:.3}s", value.as_secs_f64()))
+        .unwrap_or_else(|| "--".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scripted_frame;
+
+    const FIRST_SUBTITLE_FRAME: u64 = 12;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stops_at_first_scripted_subtitle_frame() {
+        let frames: Vec<DecoderResult<VideoFrame>> = (0..60)
+            .map(|index| Ok(scripted_frame(index, index >= FIRST_SUBTITLE_FRAME)))
+            .collect();
+        let input = StreamBundle::new(Box::pin(futures_util::stream::iter(frames)), Some(60));
+        let settings = DetectionSettings {
+            samples_per_second: 60,
+            ..Default::default()
+        };
+
+        let preview = first_detection(input, &settings)
+            .await
+            .expect("preview run")
+            .expect("subtitle frame detected");
+
+        assert_eq!(preview.frame_index, FIRST_SUBTITLE_FRAME);
+        assert!(preview.detection.has_subtitle);
+        assert!(!preview.detection.regions.is_empty());
+    }
+
+    #[test]
+    fn frame_info_matches_synthetic_frame() {
+        let frame = scripted_frame(30, false).with_serial(2);
+
+        assert_eq!(
+            frame_info(&frame),
+            "format=nv12 640x360 y_stride=640 uv_stride=640 y_bytes=230400 uv_bytes=115200 \
+             pts=0.500s dts=-- index=30 serial=2"
+        );
+    }
+
+    #[test]
+    fn fourcc_name_decodes_core_video_codes() {
+        assert_eq!(fourcc_name(u32::from_be_bytes(*b"420v")), "'420v'");
+        assert_eq!(fourcc_name(u32::from_be_bytes(*b"BGRA")), "'BGRA'");
+        assert_eq!(fourcc_name(23), "'????'");
+    }
+}
@@ -0,0 +1,374 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use subtitle_fast_decoder::backends::mock::MockProvider;
+use subtitle_fast_decoder::{
+    Backend, Configuration, DecoderProvider, DynDecoderProvider, OutputFormat, RawFormat,
+};
+use subtitle_fast_ocr::{NoopOcrEngine, OcrEngine};
+use subtitle_fast_types::{DecoderError, VideoFrame};
+use subtitle_fast_validator::subtitle_detection::{
+    DEFAULT_TARGET, SubtitleDetectorKind, preflight_detection,
+};
+
+use crate::overlay::OverlayImage;
+use crate::preview::first_detection;
+use crate::settings::DetectionSettings;
+use crate::stage::StreamBundle;
+
+const CLIP_WIDTH: u32 = 640;
+const CLIP_HEIGHT: u32 = 360;
+const CLIP_FPS: f64 = 25.0;
+const CLIP_FRAMES: u64 = 6;
+const FIRST_SUBTITLE_FRAME: u64 = 2;
+const FIRST_FRAME_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckCategory {
+    Backend,
+    Detector,
+    Ocr,
+    Pipeline,
+}
+
+impl CheckCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CheckCategory::Backend => "backend",
+            CheckCategory::Detector => "detector",
+            CheckCategory::Ocr => "ocr",
+            CheckCategory::Pipeline => "pipeline",
+        }
+    }
+}
+
+/// One row of the self-test matrix.
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub category: CheckCategory,
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(
+        category: CheckCategory,
+        name: impl Into<String>,
+        outcome: Result<String, String>,
+    ) -> Self {
+        let (passed, detail) = match outcome {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        Self {
+            category,
+            name: name.into(),
+            passed,
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    pub fn find(&self, category: CheckCategory, name: &str) -> Option<&CheckResult> {
+        self.checks
+            .iter()
+            .find(|check| check.category == category && check.name == name)
+    }
+
+    /// A run is usable when every category has at least one passing entry.
+    pub fn usable(&self) -> bool {
+        [
+            CheckCategory::Backend,
+            CheckCategory::Detector,
+            CheckCategory::Ocr,
+            CheckCategory::Pipeline,
+        ]
+        .into_iter()
+        .all(|category| {
+            self.checks
+                .iter()
+                .any(|check| check.category == category && check.passed)
+        })
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .checks
+            .iter()
+            .map(|check| check.name.len())
+            .max()
+            .unwrap_or(0);
+        for check in &self.checks {
+            let status = if check.passed { "pass" } else { "FAIL" };
+            writeln!(
+                f,
+                "{:<9} {:<name_width$} {status}  {}",
+                check.category.as_str(),
+                check.name,
+                check.detail
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks decoder backends, subtitle detectors and OCR engines against a generated clip so
+/// environment problems show up without a user-provided video.
+pub async fn run_self_test() -> Result<SelfTestReport, DecoderError> {
+    let clip = ScriptedClip::create()?;
+    let mut report = SelfTestReport::default();
+
+    // The mock backend is gated to CI in `create_provider`, so it is constructed directly.
+    let outcome = match MockProvider::new(&clip.config(Backend::Mock)) {
+        Ok(provider) => decode_first_frame(Box::new(provider)).await,
+        Err(err) => Err(err.to_string()),
+    };
+    report.checks.push(CheckResult::new(
+        CheckCategory::Backend,
+        Backend::Mock.as_str(),
+        outcome,
+    ));
+
+    for backend in Configuration::available_backends() {
+        if backend == Backend::Mock {
+            continue;
+        }
+        let outcome = match clip.config(backend).create_provider() {
+            Ok(provider) => decode_first_frame(provider).await,
+            Err(err) => Err(err.to_string()),
+        };
+        report.checks.push(CheckResult::new(
+            CheckCategory::Backend,
+            backend.as_str(),
+            outcome,
+        ));
+    }
+
+    for kind in [
+        SubtitleDetectorKind::IntegralBand,
+        SubtitleDetectorKind::ProjectionBand,
+        SubtitleDetectorKind::MacVision,
+    ] {
+        let outcome = preflight_detection(kind)
+            .map(|()| "preflight ok".to_string())
+            .map_err(|err| err.to_string());
+        report.checks.push(CheckResult::new(
+            CheckCategory::Detector,
+            kind.as_str(),
+            outcome,
+        ));
+    }
+
+    for (name, outcome) in ocr_engine_checks() {
+        report
+            .checks
+            .push(CheckResult::new(CheckCategory::Ocr, name, outcome));
+    }
+
+    let outcome = detect_scripted_subtitle(&clip).await;
+    report.checks.push(CheckResult::new(
+        CheckCategory::Pipeline,
+        "scripted-subtitle",
+        outcome,
+    ));
+
+    Ok(report)
+}
+
+async fn decode_first_frame(provider: DynDecoderProvider) -> Result<String, String> {
+    let (_controller, mut stream) = provider.open().map_err(|err| err.to_string())?;
+    match tokio::time::timeout(FIRST_FRAME_TIMEOUT, stream.next()).await {
+        Ok(Some(Ok(frame))) => Ok(format!("decoded {}x{}", frame.width(), frame.height())),
+        Ok(Some(Err(err))) => Err(err.to_string()),
+        Ok(None) => Err("stream ended before the first frame".to_string()),
+        Err(_) => Err(format!(
+            "no frame within {}s",
+            FIRST_FRAME_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+fn ocr_engine_checks() -> Vec<(&'static str, Result<String, String>)> {
+    let mut checks = Vec::new();
+    #[cfg(all(feature = "ocr-vision", target_os = "macos"))]
+    {
+        let outcome = subtitle_fast_ocr::VisionOcrEngine::new()
+            .and_then(|engine| engine.warm_up())
+            .map(|()| "initialized".to_string())
+            .map_err(|err| err.to_string());
+        checks.push(("vision", outcome));
+    }
+    #[cfg(all(feature = "ocr-onnx", not(target_os = "macos")))]
+    {
+        // Looks in the default model cache; a --model-cache override is not known here.
+        let cache_dir = crate::settings::OcrSettings::default().model_cache_dir;
+        let outcome = subtitle_fast_ocr::OnnxPpocrConfig::from_model_cache(&cache_dir)
+            .and_then(subtitle_fast_ocr::OnnxPpocrRecognizer::new)
+            .and_then(|engine| engine.warm_up())
+            .map(|()| "initialized".to_string())
+            .map_err(|err| err.to_string());
+        checks.push(("onnx-ppocr", outcome));
+    }
+    #[cfg(all(feature = "ocr-tesseract", not(target_os = "macos")))]
+    {
+        let outcome = subtitle_fast_ocr::TesseractOcrEngine::new()
+            .and_then(|engine| engine.warm_up())
+            .map(|()| "initialized".to_string())
+            .map_err(|err| err.to_string());
+        checks.push(("tesseract", outcome));
+    }
+    let noop = NoopOcrEngine;
+    let outcome = noop
+        .warm_up()
+        .map(|()| "initialized (returns no text)".to_string())
+        .map_err(|err| err.to_string());
+    checks.push((noop.name(), outcome));
+    checks
+}
+
+/// Decodes the generated clip with the image-sequence backend and runs detection until the
+/// scripted subtitle is found.
+async fn detect_scripted_subtitle(clip: &ScriptedClip) -> Result<String, String> {
+    let provider = clip
+        .config(Backend::ImageSequence)
+        .create_provider()
+        .map_err(|err| err.to_string())?;
+    let total_frames = provider.metadata().total_frames;
+    let (_controller, stream) = provider.open().map_err(|err| err.to_string())?;
+    let settings = DetectionSettings {
+        samples_per_second: CLIP_FPS as u32,
+        ..Default::default()
+    };
+    match first_detection(StreamBundle::new(stream, total_frames), &settings).await {
+        Ok(Some(preview)) => Ok(format!(
+            "subtitle found at frame {} ({} region(s))",
+            preview.frame_index,
+            preview.detection.regions.len()
+        )),
+        Ok(None) => Err("scripted subtitle was not detected".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// A few PNG frames written to a temporary directory; the later ones carry a bright text band
+/// near the bottom like a burned-in subtitle.
+struct ScriptedClip {
+    dir: PathBuf,
+}
+
+impl ScriptedClip {
+    fn create() -> Result<Self, DecoderError> {
+        let dir =
+            std::env::temp_dir().join(format!("subtitle-fast-self-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).map_err(|err| clip_error(&dir, err))?;
+        let clip = Self { dir };
+        for index in 0..CLIP_FRAMES {
+            let frame = scripted_frame(index >= FIRST_SUBTITLE_FRAME)?;
+            let path = clip.dir.join(format!("frame_{index:03}.png"));
+            OverlayImage::from_frame(&frame)
+                .write_png(&path)
+                .map_err(|err| clip_error(&path, err))?;
+        }
+        Ok(clip)
+    }
+
+    /// Directory-based backends read the whole sequence; container backends get the first
+    /// frame as a single-image input.
+    fn config(&self, backend: Backend) -> Configuration {
+        let input = if backend == Backend::ImageSequence {
+            self.dir.clone()
+        } else {
+            self.dir.join("frame_000.png")
+        };
+        Configuration {
+            backend,
+            input: Some(input),
+            channel_capacity: None,
+            output_format: OutputFormat::Nv12,
+            start_frame: None,
+            end_frame: None,
+            frame_rate: Some(CLIP_FPS),
+            raw_width: None,
+            raw_height: None,
+            raw_format: RawFormat::Nv12,
+            video_stream_index: None,
+            fps_override: None,
+            queue_full_warning: None,
+            sample_timestamps: None,
+            reverse: false,
+        }
+    }
+}
+
+impl Drop for ScriptedClip {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A dark 640x360 NV12 frame; `with_subtitle` adds a striped bright band near the bottom that
+/// the default luma-band detector picks up as a subtitle. Unit tests share it too.
+pub(crate) fn scripted_frame(with_subtitle: bool) -> Result<VideoFrame, DecoderError> {
+    let width = CLIP_WIDTH as usize;
+    let height = CLIP_HEIGHT as usize;
+    let mut y_plane = vec![16u8; width * height];
+    if with_subtitle {
+        for row in 290..320 {
+            for col in 180..460 {
+                if (col / 3) % 2 == 0 {
+                    y_plane[row * width + col] = DEFAULT_TARGET;
+                }
+            }
+        }
+    }
+    let uv_plane = vec![128u8; width * height.div_ceil(2)];
+    VideoFrame::from_nv12_owned(
+        CLIP_WIDTH,
+        CLIP_HEIGHT,
+        width,
+        width,
+        None,
+        None,
+        y_plane,
+        uv_plane,
+    )
+}
+
+fn clip_error(path: &Path, err: std::io::Error) -> DecoderError {
+    DecoderError::configuration(format!(
+        "failed to write self-test clip {}: {err}",
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn matrix_reports_mock_backend_as_available() {
+        let report = run_self_test().await.expect("self-test runs");
+
+        let mock = report
+            .find(CheckCategory::Backend, "mock")
+            .expect("mock backend row");
+        assert!(mock.passed, "mock backend failed: {}", mock.detail);
+
+        let pipeline = report
+            .find(CheckCategory::Pipeline, "scripted-subtitle")
+            .expect("pipeline row");
+        assert!(pipeline.passed, "pipeline failed: {}", pipeline.detail);
+        assert!(report.to_string().contains("mock"));
+    }
+}
@@ -1,22 +1,41 @@
 use std::env;
 use std::fmt;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 use directories::ProjectDirs;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use subtitle_fast_comparator::ComparatorKind;
+use subtitle_fast_decoder::{DEFAULT_QUEUE_FULL_WARNING, RawFormat};
 use subtitle_fast_types::RoiConfig;
-use subtitle_fast_validator::subtitle_detection::{DEFAULT_DELTA, DEFAULT_TARGET};
+use subtitle_fast_validator::subtitle_detection::{
+    DEFAULT_DELTA, DEFAULT_TARGET, SubtitleDetectorKind,
+};
 
 use crate::cli::{CliArgs, CliSources};
+use crate::contact_sheet::{ContactSheetOptions, DEFAULT_COLUMNS, DEFAULT_THUMB_WIDTH};
+use crate::stage::lifecycle::{
+    BestFramePolicy, DEFAULT_MIN_CONSECUTIVE_SAMPLES, DEFAULT_TEMPLATE_REFRESH_INTERVAL,
+};
+use crate::stage::sampler::{DEFAULT_MAX_HISTORY_FRAMES, MAX_POOL_CAPACITY};
+use crate::stage::sorter::MissingFramePolicy;
+use crate::subtitle::{MultiRegionMode, ShortCuePolicy, SubtitleFormat};
+
+pub const DEFAULT_OCR_MAX_CONSECUTIVE_FAILURES: u32 = 5;
+pub const DEFAULT_OCR_RETRIES: u32 = 2;
+pub const DEFAULT_OCR_RETRY_DELAY: Duration = Duration::from_millis(50);
+pub const DEFAULT_SMOOTHING_WINDOW: usize = 1;
+pub const DEFAULT_SAMPLES_PER_SECOND: u32 = 7;
 
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 struct FileConfig {
     detection: Option<DetectionFileConfig>,
     decoder: Option<DecoderFileConfig>,
+    ocr: Option<OcrFileConfig>,
     output: Option<OutputFileConfig>,
 }
 
@@ -24,20 +43,44 @@ struct FileConfig {
 #[serde(default)]
 struct DecoderFileConfig {
     backend: Option<String>,
+    backend_priority: Option<Vec<String>>,
     channel_capacity: Option<usize>,
+    tolerate_errors: Option<u32>,
+    fps: Option<f64>,
+    raw_width: Option<u32>,
+    raw_height: Option<u32>,
+    raw_format: Option<String>,
+    raw_fps: Option<f64>,
+    queue_full_warning_secs: Option<f64>,
+    missing_frames: Option<String>,
 }
 
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 #[serde(default)]
 struct DetectionFileConfig {
     samples_per_second: Option<u32>,
     target: Option<u8>,
     delta: Option<u8>,
     comparator: Option<String>,
+    comparator_fallback: Option<String>,
     roi: Option<RoiFileConfig>,
+    expected_aspect: Option<f32>,
+    min_area_ratio: Option<f32>,
+    max_bright_fraction: Option<f32>,
+    edge_exclusion_px: Option<u32>,
+    min_component_area: Option<usize>,
+    min_region_aspect: Option<f32>,
+    max_region_area_ratio: Option<f32>,
+    smoothing_window: Option<usize>,
+    max_history_frames: Option<usize>,
+    template_refresh_interval: Option<usize>,
+    min_consecutive_samples: Option<usize>,
+    best_frame: Option<String>,
+    reuse_static_features: Option<bool>,
+    detector: Option<String>,
 }
 
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 #[serde(default)]
 struct RoiFileConfig {
     x: Option<f32>,
@@ -46,16 +89,47 @@ struct RoiFileConfig {
     height: Option<f32>,
 }
 
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+struct OcrFileConfig {
+    max_consecutive_failures: Option<u32>,
+    retries: Option<u32>,
+    retry_delay_ms: Option<u64>,
+    model_cache: Option<PathBuf>,
+    drop_invalid_text: Option<bool>,
+    min_confidence: Option<f32>,
+    require_confidence: Option<bool>,
+}
+
 #[derive(Debug, Default, Deserialize, Clone)]
 #[serde(default)]
 struct OutputFileConfig {
     path: Option<PathBuf>,
+    multi_region: Option<String>,
+    format: Option<String>,
+    max_cps: Option<f64>,
+    extend_fast_cues: Option<bool>,
+    min_cue_gap_ms: Option<u64>,
+    min_duration_ms: Option<u64>,
+    short_cue_policy: Option<String>,
+    merge_gap_ms: Option<u64>,
+    merge_ignore_case: Option<bool>,
+    offset_ms: Option<i64>,
+    rate_scale: Option<f64>,
+    min_cue_chars: Option<usize>,
+    ndjson_stdout: Option<bool>,
+    contact_sheet: Option<PathBuf>,
+    contact_sheet_columns: Option<usize>,
+    contact_sheet_thumb_width: Option<u32>,
+    split_by_position: Option<bool>,
+    timeline_csv: Option<PathBuf>,
 }
 
 #[derive(Debug)]
 pub struct EffectiveSettings {
     pub detection: DetectionSettings,
     pub decoder: DecoderSettings,
+    pub ocr: OcrSettings,
     pub output: OutputSettings,
 }
 
@@ -64,24 +138,165 @@ pub struct ResolvedSettings {
     pub settings: EffectiveSettings,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DetectionSettings {
     pub samples_per_second: u32,
     pub target: u8,
     pub delta: u8,
     pub comparator: Option<ComparatorKind>,
+    /// Comparator used for regions the primary one cannot extract features from.
+    pub comparator_fallback: Option<ComparatorKind>,
     pub roi: Option<RoiConfig>,
+    /// Expected subtitle band width/height used to bias detection scoring.
+    pub expected_aspect: Option<f32>,
+    /// Minimum region area as a fraction of the frame; `None` keeps the detector default.
+    pub min_area_ratio: Option<f32>,
+    /// ROI bright-pixel fraction above which a frame counts as a solid fill; `None` keeps the
+    /// detector default.
+    pub max_bright_fraction: Option<f32>,
+    /// Regions within this many pixels of the frame border are rejected; 0 keeps them.
+    pub edge_exclusion_px: u32,
+    /// Smallest integral-band component in pixels; `None` keeps the detector default.
+    pub min_component_area: Option<usize>,
+    /// Smallest integral-band region width/height; `None` keeps the detector default.
+    pub min_region_aspect: Option<f32>,
+    /// Largest integral-band region as a fraction of the frame; `None` keeps the detector
+    /// default.
+    pub max_region_area_ratio: Option<f32>,
+    /// Odd number of consecutive samples voting on each detection; 1 disables smoothing.
+    pub detection_smoothing_window: usize,
+    /// Most recent frames kept as history for start/end refinement; bounds memory at high fps.
+    pub max_history_frames: usize,
+    /// Matched samples between re-basing an active subtitle's template; 0 never re-bases.
+    pub template_refresh_interval: usize,
+    /// Consecutive samples a new region must be detected in before its subtitle opens.
+    pub min_consecutive_samples: usize,
+    /// Which frame of a segment is OCR'd and cropped.
+    pub best_frame_policy: BestFramePolicy,
+    /// Reuse a region's features while its ROI and pixels match the previous sample.
+    pub reuse_static_features: bool,
+    /// Detector backend the frame validator is built with.
+    pub detector: SubtitleDetectorKind,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct DecoderSettings {
     pub backend: Option<String>,
+    /// Backends tried first, in order, when none is locked with `backend`.
+    pub backend_priority: Vec<String>,
     pub channel_capacity: Option<usize>,
+    /// Recoverable decode errors skipped before the stream aborts.
+    pub error_tolerance: u32,
+    /// Reaction to gaps in the decoded frame indices.
+    pub missing_frames: MissingFramePolicy,
+    /// Frame rate used instead of the probed one.
+    pub fps_override: Option<f64>,
+    /// Frame size of raw YUV input, which has no header to probe.
+    pub raw_width: Option<u32>,
+    pub raw_height: Option<u32>,
+    pub raw_format: Option<RawFormat>,
+    /// Playback rate of raw YUV input.
+    pub raw_fps: Option<f64>,
+    /// Warn when decoded frames wait this long for the pipeline; `None` never warns.
+    pub queue_full_warning: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OcrSettings {
+    /// Consecutive OCR failures tolerated before the pipeline aborts.
+    pub max_consecutive_failures: u32,
+    /// Extra attempts for a cue whose recognition failed transiently.
+    pub retries: u32,
+    /// Pause before each retry.
+    pub retry_delay: Duration,
+    /// Directory where OCR/detector models are looked up and downloaded to.
+    pub model_cache_dir: PathBuf,
+    /// Drop OCR lines that contained invalid UTF-8 instead of keeping the lossy text.
+    pub drop_invalid_text: bool,
+    /// Recognized lines below this confidence (0-1) are dropped, and cues left without lines.
+    pub min_confidence: Option<f32>,
+    /// Treat lines without a reported confidence as below `min_confidence`.
+    pub require_confidence: bool,
+}
+
+/// The values a run gets when neither the CLI nor the config file sets anything.
+impl Default for DetectionSettings {
+    fn default() -> Self {
+        Self {
+            samples_per_second: DEFAULT_SAMPLES_PER_SECOND,
+            target: DEFAULT_TARGET,
+            delta: DEFAULT_DELTA,
+            comparator: None,
+            comparator_fallback: None,
+            roi: None,
+            expected_aspect: None,
+            min_area_ratio: None,
+            max_bright_fraction: None,
+            edge_exclusion_px: 0,
+            min_component_area: None,
+            min_region_aspect: None,
+            max_region_area_ratio: None,
+            detection_smoothing_window: DEFAULT_SMOOTHING_WINDOW,
+            max_history_frames: DEFAULT_MAX_HISTORY_FRAMES,
+            template_refresh_interval: DEFAULT_TEMPLATE_REFRESH_INTERVAL,
+            min_consecutive_samples: DEFAULT_MIN_CONSECUTIVE_SAMPLES,
+            best_frame_policy: BestFramePolicy::default(),
+            reuse_static_features: false,
+            detector: SubtitleDetectorKind::default(),
+        }
+    }
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            max_consecutive_failures: DEFAULT_OCR_MAX_CONSECUTIVE_FAILURES,
+            retries: DEFAULT_OCR_RETRIES,
+            retry_delay: DEFAULT_OCR_RETRY_DELAY,
+            model_cache_dir: default_model_cache_dir(),
+            drop_invalid_text: false,
+            min_confidence: None,
+            require_confidence: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct OutputSettings {
     pub path: Option<PathBuf>,
+    /// Stream subtitles to stdout instead of a file; `--stdout` or an output path of `-`.
+    pub stdout: bool,
+    pub multi_region: MultiRegionMode,
+    /// Format of the written subtitle files.
+    pub format: SubtitleFormat,
+    /// Reading-speed limit in characters per second; faster cues are reported.
+    pub max_cps: Option<f64>,
+    /// Extend cues over `max_cps` up to the next cue's start.
+    pub extend_fast_cues: bool,
+    /// Written cues end at least this long before the next one starts; zero leaves them as is.
+    pub min_cue_gap: Duration,
+    /// Cues shown for less than this are handled by `short_cue_policy`; `None` keeps them.
+    pub min_duration: Option<Duration>,
+    /// Whether cues under `min_duration` are dropped or extended.
+    pub short_cue_policy: ShortCuePolicy,
+    /// Consecutive cues with the same text and a shorter gap than this are merged into one.
+    pub merge_gap: Duration,
+    /// Compare text case-insensitively when merging cues.
+    pub merge_ignore_case: bool,
+    /// Added to every written cue time after scaling; negative times clamp to zero.
+    pub offset_ms: i64,
+    /// Multiplies every written cue time; `None` keeps the detected timing.
+    pub rate_scale: Option<f64>,
+    /// Cues with fewer visible characters are dropped; `None` keeps the default filter.
+    pub min_cue_chars: Option<usize>,
+    /// Also stream each finalized cue to stdout as one JSON object per line.
+    pub ndjson_stdout: bool,
+    /// Grid of labeled ROI thumbnails, one per cue, written next to the subtitles.
+    pub contact_sheet: Option<ContactSheetOptions>,
+    /// Write cues from each screen band to their own file.
+    pub split_by_position: bool,
+    /// Per-sample detection and segmentation CSV, written while the pipeline runs.
+    pub timeline_csv: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -94,6 +309,10 @@ pub enum ConfigError {
         path: PathBuf,
         source: toml::de::Error,
     },
+    ParseJson {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
     InvalidValue {
         path: Option<PathBuf>,
         field: &'static str,
@@ -123,6 +342,14 @@ impl fmt::Display for ConfigError {
                     source
                 )
             }
+            ConfigError::ParseJson { path, source } => {
+                write!(
+                    f,
+                    "failed to parse config file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
             ConfigError::InvalidValue { path, field, value } => {
                 if let Some(path) = path {
                     write!(
@@ -148,6 +375,7 @@ impl std::error::Error for ConfigError {
         match self {
             ConfigError::Io { source, .. } => Some(source),
             ConfigError::Parse { source, .. } => Some(source),
+            ConfigError::ParseJson { source, .. } => Some(source),
             ConfigError::InvalidValue { .. } => None,
             ConfigError::NotFound { .. } => None,
         }
@@ -168,28 +396,14 @@ fn load_config(path_override: Option<&Path>) -> Result<(FileConfig, Option<PathB
         if !path.exists() {
             return Err(ConfigError::NotFound { path });
         }
-        let contents = fs::read_to_string(&path).map_err(|source| ConfigError::Io {
-            path: path.clone(),
-            source,
-        })?;
-        let config = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
-            path: path.clone(),
-            source,
-        })?;
+        let config = read_config(&path)?;
         return Ok((config, Some(path)));
     }
 
     if let Some(project_path) = project_config_path()
         && project_path.exists()
     {
-        let contents = fs::read_to_string(&project_path).map_err(|source| ConfigError::Io {
-            path: project_path.clone(),
-            source,
-        })?;
-        let config = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
-            path: project_path.clone(),
-            source,
-        })?;
+        let config = read_config(&project_path)?;
         return Ok((config, Some(project_path)));
     }
 
@@ -199,15 +413,71 @@ fn load_config(path_override: Option<&Path>) -> Result<(FileConfig, Option<PathB
     if !default_path.exists() {
         return Ok((FileConfig::default(), None));
     }
-    let contents = fs::read_to_string(&default_path).map_err(|source| ConfigError::Io {
-        path: default_path.clone(),
+    let config = read_config(&default_path)?;
+    Ok((config, Some(default_path)))
+}
+
+/// Parses TOML, or JSON for a `.json` path such as a `--dump-tuning` file.
+fn read_config(path: &Path) -> Result<FileConfig, ConfigError> {
+    let contents = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
         source,
     })?;
-    let config = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
-        path: default_path.clone(),
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+    {
+        return serde_json::from_str(&contents).map_err(|source| ConfigError::ParseJson {
+            path: path.to_path_buf(),
+            source,
+        });
+    }
+    toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+        path: path.to_path_buf(),
         source,
-    })?;
-    Ok((config, Some(default_path)))
+    })
+}
+
+/// Resolved detection settings in the shape of a config file with only `[detection]`.
+#[derive(Serialize)]
+struct TuningFile {
+    detection: DetectionFileConfig,
+}
+
+/// Writes the resolved detection settings as JSON that `--config` loads back unchanged, so a
+/// working tuning can be pinned after CLI and file overrides are applied.
+pub fn write_tuning(path: &Path, settings: &DetectionSettings) -> io::Result<()> {
+    let detection = DetectionFileConfig {
+        samples_per_second: Some(settings.samples_per_second),
+        target: Some(settings.target),
+        delta: Some(settings.delta),
+        comparator: settings.comparator.map(|kind| kind.as_str().to_string()),
+        comparator_fallback: settings
+            .comparator_fallback
+            .map(|kind| kind.as_str().to_string()),
+        roi: settings.roi.map(|roi| RoiFileConfig {
+            x: Some(roi.x),
+            y: Some(roi.y),
+            width: Some(roi.width),
+            height: Some(roi.height),
+        }),
+        expected_aspect: settings.expected_aspect,
+        min_area_ratio: settings.min_area_ratio,
+        max_bright_fraction: settings.max_bright_fraction,
+        edge_exclusion_px: Some(settings.edge_exclusion_px),
+        min_component_area: settings.min_component_area,
+        min_region_aspect: settings.min_region_aspect,
+        max_region_area_ratio: settings.max_region_area_ratio,
+        smoothing_window: Some(settings.detection_smoothing_window),
+        max_history_frames: Some(settings.max_history_frames),
+        template_refresh_interval: Some(settings.template_refresh_interval),
+        min_consecutive_samples: Some(settings.min_consecutive_samples),
+        best_frame: Some(settings.best_frame_policy.as_str().to_string()),
+        reuse_static_features: Some(settings.reuse_static_features),
+        detector: Some(settings.detector.as_str().to_string()),
+    };
+    let json = serde_json::to_string_pretty(&TuningFile { detection }).map_err(io::Error::other)?;
+    fs::write(path, json + "\n")
 }
 
 fn merge(
@@ -219,11 +489,13 @@ fn merge(
     let FileConfig {
         detection: file_detection,
         decoder: file_decoder,
+        ocr: file_ocr,
         output: file_output,
     } = file;
 
     let detection_cfg = file_detection.unwrap_or_default();
     let decoder_cfg = file_decoder.unwrap_or_default();
+    let ocr_cfg = file_ocr.unwrap_or_default();
     let output_cfg = file_output.unwrap_or_default();
 
     let detection_samples_per_second = resolve_detection_sps(
@@ -251,6 +523,15 @@ fn merge(
         detection_cfg.comparator.clone(),
         !sources.comparator_from_cli,
         config_path.as_ref(),
+        "comparator",
+    )?;
+
+    let comparator_fallback = resolve_comparator_kind(
+        cli.comparator_fallback.clone(),
+        detection_cfg.comparator_fallback.clone(),
+        !sources.comparator_fallback_from_cli,
+        config_path.as_ref(),
+        "comparator_fallback",
     )?;
 
     let detection_roi = resolve_detection_roi(
@@ -260,6 +541,73 @@ fn merge(
         config_path.as_ref(),
     )?;
 
+    let expected_aspect = resolve_expected_aspect(
+        cli.expected_aspect,
+        detection_cfg.expected_aspect,
+        !sources.expected_aspect_from_cli,
+        config_path.as_ref(),
+    )?;
+
+    let min_area_ratio = resolve_min_area_ratio(
+        cli.min_area_ratio,
+        detection_cfg.min_area_ratio,
+        !sources.min_area_ratio_from_cli,
+        config_path.as_ref(),
+    )?;
+
+    let max_bright_fraction = resolve_max_bright_fraction(
+        cli.max_bright_fraction,
+        detection_cfg.max_bright_fraction,
+        !sources.max_bright_fraction_from_cli,
+        config_path.as_ref(),
+    )?;
+
+    let min_region_aspect = resolve_min_region_aspect(
+        cli.min_region_aspect,
+        detection_cfg.min_region_aspect,
+        config_path.as_ref(),
+    )?;
+    let max_region_area_ratio = resolve_max_region_area_ratio(
+        cli.max_region_area_ratio,
+        detection_cfg.max_region_area_ratio,
+        config_path.as_ref(),
+    )?;
+
+    let detection_smoothing_window = resolve_smoothing_window(
+        cli.detection_smoothing_window,
+        detection_cfg.smoothing_window,
+        !sources.detection_smoothing_window_from_cli,
+        config_path.as_ref(),
+    )?;
+
+    let max_history_frames = resolve_max_history_frames(
+        cli.max_history_frames,
+        detection_cfg.max_history_frames,
+        config_path.as_ref(),
+    )?;
+
+    let template_refresh_interval = cli
+        .template_refresh_interval
+        .or(detection_cfg.template_refresh_interval)
+        .unwrap_or(DEFAULT_TEMPLATE_REFRESH_INTERVAL);
+
+    let min_consecutive_samples = resolve_min_consecutive_samples(
+        cli.min_consecutive_samples,
+        detection_cfg.min_consecutive_samples,
+        config_path.as_ref(),
+    )?;
+
+    let best_frame_policy = resolve_best_frame_policy(
+        cli.best_frame.clone(),
+        detection_cfg.best_frame.clone(),
+        config_path.as_ref(),
+    )?;
+
+    let detector = match cli.detector {
+        Some(kind) => kind,
+        None => resolve_detector_kind(detection_cfg.detector.clone(), config_path.as_ref())?,
+    };
+
     let decoder_channel_capacity = resolve_decoder_capacity(
         cli.decoder_channel_capacity,
         decoder_cfg.channel_capacity,
@@ -270,13 +618,136 @@ fn merge(
     let decoder_backend = normalize_string(cli.backend.clone())
         .or_else(|| normalize_string(decoder_cfg.backend.clone()));
 
+    let decoder_error_tolerance = if sources.decoder_error_tolerance_from_cli {
+        cli.tolerate_decode_errors.unwrap_or(0)
+    } else {
+        decoder_cfg.tolerate_errors.unwrap_or(0)
+    };
+
+    let decoder_fps = resolve_frame_rate(cli.fps, decoder_cfg.fps, "fps", config_path.as_ref())?;
+    let raw_fps = resolve_frame_rate(
+        cli.raw_fps,
+        decoder_cfg.raw_fps,
+        "raw_fps",
+        config_path.as_ref(),
+    )?;
+
+    let backend_priority = if cli.backend_priority.is_empty() {
+        decoder_cfg.backend_priority.clone().unwrap_or_default()
+    } else {
+        cli.backend_priority.clone()
+    };
+
     let decoder_settings = DecoderSettings {
         backend: decoder_backend,
+        backend_priority,
         channel_capacity: decoder_channel_capacity,
+        error_tolerance: decoder_error_tolerance,
+        missing_frames: resolve_missing_frames(
+            cli.missing_frames.clone(),
+            decoder_cfg.missing_frames.clone(),
+            config_path.as_ref(),
+        )?,
+        fps_override: decoder_fps,
+        raw_width: cli.raw_width.or(decoder_cfg.raw_width),
+        raw_height: cli.raw_height.or(decoder_cfg.raw_height),
+        raw_format: resolve_raw_format(
+            cli.raw_format.clone(),
+            decoder_cfg.raw_format.clone(),
+            config_path.as_ref(),
+        )?,
+        raw_fps,
+        queue_full_warning: resolve_queue_full_warning(
+            cli.queue_full_warning_secs,
+            decoder_cfg.queue_full_warning_secs,
+            config_path.as_ref(),
+        )?,
+    };
+
+    let ocr_settings = OcrSettings {
+        max_consecutive_failures: resolve_ocr_max_failures(
+            cli.ocr_max_consecutive_failures,
+            ocr_cfg.max_consecutive_failures,
+            !sources.ocr_max_consecutive_failures_from_cli,
+        ),
+        retries: cli
+            .ocr_retries
+            .or(ocr_cfg.retries)
+            .unwrap_or(DEFAULT_OCR_RETRIES),
+        retry_delay: cli
+            .ocr_retry_delay_ms
+            .or(ocr_cfg.retry_delay_ms)
+            .map_or(DEFAULT_OCR_RETRY_DELAY, Duration::from_millis),
+        model_cache_dir: resolve_model_cache_dir(cli.model_cache.clone(), ocr_cfg.model_cache),
+        drop_invalid_text: cli.drop_invalid_text || ocr_cfg.drop_invalid_text.unwrap_or(false),
+        min_confidence: resolve_min_confidence(
+            cli.ocr_min_confidence,
+            ocr_cfg.min_confidence,
+            config_path.as_ref(),
+        )?,
+        require_confidence: cli.ocr_require_confidence
+            || ocr_cfg.require_confidence.unwrap_or(false),
     };
 
+    let multi_region = resolve_multi_region(
+        cli.multi_region.clone(),
+        output_cfg.multi_region.clone(),
+        !sources.multi_region_from_cli,
+        config_path.as_ref(),
+    )?;
+
+    let max_cps = resolve_max_cps(
+        cli.max_cps,
+        output_cfg.max_cps,
+        !sources.max_cps_from_cli,
+        config_path.as_ref(),
+    )?;
+
+    let rate_scale =
+        resolve_rate_scale(cli.rate_scale, output_cfg.rate_scale, config_path.as_ref())?;
+
+    let short_cue_policy = resolve_short_cue_policy(
+        cli.short_cue_policy.clone(),
+        output_cfg.short_cue_policy.clone(),
+        config_path.as_ref(),
+    )?;
+
+    let contact_sheet = resolve_contact_sheet(cli, &output_cfg, config_path.as_ref())?;
+
+    let output_path = cli.output.clone().or(output_cfg.path);
+    let stdout = cli.stdout || output_path.as_deref() == Some(Path::new("-"));
+    let output_path = output_path.filter(|path| path != Path::new("-"));
+    let format = resolve_subtitle_format(
+        cli.format.clone(),
+        output_cfg.format,
+        output_path.as_deref(),
+        config_path.as_ref(),
+    )?;
+
     let output_settings = OutputSettings {
-        path: cli.output.clone().or(output_cfg.path),
+        path: output_path,
+        stdout,
+        multi_region,
+        format,
+        max_cps,
+        extend_fast_cues: cli.extend_fast_cues || output_cfg.extend_fast_cues.unwrap_or(false),
+        min_cue_gap: Duration::from_millis(
+            cli.min_cue_gap.or(output_cfg.min_cue_gap_ms).unwrap_or(0),
+        ),
+        min_duration: cli
+            .min_duration
+            .or(output_cfg.min_duration_ms)
+            .map(Duration::from_millis),
+        short_cue_policy,
+        merge_gap: Duration::from_millis(cli.merge_gap.or(output_cfg.merge_gap_ms).unwrap_or(0)),
+        merge_ignore_case: cli.merge_ignore_case || output_cfg.merge_ignore_case.unwrap_or(false),
+        offset_ms: cli.offset_ms.or(output_cfg.offset_ms).unwrap_or(0),
+        rate_scale,
+        min_cue_chars: cli.min_cue_chars.or(output_cfg.min_cue_chars),
+        ndjson_stdout: cli.ndjson_stdout || output_cfg.ndjson_stdout.unwrap_or(false),
+        contact_sheet,
+        split_by_position: cli.split_by_position || output_cfg.split_by_position.unwrap_or(false),
+        timeline_csv: cli.timeline_csv.clone().or(output_cfg.timeline_csv),
     };
 
     let settings = EffectiveSettings {
@@ -285,9 +756,29 @@ fn merge(
             target: detector_target,
             delta: detector_delta,
             comparator: comparator_kind,
+            comparator_fallback,
             roi: Some(detection_roi),
+            expected_aspect,
+            min_area_ratio,
+            max_bright_fraction,
+            edge_exclusion_px: cli
+                .edge_exclusion_px
+                .or(detection_cfg.edge_exclusion_px)
+                .unwrap_or(0),
+            min_component_area: cli.min_component_area.or(detection_cfg.min_component_area),
+            min_region_aspect,
+            max_region_area_ratio,
+            detection_smoothing_window,
+            max_history_frames,
+            template_refresh_interval,
+            min_consecutive_samples,
+            best_frame_policy,
+            reuse_static_features: cli.reuse_static_features
+                || detection_cfg.reuse_static_features.unwrap_or(false),
+            detector,
         },
         decoder: decoder_settings,
+        ocr: ocr_settings,
         output: output_settings,
     };
 
@@ -299,6 +790,19 @@ fn default_config_path() -> Option<PathBuf> {
         .map(|dirs| dirs.config_dir().join("config.toml"))
 }
 
+fn default_model_cache_dir() -> PathBuf {
+    ProjectDirs::from("rs", "subtitle-fast", "subtitle-fast")
+        .map(|dirs| dirs.cache_dir().join("models"))
+        .unwrap_or_else(|| PathBuf::from("models"))
+}
+
+fn resolve_model_cache_dir(cli_value: Option<PathBuf>, file_value: Option<PathBuf>) -> PathBuf {
+    cli_value
+        .or(file_value)
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or_else(default_model_cache_dir)
+}
+
 fn project_config_path() -> Option<PathBuf> {
     env::current_dir().ok().map(|dir| dir.join("config.toml"))
 }
@@ -429,6 +933,7 @@ fn resolve_comparator_kind(
     file_value: Option<String>,
     use_file: bool,
     config_path: Option<&PathBuf>,
+    field: &'static str,
 ) -> Result<Option<ComparatorKind>, ConfigError> {
     let raw = match normalize_string(cli_value) {
         Some(value) => Some(value),
@@ -449,12 +954,473 @@ fn resolve_comparator_kind(
         Ok(kind) => Ok(Some(kind)),
         Err(_) => Err(ConfigError::InvalidValue {
             path: config_path.cloned(),
-            field: "comparator",
+            field,
             value,
         }),
     }
 }
 
+fn resolve_ocr_max_failures(
+    cli_value: Option<u32>,
+    file_value: Option<u32>,
+    use_file: bool,
+) -> u32 {
+    if let Some(value) = cli_value {
+        return value;
+    }
+    if use_file && let Some(value) = file_value {
+        return value;
+    }
+    DEFAULT_OCR_MAX_CONSECUTIVE_FAILURES
+}
+
+fn resolve_multi_region(
+    cli_value: Option<String>,
+    file_value: Option<String>,
+    use_file: bool,
+    config_path: Option<&PathBuf>,
+) -> Result<MultiRegionMode, ConfigError> {
+    let raw = match normalize_string(cli_value) {
+        Some(value) => Some(value),
+        None if use_file => normalize_string(file_value),
+        None => None,
+    };
+
+    let Some(value) = raw else {
+        return Ok(MultiRegionMode::default());
+    };
+
+    MultiRegionMode::from_str(&value).map_err(|_| ConfigError::InvalidValue {
+        path: config_path.cloned(),
+        field: "multi_region",
+        value,
+    })
+}
+
+/// An explicit format wins; otherwise a `.vtt`, `.ass` or `.json` output path selects that
+/// format.
+fn resolve_subtitle_format(
+    cli_value: Option<String>,
+    file_value: Option<String>,
+    output_path: Option<&Path>,
+    config_path: Option<&PathBuf>,
+) -> Result<SubtitleFormat, ConfigError> {
+    let (value, path) = match normalize_string(cli_value) {
+        Some(value) => (value, None),
+        None => match normalize_string(file_value) {
+            Some(value) => (value, config_path.cloned()),
+            None => {
+                let from_extension = output_path
+                    .and_then(|path| path.extension())
+                    .and_then(|ext| ext.to_str())
+                    .and_then(|ext| SubtitleFormat::from_str(ext).ok());
+                return Ok(from_extension.unwrap_or_default());
+            }
+        },
+    };
+    SubtitleFormat::from_str(&value).map_err(|_| ConfigError::InvalidValue {
+        path,
+        field: "format",
+        value,
+    })
+}
+
+fn resolve_rate_scale(
+    cli_value: Option<f64>,
+    file_value: Option<f64>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f64>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(value) if !value.is_finite() || value <= 0.0 => Err(ConfigError::InvalidValue {
+            path,
+            field: "rate_scale",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_max_cps(
+    cli_value: Option<f64>,
+    file_value: Option<f64>,
+    use_file: bool,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f64>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None if use_file => (file_value, config_path.cloned()),
+        None => (None, None),
+    };
+    match value {
+        Some(value) if !value.is_finite() || value <= 0.0 => Err(ConfigError::InvalidValue {
+            path,
+            field: "max_cps",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_contact_sheet(
+    cli: &CliArgs,
+    file: &OutputFileConfig,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<ContactSheetOptions>, ConfigError> {
+    let Some(path) = cli
+        .contact_sheet
+        .clone()
+        .or_else(|| file.contact_sheet.clone())
+    else {
+        return Ok(None);
+    };
+    let (columns, columns_path) = match cli.contact_sheet_columns {
+        Some(value) => (Some(value), None),
+        None => (file.contact_sheet_columns, config_path.cloned()),
+    };
+    if columns == Some(0) {
+        return Err(ConfigError::InvalidValue {
+            path: columns_path,
+            field: "contact_sheet_columns",
+            value: "0".to_string(),
+        });
+    }
+    let (thumb_width, width_path) = match cli.contact_sheet_thumb_width {
+        Some(value) => (Some(value), None),
+        None => (file.contact_sheet_thumb_width, config_path.cloned()),
+    };
+    if thumb_width == Some(0) {
+        return Err(ConfigError::InvalidValue {
+            path: width_path,
+            field: "contact_sheet_thumb_width",
+            value: "0".to_string(),
+        });
+    }
+    Ok(Some(ContactSheetOptions {
+        path,
+        columns: columns.unwrap_or(DEFAULT_COLUMNS),
+        thumb_width: thumb_width.unwrap_or(DEFAULT_THUMB_WIDTH),
+    }))
+}
+
+fn resolve_expected_aspect(
+    cli_value: Option<f32>,
+    file_value: Option<f32>,
+    use_file: bool,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f32>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None if use_file => (file_value, config_path.cloned()),
+        None => (None, None),
+    };
+    match value {
+        Some(value) if !value.is_finite() || value <= 0.0 => Err(ConfigError::InvalidValue {
+            path,
+            field: "expected_aspect",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_min_area_ratio(
+    cli_value: Option<f32>,
+    file_value: Option<f32>,
+    use_file: bool,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f32>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None if use_file => (file_value, config_path.cloned()),
+        None => (None, None),
+    };
+    match value {
+        Some(value) if !(0.0..1.0).contains(&value) => Err(ConfigError::InvalidValue {
+            path,
+            field: "min_area_ratio",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_max_bright_fraction(
+    cli_value: Option<f32>,
+    file_value: Option<f32>,
+    use_file: bool,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f32>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None if use_file => (file_value, config_path.cloned()),
+        None => (None, None),
+    };
+    match value {
+        Some(value) if !(value > 0.0 && value <= 1.0) => Err(ConfigError::InvalidValue {
+            path,
+            field: "max_bright_fraction",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_min_region_aspect(
+    cli_value: Option<f32>,
+    file_value: Option<f32>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f32>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(value) if !value.is_finite() || value <= 0.0 => Err(ConfigError::InvalidValue {
+            path,
+            field: "min_region_aspect",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_max_region_area_ratio(
+    cli_value: Option<f32>,
+    file_value: Option<f32>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f32>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(value) if !(value > 0.0 && value <= 1.0) => Err(ConfigError::InvalidValue {
+            path,
+            field: "max_region_area_ratio",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_min_confidence(
+    cli_value: Option<f32>,
+    file_value: Option<f32>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f32>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(value) if !(0.0..=1.0).contains(&value) => Err(ConfigError::InvalidValue {
+            path,
+            field: "min_confidence",
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_smoothing_window(
+    cli_value: Option<usize>,
+    file_value: Option<usize>,
+    use_file: bool,
+    config_path: Option<&PathBuf>,
+) -> Result<usize, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None if use_file => (file_value, config_path.cloned()),
+        None => (None, None),
+    };
+    match value {
+        // An even window could tie, so only odd sizes are accepted.
+        Some(value) if value % 2 == 0 => Err(ConfigError::InvalidValue {
+            path,
+            field: "smoothing_window",
+            value: value.to_string(),
+        }),
+        other => Ok(other.unwrap_or(DEFAULT_SMOOTHING_WINDOW)),
+    }
+}
+
+fn resolve_max_history_frames(
+    cli_value: Option<usize>,
+    file_value: Option<usize>,
+    config_path: Option<&PathBuf>,
+) -> Result<usize, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(value) if !(1..=MAX_POOL_CAPACITY).contains(&value) => {
+            Err(ConfigError::InvalidValue {
+                path,
+                field: "max_history_frames",
+                value: value.to_string(),
+            })
+        }
+        other => Ok(other.unwrap_or(DEFAULT_MAX_HISTORY_FRAMES)),
+    }
+}
+
+fn resolve_min_consecutive_samples(
+    cli_value: Option<usize>,
+    file_value: Option<usize>,
+    config_path: Option<&PathBuf>,
+) -> Result<usize, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(0) => Err(ConfigError::InvalidValue {
+            path,
+            field: "min_consecutive_samples",
+            value: "0".to_string(),
+        }),
+        other => Ok(other.unwrap_or(DEFAULT_MIN_CONSECUTIVE_SAMPLES)),
+    }
+}
+
+fn resolve_best_frame_policy(
+    cli_value: Option<String>,
+    file_value: Option<String>,
+    config_path: Option<&PathBuf>,
+) -> Result<BestFramePolicy, ConfigError> {
+    let (value, path) = match normalize_string(cli_value) {
+        Some(value) => (value, None),
+        None => match normalize_string(file_value) {
+            Some(value) => (value, config_path.cloned()),
+            None => return Ok(BestFramePolicy::default()),
+        },
+    };
+    BestFramePolicy::from_str(&value).map_err(|_| ConfigError::InvalidValue {
+        path,
+        field: "best_frame",
+        value,
+    })
+}
+
+fn resolve_short_cue_policy(
+    cli_value: Option<String>,
+    file_value: Option<String>,
+    config_path: Option<&PathBuf>,
+) -> Result<ShortCuePolicy, ConfigError> {
+    let (value, path) = match normalize_string(cli_value) {
+        Some(value) => (value, None),
+        None => match normalize_string(file_value) {
+            Some(value) => (value, config_path.cloned()),
+            None => return Ok(ShortCuePolicy::default()),
+        },
+    };
+    ShortCuePolicy::from_str(&value).map_err(|_| ConfigError::InvalidValue {
+        path,
+        field: "short_cue_policy",
+        value,
+    })
+}
+
+fn resolve_detector_kind(
+    file_value: Option<String>,
+    config_path: Option<&PathBuf>,
+) -> Result<SubtitleDetectorKind, ConfigError> {
+    let Some(value) = normalize_string(file_value) else {
+        return Ok(SubtitleDetectorKind::default());
+    };
+    SubtitleDetectorKind::from_str(&value).map_err(|_| ConfigError::InvalidValue {
+        path: config_path.cloned(),
+        field: "detector",
+        value,
+    })
+}
+
+fn resolve_missing_frames(
+    cli_value: Option<String>,
+    file_value: Option<String>,
+    config_path: Option<&PathBuf>,
+) -> Result<MissingFramePolicy, ConfigError> {
+    let (value, path) = match normalize_string(cli_value) {
+        Some(value) => (value, None),
+        None => match normalize_string(file_value) {
+            Some(value) => (value, config_path.cloned()),
+            None => return Ok(MissingFramePolicy::default()),
+        },
+    };
+    MissingFramePolicy::from_str(&value).map_err(|_| ConfigError::InvalidValue {
+        path,
+        field: "missing_frames",
+        value,
+    })
+}
+
+fn resolve_raw_format(
+    cli_value: Option<String>,
+    file_value: Option<String>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<RawFormat>, ConfigError> {
+    let (value, path) = match normalize_string(cli_value) {
+        Some(value) => (value, None),
+        None => match normalize_string(file_value) {
+            Some(value) => (value, config_path.cloned()),
+            None => return Ok(None),
+        },
+    };
+    RawFormat::from_str(&value)
+        .map(Some)
+        .map_err(|_| ConfigError::InvalidValue {
+            path,
+            field: "raw_format",
+            value,
+        })
+}
+
+fn resolve_frame_rate(
+    cli_value: Option<f64>,
+    file_value: Option<f64>,
+    field: &'static str,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<f64>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        Some(value) if !value.is_finite() || value <= 0.0 => Err(ConfigError::InvalidValue {
+            path,
+            field,
+            value: value.to_string(),
+        }),
+        other => Ok(other),
+    }
+}
+
+fn resolve_queue_full_warning(
+    cli_value: Option<f64>,
+    file_value: Option<f64>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<Duration>, ConfigError> {
+    let (value, path) = match cli_value {
+        Some(value) => (Some(value), None),
+        None => (file_value, config_path.cloned()),
+    };
+    match value {
+        None => Ok(Some(DEFAULT_QUEUE_FULL_WARNING)),
+        Some(value) if !value.is_finite() || value < 0.0 => Err(ConfigError::InvalidValue {
+            path,
+            field: "queue_full_warning_secs",
+            value: value.to_string(),
+        }),
+        Some(0.0) => Ok(None),
+        Some(value) => Ok(Some(Duration::from_secs_f64(value))),
+    }
+}
+
 fn resolve_decoder_capacity(
     cli_value: Option<usize>,
     file_value: Option<usize>,
@@ -484,8 +1450,56 @@ fn resolve_decoder_capacity(
 
 #[cfg(test)]
 mod tests {
+    use clap::Parser;
+
     use super::*;
 
+    #[test]
+    fn model_cache_prefers_override() {
+        let dir = resolve_model_cache_dir(
+            Some(PathBuf::from("/tmp/cli-models")),
+            Some(PathBuf::from("/tmp/file-models")),
+        );
+        assert_eq!(dir, PathBuf::from("/tmp/cli-models"));
+        let dir = resolve_model_cache_dir(None, Some(PathBuf::from("/tmp/file-models")));
+        assert_eq!(dir, PathBuf::from("/tmp/file-models"));
+    }
+
+    #[test]
+    fn model_cache_falls_back_to_platform_default() {
+        assert_eq!(
+            resolve_model_cache_dir(None, None),
+            default_model_cache_dir()
+        );
+        assert_eq!(
+            resolve_model_cache_dir(Some(PathBuf::new()), None),
+            default_model_cache_dir()
+        );
+        assert!(default_model_cache_dir().ends_with("models"));
+    }
+
+    #[test]
+    fn subtitle_format_follows_flag_then_output_extension() {
+        let vtt_path = Path::new("out/movie.vtt");
+        assert_eq!(
+            resolve_subtitle_format(None, None, Some(vtt_path), None).unwrap(),
+            SubtitleFormat::WebVtt
+        );
+        assert_eq!(
+            resolve_subtitle_format(Some("srt".into()), None, Some(vtt_path), None).unwrap(),
+            SubtitleFormat::Srt
+        );
+        assert_eq!(
+            resolve_subtitle_format(None, None, Some(Path::new("movie.txt")), None).unwrap(),
+            SubtitleFormat::Srt
+        );
+        assert_eq!(
+            resolve_subtitle_format(None, None, Some(Path::new("movie.ass")), None).unwrap(),
+            SubtitleFormat::Ass
+        );
+        assert!(resolve_subtitle_format(None, Some("sub".into()), None, None).is_err());
+    }
+
     #[test]
     fn roi_defaults_to_full_when_missing() {
         let roi = resolve_detection_roi(None, None, true, None).unwrap();
@@ -565,4 +1579,113 @@ mod tests {
         let roi = resolve_detection_roi(None, Some(file_roi), true, None).unwrap();
         assert_eq!(roi, full_frame_roi());
     }
+
+    #[test]
+    fn video_track_is_an_alias_for_video_stream() {
+        let cli = CliArgs::try_parse_from(["subtitle-fast", "--video-track", "1"]).unwrap();
+        assert_eq!(cli.video_stream, Some(1));
+    }
+
+    #[test]
+    fn dumped_tuning_reloads_to_the_same_settings() {
+        let cli = CliArgs::try_parse_from([
+            "subtitle-fast",
+            "--detection-sps",
+            "4",
+            "--detector-target",
+            "210",
+            "--comparator",
+            "sparse-chamfer",
+            "--roi",
+            "0.1,0.6,0.8,0.3",
+            "--min-area-ratio",
+            "0.0005",
+            "--edge-exclusion-px",
+            "12",
+            "--best-frame",
+            "middle",
+            "--reuse-static-features",
+            "--detector",
+            "luma",
+        ])
+        .unwrap();
+        let resolved = merge(&cli, &CliSources::default(), FileConfig::default(), None)
+            .unwrap()
+            .settings
+            .detection;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tuning.json");
+        write_tuning(&path, &resolved).unwrap();
+        let (file, config_path) = load_config(Some(&path)).unwrap();
+        let bare = CliArgs::try_parse_from(["subtitle-fast"]).unwrap();
+        let reloaded = merge(&bare, &CliSources::default(), file, config_path)
+            .unwrap()
+            .settings
+            .detection;
+
+        assert_eq!(reloaded, resolved);
+    }
+
+    #[test]
+    fn detector_flag_reaches_the_frame_validator_config() {
+        let bare = CliArgs::try_parse_from(["subtitle-fast"]).unwrap();
+        let default = merge(&bare, &CliSources::default(), FileConfig::default(), None)
+            .unwrap()
+            .settings
+            .detection;
+        assert_eq!(
+            crate::stage::detector::detection_options(&default).detector,
+            SubtitleDetectorKind::ProjectionBand
+        );
+
+        let cli = CliArgs::try_parse_from(["subtitle-fast", "--detector", "luma"]).unwrap();
+        let detection = merge(&cli, &CliSources::default(), FileConfig::default(), None)
+            .unwrap()
+            .settings
+            .detection;
+        assert_eq!(detection.detector, SubtitleDetectorKind::IntegralBand);
+        assert_eq!(
+            crate::stage::detector::detection_options(&detection).detector,
+            SubtitleDetectorKind::IntegralBand
+        );
+
+        let err = CliArgs::try_parse_from(["subtitle-fast", "--detector", "ocr"]).unwrap_err();
+        assert!(err.to_string().contains("expected auto, luma"));
+    }
+
+    #[test]
+    fn dash_output_path_streams_to_stdout() {
+        let cli = CliArgs::try_parse_from(["subtitle-fast", "--output", "-"]).unwrap();
+        let output = merge(&cli, &CliSources::default(), FileConfig::default(), None)
+            .unwrap()
+            .settings
+            .output;
+
+        assert!(output.stdout);
+        assert_eq!(output.path, None);
+    }
+
+    #[test]
+    fn raw_frame_settings_come_from_flags_or_the_decoder_section() {
+        let file: FileConfig = toml::from_str(
+            "[decoder]\nraw_width = 640\nraw_height = 360\nraw_format = \"i420\"\nraw_fps = 25.0\n",
+        )
+        .unwrap();
+        let cli =
+            CliArgs::try_parse_from(["subtitle-fast", "--raw-width", "320", "--raw-fps", "30"])
+                .unwrap();
+        let decoder = merge(&cli, &CliSources::default(), file, None)
+            .unwrap()
+            .settings
+            .decoder;
+        assert_eq!(decoder.raw_width, Some(320));
+        assert_eq!(decoder.raw_height, Some(360));
+        assert_eq!(decoder.raw_format, Some(RawFormat::I420));
+        assert_eq!(decoder.raw_fps, Some(30.0));
+
+        let bare = CliArgs::try_parse_from(["subtitle-fast"]).unwrap();
+        let file: FileConfig = toml::from_str("[decoder]\nraw_format = \"yuyv\"\n").unwrap();
+        assert!(merge(&bare, &CliSources::default(), file, None).is_err());
+    }
 }
@@ -26,21 +26,33 @@ pub enum DetectorError {
 }
 
 pub struct Detector {
-    validator: FrameValidator,
+    config: FrameValidatorConfig,
+    validators: Vec<FrameValidator>,
 }
 
 impl Detector {
     pub fn new(settings: &DetectionSettings) -> Result<Self, SubtitleDetectionError> {
-        let mut detection_options = SubtitleDetectionOptions::default();
-        detection_options.luma_band.target = settings.target;
-        detection_options.luma_band.delta = settings.delta;
-        detection_options.roi = settings.roi;
-
         let config = FrameValidatorConfig {
-            detection: detection_options,
+            detection: detection_options(settings),
         };
-        let validator = FrameValidator::new(config)?;
-        Ok(Self { validator })
+        let validator = FrameValidator::new(config.clone())?;
+        Ok(Self {
+            config,
+            validators: vec![validator],
+        })
+    }
+
+    /// Detects up to `samples` sampled frames at once, each on its own validator so detector
+    /// state is never shared between frames in flight. Results still leave the stage in
+    /// sample order.
+    pub fn with_concurrency(mut self, samples: usize) -> Result<Self, SubtitleDetectionError> {
+        let samples = samples.max(1);
+        self.validators.truncate(samples);
+        while self.validators.len() < samples {
+            self.validators
+                .push(FrameValidator::new(self.config.clone())?);
+        }
+        Ok(self)
     }
 
     pub fn attach(self, input: StreamBundle<SamplerResult>) -> StreamBundle<DetectionSampleResult> {
@@ -50,32 +62,47 @@ impl Detector {
         } = input;
 
         let (tx, rx) = mpsc::channel::<DetectionSampleResult>(DETECTOR_CHANNEL_CAPACITY);
-        let validator = self.validator;
+        let workers: Vec<DetectorWorker> = self
+            .validators
+            .into_iter()
+            .map(DetectorWorker::new)
+            .collect();
 
         tokio::spawn(async move {
-            let worker = DetectorWorker::new(validator);
-            let mut upstream = stream;
-
-            while let Some(sample_result) = upstream.next().await {
-                match sample_result {
-                    Ok(sample) => {
-                        let result = worker.handle_sample(sample).await;
-                        let is_err = result.is_err();
-                        if tx.send(result).await.is_err() {
-                            break;
-                        }
-                        if is_err {
-                            break;
+            let concurrency = workers.len();
+            let pool = workers.clone();
+            // `buffered` runs at most `concurrency` detections and yields them in sample
+            // order, so the samples in flight are consecutive and never share a worker.
+            let mut results = stream
+                .enumerate()
+                .map(move |(position, sample_result)| {
+                    let worker = pool[position % pool.len()].clone();
+                    async move {
+                        let sample = sample_result.map_err(DetectorError::Sampler)?;
+                        match tokio::spawn(async move { worker.handle_sample(sample).await }).await
+                        {
+                            Ok(result) => result,
+                            // Re-raised so a detector panic stops the stage as it would inline;
+                            // the task is never aborted, so this is always a panic.
+                            Err(err) => std::panic::resume_unwind(err.into_panic()),
                         }
                     }
-                    Err(err) => {
-                        let _ = tx.send(Err(DetectorError::Sampler(err))).await;
-                        break;
-                    }
+                })
+                .buffered(concurrency);
+
+            while let Some(result) = results.next().await {
+                let is_err = result.is_err();
+                if tx.send(result).await.is_err() {
+                    break;
+                }
+                if is_err {
+                    break;
                 }
             }
 
-            worker.finalize().await;
+            for worker in &workers {
+                worker.finalize().await;
+            }
         });
 
         let stream = Box::pin(unfold(rx, |mut receiver| async {
@@ -86,6 +113,34 @@ impl Detector {
     }
 }
 
+/// Validator options for the configured detector kind, luma band, ROI and region filters.
+pub(crate) fn detection_options(settings: &DetectionSettings) -> SubtitleDetectionOptions {
+    let mut detection_options = SubtitleDetectionOptions::default();
+    detection_options.luma_band.target = settings.target;
+    detection_options.luma_band.delta = settings.delta;
+    detection_options.detector = settings.detector;
+    detection_options.roi = settings.roi;
+    detection_options.expected_aspect = settings.expected_aspect;
+    if let Some(ratio) = settings.min_area_ratio {
+        detection_options.min_area_ratio = ratio;
+    }
+    if let Some(fraction) = settings.max_bright_fraction {
+        detection_options.max_bright_fraction = fraction;
+    }
+    detection_options.edge_exclusion_px = settings.edge_exclusion_px;
+    if let Some(area) = settings.min_component_area {
+        detection_options.tuning.min_area = area;
+    }
+    if let Some(aspect) = settings.min_region_aspect {
+        detection_options.tuning.min_aspect_ratio = aspect;
+    }
+    if let Some(ratio) = settings.max_region_area_ratio {
+        detection_options.tuning.max_area_ratio = ratio;
+    }
+    detection_options
+}
+
+#[derive(Clone)]
 struct DetectorWorker {
     validator: FrameValidator,
 }
@@ -116,3 +171,59 @@ impl DetectorWorker {
         self.validator.finalize().await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::stage::sampler::{FrameHistory, SamplerContext};
+    use crate::test_support::scripted_frame;
+
+    fn settings() -> DetectionSettings {
+        DetectionSettings {
+            samples_per_second: 60,
+            ..Default::default()
+        }
+    }
+
+    fn sample(index: u64, with_subtitle: bool) -> SamplerResult {
+        Ok(SampledFrame::new(
+            index,
+            Arc::new(scripted_frame(index, with_subtitle)),
+            FrameHistory::new(Vec::new()),
+            Arc::new(SamplerContext::initial()),
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_detection_keeps_sample_order() {
+        let script: Vec<bool> = (0..48).map(|index| index % 5 < 2).collect();
+        let samples: Vec<SamplerResult> = script
+            .iter()
+            .enumerate()
+            .map(|(index, &with_subtitle)| sample(index as u64, with_subtitle))
+            .collect();
+        let input = StreamBundle::new(Box::pin(futures_util::stream::iter(samples)), None);
+
+        let detector = Detector::new(&settings())
+            .and_then(|detector| detector.with_concurrency(4))
+            .expect("detector");
+        let mut output = detector.attach(input).stream;
+        let mut seen = Vec::new();
+        while let Some(result) = output.next().await {
+            let detected = result.expect("detection");
+            seen.push((
+                detected.sample.frame_index(),
+                detected.detection.has_subtitle,
+            ));
+        }
+
+        let expected: Vec<(u64, bool)> = script
+            .iter()
+            .enumerate()
+            .map(|(index, &with_subtitle)| (index as u64, with_subtitle))
+            .collect();
+        assert_eq!(seen, expected);
+    }
+}
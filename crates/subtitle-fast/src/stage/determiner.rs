@@ -18,6 +18,8 @@ pub struct RegionUnit {
     pub id: RegionId,
     pub label: String,
     pub roi: RoiConfig,
+    /// Detector score of the region in this sample.
+    pub score: f32,
 }
 
 pub struct RegionDeterminerEvent {
@@ -141,10 +143,16 @@ impl RegionDeterminerWorker {
                     id: guard.id,
                     label: guard.label.clone(),
                     roi: clipped,
+                    score: region.score,
                 });
             }
 
-            emitted.push(RegionUnit { id, label, roi });
+            emitted.push(RegionUnit {
+                id,
+                label,
+                roi,
+                score: region.score,
+            });
         }
 
         RegionDeterminerEvent {
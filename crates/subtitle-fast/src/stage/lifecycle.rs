@@ -1,8 +1,10 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures_util::{StreamExt, stream::unfold};
+use rayon::ThreadPool;
 use tokio::sync::mpsc;
 
 use super::StreamBundle;
@@ -13,7 +15,9 @@ use super::determiner::{
 use super::sampler::{FrameHistory, SampledFrame, SamplerContext};
 use crate::settings::DetectionSettings;
 use subtitle_fast_comparator::{
-    ComparatorFactory, ComparatorKind, ComparatorSettings, FeatureBlob, SubtitleComparator,
+    BitsetCoverSettings, ComparatorFactory, ComparatorKind, ComparatorSettings,
+    DEFAULT_MIN_EDGE_POINTS, FeatureBlob, SparseChamferSettings, SsimSettings, SubtitleComparator,
+    pipeline::region_hash,
 };
 use subtitle_fast_types::{RoiConfig, VideoFrame};
 
@@ -21,6 +25,50 @@ const REGION_TRACKER_CHANNEL_CAPACITY: usize = 4;
 const MIN_REGION_AREA_FRACTION: f32 = 0.001;
 const MIN_REGION_DURATION: Duration = Duration::from_millis(200);
 const MIN_REGION_DIM_PX: u32 = 15;
+/// Matched samples between template refreshes; 1 re-bases on every match.
+pub const DEFAULT_TEMPLATE_REFRESH_INTERVAL: usize = 1;
+/// Consecutive samples a new region must be detected in before it opens; 1 opens at once.
+pub const DEFAULT_MIN_CONSECUTIVE_SAMPLES: usize = 1;
+
+/// Which frame of a segment stands for it in OCR and crops.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BestFramePolicy {
+    /// Sample with the highest detection score; the earliest wins ties.
+    #[default]
+    HighestScore,
+    /// Sample in the middle of the segment; the earlier one for an even count.
+    Middle,
+    /// Last frame still matching the segment, refined through the frame history.
+    Last,
+    /// Sample the segment opened on.
+    First,
+}
+
+impl BestFramePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BestFramePolicy::HighestScore => "highest-score",
+            BestFramePolicy::Middle => "middle",
+            BestFramePolicy::Last => "last",
+            BestFramePolicy::First => "first",
+        }
+    }
+}
+
+impl FromStr for BestFramePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "highest-score" | "score" => Ok(BestFramePolicy::HighestScore),
+            "middle" => Ok(BestFramePolicy::Middle),
+            "last" => Ok(BestFramePolicy::Last),
+            "first" => Ok(BestFramePolicy::First),
+            _ => Err(lower),
+        }
+    }
+}
 
 pub struct CompletedRegion {
     pub id: RegionId,
@@ -31,11 +79,15 @@ pub struct CompletedRegion {
     pub end_frame: u64,
     pub roi: RoiConfig,
     pub frame: Arc<VideoFrame>,
+    /// Highest detection score over the segment's samples; 0 when replayed from the cache.
+    pub max_score: f32,
 }
 
 pub struct LifecycleEvent {
     pub sample: Option<DetectionSample>,
     pub completed: Vec<CompletedRegion>,
+    /// Regions still open after this sample, in ascending id order.
+    pub active: Vec<RegionId>,
     pub region_timings: Option<RegionTimings>,
 }
 
@@ -50,6 +102,8 @@ pub enum RegionLifecycleError {
 pub struct RegionTimings {
     pub frames: u64,
     pub roi_extracts: u64,
+    /// Samples whose features were reused because their region had not changed.
+    pub reused_extracts: u64,
     pub comparisons: u64,
     pub extract: Duration,
     pub compare: Duration,
@@ -58,6 +112,11 @@ pub struct RegionTimings {
 
 pub struct RegionLifecycleTracker {
     comparator_factory: ComparatorFactory,
+    template_refresh_interval: usize,
+    min_consecutive_samples: usize,
+    best_frame_policy: BestFramePolicy,
+    reuse_static_features: bool,
+    thread_pool: Option<Arc<ThreadPool>>,
 }
 
 impl RegionLifecycleTracker {
@@ -67,9 +126,27 @@ impl RegionLifecycleTracker {
             kind: comparator_kind,
             target: settings.target,
             delta: settings.delta,
+            bitset_cover: BitsetCoverSettings::default(),
+            min_edge_points: DEFAULT_MIN_EDGE_POINTS,
+            sparse_chamfer: SparseChamferSettings::default(),
+            ssim: SsimSettings::default(),
+            fallback: settings.comparator_fallback,
         };
         let comparator_factory = ComparatorFactory::new(comparator_settings);
-        Self { comparator_factory }
+        Self {
+            comparator_factory,
+            template_refresh_interval: settings.template_refresh_interval,
+            min_consecutive_samples: settings.min_consecutive_samples,
+            best_frame_policy: settings.best_frame_policy,
+            reuse_static_features: settings.reuse_static_features,
+            thread_pool: None,
+        }
+    }
+
+    /// Runs feature extraction and comparison on `pool` instead of rayon's global pool.
+    pub fn with_thread_pool(mut self, pool: Option<Arc<ThreadPool>>) -> Self {
+        self.thread_pool = pool;
+        self
     }
 
     pub fn attach(
@@ -82,11 +159,22 @@ impl RegionLifecycleTracker {
         } = input;
 
         let comparator_factory = self.comparator_factory;
+        let template_refresh_interval = self.template_refresh_interval;
+        let open_gate = OpenGate::new(self.min_consecutive_samples);
+        let best_frame_policy = self.best_frame_policy;
+        let static_features = self.reuse_static_features.then(HashMap::new);
+        let thread_pool = self.thread_pool;
         let (tx, rx) = mpsc::channel::<LifecycleResult>(REGION_TRACKER_CHANNEL_CAPACITY);
 
         tokio::spawn(async move {
             let comparator = comparator_factory.build();
-            let mut worker = RegionLifecycleWorker::new(comparator);
+            let mut worker = RegionLifecycleWorker::new(
+                comparator,
+                template_refresh_interval,
+                open_gate,
+                best_frame_policy,
+                static_features,
+            );
             let mut upstream = stream;
 
             while let Some(event) = upstream.next().await {
@@ -94,7 +182,9 @@ impl RegionLifecycleTracker {
                     Ok(regions) => {
                         let started = Instant::now();
                         let mut timings = RegionTimings::default();
-                        let mut lifecycle_event = worker.handle_event(regions, &mut timings);
+                        let mut lifecycle_event = in_pool(thread_pool.as_deref(), || {
+                            worker.handle_event(regions, &mut timings)
+                        });
                         timings.total = started.elapsed();
                         timings.frames = 1;
                         lifecycle_event.region_timings = Some(timings);
@@ -104,12 +194,14 @@ impl RegionLifecycleTracker {
                     }
                     Err(err) => {
                         let mut timings = RegionTimings::default();
-                        let flush = worker.flush_active(&mut timings);
+                        let flush =
+                            in_pool(thread_pool.as_deref(), || worker.flush_active(&mut timings));
                         if !flush.is_empty() {
                             let _ = tx
                                 .send(Ok(LifecycleEvent {
                                     sample: None,
                                     completed: flush,
+                                    active: Vec::new(),
                                     region_timings: None,
                                 }))
                                 .await;
@@ -121,12 +213,13 @@ impl RegionLifecycleTracker {
             }
 
             let mut timings = RegionTimings::default();
-            let flush = worker.flush_active(&mut timings);
+            let flush = in_pool(thread_pool.as_deref(), || worker.flush_active(&mut timings));
             if !flush.is_empty() {
                 let _ = tx
                     .send(Ok(LifecycleEvent {
                         sample: None,
                         completed: flush,
+                        active: Vec::new(),
                         region_timings: None,
                     }))
                     .await;
@@ -147,23 +240,41 @@ struct ActiveRegion {
     roi: RoiConfig,
     template_features: FeatureBlob,
     anchor_features: Option<FeatureBlob>,
+    matches_since_refresh: usize,
     start_time: Duration,
     start_frame: u64,
     last_time: Duration,
     last_frame: u64,
     frame: Arc<VideoFrame>,
+    best_shot: BestShot,
+    max_score: f32,
 }
 
 struct RegionLifecycleWorker {
     comparator: Arc<dyn SubtitleComparator>,
     active: HashMap<RegionId, ActiveRegion>,
     last_history: Option<FrameHistory>,
+    template_refresh_interval: usize,
+    open_gate: OpenGate,
+    best_frame_policy: BestFramePolicy,
+    /// Last sample's features per region, when unchanged regions skip extraction.
+    static_features: Option<HashMap<RegionId, StaticFeatures>>,
 }
 
 impl RegionLifecycleWorker {
-    fn new(comparator: Arc<dyn SubtitleComparator>) -> Self {
+    fn new(
+        comparator: Arc<dyn SubtitleComparator>,
+        template_refresh_interval: usize,
+        open_gate: OpenGate,
+        best_frame_policy: BestFramePolicy,
+        static_features: Option<HashMap<RegionId, StaticFeatures>>,
+    ) -> Self {
         Self {
             comparator,
+            template_refresh_interval,
+            open_gate,
+            best_frame_policy,
+            static_features,
             active: HashMap::new(),
             last_history: None,
         }
@@ -179,14 +290,12 @@ impl RegionLifecycleWorker {
 
         let mut roi_features: Vec<Option<FeatureBlob>> = Vec::with_capacity(event.regions.len());
         for region in &event.regions {
-            let features = timed_extract(
-                timings,
-                self.comparator.as_ref(),
-                &frame_ctx.frame,
-                &region.roi,
-            );
+            let features = self.region_features(&frame_ctx.frame, region, timings);
             roi_features.push(features);
         }
+        if let Some(cache) = self.static_features.as_mut() {
+            cache.retain(|id, _| event.regions.iter().any(|region| region.id == *id));
+        }
 
         let mut completed = Vec::new();
         let mut seen: HashSet<RegionId> = HashSet::new();
@@ -195,35 +304,37 @@ impl RegionLifecycleWorker {
             let Some(features) = roi_features.get(idx).and_then(|f| f.clone()) else {
                 continue;
             };
-            if let Some(active) = self.active.get(&region.id) {
+            if let Some(active) = self.active.get_mut(&region.id) {
                 let matched = match_active(
                     self.comparator.as_ref(),
                     active,
-                    &frame_ctx,
-                    &region.roi,
                     &features,
+                    self.template_refresh_interval,
                     timings,
                 );
                 if matched {
-                    if let Some(active) = self.active.get_mut(&region.id) {
-                        active.roi = region.roi;
-                        active.frame = Arc::clone(&frame_ctx.frame);
-                        active.last_time = frame_ctx.time;
-                        active.last_frame = frame_ctx.frame_index;
-                        active.template_features = features.clone();
-                        active.anchor_features = Some(features);
-                    }
+                    active.roi = region.roi;
+                    active.frame = Arc::clone(&frame_ctx.frame);
+                    active.last_time = frame_ctx.time;
+                    active.last_frame = frame_ctx.frame_index;
+                    active.max_score = active.max_score.max(region.score);
+                    active
+                        .best_shot
+                        .offer(ShotCandidate::new(&frame_ctx, region));
                     seen.insert(region.id);
                 } else {
                     // Keep the existing active region; treat this as a non-updating observation.
                     seen.insert(region.id);
                 }
             } else {
-                let active = self.start_region(region, frame_ctx.clone(), features, timings);
-                self.active.insert(region.id, active);
                 seen.insert(region.id);
+                if self.open_gate.observe(region.id) {
+                    let active = self.start_region(region, frame_ctx.clone(), features, timings);
+                    self.active.insert(region.id, active);
+                }
             }
         }
+        self.open_gate.retain_seen(&seen);
 
         let missing: Vec<RegionId> = self
             .active
@@ -237,13 +348,56 @@ impl RegionLifecycleWorker {
             }
         }
 
+        let mut active: Vec<RegionId> = self.active.keys().copied().collect();
+        active.sort_unstable();
+
         LifecycleEvent {
             sample: Some(event.sample),
             completed,
+            active,
             region_timings: None,
         }
     }
 
+    /// Extracts the region's features, or reuses the previous sample's when its ROI and
+    /// pixels are unchanged and static feature reuse is enabled.
+    fn region_features(
+        &mut self,
+        frame: &VideoFrame,
+        region: &super::determiner::RegionUnit,
+        timings: &mut RegionTimings,
+    ) -> Option<FeatureBlob> {
+        let Some(cache) = self.static_features.as_mut() else {
+            return timed_extract(timings, self.comparator.as_ref(), frame, &region.roi);
+        };
+        let hash = region_hash(frame, &region.roi);
+        if let Some(hash) = hash
+            && let Some(previous) = cache.get(&region.id)
+            && previous.roi == region.roi
+            && previous.hash == hash
+        {
+            timings.reused_extracts = timings.reused_extracts.saturating_add(1);
+            return previous.features.clone();
+        }
+        let features = timed_extract(timings, self.comparator.as_ref(), frame, &region.roi);
+        match hash {
+            Some(hash) => {
+                cache.insert(
+                    region.id,
+                    StaticFeatures {
+                        roi: region.roi,
+                        hash,
+                        features: features.clone(),
+                    },
+                );
+            }
+            None => {
+                cache.remove(&region.id);
+            }
+        }
+        features
+    }
+
     fn start_region(
         &self,
         region: &super::determiner::RegionUnit,
@@ -259,17 +413,23 @@ impl RegionLifecycleWorker {
             timings,
         );
 
+        let mut best_shot = BestShot::new(self.best_frame_policy);
+        best_shot.offer(ShotCandidate::new(&frame, region));
+
         ActiveRegion {
             id: region.id,
             label: region.label.clone(),
             roi: region.roi,
             template_features,
             anchor_features,
+            matches_since_refresh: 0,
             start_time,
             start_frame,
             last_time: frame.time,
             last_frame: frame.frame_index,
             frame: frame.frame,
+            best_shot,
+            max_score: region.score,
         }
     }
 
@@ -292,6 +452,10 @@ impl RegionLifecycleWorker {
     ) -> CompletedRegion {
         let (end_time, end_frame, frame_handle) =
             refine_end(self.comparator.as_ref(), &active, history, timings);
+        let (roi, frame) = match active.best_shot.pick() {
+            Some(shot) => (shot.roi, Arc::clone(&shot.frame)),
+            None => (active.roi, frame_handle),
+        };
 
         CompletedRegion {
             id: active.id,
@@ -300,8 +464,9 @@ impl RegionLifecycleWorker {
             end_time,
             start_frame: active.start_frame,
             end_frame,
-            roi: active.roi,
-            frame: frame_handle,
+            roi,
+            frame,
+            max_score: active.max_score,
         }
     }
 
@@ -321,6 +486,121 @@ impl RegionLifecycleWorker {
     }
 }
 
+/// Features extracted for a region, keyed by the ROI and pixel hash they were taken from.
+struct StaticFeatures {
+    roi: RoiConfig,
+    hash: u64,
+    features: Option<FeatureBlob>,
+}
+
+/// Holds back new regions until they have been detected in enough consecutive samples, so
+/// sparse sampling still demands a stable detection before a subtitle opens. The start time
+/// is refined back through the history once the region does open.
+struct OpenGate {
+    min_consecutive: usize,
+    streaks: HashMap<RegionId, usize>,
+}
+
+impl OpenGate {
+    fn new(min_consecutive: usize) -> Self {
+        Self {
+            min_consecutive: min_consecutive.max(1),
+            streaks: HashMap::new(),
+        }
+    }
+
+    /// Counts one more consecutive detection of `id` and reports whether it may open.
+    fn observe(&mut self, id: RegionId) -> bool {
+        let streak = self.streaks.entry(id).or_insert(0);
+        *streak += 1;
+        if *streak < self.min_consecutive {
+            return false;
+        }
+        self.streaks.remove(&id);
+        true
+    }
+
+    /// Resets the streak of every pending region missing from the current sample.
+    fn retain_seen(&mut self, seen: &HashSet<RegionId>) {
+        self.streaks.retain(|id, _| seen.contains(id));
+    }
+}
+
+/// A matched sample of an active region that may become its representative frame.
+struct ShotCandidate {
+    frame: Arc<VideoFrame>,
+    roi: RoiConfig,
+    score: f32,
+}
+
+impl ShotCandidate {
+    fn new(frame: &FrameContext, region: &super::determiner::RegionUnit) -> Self {
+        Self {
+            frame: Arc::clone(&frame.frame),
+            roi: region.roi,
+            score: region.score,
+        }
+    }
+}
+
+/// Tracks the representative frame of an active region as its samples arrive.
+struct BestShot {
+    policy: BestFramePolicy,
+    chosen: Option<ShotCandidate>,
+    /// Samples from the current middle onwards, for [`BestFramePolicy::Middle`].
+    tail: VecDeque<ShotCandidate>,
+    offered: usize,
+}
+
+impl BestShot {
+    fn new(policy: BestFramePolicy) -> Self {
+        Self {
+            policy,
+            chosen: None,
+            tail: VecDeque::new(),
+            offered: 0,
+        }
+    }
+
+    fn offer(&mut self, candidate: ShotCandidate) {
+        self.offered += 1;
+        match self.policy {
+            BestFramePolicy::HighestScore => {
+                if self
+                    .chosen
+                    .as_ref()
+                    .is_none_or(|best| candidate.score > best.score)
+                {
+                    self.chosen = Some(candidate);
+                }
+            }
+            BestFramePolicy::Middle => {
+                // The middle index (offered - 1) / 2 never moves back, so earlier samples can go.
+                self.tail.push_back(candidate);
+                let keep = self.offered - (self.offered - 1) / 2;
+                while self.tail.len() > keep {
+                    self.tail.pop_front();
+                }
+            }
+            BestFramePolicy::First => {
+                if self.chosen.is_none() {
+                    self.chosen = Some(candidate);
+                }
+            }
+            // The end refinement finds the last frame, including history after the last sample.
+            BestFramePolicy::Last => {}
+        }
+    }
+
+    /// The chosen sample, or `None` when the segment's refined last frame should be used.
+    fn pick(&self) -> Option<&ShotCandidate> {
+        match self.policy {
+            BestFramePolicy::Middle => self.tail.front(),
+            _ => self.chosen.as_ref(),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct FrameContext {
     time: Duration,
@@ -414,17 +694,29 @@ fn refine_end(
     (best_time, best_frame, best_frame_handle)
 }
 
+/// Compares a sample against the active region and, every `refresh_interval` matches,
+/// re-bases the template on the matching features so slow drift over a long subtitle
+/// (compression noise, fading overlays) is not measured against its very first frame.
+/// An interval of 0 never re-bases.
 fn match_active(
     comparator: &dyn SubtitleComparator,
-    active: &ActiveRegion,
-    _frame: &FrameContext,
-    _roi: &RoiConfig,
+    active: &mut ActiveRegion,
     candidate: &FeatureBlob,
+    refresh_interval: usize,
     timings: &mut RegionTimings,
 ) -> bool {
     let reference = comparison_anchor(&active.anchor_features, &active.template_features);
     let report = timed_compare(timings, comparator, reference, candidate);
-    report.same_segment
+    if !report.same_segment {
+        return false;
+    }
+    active.matches_since_refresh += 1;
+    if refresh_interval > 0 && active.matches_since_refresh >= refresh_interval {
+        active.template_features = candidate.clone();
+        active.anchor_features = Some(candidate.clone());
+        active.matches_since_refresh = 0;
+    }
+    true
 }
 
 fn comparison_anchor<'a>(
@@ -434,6 +726,13 @@ fn comparison_anchor<'a>(
     anchor.as_ref().unwrap_or(template)
 }
 
+fn in_pool<R: Send>(pool: Option<&ThreadPool>, op: impl FnOnce() -> R + Send) -> R {
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
 fn timed_extract(
     timings: &mut RegionTimings,
     comparator: &dyn SubtitleComparator,
@@ -460,7 +759,7 @@ fn timed_compare(
     report
 }
 
-fn sample_time(sample: &SampledFrame) -> Duration {
+pub(crate) fn sample_time(sample: &SampledFrame) -> Duration {
     if let Some(ts) = sample.frame().pts() {
         return ts;
     }
@@ -502,3 +801,265 @@ fn is_valid_region(region: &CompletedRegion) -> bool {
         .unwrap_or_else(|| Duration::from_secs(0));
     duration >= MIN_REGION_DURATION
 }
+
+#[cfg(test)]
+mod tests {
+    use subtitle_fast_comparator::ComparisonReport;
+    use subtitle_fast_types::SubtitleDetectionResult;
+
+    use super::*;
+    use crate::stage::determiner::RegionUnit;
+
+    /// Drift levels carried in the feature tag; neighbours within two levels match.
+    const LEVELS: [&str; 12] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11"];
+    const MATCH_DISTANCE: i32 = 2;
+
+    struct DriftComparator;
+
+    impl SubtitleComparator for DriftComparator {
+        fn name(&self) -> &'static str {
+            "drift"
+        }
+
+        fn extract(&self, _frame: &VideoFrame, _roi: &RoiConfig) -> Option<FeatureBlob> {
+            None
+        }
+
+        fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+            let level = |blob: &FeatureBlob| blob.tag().parse::<i32>().unwrap();
+            let distance = (level(reference) - level(candidate)).abs();
+            ComparisonReport::new(1.0 / (1 + distance) as f32, distance <= MATCH_DISTANCE)
+        }
+    }
+
+    /// Extracts the same features from every frame, so only extraction counts differ.
+    struct ConstantComparator;
+
+    impl SubtitleComparator for ConstantComparator {
+        fn name(&self) -> &'static str {
+            "constant"
+        }
+
+        fn extract(&self, _frame: &VideoFrame, _roi: &RoiConfig) -> Option<FeatureBlob> {
+            Some(features(0))
+        }
+
+        fn compare(&self, reference: &FeatureBlob, candidate: &FeatureBlob) -> ComparisonReport {
+            DriftComparator.compare(reference, candidate)
+        }
+    }
+
+    fn features(level: usize) -> FeatureBlob {
+        FeatureBlob::new(LEVELS[level], ())
+    }
+
+    fn active_region() -> ActiveRegion {
+        let frame =
+            VideoFrame::from_nv12_owned(4, 2, 4, 4, None, None, vec![0; 8], vec![128; 4]).unwrap();
+        ActiveRegion {
+            id: 1,
+            label: "region-1".to_string(),
+            roi: RoiConfig {
+                x: 0.0,
+                y: 0.5,
+                width: 1.0,
+                height: 0.5,
+            },
+            template_features: features(0),
+            anchor_features: None,
+            matches_since_refresh: 0,
+            start_time: Duration::ZERO,
+            start_frame: 0,
+            last_time: Duration::ZERO,
+            last_frame: 0,
+            frame: Arc::new(frame),
+            best_shot: BestShot::new(BestFramePolicy::Last),
+            max_score: 0.0,
+        }
+    }
+
+    fn shot(frame_index: u64, score: f32) -> ShotCandidate {
+        let frame =
+            VideoFrame::from_nv12_owned(4, 2, 4, 4, None, None, vec![0; 8], vec![128; 4]).unwrap();
+        ShotCandidate {
+            frame: Arc::new(frame.with_index(Some(frame_index))),
+            roi: RoiConfig {
+                x: 0.0,
+                y: 0.5,
+                width: 1.0,
+                height: 0.5,
+            },
+            score,
+        }
+    }
+
+    /// Frame the closed segment carries when sampled at frames 10, 20, ... with `scores`.
+    fn picked_frame(policy: BestFramePolicy, scores: &[f32]) -> Option<u64> {
+        let worker = RegionLifecycleWorker::new(
+            Arc::new(DriftComparator),
+            DEFAULT_TEMPLATE_REFRESH_INTERVAL,
+            OpenGate::new(DEFAULT_MIN_CONSECUTIVE_SAMPLES),
+            policy,
+            None,
+        );
+        let mut active = active_region();
+        active.best_shot = BestShot::new(policy);
+        for (idx, &score) in scores.iter().enumerate() {
+            let frame_index = (idx as u64 + 1) * 10;
+            let candidate = shot(frame_index, score);
+            active.last_frame = frame_index;
+            active.frame = Arc::clone(&candidate.frame);
+            active.best_shot.offer(candidate);
+        }
+        let history = FrameHistory::new(Vec::new());
+        let completed = worker.close_active(active, &history, &mut RegionTimings::default());
+        completed.frame.index()
+    }
+
+    /// One sample at `frame_index` showing region 1 over a bottom band filled with `luma`.
+    fn region_event(frame_index: u64, luma: u8) -> RegionDeterminerEvent {
+        let frame = VideoFrame::from_nv12_owned(
+            8,
+            4,
+            8,
+            8,
+            Some(Duration::from_millis(frame_index * 40)),
+            None,
+            vec![luma; 32],
+            vec![128; 16],
+        )
+        .unwrap();
+        RegionDeterminerEvent {
+            sample: DetectionSample {
+                sample: SampledFrame::new(
+                    frame_index,
+                    Arc::new(frame),
+                    FrameHistory::new(Vec::new()),
+                    Arc::new(SamplerContext::initial()),
+                ),
+                detection: SubtitleDetectionResult::empty(),
+                elapsed: Duration::ZERO,
+            },
+            regions: vec![RegionUnit {
+                id: 1,
+                label: "region-1".to_string(),
+                roi: RoiConfig {
+                    x: 0.0,
+                    y: 0.5,
+                    width: 1.0,
+                    height: 0.5,
+                },
+                score: 1.0,
+            }],
+        }
+    }
+
+    /// Feature extractions over samples with the given bottom-band luma values.
+    fn extract_count(reuse_static_features: bool, lumas: &[u8]) -> u64 {
+        let mut worker = RegionLifecycleWorker::new(
+            Arc::new(ConstantComparator),
+            DEFAULT_TEMPLATE_REFRESH_INTERVAL,
+            OpenGate::new(DEFAULT_MIN_CONSECUTIVE_SAMPLES),
+            BestFramePolicy::default(),
+            reuse_static_features.then(HashMap::new),
+        );
+        let mut timings = RegionTimings::default();
+        for (idx, &luma) in lumas.iter().enumerate() {
+            let event = worker.handle_event(region_event(idx as u64 * 10, luma), &mut timings);
+            assert_eq!(event.active, [1]);
+        }
+        timings.roi_extracts
+    }
+
+    /// Index of the first sample in a slowly drifting sequence that no longer matches.
+    fn first_mismatch(refresh_interval: usize) -> Option<usize> {
+        let mut active = active_region();
+        let mut timings = RegionTimings::default();
+        (1..LEVELS.len()).find(|&level| {
+            !match_active(
+                &DriftComparator,
+                &mut active,
+                &features(level),
+                refresh_interval,
+                &mut timings,
+            )
+        })
+    }
+
+    #[test]
+    fn new_regions_wait_for_the_consecutive_sample_floor() {
+        let mut gate = OpenGate::new(3);
+        let seen = HashSet::from([4]);
+        assert!(!gate.observe(4));
+        assert!(!gate.observe(4));
+        gate.retain_seen(&seen);
+        assert!(gate.observe(4));
+
+        // A sample without the region restarts its streak.
+        assert!(!gate.observe(5));
+        gate.retain_seen(&seen);
+        assert!(!gate.observe(5));
+        assert!(!gate.observe(5));
+        assert!(gate.observe(5));
+    }
+
+    #[test]
+    fn default_floor_opens_regions_immediately() {
+        let mut gate = OpenGate::new(DEFAULT_MIN_CONSECUTIVE_SAMPLES);
+        assert!(gate.observe(1));
+        assert!(OpenGate::new(0).observe(2));
+    }
+
+    #[test]
+    fn each_best_frame_policy_picks_its_frame() {
+        let scores = [0.4, 0.9, 0.6, 0.9, 0.5];
+        assert_eq!(
+            picked_frame(BestFramePolicy::HighestScore, &scores),
+            Some(20)
+        );
+        assert_eq!(picked_frame(BestFramePolicy::Middle, &scores), Some(30));
+        assert_eq!(picked_frame(BestFramePolicy::First, &scores), Some(10));
+        assert_eq!(picked_frame(BestFramePolicy::Last, &scores), Some(50));
+
+        assert_eq!(
+            picked_frame(BestFramePolicy::Middle, &scores[..4]),
+            Some(20)
+        );
+        assert_eq!(
+            picked_frame(BestFramePolicy::Middle, &scores[..1]),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn best_frame_policy_round_trips_through_its_name() {
+        for policy in [
+            BestFramePolicy::HighestScore,
+            BestFramePolicy::Middle,
+            BestFramePolicy::Last,
+            BestFramePolicy::First,
+        ] {
+            assert_eq!(policy.as_str().parse::<BestFramePolicy>(), Ok(policy));
+        }
+        assert!("sharpest".parse::<BestFramePolicy>().is_err());
+    }
+
+    #[test]
+    fn static_regions_reuse_the_previous_features() {
+        let still = [200; 5];
+        assert_eq!(extract_count(false, &still), 5);
+        assert_eq!(extract_count(true, &still), 1);
+        assert_eq!(extract_count(true, &[200, 200, 90, 90, 200]), 3);
+    }
+
+    #[test]
+    fn rebasing_keeps_a_slowly_drifting_subtitle_in_one_segment() {
+        assert_eq!(first_mismatch(1), None);
+        assert_eq!(first_mismatch(2), None);
+    }
+
+    #[test]
+    fn drift_splits_the_segment_without_rebasing() {
+        assert_eq!(first_mismatch(0), Some(3));
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use futures_util::{StreamExt, stream::unfold};
@@ -7,28 +8,48 @@ use super::StreamBundle;
 use super::detector::DetectionSample;
 use super::lifecycle::RegionTimings;
 use super::ocr::{OcrEvent, OcrStageError, OcrStageResult, OcrTimings};
-use crate::subtitle::{MergedSubtitle, SubtitleLine};
+use super::postprocess::TextPostProcessor;
+use crate::subtitle::{MergedSubtitle, MultiRegionMode, SubtitleLine};
 use subtitle_fast_ocr::OcrResponse;
+use subtitle_fast_types::OcrRegion;
 
 const MERGE_CHANNEL_CAPACITY: usize = 4;
 const MERGE_GAP: Duration = Duration::from_millis(120);
-const SUBTITLE_CACHE_WINDOW: Duration = Duration::from_secs(2);
+/// Cues that ended longer than this before an incoming cue starts can no longer be merged into.
+pub(crate) const SUBTITLE_CACHE_WINDOW: Duration = Duration::from_secs(2);
 
 pub type MergeResult = Result<MergeOutput, OcrStageError>;
 
 pub struct Merge {
     cache_window: Duration,
+    multi_region: MultiRegionMode,
+    post_processor: Option<Arc<dyn TextPostProcessor>>,
 }
 
 impl Merge {
     pub fn new(cache_window: Duration) -> Self {
-        Self { cache_window }
+        Self {
+            cache_window,
+            multi_region: MultiRegionMode::default(),
+            post_processor: None,
+        }
     }
 
     pub fn with_default_window() -> Self {
         Self::new(SUBTITLE_CACHE_WINDOW)
     }
 
+    pub fn with_multi_region(mut self, mode: MultiRegionMode) -> Self {
+        self.multi_region = mode;
+        self
+    }
+
+    /// Runs every normalized cue text through `processor` before merging.
+    pub fn with_post_processor(mut self, processor: Arc<dyn TextPostProcessor>) -> Self {
+        self.post_processor = Some(processor);
+        self
+    }
+
     pub fn attach(self, input: StreamBundle<OcrStageResult>) -> StreamBundle<MergeResult> {
         let StreamBundle {
             stream,
@@ -37,10 +58,13 @@ impl Merge {
 
         let (tx, rx) = mpsc::channel::<MergeResult>(MERGE_CHANNEL_CAPACITY);
         let cache_window = self.cache_window;
+        let multi_region = self.multi_region;
+        let post_processor = self.post_processor;
 
         tokio::spawn(async move {
             let mut upstream = stream;
-            let mut worker = MergeWorker::new(cache_window);
+            let mut worker =
+                MergeWorker::new(cache_window, multi_region).with_post_processor(post_processor);
 
             while let Some(event) = upstream.next().await {
                 match event {
@@ -99,30 +123,47 @@ struct SubtitleCue {
     start_frame: u64,
     text: String,
     center: f32,
+    region: OcrRegion,
+    score: f32,
 }
 
 struct MergeWorker {
     cache_window: Duration,
+    multi_region: MultiRegionMode,
+    post_processor: Option<Arc<dyn TextPostProcessor>>,
     subtitles: Vec<MergedSubtitle>,
     next_id: u64,
     stats: SubtitleStats,
 }
 
 impl MergeWorker {
-    fn new(cache_window: Duration) -> Self {
+    fn new(cache_window: Duration, multi_region: MultiRegionMode) -> Self {
         Self {
             cache_window,
+            multi_region,
+            post_processor: None,
             subtitles: Vec::new(),
             next_id: 0,
             stats: SubtitleStats::default(),
         }
     }
 
+    fn with_post_processor(mut self, processor: Option<Arc<dyn TextPostProcessor>>) -> Self {
+        self.post_processor = processor;
+        self
+    }
+
     fn handle_event(&mut self, event: OcrEvent) -> MergeOutput {
         let mut updates = Vec::new();
 
         for subtitle in event.regions {
-            let text = normalize_text(&response_to_text(&subtitle.response));
+            let mut text = normalize_text(&response_to_text(&subtitle.response));
+            if !text.is_empty()
+                && let Some(processor) = self.post_processor.as_ref()
+            {
+                // A cue the post-processor rejects counts as OCR without usable text.
+                text = processor.process(&text).unwrap_or_default();
+            }
             if text.is_empty() {
                 self.stats.ocr_empty = self.stats.ocr_empty.saturating_add(1);
                 continue;
@@ -134,6 +175,8 @@ impl MergeWorker {
                 start_frame: subtitle.lifecycle.start_frame,
                 text,
                 center,
+                region: subtitle.region,
+                score: subtitle.lifecycle.max_score,
             };
             if let Some(update) = self.apply_cue(cue) {
                 updates.push(update);
@@ -152,17 +195,30 @@ impl MergeWorker {
     fn apply_cue(&mut self, cue: SubtitleCue) -> Option<SubtitleUpdate> {
         self.prune(cue.start_time);
 
-        if let Some(last) = self.subtitles.last_mut()
-            && should_merge(last, &cue)
-        {
+        let target = match self.multi_region {
+            MultiRegionMode::Merge => self
+                .subtitles
+                .last_mut()
+                .filter(|last| should_merge(last, &cue)),
+            MultiRegionMode::SeparateCues | MultiRegionMode::MultiLine => self
+                .subtitles
+                .iter_mut()
+                .rev()
+                .find(|existing| is_continuation(existing, &cue)),
+        };
+
+        if let Some(last) = target {
             last.start_time = last.start_time.min(cue.start_time);
             last.end_time = last.end_time.max(cue.end_time);
             last.start_frame = last.start_frame.min(cue.start_frame);
-            if !last.lines.iter().any(|line| line.text == cue.text) {
-                last.lines.push(SubtitleLine {
+            match last.lines.iter_mut().find(|line| line.text == cue.text) {
+                Some(line) => line.score = line.score.max(cue.score),
+                None => last.lines.push(SubtitleLine {
                     center: cue.center,
                     text: cue.text.clone(),
-                });
+                    region: cue.region,
+                    score: cue.score,
+                }),
             }
             self.stats.merged = self.stats.merged.saturating_add(1);
             return Some(SubtitleUpdate {
@@ -179,6 +235,8 @@ impl MergeWorker {
             lines: vec![SubtitleLine {
                 center: cue.center,
                 text: cue.text,
+                region: cue.region,
+                score: cue.score,
             }],
         };
         self.next_id = self.next_id.saturating_add(1);
@@ -219,6 +277,18 @@ fn should_merge(current: &MergedSubtitle, incoming: &SubtitleCue) -> bool {
     }
 }
 
+/// Same text reappearing within the merge gap extends its own cue instead of joining another region.
+fn is_continuation(current: &MergedSubtitle, incoming: &SubtitleCue) -> bool {
+    if !current.lines.iter().any(|line| line.text == incoming.text) {
+        return false;
+    }
+    let gap = incoming
+        .start_time
+        .checked_sub(current.end_time)
+        .unwrap_or(Duration::ZERO);
+    gap <= MERGE_GAP
+}
+
 fn response_to_text(response: &OcrResponse) -> String {
     if response.texts.is_empty() {
         return String::new();
@@ -247,3 +317,57 @@ fn normalize_text(text: &str) -> String {
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subtitle::{layout_subtitles, render_srt, sort_subtitles};
+
+    fn cue(start_ms: u64, end_ms: u64, text: &str, center: f32) -> SubtitleCue {
+        SubtitleCue {
+            start_time: Duration::from_millis(start_ms),
+            end_time: Duration::from_millis(end_ms),
+            start_frame: start_ms / 40,
+            text: text.to_string(),
+            center,
+            region: OcrRegion::new(0.0, center, 1.0, 0.0),
+            score: 1.0,
+        }
+    }
+
+    fn render_two_speakers(mode: MultiRegionMode) -> String {
+        let mut worker = MergeWorker::new(SUBTITLE_CACHE_WINDOW, mode);
+        worker.apply_cue(cue(1000, 3000, "Hello", 0.85));
+        worker.apply_cue(cue(1500, 2500, "World", 0.15));
+        let mut subtitles = worker.subtitles.clone();
+        sort_subtitles(&mut subtitles);
+        render_srt(&layout_subtitles(&subtitles, mode))
+    }
+
+    #[test]
+    fn merge_mode_combines_concurrent_regions() {
+        assert_eq!(
+            render_two_speakers(MultiRegionMode::Merge),
+            "1\n00:00:01,000 --> 00:00:03,000\nWorld\nHello\n"
+        );
+    }
+
+    #[test]
+    fn separate_cues_mode_keeps_one_cue_per_region() {
+        assert_eq!(
+            render_two_speakers(MultiRegionMode::SeparateCues),
+            "1\n00:00:01,000 --> 00:00:03,000\nHello\n\n\
+             2\n00:00:01,500 --> 00:00:02,500\nWorld\n"
+        );
+    }
+
+    #[test]
+    fn multi_line_mode_slices_overlaps() {
+        assert_eq!(
+            render_two_speakers(MultiRegionMode::MultiLine),
+            "1\n00:00:01,000 --> 00:00:01,500\nHello\n\n\
+             2\n00:00:01,500 --> 00:00:02,500\nWorld\nHello\n\n\
+             3\n00:00:02,500 --> 00:00:03,000\nHello\n"
+        );
+    }
+}
@@ -4,34 +4,52 @@ pub mod determiner;
 pub mod lifecycle;
 pub mod merge;
 pub mod ocr;
+pub mod postprocess;
+pub mod replay;
 pub mod sampler;
+pub mod smoother;
 pub mod sorter;
 
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use averager::{Averager, AveragerResult};
 use detector::Detector;
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
 use tokio_stream::wrappers::WatchStream;
 
+use crate::cache::{CachedInterval, IntervalLog};
+use crate::contact_sheet::{ContactSheetOptions, CropLog};
 use crate::settings::{DetectionSettings, EffectiveSettings};
+use crate::timeline::TimelineLog;
 use determiner::{RegionDeterminer, RegionDeterminerError};
+use lifecycle::LifecycleResult;
 use lifecycle::{RegionLifecycleError, RegionLifecycleTracker};
 use merge::{Merge, MergeResult};
 use ocr::{OcrStageError, SubtitleOcr};
+use postprocess::{DEFAULT_MIN_CUE_CHARS, DefaultTextPostProcessor, TextPostProcessor};
+use replay::CachedIntervalReplay;
 use sampler::FrameSampler;
-use sorter::FrameSorter;
-use subtitle_fast_decoder::DynDecoderProvider;
+use smoother::DetectionSmoother;
+use sorter::{FrameSorter, MissingFramePolicy, SortKey};
+use subtitle_fast_decoder::{DynDecoderProvider, SeekInfo, SeekMode, VideoMetadata};
+#[cfg(all(feature = "ocr-tesseract", not(target_os = "macos")))]
+use subtitle_fast_ocr::TesseractOcrEngine;
 #[cfg(all(feature = "ocr-vision", target_os = "macos"))]
 use subtitle_fast_ocr::VisionOcrEngine;
-use subtitle_fast_ocr::{NoopOcrEngine, OcrEngine};
-use subtitle_fast_types::DecoderError;
+use subtitle_fast_ocr::{AsyncOcrEngine, NoopOcrEngine, OcrEngine, SyncOcrAdapter};
+#[cfg(all(feature = "ocr-onnx", not(target_os = "macos")))]
+use subtitle_fast_ocr::{OnnxPpocrConfig, OnnxPpocrRecognizer};
+use subtitle_fast_types::{DecoderError, DecoderResult, VideoFrame};
 use subtitle_fast_validator::subtitle_detection::SubtitleDetectionError;
 
 pub use crate::subtitle::{
-    MergedSubtitle, SubtitleLine, TimedSubtitle, render_srt, sort_subtitles,
+    BandGroup, MergedSubtitle, MultiRegionMode, ShortCuePolicy, SubtitleFormat, SubtitleLine,
+    TimedSubtitle, enforce_max_cps, enforce_min_duration, enforce_min_gap, layout_subtitles,
+    merge_identical_cues, render_ndjson_cue, render_srt, render_vtt, retime_cues, sort_subtitles,
+    split_by_band,
 };
 pub use merge::{SubtitleStats, SubtitleUpdate, SubtitleUpdateKind};
 
@@ -51,6 +69,19 @@ impl<T> StreamBundle<T> {
 
 #[derive(Clone)]
 pub struct PipelineConfig {
+    pub sort_key: SortKey,
+    pub decode_error_tolerance: u32,
+    /// What to do when the decoded frame indices skip a value.
+    pub missing_frames: MissingFramePolicy,
+    /// Frame rate the sampler uses instead of estimating one from timestamps.
+    pub fps_override: Option<f64>,
+    /// Position to seek to once the decoder opens, for a start time that could not be turned
+    /// into a start frame because the frame rate is unknown.
+    pub start_seek: Option<Duration>,
+    /// Pool the comparators' parallel sections run on; `None` uses rayon's global pool.
+    pub thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// Sampled frames the detector works on at once; results keep their sample order.
+    pub detection_concurrency: usize,
     pub detection: DetectionSettings,
     pub ocr: OcrPipelineConfig,
     pub output: OutputPipelineConfig,
@@ -58,26 +89,121 @@ pub struct PipelineConfig {
 
 #[derive(Clone)]
 pub struct OcrPipelineConfig {
-    pub engine: Arc<dyn OcrEngine>,
+    /// Synchronous engines are wrapped in `SyncOcrAdapter`.
+    pub engine: Arc<dyn AsyncOcrEngine>,
+    pub max_consecutive_failures: u32,
+    pub retries: u32,
+    pub retry_delay: Duration,
+    pub drop_invalid_text: bool,
+    /// Lines recognized with a lower confidence are dropped; `None` keeps every line.
+    pub min_confidence: Option<f32>,
+    /// With `min_confidence`, also drop lines whose engine reported no confidence.
+    pub require_confidence: bool,
+}
+
+/// Where subtitles are written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputTarget {
+    Path(PathBuf),
+    /// Cues are streamed to stdout as they become final.
+    Stdout,
 }
 
 #[derive(Clone)]
 pub struct OutputPipelineConfig {
-    pub path: PathBuf,
+    pub target: OutputTarget,
+    pub multi_region: MultiRegionMode,
+    pub format: SubtitleFormat,
+    pub max_cps: Option<f64>,
+    pub extend_fast_cues: bool,
+    /// Minimum gap between the end of a cue and the start of the next one in written files.
+    pub min_cue_gap: Duration,
+    /// Cues shown for less than this are dropped or extended per `short_cue_policy`.
+    pub min_duration: Option<Duration>,
+    pub short_cue_policy: ShortCuePolicy,
+    /// Consecutive cues with the same text closer than this are merged; zero disables it.
+    pub merge_gap: Duration,
+    pub merge_ignore_case: bool,
+    /// Written cue times become `time * rate_scale + offset_ms`, clamped at zero.
+    pub offset_ms: i64,
+    pub rate_scale: f64,
+    /// Applied to each cue's text before merging; replace it to plug in a custom filter.
+    pub text_post_processor: Arc<dyn TextPostProcessor>,
+    /// Stream finalized cues to stdout as NDJSON while the file is still being collected.
+    pub ndjson_stdout: bool,
+    pub contact_sheet: Option<ContactSheetOptions>,
+    /// Write one file per horizontal subtitle band (`name.top.srt`, `name.bottom.srt`).
+    pub split_by_position: bool,
+    /// Per-sample timeline CSV; cached runs skip detection and leave it unwritten.
+    pub timeline_csv: Option<PathBuf>,
 }
 
 impl PipelineConfig {
     pub fn from_settings(settings: &EffectiveSettings, input: &Path) -> Result<Self, DecoderError> {
-        let engine = build_ocr_engine(settings);
-        let output_path = settings
-            .output
-            .path
-            .clone()
-            .unwrap_or_else(|| default_output_path(input));
+        let engine = Arc::new(SyncOcrAdapter::new(build_ocr_engine(settings)));
+        let target = if settings.output.stdout {
+            if settings.output.ndjson_stdout {
+                return Err(DecoderError::configuration(
+                    "--ndjson-stdout cannot be combined with writing subtitles to stdout",
+                ));
+            }
+            if settings.output.split_by_position {
+                return Err(DecoderError::configuration(
+                    "--split-by-position writes one file per band and cannot stream to stdout",
+                ));
+            }
+            OutputTarget::Stdout
+        } else {
+            OutputTarget::Path(
+                settings
+                    .output
+                    .path
+                    .clone()
+                    .unwrap_or_else(|| default_output_path(input, settings.output.format)),
+            )
+        };
         Ok(Self {
+            sort_key: SortKey::default(),
+            decode_error_tolerance: settings.decoder.error_tolerance,
+            missing_frames: settings.decoder.missing_frames,
+            fps_override: settings.decoder.fps_override,
+            start_seek: None,
+            thread_pool: None,
+            detection_concurrency: 1,
             detection: settings.detection.clone(),
-            ocr: OcrPipelineConfig { engine },
-            output: OutputPipelineConfig { path: output_path },
+            ocr: OcrPipelineConfig {
+                engine,
+                max_consecutive_failures: settings.ocr.max_consecutive_failures,
+                retries: settings.ocr.retries,
+                retry_delay: settings.ocr.retry_delay,
+                drop_invalid_text: settings.ocr.drop_invalid_text,
+                min_confidence: settings.ocr.min_confidence,
+                require_confidence: settings.ocr.require_confidence,
+            },
+            output: OutputPipelineConfig {
+                target,
+                multi_region: settings.output.multi_region,
+                format: settings.output.format,
+                max_cps: settings.output.max_cps,
+                extend_fast_cues: settings.output.extend_fast_cues,
+                min_cue_gap: settings.output.min_cue_gap,
+                min_duration: settings.output.min_duration,
+                short_cue_policy: settings.output.short_cue_policy,
+                merge_gap: settings.output.merge_gap,
+                merge_ignore_case: settings.output.merge_ignore_case,
+                offset_ms: settings.output.offset_ms,
+                rate_scale: settings.output.rate_scale.unwrap_or(1.0),
+                text_post_processor: Arc::new(DefaultTextPostProcessor::new(
+                    settings
+                        .output
+                        .min_cue_chars
+                        .unwrap_or(DEFAULT_MIN_CUE_CHARS),
+                )),
+                ndjson_stdout: settings.output.ndjson_stdout,
+                contact_sheet: settings.output.contact_sheet.clone(),
+                split_by_position: settings.output.split_by_position,
+                timeline_csv: settings.output.timeline_csv.clone(),
+            },
         })
     }
 }
@@ -115,14 +241,31 @@ pub struct PipelineOutputs {
     pub stream: Pin<Box<dyn Stream<Item = PipelineResult> + Send>>,
     pub total_frames: Option<u64>,
     pub handle: PipelineHandle,
+    /// Subtitle intervals handed to OCR, for the detection cache.
+    pub intervals: IntervalLog,
+    /// ROI thumbnails for the contact sheet, when one was requested.
+    pub crops: Option<CropLog>,
+    /// Detection timeline CSV, when one was requested; finish it once the stream ends.
+    pub timeline: Option<TimelineLog>,
+    /// Frame width and height reported by the decoder, for formats that position cues.
+    pub frame_size: Option<(u32, u32)>,
+    /// Source frame rate (after `fps_override`), for reporting speed relative to real time.
+    pub source_fps: Option<f64>,
 }
 
 #[derive(Clone)]
 pub struct PipelineHandle {
     pause_tx: tokio::sync::watch::Sender<bool>,
+    stop_tx: tokio::sync::watch::Sender<bool>,
 }
 
 impl PipelineHandle {
+    /// Ends decoding early; downstream stages see end-of-stream, finalize active regions and drain.
+    pub fn stop(&self) {
+        let _ = self.stop_tx.send(true);
+        let _ = self.pause_tx.send(false);
+    }
+
     pub fn pause_sender(&self) -> tokio::sync::watch::Sender<bool> {
         self.pause_tx.clone()
     }
@@ -136,33 +279,159 @@ pub fn build_pipeline(
     provider: DynDecoderProvider,
     pipeline: &PipelineConfig,
 ) -> Result<PipelineOutputs, DecoderError> {
+    let detector_stage = Detector::new(&pipeline.detection)
+        .and_then(|detector| detector.with_concurrency(pipeline.detection_concurrency))
+        .map_err(detection_error_to_frame)?;
+    let metadata = provider.metadata();
+    let frame_size = frame_size(&metadata);
+    let source_fps = pipeline.fps_override.or(metadata.fps);
+    let (sorted, handle) = open_source(provider, pipeline)?;
+    let sampled = FrameSampler::new(pipeline.detection.samples_per_second)
+        .with_fps_override(pipeline.fps_override)
+        .with_max_history(pipeline.detection.max_history_frames)
+        .attach(sorted);
+
+    let detected = detector_stage.attach(sampled);
+    let detected =
+        DetectionSmoother::new(pipeline.detection.detection_smoothing_window).attach(detected);
+    let determined = RegionDeterminer::new().attach(detected);
+    let tracked = RegionLifecycleTracker::new(&pipeline.detection)
+        .with_thread_pool(pipeline.thread_pool.clone())
+        .attach(determined);
+
+    let timeline = match pipeline.output.timeline_csv.as_deref() {
+        Some(path) => Some(TimelineLog::create(path).map_err(|err| {
+            DecoderError::configuration(format!(
+                "failed to create timeline CSV {}: {err}",
+                path.display()
+            ))
+        })?),
+        None => None,
+    };
+    let intervals = IntervalLog::default();
+    let log = intervals.clone();
+    let timeline_log = timeline.clone();
+    let tracked = StreamBundle::new(
+        Box::pin(tracked.stream.inspect(move |event| {
+            if let Ok(event) = event {
+                event.completed.iter().for_each(|region| log.record(region));
+                if let Some(timeline) = timeline_log.as_ref() {
+                    timeline.record(event);
+                }
+            }
+        })),
+        tracked.total_frames,
+    );
+
+    let mut outputs = finish_pipeline(tracked, pipeline, handle, intervals);
+    outputs.timeline = timeline;
+    outputs.frame_size = frame_size;
+    outputs.source_fps = source_fps;
+    Ok(outputs)
+}
+
+/// Skips detection entirely and runs OCR on the frames referenced by cached intervals.
+pub fn build_cached_pipeline(
+    provider: DynDecoderProvider,
+    pipeline: &PipelineConfig,
+    intervals: Vec<CachedInterval>,
+) -> Result<PipelineOutputs, DecoderError> {
+    let metadata = provider.metadata();
+    let frame_size = frame_size(&metadata);
+    let source_fps = pipeline.fps_override.or(metadata.fps);
+    let (sorted, handle) = open_source(provider, pipeline)?;
+    let log = IntervalLog::from_intervals(intervals.clone());
+    let tracked = CachedIntervalReplay::new(intervals).attach(sorted);
+    let mut outputs = finish_pipeline(tracked, pipeline, handle, log);
+    outputs.frame_size = frame_size;
+    outputs.source_fps = source_fps;
+    Ok(outputs)
+}
+
+fn frame_size(metadata: &VideoMetadata) -> Option<(u32, u32)> {
+    metadata.width.zip(metadata.height)
+}
+
+fn open_source(
+    provider: DynDecoderProvider,
+    pipeline: &PipelineConfig,
+) -> Result<(StreamBundle<DecoderResult<VideoFrame>>, PipelineHandle), DecoderError> {
     let initial_total_frames = provider.metadata().total_frames;
-    let (_, initial_stream) = provider.open()?;
+    let (controller, initial_stream) = provider.open()?;
+    if let Some(position) = pipeline.start_seek {
+        controller.seek(SeekInfo::Time {
+            position,
+            mode: SeekMode::Accurate,
+        })?;
+    }
 
     let (pause_tx, pause_rx) = tokio::sync::watch::channel(false);
+    let (stop_tx, mut stop_rx) = tokio::sync::watch::channel(false);
+    let stopped = async move {
+        // Seeks are ignored once the controller is dropped, so it lives as long as the stream.
+        let _controller = controller;
+        if stop_rx.wait_for(|stopped| *stopped).await.is_err() {
+            // Every handle was dropped without stopping; keep decoding to the end.
+            std::future::pending::<()>().await;
+        }
+    };
 
     let paused_stream = StreamBundle::new(
-        Box::pin(PauseStream::new(initial_stream, pause_rx.clone())),
+        Box::pin(PauseStream::new(initial_stream, pause_rx).take_until(stopped)),
         initial_total_frames,
     );
 
-    let sorted = FrameSorter::new().attach(paused_stream);
-    let sampled = FrameSampler::new(pipeline.detection.samples_per_second).attach(sorted);
-
-    let detector_stage = Detector::new(&pipeline.detection).map_err(detection_error_to_frame)?;
+    let sorted = FrameSorter::with_key(pipeline.sort_key)
+        .with_error_tolerance(pipeline.decode_error_tolerance)
+        .with_missing_frame_policy(pipeline.missing_frames)
+        .attach(paused_stream);
+    Ok((sorted, PipelineHandle { pause_tx, stop_tx }))
+}
 
-    let detected = detector_stage.attach(sampled);
-    let determined = RegionDeterminer::new().attach(detected);
-    let tracked = RegionLifecycleTracker::new(&pipeline.detection).attach(determined);
-    let ocred = SubtitleOcr::new(Arc::clone(&pipeline.ocr.engine)).attach(tracked);
-    let merged: StreamBundle<MergeResult> = Merge::with_default_window().attach(ocred);
+fn finish_pipeline(
+    tracked: StreamBundle<LifecycleResult>,
+    pipeline: &PipelineConfig,
+    handle: PipelineHandle,
+    intervals: IntervalLog,
+) -> PipelineOutputs {
+    let crops = pipeline
+        .output
+        .contact_sheet
+        .as_ref()
+        .map(|options| CropLog::new(options.thumb_width));
+    let tracked = match crops.clone() {
+        Some(log) => StreamBundle::new(
+            Box::pin(tracked.stream.inspect(move |event| {
+                if let Ok(event) = event {
+                    event.completed.iter().for_each(|region| log.record(region));
+                }
+            })),
+            tracked.total_frames,
+        ),
+        None => tracked,
+    };
+    let ocred = SubtitleOcr::new(Arc::clone(&pipeline.ocr.engine))
+        .with_max_consecutive_failures(pipeline.ocr.max_consecutive_failures)
+        .with_retries(pipeline.ocr.retries, pipeline.ocr.retry_delay)
+        .with_drop_invalid_text(pipeline.ocr.drop_invalid_text)
+        .with_min_confidence(pipeline.ocr.min_confidence, pipeline.ocr.require_confidence)
+        .attach(tracked);
+    let merged: StreamBundle<MergeResult> = Merge::with_default_window()
+        .with_multi_region(pipeline.output.multi_region)
+        .with_post_processor(Arc::clone(&pipeline.output.text_post_processor))
+        .attach(ocred);
     let averaged: StreamBundle<AveragerResult> = Averager::new().attach(merged);
 
-    Ok(PipelineOutputs {
+    PipelineOutputs {
         stream: averaged.stream,
         total_frames: averaged.total_frames,
-        handle: PipelineHandle { pause_tx },
-    })
+        handle,
+        intervals,
+        crops,
+        timeline: None,
+        frame_size: None,
+        source_fps: None,
+    }
 }
 
 struct PauseStream<S> {
@@ -266,11 +535,35 @@ fn build_ocr_engine(_settings: &EffectiveSettings) -> Arc<dyn OcrEngine> {
             }
         }
     }
+    #[cfg(all(feature = "ocr-onnx", not(target_os = "macos")))]
+    {
+        match OnnxPpocrConfig::from_model_cache(&_settings.ocr.model_cache_dir)
+            .and_then(OnnxPpocrRecognizer::new)
+        {
+            Ok(engine) => return Arc::new(engine),
+            Err(err) => {
+                eprintln!("onnx OCR engine failed to initialize: {err}");
+            }
+        }
+    }
+    #[cfg(all(feature = "ocr-tesseract", not(target_os = "macos")))]
+    {
+        match TesseractOcrEngine::new() {
+            Ok(engine) => return Arc::new(engine),
+            Err(err) => {
+                eprintln!("tesseract OCR engine failed to initialize: {err}");
+            }
+        }
+    }
     Arc::new(NoopOcrEngine)
 }
 
-fn default_output_path(input: &Path) -> PathBuf {
+/// Next to the input with the format's extension; frames read from stdin name it `subtitles`.
+fn default_output_path(input: &Path, format: SubtitleFormat) -> PathBuf {
+    if input.as_os_str() == "-" {
+        return PathBuf::from("subtitles").with_extension(format.extension());
+    }
     let mut path = input.to_path_buf();
-    path.set_extension("srt");
+    path.set_extension(format.extension());
     path
 }
@@ -1,7 +1,8 @@
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use futures_util::{StreamExt, stream::unfold};
+use futures_util::{FutureExt, StreamExt, stream::unfold};
 use tokio::sync::mpsc;
 
 use super::StreamBundle;
@@ -9,8 +10,11 @@ use super::detector::DetectionSample;
 use super::lifecycle::{
     CompletedRegion, LifecycleEvent, LifecycleResult, RegionLifecycleError, RegionTimings,
 };
-use subtitle_fast_ocr::{LumaPlane, OcrEngine, OcrError, OcrRequest};
-use subtitle_fast_types::{OcrRegion, OcrResponse, RoiConfig, VideoFrame};
+use image::GrayImage;
+use subtitle_fast_ocr::{AsyncOcrEngine, OcrError, OwnedOcrRequest};
+use subtitle_fast_types::{
+    DecoderError, DecoderResult, OcrRegion, OcrResponse, RoiConfig, VideoFrame,
+};
 
 const OCR_CHANNEL_CAPACITY: usize = 4;
 
@@ -18,12 +22,52 @@ pub(crate) type RegionBounds = (usize, usize, usize, usize);
 pub type OcrStageResult = Result<OcrEvent, OcrStageError>;
 
 pub struct SubtitleOcr {
-    engine: Arc<dyn OcrEngine>,
+    engine: Arc<dyn AsyncOcrEngine>,
+    max_consecutive_failures: u32,
+    retries: u32,
+    retry_delay: Duration,
+    drop_invalid_text: bool,
+    confidence_floor: ConfidenceFloor,
 }
 
 impl SubtitleOcr {
-    pub fn new(engine: Arc<dyn OcrEngine>) -> Self {
-        Self { engine }
+    /// Synchronous engines are passed in through `SyncOcrAdapter`, which keeps their calls off
+    /// the runtime's worker threads.
+    pub fn new(engine: Arc<dyn AsyncOcrEngine>) -> Self {
+        Self {
+            engine,
+            max_consecutive_failures: 0,
+            retries: 0,
+            retry_delay: Duration::ZERO,
+            drop_invalid_text: false,
+            confidence_floor: ConfidenceFloor::default(),
+        }
+    }
+
+    pub fn with_max_consecutive_failures(mut self, limit: u32) -> Self {
+        self.max_consecutive_failures = limit;
+        self
+    }
+
+    /// Repeat a recognition that failed with a transient error up to `retries` times,
+    /// sleeping `delay` before each attempt; only the final failure counts toward the limit.
+    pub fn with_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Drop recognized lines that needed lossy UTF-8 recovery instead of only reporting them.
+    pub fn with_drop_invalid_text(mut self, drop: bool) -> Self {
+        self.drop_invalid_text = drop;
+        self
+    }
+
+    /// Drop recognized lines whose confidence is below `min`, and the cue when none remain.
+    /// Lines without a confidence are kept unless `require` is set.
+    pub fn with_min_confidence(mut self, min: Option<f32>, require: bool) -> Self {
+        self.confidence_floor = ConfidenceFloor { min, require };
+        self
     }
 
     pub fn attach(self, input: StreamBundle<LifecycleResult>) -> StreamBundle<OcrStageResult> {
@@ -33,21 +77,28 @@ impl SubtitleOcr {
         } = input;
 
         let engine = self.engine;
+        let max_consecutive_failures = self.max_consecutive_failures;
+        let (retries, retry_delay) = (self.retries, self.retry_delay);
+        let drop_invalid_text = self.drop_invalid_text;
+        let confidence_floor = self.confidence_floor;
         let (tx, rx) = mpsc::channel::<OcrStageResult>(OCR_CHANNEL_CAPACITY);
 
         tokio::spawn(async move {
-            if let Err(err) = engine.warm_up() {
+            if let Err(err) = engine.warm_up().await {
                 let _ = tx.send(Err(OcrStageError::Engine(err))).await;
                 return;
             }
 
-            let worker = OcrWorker::new(Arc::clone(&engine));
+            let mut worker = OcrWorker::new(Arc::clone(&engine), max_consecutive_failures)
+                .with_retries(retries, retry_delay)
+                .with_drop_invalid_text(drop_invalid_text)
+                .with_confidence_floor(confidence_floor);
             let mut upstream = stream;
 
             while let Some(event) = upstream.next().await {
                 match event {
                     Ok(segment_event) => {
-                        let result = worker.handle_event(segment_event);
+                        let result = worker.handle_event(segment_event).await;
                         let is_err = result.is_err();
                         if tx.send(result).await.is_err() {
                             return;
@@ -89,10 +140,35 @@ pub struct OcredSubtitle {
 pub struct OcrTimings {
     pub intervals: u64,
     pub ocr_calls: u64,
+    pub ocr_failures: u64,
+    pub invalid_texts: u64,
+    /// Lines dropped for falling under the confidence floor.
+    pub low_confidence_texts: u64,
     pub ocr_duration: Duration,
     pub total: Duration,
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct ConfidenceFloor {
+    min: Option<f32>,
+    require: bool,
+}
+
+impl ConfidenceFloor {
+    /// Removes the lines under the floor and returns how many were dropped.
+    fn apply(&self, response: &mut OcrResponse) -> usize {
+        let Some(min) = self.min else {
+            return 0;
+        };
+        let before = response.texts.len();
+        response.texts.retain(|text| match text.confidence {
+            Some(confidence) => confidence >= min,
+            None => !self.require,
+        });
+        before - response.texts.len()
+    }
+}
+
 #[derive(Debug)]
 pub enum OcrStageError {
     Lifecycle(RegionLifecycleError),
@@ -100,15 +176,78 @@ pub enum OcrStageError {
 }
 
 struct OcrWorker {
-    engine: Arc<dyn OcrEngine>,
+    engine: Arc<dyn AsyncOcrEngine>,
+    max_consecutive_failures: u32,
+    consecutive_failures: u32,
+    retries: u32,
+    retry_delay: Duration,
+    drop_invalid_text: bool,
+    confidence_floor: ConfidenceFloor,
 }
 
 impl OcrWorker {
-    fn new(engine: Arc<dyn OcrEngine>) -> Self {
-        Self { engine }
+    fn new(engine: Arc<dyn AsyncOcrEngine>, max_consecutive_failures: u32) -> Self {
+        Self {
+            engine,
+            max_consecutive_failures,
+            consecutive_failures: 0,
+            retries: 0,
+            retry_delay: Duration::ZERO,
+            drop_invalid_text: false,
+            confidence_floor: ConfidenceFloor::default(),
+        }
     }
 
-    fn handle_event(&self, event: LifecycleEvent) -> Result<OcrEvent, OcrStageError> {
+    fn with_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    fn with_drop_invalid_text(mut self, drop: bool) -> Self {
+        self.drop_invalid_text = drop;
+        self
+    }
+
+    fn with_confidence_floor(mut self, floor: ConfidenceFloor) -> Self {
+        self.confidence_floor = floor;
+        self
+    }
+
+    async fn recognize(&self, request: OwnedOcrRequest) -> Result<OcrResponse, OcrError> {
+        match AssertUnwindSafe(self.engine.recognize(request))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(OcrError::backend(format!(
+                "{} engine panicked during recognition",
+                self.engine.name()
+            ))),
+        }
+    }
+
+    async fn recognize_with_retries(
+        &self,
+        request: &OwnedOcrRequest,
+    ) -> Result<OcrResponse, OcrError> {
+        let mut attempt = 0;
+        loop {
+            match self.recognize(request.clone()).await {
+                Err(err) if err.is_transient() && attempt < self.retries => {
+                    attempt += 1;
+                    eprintln!(
+                        "ocr attempt {attempt} of {} failed: {err}; retrying",
+                        self.retries + 1
+                    );
+                    tokio::time::sleep(self.retry_delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn handle_event(&mut self, event: LifecycleEvent) -> Result<OcrEvent, OcrStageError> {
         let started = Instant::now();
         let mut timings = OcrTimings::default();
         let mut subtitles = Vec::with_capacity(event.completed.len());
@@ -120,13 +259,37 @@ impl OcrWorker {
                 continue;
             };
 
-            let plane = LumaPlane::from_frame(&lifecycle.frame);
-            let regions = [region];
-            let request = OcrRequest::new(plane, &regions);
+            let request = OwnedOcrRequest::new(Arc::clone(&lifecycle.frame), vec![region]);
             let ocr_started = Instant::now();
-            let response = match self.engine.recognize(&request) {
-                Ok(resp) => resp,
+            let mut response = match self.recognize_with_retries(&request).await {
+                Ok(mut resp) => {
+                    self.consecutive_failures = 0;
+                    let invalid = resp
+                        .texts
+                        .iter()
+                        .filter(|text| text.had_invalid_bytes)
+                        .count();
+                    if invalid > 0 {
+                        timings.invalid_texts =
+                            timings.invalid_texts.saturating_add(invalid as u64);
+                        eprintln!(
+                            "ocr returned {invalid} line(s) with invalid UTF-8 for frame {}{}",
+                            lifecycle.start_frame,
+                            if self.drop_invalid_text {
+                                "; dropping them"
+                            } else {
+                                ""
+                            }
+                        );
+                        if self.drop_invalid_text {
+                            resp.texts.retain(|text| !text.had_invalid_bytes);
+                        }
+                    }
+                    resp
+                }
                 Err(err) => {
+                    self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                    timings.ocr_failures = timings.ocr_failures.saturating_add(1);
                     eprintln!(
                         "[ocr-error-debug] frame={} roi_norm=({:.3},{:.3},{:.3},{:.3}) region_px={}x{}@({},{}) error={}",
                         lifecycle.start_frame,
@@ -140,11 +303,25 @@ impl OcrWorker {
                         bounds.1,
                         err,
                     );
-                    return Err(OcrStageError::Engine(err));
+                    if self.consecutive_failures > self.max_consecutive_failures {
+                        eprintln!(
+                            "ocr failed {} times in a row (limit {}); aborting",
+                            self.consecutive_failures, self.max_consecutive_failures
+                        );
+                        return Err(OcrStageError::Engine(err));
+                    }
+                    OcrResponse::empty()
                 }
             };
             timings.ocr_calls = timings.ocr_calls.saturating_add(1);
             timings.ocr_duration = timings.ocr_duration.saturating_add(ocr_started.elapsed());
+            let low_confidence = self.confidence_floor.apply(&mut response);
+            timings.low_confidence_texts = timings
+                .low_confidence_texts
+                .saturating_add(low_confidence as u64);
+            if low_confidence > 0 && response.texts.is_empty() {
+                continue;
+            }
             subtitles.push(OcredSubtitle {
                 lifecycle,
                 region,
@@ -163,6 +340,40 @@ impl OcrWorker {
     }
 }
 
+impl CompletedRegion {
+    /// Copies the ROI luma of the frame this segment was recognized from into a `GrayImage`.
+    pub fn crop_image(&self) -> DecoderResult<GrayImage> {
+        let frame = self.frame.as_ref();
+        if let Some(native) = frame.native() {
+            return Err(DecoderError::InvalidFrame {
+                reason: format!(
+                    "cannot crop {} native-handle frame; request NV12 output",
+                    native.backend()
+                ),
+            });
+        }
+        let region = roi_to_region(&self.roi, frame);
+        let (left, top, right, bottom) =
+            region_bounds(&region, frame).ok_or_else(|| DecoderError::InvalidFrame {
+                reason: format!("segment {} ROI is empty", self.label),
+            })?;
+
+        let width = right - left;
+        let stride = frame.y_stride();
+        let plane = frame.y_plane();
+        let mut pixels = Vec::with_capacity(width * (bottom - top));
+        for row in top..bottom {
+            let start = row * stride + left;
+            pixels.extend_from_slice(&plane[start..start + width]);
+        }
+        GrayImage::from_raw(width as u32, (bottom - top) as u32, pixels).ok_or_else(|| {
+            DecoderError::InvalidFrame {
+                reason: "cropped luma buffer does not match ROI size".into(),
+            }
+        })
+    }
+}
+
 fn roi_to_region(roi: &RoiConfig, frame: &VideoFrame) -> OcrRegion {
     let width = frame.width().max(1) as f32;
     let height = frame.height().max(1) as f32;
@@ -212,8 +423,313 @@ pub(crate) fn region_bounds(region: &OcrRegion, frame: &VideoFrame) -> Option<Re
 
 #[cfg(test)]
 mod tests {
-    use super::roi_to_region;
-    use subtitle_fast_types::{RoiConfig, VideoFrame};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use subtitle_fast_ocr::{OcrEngine, OcrRequest, SyncOcrAdapter};
+    use subtitle_fast_types::{OcrText, RoiConfig, VideoFrame};
+
+    struct FailingEngine;
+
+    /// Fails transiently `failures` times, then recognizes a fixed line.
+    struct FlakyEngine {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl OcrEngine for FlakyEngine {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(OcrError::transient("vision request failed"));
+            }
+            Ok(OcrResponse::new(vec![OcrText::from_utf8_lossy(
+                request.regions()[0],
+                b"recovered",
+            )]))
+        }
+    }
+
+    /// Returns the given raw bytes as a single recognized line.
+    struct RawBytesEngine(&'static [u8]);
+
+    impl OcrEngine for RawBytesEngine {
+        fn name(&self) -> &'static str {
+            "raw-bytes"
+        }
+
+        fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+            let region = request.regions()[0];
+            Ok(OcrResponse::new(vec![OcrText::from_utf8_lossy(
+                region, self.0,
+            )]))
+        }
+    }
+
+    /// Returns one line per entry, named after its index, with that confidence.
+    struct ScoredEngine(&'static [Option<f32>]);
+
+    impl OcrEngine for ScoredEngine {
+        fn name(&self) -> &'static str {
+            "scored"
+        }
+
+        fn recognize(&self, request: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+            let region = request.regions()[0];
+            let texts = self
+                .0
+                .iter()
+                .enumerate()
+                .map(|(idx, confidence)| {
+                    let text = OcrText::new(region, format!("line {idx}"));
+                    match confidence {
+                        Some(value) => text.with_confidence(*value),
+                        None => text,
+                    }
+                })
+                .collect();
+            Ok(OcrResponse::new(texts))
+        }
+    }
+
+    impl OcrEngine for FailingEngine {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn recognize(&self, _: &OcrRequest<'_>) -> Result<OcrResponse, OcrError> {
+            Err(OcrError::backend("simulated crash"))
+        }
+    }
+
+    fn sync_worker(engine: Arc<dyn OcrEngine>, max_consecutive_failures: u32) -> OcrWorker {
+        OcrWorker::new(
+            Arc::new(SyncOcrAdapter::new(engine)),
+            max_consecutive_failures,
+        )
+    }
+
+    fn completed_event(id: u64) -> LifecycleEvent {
+        let frame = VideoFrame::from_nv12_owned(
+            64,
+            32,
+            64,
+            64,
+            None,
+            None,
+            vec![0; 64 * 32],
+            vec![128; 64 * 16],
+        )
+        .unwrap();
+        LifecycleEvent {
+            sample: None,
+            completed: vec![CompletedRegion {
+                id,
+                label: format!("region-{id}"),
+                start_time: Duration::from_millis(id * 1000),
+                end_time: Duration::from_millis(id * 1000 + 500),
+                start_frame: id * 30,
+                end_frame: id * 30 + 15,
+                roi: RoiConfig {
+                    x: 0.1,
+                    y: 0.5,
+                    width: 0.8,
+                    height: 0.4,
+                },
+                frame: Arc::new(frame),
+                max_score: 1.0,
+            }],
+            active: Vec::new(),
+            region_timings: None,
+        }
+    }
+
+    #[test]
+    fn crop_image_matches_roi_pixel_size() {
+        let event = completed_event(0);
+        let segment = &event.completed[0];
+        let image = segment.crop_image().expect("nv12 crop");
+        let region = roi_to_region(&segment.roi, &segment.frame);
+        let (left, top, right, bottom) = region_bounds(&region, &segment.frame).unwrap();
+        assert_eq!(image.width() as usize, right - left);
+        assert_eq!(image.height() as usize, bottom - top);
+        assert_eq!((image.width(), image.height()), (52, 13));
+    }
+
+    #[test]
+    fn crop_image_skips_stride_padding() {
+        // 64x32 luma with a 32-byte row padding of bright bytes; ROI rows hold their row number.
+        let stride = 96;
+        let mut y_plane = vec![255; stride * 32];
+        for row in 0..32 {
+            y_plane[row * stride..row * stride + 64].fill(row as u8);
+        }
+        let frame = VideoFrame::from_nv12_owned(
+            64,
+            32,
+            stride,
+            stride,
+            None,
+            None,
+            y_plane,
+            vec![128; stride * 16],
+        )
+        .unwrap();
+        let mut event = completed_event(0);
+        event.completed[0].frame = Arc::new(frame);
+        let segment = &event.completed[0];
+
+        let image = segment.crop_image().expect("nv12 crop");
+        let region = roi_to_region(&segment.roi, &segment.frame);
+        let (_, top, _, _) = region_bounds(&region, &segment.frame).unwrap();
+        for (y, row) in image.rows().enumerate() {
+            assert!(row.into_iter().all(|pixel| pixel.0[0] == (top + y) as u8));
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_engine_survives_under_limit() {
+        let mut worker = sync_worker(Arc::new(FailingEngine), 2);
+        for id in 0..2 {
+            let event = worker
+                .handle_event(completed_event(id))
+                .await
+                .expect("failure within limit keeps the cue");
+            assert_eq!(event.regions.len(), 1);
+            assert!(event.regions[0].response.texts.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_engine_aborts_above_limit() {
+        let mut worker = sync_worker(Arc::new(FailingEngine), 2);
+        worker.handle_event(completed_event(0)).await.unwrap();
+        worker.handle_event(completed_event(1)).await.unwrap();
+        let result = worker.handle_event(completed_event(2)).await;
+        assert!(matches!(result, Err(OcrStageError::Engine(_))));
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_until_the_cue_is_recovered() {
+        let engine = Arc::new(FlakyEngine {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        });
+        let mut worker = sync_worker(engine.clone(), 0).with_retries(2, Duration::ZERO);
+
+        let event = worker.handle_event(completed_event(0)).await.unwrap();
+
+        assert_eq!(engine.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(event.regions[0].response.texts[0].text, "recovered");
+        assert_eq!(event.timings.unwrap().ocr_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_are_not_retried() {
+        let mut worker = sync_worker(Arc::new(FailingEngine), 1).with_retries(2, Duration::ZERO);
+        let event = worker.handle_event(completed_event(0)).await.unwrap();
+        assert!(event.regions[0].response.texts.is_empty());
+        assert_eq!(event.timings.unwrap().ocr_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8_sets_flag_and_can_be_dropped() {
+        let bytes: &'static [u8] = b"caf\xc3 ok";
+        let mut worker = sync_worker(Arc::new(RawBytesEngine(bytes)), 0);
+        let event = worker.handle_event(completed_event(0)).await.unwrap();
+        let texts = &event.regions[0].response.texts;
+        assert_eq!(texts.len(), 1);
+        assert!(texts[0].had_invalid_bytes);
+        assert_eq!(texts[0].text, "caf\u{fffd} ok");
+        assert_eq!(event.timings.unwrap().invalid_texts, 1);
+
+        let mut dropping =
+            sync_worker(Arc::new(RawBytesEngine(bytes)), 0).with_drop_invalid_text(true);
+        let event = dropping.handle_event(completed_event(0)).await.unwrap();
+        assert!(event.regions[0].response.texts.is_empty());
+
+        let mut clean = sync_worker(Arc::new(RawBytesEngine("café".as_bytes())), 0)
+            .with_drop_invalid_text(true);
+        let event = clean.handle_event(completed_event(0)).await.unwrap();
+        assert!(!event.regions[0].response.texts[0].had_invalid_bytes);
+    }
+
+    #[tokio::test]
+    async fn confidence_floor_drops_low_lines_and_empty_cues() {
+        const MIXED: &[Option<f32>] = &[Some(0.9), Some(0.2), None, Some(0.5)];
+        let lines = async |floor: ConfidenceFloor| {
+            let mut worker =
+                sync_worker(Arc::new(ScoredEngine(MIXED)), 0).with_confidence_floor(floor);
+            let event = worker.handle_event(completed_event(0)).await.unwrap();
+            let texts: Vec<String> = event.regions[0]
+                .response
+                .texts
+                .iter()
+                .map(|text| text.text.clone())
+                .collect();
+            (texts, event.timings.unwrap().low_confidence_texts)
+        };
+
+        assert_eq!(lines(ConfidenceFloor::default()).await.0.len(), 4);
+        let lenient = ConfidenceFloor {
+            min: Some(0.5),
+            require: false,
+        };
+        assert_eq!(
+            lines(lenient).await,
+            (vec!["line 0".into(), "line 2".into(), "line 3".into()], 1)
+        );
+        let strict = ConfidenceFloor {
+            min: Some(0.5),
+            require: true,
+        };
+        assert_eq!(
+            lines(strict).await,
+            (vec!["line 0".into(), "line 3".into()], 2)
+        );
+
+        let mut worker = sync_worker(Arc::new(ScoredEngine(&[Some(0.1), None])), 0)
+            .with_confidence_floor(strict);
+        let event = worker.handle_event(completed_event(0)).await.unwrap();
+        assert!(event.regions.is_empty());
+        assert_eq!(event.timings.unwrap().low_confidence_texts, 2);
+    }
+
+    #[test]
+    fn confidence_floor_filters_a_mixed_response() {
+        let region = OcrRegion::new(0.0, 0.0, 10.0, 4.0);
+        let response = || {
+            OcrResponse::new(vec![
+                OcrText::new(region, "clear".into()).with_confidence(0.95),
+                OcrText::new(region, "garbage".into()).with_confidence(0.3),
+                OcrText::new(region, "unscored".into()),
+            ])
+        };
+        let kept = |floor: ConfidenceFloor| {
+            let mut response = response();
+            let dropped = floor.apply(&mut response);
+            let texts: Vec<String> = response.texts.into_iter().map(|text| text.text).collect();
+            (texts, dropped)
+        };
+
+        let keep_unscored = ConfidenceFloor {
+            min: Some(0.6),
+            require: false,
+        };
+        assert_eq!(
+            kept(keep_unscored),
+            (vec!["clear".into(), "unscored".into()], 1)
+        );
+        let drop_unscored = ConfidenceFloor {
+            min: Some(0.6),
+            require: true,
+        };
+        assert_eq!(kept(drop_unscored), (vec!["clear".into()], 2));
+        assert_eq!(kept(ConfidenceFloor::default()).1, 0);
+    }
 
     #[test]
     fn roi_to_region_clamps_to_bounds() {
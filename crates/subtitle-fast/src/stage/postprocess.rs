@@ -0,0 +1,80 @@
+/// Cues whose text has fewer visible characters than this are dropped by default.
+pub const DEFAULT_MIN_CUE_CHARS: usize = 2;
+
+/// Cleans up recognized cue text after whitespace normalization and before merging.
+///
+/// Returning `None` drops the cue; returning a different string replaces its text.
+pub trait TextPostProcessor: Send + Sync {
+    fn process(&self, text: &str) -> Option<String>;
+}
+
+/// Strips punctuation-only lines from the start and end of a cue and drops cues left with
+/// fewer than `min_chars` visible characters, which filters most stray OCR specks.
+#[derive(Debug, Clone)]
+pub struct DefaultTextPostProcessor {
+    min_chars: usize,
+}
+
+impl DefaultTextPostProcessor {
+    pub fn new(min_chars: usize) -> Self {
+        Self { min_chars }
+    }
+}
+
+impl Default for DefaultTextPostProcessor {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN_CUE_CHARS)
+    }
+}
+
+impl TextPostProcessor for DefaultTextPostProcessor {
+    fn process(&self, text: &str) -> Option<String> {
+        let lines: Vec<&str> = text.lines().collect();
+        let first = lines.iter().position(|line| !is_punctuation_only(line))?;
+        let last = lines.iter().rposition(|line| !is_punctuation_only(line))?;
+        let kept = lines[first..=last].join("\n");
+        let visible = kept.chars().filter(|ch| !ch.is_whitespace()).count();
+        (visible >= self.min_chars).then_some(kept)
+    }
+}
+
+fn is_punctuation_only(line: &str) -> bool {
+    line.chars()
+        .all(|ch| ch.is_whitespace() || ch.is_ascii_punctuation() || is_unicode_punctuation(ch))
+}
+
+/// Common non-ASCII punctuation OCR engines produce for specks and borders.
+fn is_unicode_punctuation(ch: char) -> bool {
+    "。，、；：？！…—–·・「」『』（）《》“”‘’¡¿".contains(ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_character_cue_is_dropped() {
+        let filter = DefaultTextPostProcessor::default();
+        assert_eq!(filter.process("l"), None);
+        assert_eq!(filter.process("-\n|\n."), None);
+    }
+
+    #[test]
+    fn real_line_is_kept_and_punctuation_lines_are_stripped() {
+        let filter = DefaultTextPostProcessor::default();
+        assert_eq!(
+            filter.process("Where are you going?").as_deref(),
+            Some("Where are you going?")
+        );
+        assert_eq!(
+            filter.process("..\nI'm right here.\n- -").as_deref(),
+            Some("I'm right here.")
+        );
+    }
+
+    #[test]
+    fn min_chars_is_configurable() {
+        let filter = DefaultTextPostProcessor::new(1);
+        assert_eq!(filter.process("嗯").as_deref(), Some("嗯"));
+    }
+}
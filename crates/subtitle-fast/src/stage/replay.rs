@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::{Stream, StreamExt};
+
+use super::StreamBundle;
+use super::detector::DetectorError;
+use super::determiner::RegionDeterminerError;
+use super::lifecycle::{CompletedRegion, LifecycleEvent, LifecycleResult, RegionLifecycleError};
+use crate::cache::CachedInterval;
+use subtitle_fast_types::{DecoderResult, VideoFrame};
+
+/// Stands in for detection and lifecycle tracking by re-emitting cached intervals when the
+/// frame each one was recognized from comes by again.
+pub struct CachedIntervalReplay {
+    intervals: Vec<CachedInterval>,
+}
+
+impl CachedIntervalReplay {
+    pub fn new(intervals: Vec<CachedInterval>) -> Self {
+        Self { intervals }
+    }
+
+    pub fn attach(
+        self,
+        input: StreamBundle<DecoderResult<VideoFrame>>,
+    ) -> StreamBundle<LifecycleResult> {
+        let StreamBundle {
+            stream,
+            total_frames,
+        } = input;
+
+        let mut pending: BTreeMap<u64, Vec<CachedInterval>> = BTreeMap::new();
+        for interval in self.intervals {
+            pending
+                .entry(interval.frame_index)
+                .or_default()
+                .push(interval);
+        }
+
+        let state = ReplayState {
+            upstream: stream,
+            pending,
+        };
+        let stream = Box::pin(futures_util::stream::unfold(state, ReplayState::next));
+        StreamBundle::new(stream, total_frames)
+    }
+}
+
+struct ReplayState {
+    upstream: Pin<Box<dyn Stream<Item = DecoderResult<VideoFrame>> + Send>>,
+    pending: BTreeMap<u64, Vec<CachedInterval>>,
+}
+
+impl ReplayState {
+    async fn next(mut state: ReplayState) -> Option<(LifecycleResult, ReplayState)> {
+        // Stop decoding once every cached frame has been seen.
+        while !state.pending.is_empty() {
+            match state.upstream.as_mut().next().await? {
+                Ok(frame) => {
+                    let Some(index) = frame.index() else {
+                        continue;
+                    };
+                    let Some(intervals) = state.pending.remove(&index) else {
+                        continue;
+                    };
+                    let frame = Arc::new(frame);
+                    let completed = intervals
+                        .into_iter()
+                        .map(|interval| CompletedRegion {
+                            id: interval.id,
+                            label: interval.label.clone(),
+                            start_time: interval.start_time(),
+                            end_time: interval.end_time(),
+                            start_frame: interval.start_frame,
+                            end_frame: interval.end_frame,
+                            roi: interval.roi(),
+                            frame: Arc::clone(&frame),
+                            max_score: interval.max_score,
+                        })
+                        .collect();
+                    let event = LifecycleEvent {
+                        sample: None,
+                        completed,
+                        active: Vec::new(),
+                        region_timings: None,
+                    };
+                    return Some((Ok(event), state));
+                }
+                Err(err) => {
+                    state.pending.clear();
+                    let err = RegionLifecycleError::Determiner(RegionDeterminerError::Detector(
+                        DetectorError::Sampler(err),
+                    ));
+                    return Some((Err(err), state));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::cache::CachedRoi;
+
+    fn frame(index: u64) -> VideoFrame {
+        VideoFrame::from_nv12_owned(
+            4,
+            2,
+            4,
+            4,
+            Some(Duration::from_millis(index * 40)),
+            None,
+            vec![0; 8],
+            vec![128; 4],
+        )
+        .unwrap()
+        .with_index(Some(index))
+    }
+
+    fn interval(id: u64, frame_index: u64) -> CachedInterval {
+        CachedInterval {
+            id,
+            label: format!("region-{id}"),
+            start_us: frame_index * 40_000,
+            end_us: frame_index * 40_000 + 500_000,
+            start_frame: frame_index,
+            end_frame: frame_index + 12,
+            roi: CachedRoi {
+                x: 0.0,
+                y: 0.5,
+                width: 1.0,
+                height: 0.5,
+            },
+            frame_index,
+            max_score: 0.5 + id as f32 / 10.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_cached_intervals_at_their_frames() {
+        let frames: Vec<DecoderResult<VideoFrame>> = (0..20).map(|i| Ok(frame(i))).collect();
+        let input = StreamBundle::new(Box::pin(futures_util::stream::iter(frames)), Some(20));
+        let replay = CachedIntervalReplay::new(vec![interval(1, 3), interval(2, 9)]);
+
+        let events: Vec<_> = replay.attach(input).stream.collect().await;
+
+        let replayed: Vec<(u64, Option<u64>, f32)> = events
+            .iter()
+            .flat_map(|event| event.as_ref().unwrap().completed.iter())
+            .map(|region| (region.id, region.frame.index(), region.max_score))
+            .collect();
+        assert_eq!(replayed, vec![(1, Some(3), 0.6), (2, Some(9), 0.7)]);
+    }
+}
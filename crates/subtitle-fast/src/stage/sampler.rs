@@ -10,7 +10,11 @@ use subtitle_fast_types::{DecoderError, DecoderResult, VideoFrame};
 
 const SAMPLER_CHANNEL_CAPACITY: usize = 1;
 const DEFAULT_POOL_CAPACITY: usize = 24;
-const MAX_POOL_CAPACITY: usize = 240;
+/// Hard ceiling on retained history frames, whatever the configured cap.
+pub const MAX_POOL_CAPACITY: usize = 240;
+/// History normally spans one second of frames; above this frame rate it is cut short so
+/// 240 fps sources do not pin hundreds of decoded frames in memory.
+pub const DEFAULT_MAX_HISTORY_FRAMES: usize = 120;
 const EPSILON: f64 = 1e-6;
 
 pub type SamplerResult = Result<SampledFrame, DecoderError>;
@@ -27,7 +31,7 @@ pub struct SamplerContext {
 }
 
 impl SamplerContext {
-    fn initial() -> Self {
+    pub(crate) fn initial() -> Self {
         Self {
             estimated_fps: None,
         }
@@ -52,7 +56,7 @@ pub struct SampledFrame {
 }
 
 impl SampledFrame {
-    fn new(
+    pub(crate) fn new(
         frame_index: u64,
         frame: Arc<VideoFrame>,
         history: FrameHistory,
@@ -89,11 +93,31 @@ impl SampledFrame {
 
 pub struct FrameSampler {
     samples_per_second: u32,
+    fps_override: Option<f64>,
+    max_history: usize,
 }
 
 impl FrameSampler {
     pub fn new(samples_per_second: u32) -> Self {
-        Self { samples_per_second }
+        Self {
+            samples_per_second,
+            fps_override: None,
+            max_history: DEFAULT_MAX_HISTORY_FRAMES,
+        }
+    }
+
+    /// Caps how many recent frames each sample's history retains; start and end refinement
+    /// then look back at most this many frames.
+    pub fn with_max_history(mut self, frames: usize) -> Self {
+        self.max_history = frames;
+        self
+    }
+
+    /// Uses `fps` for the history span and timestamp interpolation instead of estimating it
+    /// from frame timestamps.
+    pub fn with_fps_override(mut self, fps: Option<f64>) -> Self {
+        self.fps_override = fps;
+        self
     }
 }
 
@@ -108,11 +132,15 @@ impl FrameSampler {
         } = input;
 
         let samples_per_second = self.samples_per_second;
+        let fps_override = self.fps_override;
+        let max_history = self.max_history;
         let (tx, rx) = mpsc::channel::<SamplerResult>(SAMPLER_CHANNEL_CAPACITY);
 
         tokio::spawn(async move {
             let mut upstream = stream;
-            let mut worker = SamplerWorker::new(samples_per_second);
+            let mut worker = SamplerWorker::new(samples_per_second)
+                .with_max_history(max_history)
+                .with_fixed_fps(fps_override);
 
             while let Some(maybe_item) = upstream.next().await {
                 match maybe_item {
@@ -144,6 +172,8 @@ struct SamplerWorker {
     pool: SamplerPool,
     schedule: SampleSchedule,
     fps: FpsEstimator,
+    fixed_fps: bool,
+    max_history: usize,
     context: Arc<SamplerContext>,
     last_sampled_index: Option<u64>,
 }
@@ -155,11 +185,28 @@ impl SamplerWorker {
             pool: SamplerPool::new(DEFAULT_POOL_CAPACITY),
             schedule: SampleSchedule::new(samples_per_second),
             fps: FpsEstimator::new(),
+            fixed_fps: false,
+            max_history: DEFAULT_MAX_HISTORY_FRAMES,
             context: Arc::new(SamplerContext::initial()),
             last_sampled_index: None,
         }
     }
 
+    fn with_max_history(mut self, frames: usize) -> Self {
+        self.max_history = frames.max(1);
+        self.pool
+            .set_capacity(self.pool.capacity.min(self.max_history));
+        self
+    }
+
+    fn with_fixed_fps(mut self, fps: Option<f64>) -> Self {
+        if let Some(fps) = fps {
+            self.update_tuning(fps);
+            self.fixed_fps = true;
+        }
+        self
+    }
+
     async fn handle_frame(
         &mut self,
         frame: VideoFrame,
@@ -179,7 +226,9 @@ impl SamplerWorker {
             FrameType::Skipped
         };
 
-        if let Some(fps) = self.fps.observe(frame_index, timestamp) {
+        if let Some(fps) = self.fps.observe(frame_index, timestamp)
+            && !self.fixed_fps
+        {
             self.update_tuning(fps);
         }
 
@@ -237,8 +286,8 @@ impl SamplerWorker {
         } else {
             DEFAULT_POOL_CAPACITY
         };
-        if capacity > MAX_POOL_CAPACITY {
-            capacity = MAX_POOL_CAPACITY;
+        if capacity > self.max_history {
+            capacity = self.max_history;
         }
         self.pool.set_capacity(capacity);
         self.context = Arc::new(SamplerContext::with_estimate(fps));
@@ -558,4 +607,115 @@ mod tests {
             "final history should include latest frame"
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fps_override_replaces_estimate_for_span_and_timing() {
+        let mut worker = SamplerWorker::new(30).with_fixed_fps(Some(10.0));
+        let (tx, mut rx) = mpsc::channel(64);
+
+        // Timestamps say 30 fps; the override must win.
+        for index in 0..60u64 {
+            let frame = VideoFrame::from_nv12_owned(
+                2,
+                2,
+                2,
+                2,
+                Some(Duration::from_secs_f64(index as f64 / 30.0)),
+                None,
+                vec![0; 4],
+                vec![128; 2],
+            )
+            .unwrap()
+            .with_index(Some(index));
+            worker
+                .handle_frame(frame, &tx)
+                .await
+                .expect("frame handled");
+        }
+        drop(tx);
+
+        let mut last = None;
+        while let Some(sample) = rx.recv().await {
+            last = Some(sample.expect("sample"));
+        }
+        let sample = last.expect("at least one sample");
+        assert_eq!(sample.sampler_context().estimated_fps(), Some(10.0));
+        assert_eq!(sample.history().records().len(), 10);
+    }
+
+    #[test]
+    fn history_pool_evicts_oldest_frames_past_capacity() {
+        let frame = Arc::new(
+            VideoFrame::from_nv12_owned(2, 2, 2, 2, None, None, vec![0; 4], vec![128; 2]).unwrap(),
+        );
+        let mut pool = SamplerPool::new(4);
+        for index in 0..10u64 {
+            pool.push(PoolEntry::new(
+                index,
+                FrameType::Skipped,
+                Arc::clone(&frame),
+            ));
+        }
+        let retained: Vec<u64> = pool
+            .snapshot()
+            .records()
+            .iter()
+            .map(|record| record.frame_index)
+            .collect();
+        assert_eq!(retained, vec![6, 7, 8, 9]);
+
+        pool.set_capacity(2);
+        let retained: Vec<u64> = pool
+            .snapshot()
+            .records()
+            .iter()
+            .map(|record| record.frame_index)
+            .collect();
+        assert_eq!(retained, vec![8, 9]);
+    }
+
+    async fn last_history_len_at_240fps(worker: SamplerWorker) -> usize {
+        let mut worker = worker;
+        let (tx, mut rx) = mpsc::channel(2048);
+        for index in 0..480u64 {
+            let frame = VideoFrame::from_nv12_owned(
+                2,
+                2,
+                2,
+                2,
+                Some(Duration::from_secs_f64(index as f64 / 240.0)),
+                None,
+                vec![0; 4],
+                vec![128; 2],
+            )
+            .unwrap()
+            .with_index(Some(index));
+            worker
+                .handle_frame(frame, &tx)
+                .await
+                .expect("frame handled");
+        }
+        drop(tx);
+
+        let mut last = None;
+        while let Some(sample) = rx.recv().await {
+            last = Some(sample.expect("sample"));
+        }
+        let sample = last.expect("at least one sample");
+        let fps = sample.sampler_context().estimated_fps().expect("estimate");
+        assert!((fps - 240.0).abs() < 1.0, "estimated {fps}");
+        sample.history().records().len()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn history_is_capped_at_high_frame_rates() {
+        assert_eq!(
+            last_history_len_at_240fps(SamplerWorker::new(7)).await,
+            DEFAULT_MAX_HISTORY_FRAMES
+        );
+        assert_eq!(
+            last_history_len_at_240fps(SamplerWorker::new(7).with_max_history(48)).await,
+            48
+        );
+    }
 }
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+use futures_util::{Stream, StreamExt};
+
+use super::StreamBundle;
+use super::detector::{DetectionSample, DetectionSampleResult};
+use subtitle_fast_types::SubtitleDetectionResult;
+
+/// Majority-votes each sample's subtitle flag over a centered window of neighbouring samples
+/// so that isolated flips do not split or spawn regions downstream.
+pub struct DetectionSmoother {
+    window: usize,
+}
+
+impl DetectionSmoother {
+    pub fn new(window: usize) -> Self {
+        Self { window }
+    }
+
+    pub fn attach(
+        self,
+        input: StreamBundle<DetectionSampleResult>,
+    ) -> StreamBundle<DetectionSampleResult> {
+        if self.window <= 1 {
+            return input;
+        }
+        let StreamBundle {
+            stream,
+            total_frames,
+        } = input;
+        let state = SmootherState {
+            upstream: stream,
+            window: MajorityWindow::new(self.window / 2),
+            ready: VecDeque::new(),
+            finished: false,
+        };
+        let stream = Box::pin(futures_util::stream::unfold(state, SmootherState::next));
+        StreamBundle::new(stream, total_frames)
+    }
+}
+
+struct SmootherState {
+    upstream: Pin<Box<dyn Stream<Item = DetectionSampleResult> + Send>>,
+    window: MajorityWindow<DetectionSample>,
+    ready: VecDeque<DetectionSampleResult>,
+    finished: bool,
+}
+
+impl SmootherState {
+    async fn next(mut state: SmootherState) -> Option<(DetectionSampleResult, SmootherState)> {
+        loop {
+            if let Some(item) = state.ready.pop_front() {
+                return Some((item, state));
+            }
+            if state.finished {
+                return None;
+            }
+            match state.upstream.next().await {
+                Some(Ok(sample)) => {
+                    if let Some(smoothed) = state.window.push(sample) {
+                        state.ready.push_back(Ok(smoothed));
+                    }
+                }
+                // Samples still waiting for their right-hand neighbours are voted on what was
+                // seen; an error ends the stream after them.
+                Some(Err(err)) => {
+                    state
+                        .ready
+                        .extend(state.window.finish().into_iter().map(Ok));
+                    state.ready.push_back(Err(err));
+                    state.finished = true;
+                }
+                None => {
+                    state
+                        .ready
+                        .extend(state.window.finish().into_iter().map(Ok));
+                    state.finished = true;
+                }
+            }
+        }
+    }
+}
+
+trait Smoothable {
+    fn detection(&self) -> &SubtitleDetectionResult;
+    fn detection_mut(&mut self) -> &mut SubtitleDetectionResult;
+}
+
+impl Smoothable for DetectionSample {
+    fn detection(&self) -> &SubtitleDetectionResult {
+        &self.detection
+    }
+
+    fn detection_mut(&mut self) -> &mut SubtitleDetectionResult {
+        &mut self.detection
+    }
+}
+
+/// Holds up to `radius` samples until their right-hand neighbours arrive, plus the raw
+/// detections of the last `2 * radius` samples they are voted against.
+struct MajorityWindow<T> {
+    radius: usize,
+    raw: VecDeque<SubtitleDetectionResult>,
+    pending: VecDeque<T>,
+}
+
+impl<T: Smoothable> MajorityWindow<T> {
+    fn new(radius: usize) -> Self {
+        Self {
+            radius,
+            raw: VecDeque::with_capacity(2 * radius + 1),
+            pending: VecDeque::with_capacity(radius + 1),
+        }
+    }
+
+    fn push(&mut self, item: T) -> Option<T> {
+        self.raw.push_back(item.detection().clone());
+        self.pending.push_back(item);
+        if self.pending.len() <= self.radius {
+            return None;
+        }
+        let center = self.raw.len() - 1 - self.radius;
+        let item = self
+            .pending
+            .pop_front()
+            .map(|item| self.smooth(item, center));
+        while self.raw.len() > 2 * self.radius {
+            self.raw.pop_front();
+        }
+        item
+    }
+
+    /// Emits the remaining samples with the window truncated at the end of the stream.
+    fn finish(&mut self) -> Vec<T> {
+        let mut items = Vec::with_capacity(self.pending.len());
+        while let Some(item) = self.pending.pop_front() {
+            let center = self.raw.len() - 1 - self.pending.len();
+            items.push(self.smooth(item, center));
+        }
+        self.raw.clear();
+        items
+    }
+
+    fn smooth(&self, mut item: T, center: usize) -> T {
+        let start = center.saturating_sub(self.radius);
+        let end = (center + self.radius).min(self.raw.len() - 1);
+        let votes = end - start + 1;
+        let positives = (start..=end)
+            .filter(|&idx| self.raw[idx].has_subtitle)
+            .count();
+        let raw = item.detection().has_subtitle;
+        // Truncated windows at the stream edges can tie; keep the raw decision then.
+        let smoothed = if positives * 2 == votes {
+            raw
+        } else {
+            positives * 2 > votes
+        };
+        if smoothed == raw {
+            return item;
+        }
+        let detection = item.detection_mut();
+        if smoothed {
+            // Borrow regions from the closest positive neighbour, preferring the earlier one.
+            let donor = (1..=self.radius)
+                .flat_map(|offset| [center.checked_sub(offset), Some(center + offset)])
+                .flatten()
+                .filter(|&idx| idx >= start && idx <= end)
+                .find(|&idx| self.raw[idx].has_subtitle);
+            if let Some(donor) = donor {
+                detection.regions = self.raw[donor].regions.clone();
+                detection.max_score = self.raw[donor].max_score;
+            }
+            detection.has_subtitle = true;
+        } else {
+            detection.has_subtitle = false;
+            detection.regions.clear();
+        }
+        item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use subtitle_fast_types::DetectionRegion;
+
+    use super::*;
+
+    impl Smoothable for SubtitleDetectionResult {
+        fn detection(&self) -> &SubtitleDetectionResult {
+            self
+        }
+
+        fn detection_mut(&mut self) -> &mut SubtitleDetectionResult {
+            self
+        }
+    }
+
+    fn detection(has_subtitle: bool) -> SubtitleDetectionResult {
+        let mut result = SubtitleDetectionResult::empty();
+        if has_subtitle {
+            result.has_subtitle = true;
+            result.max_score = 0.9;
+            result.regions.push(DetectionRegion {
+                x: 100.0,
+                y: 300.0,
+                width: 400.0,
+                height: 40.0,
+                score: 0.9,
+            });
+        }
+        result
+    }
+
+    fn run(window: usize, flags: &[bool]) -> Vec<SubtitleDetectionResult> {
+        let mut smoother = MajorityWindow::new(window / 2);
+        let mut out: Vec<_> = flags
+            .iter()
+            .filter_map(|&flag| smoother.push(detection(flag)))
+            .collect();
+        out.extend(smoother.finish());
+        out
+    }
+
+    #[test]
+    fn lone_false_negative_inside_positive_run_is_filled() {
+        let out = run(3, &[true, true, false, true, true]);
+
+        assert_eq!(out.len(), 5);
+        assert!(out.iter().all(|result| result.has_subtitle));
+        assert_eq!(out[2].regions.len(), 1);
+        assert_eq!(out[2].regions[0].width, 400.0);
+    }
+
+    #[test]
+    fn lone_false_positive_is_cleared_and_runs_survive() {
+        let flags = [false, false, true, false, false, true, true, true];
+        let out: Vec<bool> = run(3, &flags)
+            .iter()
+            .map(|result| result.has_subtitle)
+            .collect();
+
+        assert_eq!(
+            out,
+            vec![false, false, false, false, false, true, true, true]
+        );
+    }
+
+    #[test]
+    fn window_of_one_keeps_every_decision() {
+        let flags = [true, false, true, false];
+        let out: Vec<bool> = run(1, &flags)
+            .iter()
+            .map(|result| result.has_subtitle)
+            .collect();
+
+        assert_eq!(out, flags);
+    }
+}
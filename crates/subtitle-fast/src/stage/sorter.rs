@@ -1,16 +1,87 @@
 use std::collections::BTreeMap;
 use std::pin::Pin;
+use std::str::FromStr;
 
 use futures_util::{Stream, StreamExt};
 
 use super::StreamBundle;
-use subtitle_fast_types::{DecoderResult, VideoFrame};
+use subtitle_fast_types::{DecoderError, DecoderResult, VideoFrame};
 
-pub struct FrameSorter;
+/// Primary key used to order decoded frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Order by the decoder-assigned frame index, falling back to the timestamp.
+    #[default]
+    FrameIndex,
+    /// Order by presentation timestamp, falling back to the frame index.
+    Timestamp,
+}
+
+/// What the sorter does when consecutive frames skip an index, i.e. the decoder dropped frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingFramePolicy {
+    #[default]
+    Ignore,
+    /// Log every gap and keep going.
+    Warn,
+    /// End the stream with an error at the first gap.
+    Fail,
+}
+
+impl MissingFramePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissingFramePolicy::Ignore => "ignore",
+            MissingFramePolicy::Warn => "warn",
+            MissingFramePolicy::Fail => "fail",
+        }
+    }
+}
+
+impl FromStr for MissingFramePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "ignore" => Ok(MissingFramePolicy::Ignore),
+            "warn" => Ok(MissingFramePolicy::Warn),
+            "fail" => Ok(MissingFramePolicy::Fail),
+            _ => Err(lower),
+        }
+    }
+}
+
+pub struct FrameSorter {
+    key: SortKey,
+    error_tolerance: u32,
+    missing_frames: MissingFramePolicy,
+}
 
 impl FrameSorter {
     pub fn new() -> Self {
-        Self
+        Self::with_key(SortKey::default())
+    }
+
+    pub fn with_key(key: SortKey) -> Self {
+        Self {
+            key,
+            error_tolerance: 0,
+            missing_frames: MissingFramePolicy::default(),
+        }
+    }
+
+    /// Skips up to `limit` recoverable decode errors instead of ending the stream on the first one.
+    pub fn with_error_tolerance(mut self, limit: u32) -> Self {
+        self.error_tolerance = limit;
+        self
+    }
+
+    /// Checks that the indices of emitted frames follow each other without gaps; frames
+    /// without an index are not checked.
+    pub fn with_missing_frame_policy(mut self, policy: MissingFramePolicy) -> Self {
+        self.missing_frames = policy;
+        self
     }
 
     pub fn attach(
@@ -24,8 +95,12 @@ impl FrameSorter {
 
         let state = SorterState {
             upstream: stream,
-            pool: FramePool::default(),
+            pool: FramePool::new(self.key),
             finished: false,
+            error_tolerance: self.error_tolerance,
+            skipped_errors: 0,
+            missing_frames: self.missing_frames,
+            last_index: None,
         };
 
         let stream = Box::pin(futures_util::stream::unfold(state, SorterState::next));
@@ -43,13 +118,17 @@ struct SorterState {
     upstream: Pin<Box<dyn Stream<Item = DecoderResult<VideoFrame>> + Send>>,
     pool: FramePool,
     finished: bool,
+    error_tolerance: u32,
+    skipped_errors: u32,
+    missing_frames: MissingFramePolicy,
+    last_index: Option<u64>,
 }
 
 impl SorterState {
     async fn next(mut state: SorterState) -> Option<(DecoderResult<VideoFrame>, SorterState)> {
         loop {
             if let Some(frame) = state.pool.pop_next() {
-                return Some((Ok(frame), state));
+                return Some(state.emit(frame));
             }
 
             if state.finished {
@@ -61,30 +140,89 @@ impl SorterState {
                     state.pool.insert(frame);
                 }
                 Some(Err(err)) => {
+                    if err.is_recoverable() && state.skipped_errors < state.error_tolerance {
+                        state.skipped_errors += 1;
+                        eprintln!(
+                            "skipping decode error {}/{}: {err}",
+                            state.skipped_errors, state.error_tolerance
+                        );
+                        continue;
+                    }
                     state.finished = true;
                     return Some((Err(err), state));
                 }
                 None => {
                     state.finished = true;
                     if let Some(frame) = state.pool.pop_next() {
-                        return Some((Ok(frame), state));
+                        return Some(state.emit(frame));
                     }
                     return None;
                 }
             }
         }
     }
+
+    fn emit(mut self, frame: VideoFrame) -> (DecoderResult<VideoFrame>, SorterState) {
+        match self.check_continuity(&frame) {
+            Ok(()) => (Ok(frame), self),
+            Err(err) => {
+                self.finished = true;
+                self.pool.clear();
+                (Err(err), self)
+            }
+        }
+    }
+
+    fn check_continuity(&mut self, frame: &VideoFrame) -> DecoderResult<()> {
+        let Some(index) = frame.index() else {
+            return Ok(());
+        };
+        let Some(previous) = self.last_index.replace(index) else {
+            return Ok(());
+        };
+        let missing = index.saturating_sub(previous).saturating_sub(1);
+        if missing == 0 {
+            return Ok(());
+        }
+        match self.missing_frames {
+            MissingFramePolicy::Ignore => Ok(()),
+            MissingFramePolicy::Warn => {
+                eprintln!(
+                    "decoder dropped {missing} frame(s) between index {previous} and {index}"
+                );
+                Ok(())
+            }
+            MissingFramePolicy::Fail => Err(DecoderError::InvalidFrame {
+                reason: format!(
+                    "frame index jumped from {previous} to {index}; {missing} frame(s) missing"
+                ),
+            }),
+        }
+    }
 }
 
-#[derive(Default)]
 struct FramePool {
+    key: SortKey,
     pending: BTreeMap<u64, VideoFrame>,
     fallback_index: u64,
 }
 
 impl FramePool {
+    fn new(key: SortKey) -> Self {
+        Self {
+            key,
+            pending: BTreeMap::new(),
+            fallback_index: 0,
+        }
+    }
+
     fn insert(&mut self, frame: VideoFrame) {
-        let key = frame.index().unwrap_or_else(|| {
+        let timestamp = || frame.pts().map(|pts| pts.as_nanos() as u64);
+        let primary = match self.key {
+            SortKey::FrameIndex => frame.index().or_else(timestamp),
+            SortKey::Timestamp => timestamp().or_else(|| frame.index()),
+        };
+        let key = primary.unwrap_or_else(|| {
             let key = self.fallback_index;
             self.fallback_index = self.fallback_index.saturating_add(1);
             key
@@ -97,4 +235,140 @@ impl FramePool {
         let key = self.pending.keys().next().copied()?;
         self.pending.remove(&key)
     }
+
+    fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use subtitle_fast_types::DecoderError;
+
+    use super::*;
+
+    fn frame(index: u64, pts_ms: u64) -> VideoFrame {
+        VideoFrame::from_nv12_owned(
+            4,
+            2,
+            4,
+            4,
+            Some(Duration::from_millis(pts_ms)),
+            None,
+            vec![0; 8],
+            vec![128; 4],
+        )
+        .expect("frame")
+        .with_index(Some(index))
+    }
+
+    #[test]
+    fn timestamp_key_ignores_shuffled_indices() {
+        let mut pool = FramePool::new(SortKey::Timestamp);
+        for (index, pts_ms) in [(7, 0), (2, 40), (9, 80), (0, 120)] {
+            pool.insert(frame(index, pts_ms));
+        }
+
+        let order: Vec<_> = std::iter::from_fn(|| pool.pop_next())
+            .map(|frame| frame.pts().unwrap().as_millis())
+            .collect();
+        assert_eq!(order, vec![0, 40, 80, 120]);
+    }
+
+    #[test]
+    fn frame_index_key_orders_by_index() {
+        let mut pool = FramePool::new(SortKey::FrameIndex);
+        for (index, pts_ms) in [(7, 0), (2, 40), (9, 80), (0, 120)] {
+            pool.insert(frame(index, pts_ms));
+        }
+
+        let order: Vec<_> = std::iter::from_fn(|| pool.pop_next())
+            .map(|frame| frame.index().unwrap())
+            .collect();
+        assert_eq!(order, vec![0, 2, 7, 9]);
+    }
+
+    fn stream_with_errors(errors: usize) -> StreamBundle<DecoderResult<VideoFrame>> {
+        let mut items: Vec<DecoderResult<VideoFrame>> = Vec::new();
+        for index in 0..4u64 {
+            items.push(Ok(frame(index, index * 40)));
+            if (index as usize) < errors {
+                items.push(Err(DecoderError::backend_failure("mock", "corrupt packet")));
+            }
+        }
+        StreamBundle::new(Box::pin(futures_util::stream::iter(items)), Some(4))
+    }
+
+    #[tokio::test]
+    async fn transient_errors_under_limit_are_skipped() {
+        let sorted = FrameSorter::new()
+            .with_error_tolerance(2)
+            .attach(stream_with_errors(2));
+        let items: Vec<_> = sorted.stream.collect().await;
+        assert_eq!(items.len(), 4);
+        assert!(items.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn transient_errors_over_limit_abort() {
+        let sorted = FrameSorter::new()
+            .with_error_tolerance(2)
+            .attach(stream_with_errors(3));
+        let items: Vec<_> = sorted.stream.collect().await;
+        assert!(matches!(
+            items.last(),
+            Some(Err(DecoderError::BackendFailure { .. }))
+        ));
+        assert_eq!(items.iter().filter(|item| item.is_ok()).count(), 3);
+    }
+
+    async fn sort_with_gap(policy: MissingFramePolicy) -> Vec<DecoderResult<VideoFrame>> {
+        let items: Vec<DecoderResult<VideoFrame>> = [0, 1, 3, 4]
+            .into_iter()
+            .map(|index| Ok(frame(index, index * 40)))
+            .collect();
+        let input = StreamBundle::new(Box::pin(futures_util::stream::iter(items)), Some(5));
+        FrameSorter::new()
+            .with_missing_frame_policy(policy)
+            .attach(input)
+            .stream
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn skipped_index_is_handled_per_missing_frame_policy() {
+        for policy in [MissingFramePolicy::Ignore, MissingFramePolicy::Warn] {
+            let items = sort_with_gap(policy).await;
+            assert_eq!(items.len(), 4, "{}", policy.as_str());
+            assert!(items.iter().all(Result::is_ok));
+        }
+
+        let items = sort_with_gap(MissingFramePolicy::Fail).await;
+        assert_eq!(items.len(), 3);
+        assert!(items[..2].iter().all(Result::is_ok));
+        match &items[2] {
+            Err(DecoderError::InvalidFrame { reason }) => assert!(reason.contains("1 to 3")),
+            other => panic!(
+                "expected a missing-frame error, got {:?}",
+                other.as_ref().err()
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn fatal_errors_are_never_skipped() {
+        let items: Vec<DecoderResult<VideoFrame>> = vec![
+            Ok(frame(0, 0)),
+            Err(DecoderError::unsupported("mock")),
+            Ok(frame(1, 40)),
+        ];
+        let input = StreamBundle::new(Box::pin(futures_util::stream::iter(items)), Some(2));
+        let sorted = FrameSorter::new().with_error_tolerance(8).attach(input);
+        let items: Vec<_> = sorted.stream.collect().await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[1], Err(DecoderError::Unsupported { .. })));
+    }
 }
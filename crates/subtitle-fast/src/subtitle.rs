@@ -1,10 +1,157 @@
 use std::fmt::Write as _;
+use std::str::FromStr;
 use std::time::Duration;
 
+use serde::Serialize;
+use subtitle_fast_types::{DetectionRegion, OcrRegion, RoiConfig};
+
+/// How subtitles from regions that are on screen at the same time are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiRegionMode {
+    /// Overlapping cues are merged into one cue spanning their combined time range.
+    #[default]
+    Merge,
+    /// Every region keeps its own cue, so overlapping cues are written side by side.
+    SeparateCues,
+    /// Overlapping cues are split at their boundaries; each slice lists every visible region as a line.
+    MultiLine,
+}
+
+impl MultiRegionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MultiRegionMode::Merge => "merge",
+            MultiRegionMode::SeparateCues => "separate-cues",
+            MultiRegionMode::MultiLine => "multi-line",
+        }
+    }
+}
+
+impl FromStr for MultiRegionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "merge" => Ok(MultiRegionMode::Merge),
+            "separate-cues" | "separate" => Ok(MultiRegionMode::SeparateCues),
+            "multi-line" | "multiline" => Ok(MultiRegionMode::MultiLine),
+            _ => Err(lower),
+        }
+    }
+}
+
+/// File format subtitles are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    #[default]
+    Srt,
+    /// WebVTT for web players: a `WEBVTT` header and `.` before the milliseconds.
+    WebVtt,
+    /// Advanced SubStation Alpha, with each cue pinned to where it was detected.
+    Ass,
+    /// JSON array of cues with their regions and detection scores, for other tooling.
+    Json,
+}
+
+/// Play resolution assumed for ASS output when the decoder reports no frame size.
+pub const DEFAULT_PLAY_RES: (u32, u32) = (1920, 1080);
+
+impl SubtitleFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::WebVtt => "vtt",
+            SubtitleFormat::Ass => "ass",
+            SubtitleFormat::Json => "json",
+        }
+    }
+
+    /// File extension, without the dot.
+    pub fn extension(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// `frame_size` is the source width and height; only ASS uses it, as the play resolution.
+    /// Cues without visible text are skipped and the rest are numbered from 1 without gaps.
+    pub fn render(&self, subtitles: &[MergedSubtitle], frame_size: Option<(u32, u32)>) -> String {
+        let mut output = self.header(frame_size);
+        let mut written = 0;
+        for subtitle in subtitles {
+            if let Some(cue) = self.render_cue(written + 1, subtitle, frame_size) {
+                output.push_str(&cue);
+                written += 1;
+            }
+        }
+        output.push_str(self.footer(written));
+        output
+    }
+
+    /// Text before the first cue.
+    pub fn header(&self, frame_size: Option<(u32, u32)>) -> String {
+        match self {
+            SubtitleFormat::Srt => String::new(),
+            SubtitleFormat::WebVtt => String::from("WEBVTT\n\n"),
+            SubtitleFormat::Ass => render_ass_header(frame_size.unwrap_or(DEFAULT_PLAY_RES)),
+            SubtitleFormat::Json => String::from("[\n"),
+        }
+    }
+
+    /// Cue number `index` (1-based) with whatever separates it from the cue before; `None`
+    /// when the cue has no visible text.
+    pub fn render_cue(
+        &self,
+        index: u64,
+        subtitle: &MergedSubtitle,
+        frame_size: Option<(u32, u32)>,
+    ) -> Option<String> {
+        let lines = ordered_lines(&subtitle.lines);
+        if lines.is_empty() {
+            return None;
+        }
+        Some(match self {
+            SubtitleFormat::Srt => render_cue_block(index, subtitle, &lines, ','),
+            SubtitleFormat::WebVtt => render_cue_block(index, subtitle, &lines, '.'),
+            SubtitleFormat::Ass => {
+                render_ass_dialogue(subtitle, &lines, frame_size.unwrap_or(DEFAULT_PLAY_RES))
+            }
+            SubtitleFormat::Json => render_json_cue(index, subtitle, &lines),
+        })
+    }
+
+    /// Text after the last of `written` cues.
+    pub fn footer(&self, written: u64) -> &'static str {
+        match self {
+            SubtitleFormat::Json if written > 0 => "\n]\n",
+            SubtitleFormat::Json => "]\n",
+            _ => "",
+        }
+    }
+}
+
+impl FromStr for SubtitleFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "srt" => Ok(SubtitleFormat::Srt),
+            "vtt" | "webvtt" => Ok(SubtitleFormat::WebVtt),
+            "ass" | "ssa" => Ok(SubtitleFormat::Ass),
+            "json" => Ok(SubtitleFormat::Json),
+            _ => Err(lower),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SubtitleLine {
     pub center: f32,
     pub text: String,
+    /// Detected region the line was recognized in, in frame pixels.
+    pub region: OcrRegion,
+    /// Highest detection score of the segment the line was recognized from.
+    pub score: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -22,6 +169,7 @@ pub struct TimedSubtitle {
     pub start_ms: f64,
     pub end_ms: f64,
     pub lines: Vec<String>,
+    pub chars_per_second: f64,
 }
 
 impl MergedSubtitle {
@@ -31,8 +179,48 @@ impl MergedSubtitle {
             start_ms: self.start_time.as_secs_f64() * 1000.0,
             end_ms: self.end_time.as_secs_f64() * 1000.0,
             lines: ordered_lines(&self.lines),
+            chars_per_second: self.chars_per_second(),
         }
     }
+
+    /// Visible characters (line breaks excluded) divided by the cue duration in seconds.
+    pub fn chars_per_second(&self) -> f64 {
+        let chars = self.visible_chars();
+        if chars == 0 {
+            return 0.0;
+        }
+        let secs = self.end_time.saturating_sub(self.start_time).as_secs_f64();
+        if secs <= 0.0 {
+            return f64::INFINITY;
+        }
+        chars as f64 / secs
+    }
+
+    /// Smallest box covering the regions of every line.
+    pub fn bounds(&self) -> Option<OcrRegion> {
+        self.lines
+            .iter()
+            .map(|line| line.region)
+            .reduce(|acc, region| {
+                let x = acc.x.min(region.x);
+                let y = acc.y.min(region.y);
+                let right = (acc.x + acc.width).max(region.x + region.width);
+                let bottom = (acc.y + acc.height).max(region.y + region.height);
+                OcrRegion::new(x, y, right - x, bottom - y)
+            })
+    }
+
+    /// Highest detection score among the lines.
+    pub fn max_score(&self) -> f32 {
+        self.lines.iter().map(|line| line.score).fold(0.0, f32::max)
+    }
+
+    fn visible_chars(&self) -> usize {
+        ordered_lines(&self.lines)
+            .iter()
+            .map(|line| line.chars().count())
+            .sum()
+    }
 }
 
 impl TimedSubtitle {
@@ -48,30 +236,536 @@ pub fn sort_subtitles(subtitles: &mut [MergedSubtitle]) {
     });
 }
 
-pub fn render_srt(subtitles: &[MergedSubtitle]) -> String {
-    let mut output = String::new();
-    for (idx, cue) in subtitles.iter().enumerate() {
-        let lines = ordered_lines(&cue.lines);
-        if lines.is_empty() {
+/// Rearranges sorted subtitles for output according to `mode`.
+pub fn layout_subtitles(
+    subtitles: &[MergedSubtitle],
+    mode: MultiRegionMode,
+) -> Vec<MergedSubtitle> {
+    match mode {
+        MultiRegionMode::Merge | MultiRegionMode::SeparateCues => subtitles.to_vec(),
+        MultiRegionMode::MultiLine => slice_concurrent(subtitles),
+    }
+}
+
+/// Cues restricted to the lines recognized in one horizontal screen band.
+#[derive(Clone, Debug)]
+pub struct BandGroup {
+    /// `top`, `middle`, `bottom`, or `band1`.. when there are more than three bands.
+    pub label: String,
+    pub subtitles: Vec<MergedSubtitle>,
+}
+
+/// Groups cue lines into bands by their vertical center, top band first. Lines whose centers
+/// are within a line height of each other share a band, so jitter does not split one band.
+pub fn split_by_band(subtitles: &[MergedSubtitle]) -> Vec<BandGroup> {
+    let mut lines: Vec<(f32, f32)> = subtitles
+        .iter()
+        .flat_map(|subtitle| subtitle.lines.iter())
+        .map(|line| (line.center, line.region.height))
+        .collect();
+    lines.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // (lowest center, highest center, tallest line) per band.
+    let mut bands: Vec<(f32, f32, f32)> = Vec::new();
+    for (center, height) in lines {
+        match bands.last_mut() {
+            Some(band) if center - band.1 <= height.max(band.2) => {
+                band.1 = center;
+                band.2 = band.2.max(height);
+            }
+            _ => bands.push((center, center, height)),
+        }
+    }
+
+    let labels: Vec<String> = match bands.len() {
+        2 => vec!["top".into(), "bottom".into()],
+        3 => vec!["top".into(), "middle".into(), "bottom".into()],
+        count => (1..=count).map(|idx| format!("band{idx}")).collect(),
+    };
+    bands
+        .iter()
+        .zip(labels)
+        .map(|(&(low, high, _), label)| BandGroup {
+            label,
+            subtitles: subtitles
+                .iter()
+                .filter_map(|subtitle| {
+                    let lines: Vec<SubtitleLine> = subtitle
+                        .lines
+                        .iter()
+                        .filter(|line| line.center >= low && line.center <= high)
+                        .cloned()
+                        .collect();
+                    (!lines.is_empty()).then(|| MergedSubtitle {
+                        lines,
+                        ..subtitle.clone()
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn slice_concurrent(subtitles: &[MergedSubtitle]) -> Vec<MergedSubtitle> {
+    let mut boundaries: Vec<Duration> = subtitles
+        .iter()
+        .flat_map(|cue| [cue.start_time, cue.end_time])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut sliced: Vec<MergedSubtitle> = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let active: Vec<&MergedSubtitle> = subtitles
+            .iter()
+            .filter(|cue| cue.start_time <= start && cue.end_time >= end)
+            .collect();
+        if active.is_empty() {
             continue;
         }
-        if idx > 0 {
-            output.push('\n');
+        let mut lines: Vec<SubtitleLine> = Vec::new();
+        for cue in &active {
+            for line in &cue.lines {
+                if !lines.iter().any(|existing| existing.text == line.text) {
+                    lines.push(line.clone());
+                }
+            }
         }
-        let _ = writeln!(&mut output, "{}", idx + 1);
-        let _ = writeln!(
-            &mut output,
-            "{} --> {}",
-            format_timestamp(cue.start_time),
-            format_timestamp(cue.end_time)
+        let start_frame = active
+            .iter()
+            .map(|cue| cue.start_frame)
+            .min()
+            .unwrap_or_default();
+
+        if let Some(last) = sliced.last_mut()
+            && last.end_time == start
+            && same_lines(&last.lines, &lines)
+        {
+            last.end_time = end;
+            continue;
+        }
+        sliced.push(MergedSubtitle {
+            id: sliced.len() as u64,
+            start_time: start,
+            end_time: end,
+            start_frame,
+            lines,
+        });
+    }
+    sliced
+}
+
+fn same_lines(a: &[SubtitleLine], b: &[SubtitleLine]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|line| b.iter().any(|other| other.text == line.text))
+}
+
+/// Warns about sorted cues faster than `max_cps`; with `extend`, pushes their end time
+/// towards what the limit needs without running into the next cue. Returns the number flagged.
+pub fn enforce_max_cps(subtitles: &mut [MergedSubtitle], max_cps: f64, extend: bool) -> usize {
+    if max_cps <= 0.0 {
+        return 0;
+    }
+    let mut flagged = 0;
+    for idx in 0..subtitles.len() {
+        let cps = subtitles[idx].chars_per_second();
+        if cps <= max_cps {
+            continue;
+        }
+        flagged += 1;
+        let cue = &subtitles[idx];
+        eprintln!(
+            "cue {} at {} reads at {cps:.1} cps (limit {max_cps:.1})",
+            idx + 1,
+            format_timestamp(cue.start_time, ',')
         );
-        for line in lines {
-            let _ = writeln!(&mut output, "{line}");
+        if !extend {
+            continue;
+        }
+        let needed = cue.start_time + Duration::from_secs_f64(cue.visible_chars() as f64 / max_cps);
+        let limit = subtitles
+            .get(idx + 1)
+            .map(|next| next.start_time)
+            .unwrap_or(needed);
+        let end = needed.min(limit).max(cue.end_time);
+        subtitles[idx].end_time = end;
+    }
+    flagged
+}
+
+/// Pulls the end of each sorted cue back so at least `min_gap` separates it from the next
+/// cue's start, never before its own start. Cues overlapping the next one are meant to be on
+/// screen together and keep their timing. Returns the number of cues shortened.
+pub fn enforce_min_gap(subtitles: &mut [MergedSubtitle], min_gap: Duration) -> usize {
+    if min_gap.is_zero() {
+        return 0;
+    }
+    let mut shortened = 0;
+    for idx in 1..subtitles.len() {
+        let next_start = subtitles[idx].start_time;
+        let cue = &mut subtitles[idx - 1];
+        if next_start < cue.end_time {
+            continue;
+        }
+        let end = next_start
+            .saturating_sub(min_gap)
+            .clamp(cue.start_time, cue.end_time);
+        if end < cue.end_time {
+            cue.end_time = end;
+            shortened += 1;
+        }
+    }
+    shortened
+}
+
+/// What happens to a cue shorter than the minimum duration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShortCuePolicy {
+    /// Remove the cue; very short detections are usually false positives.
+    #[default]
+    Drop,
+    /// Keep the cue on screen for the minimum duration, without running into the next cue.
+    Extend,
+}
+
+impl ShortCuePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShortCuePolicy::Drop => "drop",
+            ShortCuePolicy::Extend => "extend",
+        }
+    }
+}
+
+impl FromStr for ShortCuePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "drop" => Ok(ShortCuePolicy::Drop),
+            "extend" => Ok(ShortCuePolicy::Extend),
+            _ => Err(lower),
+        }
+    }
+}
+
+/// Drops or extends sorted cues shown for less than `min_duration`. An extended cue ends at
+/// `start + min_duration` or at the next cue's start, whichever comes first, but never earlier
+/// than it already did. Returns the number of cues dropped or extended.
+pub fn enforce_min_duration(
+    subtitles: &mut Vec<MergedSubtitle>,
+    min_duration: Duration,
+    policy: ShortCuePolicy,
+) -> usize {
+    let is_short =
+        |cue: &MergedSubtitle| cue.end_time.saturating_sub(cue.start_time) < min_duration;
+    match policy {
+        ShortCuePolicy::Drop => {
+            let before = subtitles.len();
+            subtitles.retain(|cue| !is_short(cue));
+            before - subtitles.len()
+        }
+        ShortCuePolicy::Extend => {
+            let mut extended = 0;
+            for idx in 0..subtitles.len() {
+                if !is_short(&subtitles[idx]) {
+                    continue;
+                }
+                let next_start = subtitles.get(idx + 1).map(|next| next.start_time);
+                let cue = &mut subtitles[idx];
+                let mut end = cue.start_time + min_duration;
+                if let Some(next_start) = next_start {
+                    end = end.min(next_start.max(cue.end_time));
+                }
+                if end > cue.end_time {
+                    cue.end_time = end;
+                    extended += 1;
+                }
+            }
+            extended
         }
     }
+}
+
+/// Maps every cue time `t` to `t * rate_scale + offset_ms`, rounded to the millisecond and
+/// clamped at zero. Cues left without any duration are dropped; returns how many.
+pub fn retime_cues(subtitles: &mut Vec<MergedSubtitle>, offset_ms: i64, rate_scale: f64) -> usize {
+    if offset_ms == 0 && rate_scale == 1.0 {
+        return 0;
+    }
+    let retime = |time: Duration| {
+        let millis = time.as_secs_f64() * 1000.0 * rate_scale + offset_ms as f64;
+        Duration::from_millis(millis.max(0.0).round() as u64)
+    };
+    for cue in subtitles.iter_mut() {
+        cue.start_time = retime(cue.start_time);
+        cue.end_time = retime(cue.end_time);
+    }
+    let before = subtitles.len();
+    subtitles.retain(|cue| cue.end_time > cue.start_time);
+    before - subtitles.len()
+}
+
+/// Folds each sorted cue into the one before it when both show the same text and the gap
+/// between them is shorter than `max_gap`, extending the earlier cue's end. Trailing
+/// whitespace never counts as a difference; case only does when `ignore_case` is false.
+/// Returns the number of cues merged away.
+pub fn merge_identical_cues(
+    subtitles: &mut Vec<MergedSubtitle>,
+    max_gap: Duration,
+    ignore_case: bool,
+) -> usize {
+    if max_gap.is_zero() {
+        return 0;
+    }
+    let before = subtitles.len();
+    let mut merged: Vec<(String, MergedSubtitle)> = Vec::with_capacity(before);
+    for cue in subtitles.drain(..) {
+        let key = cue_text_key(&cue, ignore_case);
+        if let Some((last_key, last)) = merged.last_mut()
+            && *last_key == key
+            && cue.start_time.saturating_sub(last.end_time) < max_gap
+        {
+            last.end_time = last.end_time.max(cue.end_time);
+            continue;
+        }
+        merged.push((key, cue));
+    }
+    subtitles.extend(merged.into_iter().map(|(_, cue)| cue));
+    before - subtitles.len()
+}
+
+fn cue_text_key(subtitle: &MergedSubtitle, ignore_case: bool) -> String {
+    let text = ordered_lines(&subtitle.lines).join("\n");
+    let text = text.trim_end();
+    if ignore_case {
+        text.to_lowercase()
+    } else {
+        text.to_string()
+    }
+}
+
+#[derive(Serialize)]
+struct NdjsonCue<'a> {
+    index: u64,
+    start_ms: u64,
+    end_ms: u64,
+    text: &'a str,
+    roi: Option<NdjsonRoi>,
+}
+
+#[derive(Serialize)]
+struct NdjsonRoi {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// One cue as a single-line JSON object (no trailing newline); `index` is 1-based like SRT.
+pub fn render_ndjson_cue(index: u64, subtitle: &MergedSubtitle) -> String {
+    let text = ordered_lines(&subtitle.lines).join("\n");
+    let cue = NdjsonCue {
+        index,
+        start_ms: subtitle.start_time.as_millis() as u64,
+        end_ms: subtitle.end_time.as_millis() as u64,
+        text: &text,
+        roi: subtitle.bounds().map(|region| NdjsonRoi {
+            x: region.x,
+            y: region.y,
+            width: region.width,
+            height: region.height,
+        }),
+    };
+    serde_json::to_string(&cue).expect("cue serializes to JSON")
+}
+
+#[derive(Serialize)]
+struct JsonCue {
+    index: u64,
+    start_ms: u64,
+    end_ms: u64,
+    text: String,
+    /// Box covering every line, in frame pixels.
+    roi: Option<RoiConfig>,
+    max_score: f32,
+    regions: Vec<DetectionRegion>,
+}
+
+/// Pretty-printed JSON array of cues; `index` is 1-based like SRT and `regions` lists each
+/// line's region in frame pixels with its detection score.
+pub fn render_json(subtitles: &[MergedSubtitle]) -> String {
+    SubtitleFormat::Json.render(subtitles, None)
+}
+
+pub fn render_srt(subtitles: &[MergedSubtitle]) -> String {
+    SubtitleFormat::Srt.render(subtitles, None)
+}
+
+/// Same cue blocks as [`render_srt`] after a `WEBVTT` header, with `.` as the millisecond
+/// separator.
+pub fn render_vtt(subtitles: &[MergedSubtitle]) -> String {
+    SubtitleFormat::WebVtt.render(subtitles, None)
+}
+
+/// ASS script with the play resolution set to `play_res`; each dialogue line is placed with
+/// `\pos` at the centre of its detected regions, clamped to the play area.
+pub fn render_ass(subtitles: &[MergedSubtitle], play_res: (u32, u32)) -> String {
+    SubtitleFormat::Ass.render(subtitles, Some(play_res))
+}
+
+/// Numbered SRT/WebVTT cue block; `separator` goes between seconds and milliseconds.
+fn render_cue_block(
+    index: u64,
+    subtitle: &MergedSubtitle,
+    lines: &[String],
+    separator: char,
+) -> String {
+    let mut output = String::new();
+    if index > 1 {
+        output.push('\n');
+    }
+    let _ = writeln!(&mut output, "{index}");
+    let _ = writeln!(
+        &mut output,
+        "{} --> {}",
+        format_timestamp(subtitle.start_time, separator),
+        format_timestamp(subtitle.end_time, separator)
+    );
+    for line in lines {
+        let _ = writeln!(&mut output, "{line}");
+    }
+    output
+}
+
+fn render_ass_header(play_res: (u32, u32)) -> String {
+    let (width, height) = play_res;
+    let font_size = (height / 18).max(1);
+    let mut output = String::new();
+    let _ = writeln!(&mut output, "[Script Info]");
+    let _ = writeln!(&mut output, "ScriptType: v4.00+");
+    let _ = writeln!(&mut output, "PlayResX: {width}");
+    let _ = writeln!(&mut output, "PlayResY: {height}");
+    let _ = writeln!(&mut output, "WrapStyle: 0");
+    let _ = writeln!(&mut output, "ScaledBorderAndShadow: yes");
+    output.push('\n');
+    let _ = writeln!(&mut output, "[V4+ Styles]");
+    let _ = writeln!(
+        &mut output,
+        "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+         BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+         BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding"
+    );
+    let _ = writeln!(
+        &mut output,
+        "Style: Default,Arial,{font_size},&H00FFFFFF,&H000000FF,&H00000000,&H00000000,\
+         0,0,0,0,100,100,0,0,1,2,0,5,10,10,10,1"
+    );
+    output.push('\n');
+    let _ = writeln!(&mut output, "[Events]");
+    let _ = writeln!(
+        &mut output,
+        "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text"
+    );
     output
 }
 
+fn render_ass_dialogue(
+    subtitle: &MergedSubtitle,
+    lines: &[String],
+    play_res: (u32, u32),
+) -> String {
+    let (width, height) = play_res;
+    let text = lines
+        .iter()
+        .map(|line| escape_ass_text(line))
+        .collect::<Vec<_>>()
+        .join("\\N");
+    let position = subtitle
+        .bounds()
+        .map(|region| {
+            let x = (region.x + region.width / 2.0)
+                .round()
+                .clamp(0.0, width as f32);
+            let y = (region.y + region.height / 2.0)
+                .round()
+                .clamp(0.0, height as f32);
+            format!("{{\\pos({x},{y})}}")
+        })
+        .unwrap_or_default();
+    format!(
+        "Dialogue: 0,{},{},Default,,0,0,0,,{position}{text}\n",
+        format_ass_timestamp(subtitle.start_time),
+        format_ass_timestamp(subtitle.end_time)
+    )
+}
+
+/// One element of the pretty-printed cue array, indented to sit inside it.
+fn render_json_cue(index: u64, subtitle: &MergedSubtitle, lines: &[String]) -> String {
+    let cue = JsonCue {
+        index,
+        start_ms: subtitle.start_time.as_millis() as u64,
+        end_ms: subtitle.end_time.as_millis() as u64,
+        text: lines.join("\n"),
+        roi: subtitle.bounds().map(|region| RoiConfig {
+            x: region.x,
+            y: region.y,
+            width: region.width,
+            height: region.height,
+        }),
+        max_score: subtitle.max_score(),
+        regions: subtitle
+            .lines
+            .iter()
+            .map(|line| DetectionRegion {
+                x: line.region.x,
+                y: line.region.y,
+                width: line.region.width,
+                height: line.region.height,
+                score: line.score,
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string_pretty(&cue).expect("cue serializes to JSON");
+    let mut output = String::from(if index > 1 { ",\n" } else { "" });
+    for (idx, line) in json.lines().enumerate() {
+        if idx > 0 {
+            output.push('\n');
+        }
+        output.push_str("  ");
+        output.push_str(line);
+    }
+    output
+}
+
+/// Braces would open an override block and a raw newline would end the event line.
+fn escape_ass_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '{' => escaped.push_str("\\{"),
+            '}' => escaped.push_str("\\}"),
+            '\n' => escaped.push_str("\\N"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// `H:MM:SS.cc`; ASS timestamps only carry centiseconds.
+fn format_ass_timestamp(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    let hours = centis / 360_000;
+    let minutes = (centis % 360_000) / 6_000;
+    let seconds = (centis % 6_000) / 100;
+    let remain = centis % 100;
+    format!("{hours}:{minutes:02}:{seconds:02}.{remain:02}")
+}
+
 fn ordered_lines(lines: &[SubtitleLine]) -> Vec<String> {
     let mut refs: Vec<&SubtitleLine> = lines.iter().collect();
     refs.sort_by(|a, b| {
@@ -93,7 +787,7 @@ fn ordered_lines(lines: &[SubtitleLine]) -> Vec<String> {
     ordered
 }
 
-fn format_timestamp(time: Duration) -> String {
+fn format_timestamp(time: Duration, separator: char) -> String {
     let millis = time
         .as_secs()
         .saturating_mul(1000)
@@ -102,5 +796,260 @@ fn format_timestamp(time: Duration) -> String {
     let minutes = (millis % 3_600_000) / 60_000;
     let seconds = (millis % 60_000) / 1000;
     let remain_ms = millis % 1000;
-    format!("{hours:02}:{minutes:02}:{seconds:02},{remain_ms:03}")
+    format!("{hours:02}:{minutes:02}:{seconds:02}{separator}{remain_ms:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(id: u64, start_ms: u64, end_ms: u64, text: &str) -> MergedSubtitle {
+        MergedSubtitle {
+            id,
+            start_time: Duration::from_millis(start_ms),
+            end_time: Duration::from_millis(end_ms),
+            start_frame: id,
+            lines: vec![SubtitleLine {
+                center: 0.0,
+                text: text.to_string(),
+                region: OcrRegion::new(0.0, 0.0, 0.0, 0.0),
+                score: 1.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn chars_per_second_uses_visible_text() {
+        let mut two_lines = cue(0, 0, 2000, "hello");
+        two_lines.lines.push(SubtitleLine {
+            center: 1.0,
+            text: "world".to_string(),
+            region: OcrRegion::new(0.0, 0.0, 0.0, 0.0),
+            score: 1.0,
+        });
+        assert!((two_lines.chars_per_second() - 5.0).abs() < 1e-9);
+        assert_eq!(cue(1, 0, 1000, "   ").chars_per_second(), 0.0);
+        assert!(cue(2, 500, 500, "abc").chars_per_second().is_infinite());
+        assert!((two_lines.as_timed().chars_per_second - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn max_cps_extension_stops_at_next_cue() {
+        let mut cues = vec![
+            cue(0, 0, 1000, "twenty characters!!!"),
+            cue(1, 1500, 2000, "forty characters of subtitle text here!!"),
+        ];
+        let flagged = enforce_max_cps(&mut cues, 10.0, true);
+
+        assert_eq!(flagged, 2);
+        // First cue needs 2s but the next cue starts at 1.5s.
+        assert_eq!(cues[0].end_time, Duration::from_millis(1500));
+        // The last cue is free to take the full 4s it needs.
+        assert_eq!(cues[1].end_time, Duration::from_millis(5500));
+        assert!(cues[0].end_time <= cues[1].start_time);
+    }
+
+    #[test]
+    fn max_cps_without_extension_only_warns() {
+        let mut cues = vec![cue(0, 0, 1000, "twenty characters!!!")];
+        assert_eq!(enforce_max_cps(&mut cues, 10.0, false), 1);
+        assert_eq!(cues[0].end_time, Duration::from_millis(1000));
+        assert_eq!(enforce_max_cps(&mut cues, 25.0, true), 0);
+    }
+
+    #[test]
+    fn min_gap_separates_adjacent_cues_only() {
+        let mut cues = vec![
+            cue(0, 0, 1000, "back to back"),
+            cue(1, 1000, 2000, "close behind"),
+            cue(2, 2050, 3000, "well apart"),
+            cue(3, 3500, 4000, "last"),
+        ];
+        let shortened = enforce_min_gap(&mut cues, Duration::from_millis(100));
+
+        assert_eq!(shortened, 2);
+        assert_eq!(cues[0].end_time, Duration::from_millis(900));
+        assert_eq!(cues[1].end_time, Duration::from_millis(1950));
+        assert_eq!(cues[2].end_time, Duration::from_millis(3000));
+        assert_eq!(cues[3].end_time, Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn min_gap_never_ends_a_cue_before_it_starts() {
+        let mut cues = vec![
+            cue(0, 1000, 1040, "flash"),
+            cue(1, 1040, 2000, "next"),
+            cue(2, 1500, 2500, "overlapping"),
+        ];
+        enforce_min_gap(&mut cues, Duration::from_millis(100));
+
+        assert_eq!(cues[0].end_time, Duration::from_millis(1000));
+        // Overlapping cues are shown together on purpose.
+        assert_eq!(cues[1].end_time, Duration::from_millis(2000));
+        assert_eq!(enforce_min_gap(&mut cues, Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn vtt_differs_from_srt_only_in_header_and_millisecond_separator() {
+        let cues = vec![
+            cue(0, 1_500, 4_250, "Hello"),
+            cue(1, 3_723_004, 3_725_000, "World"),
+        ];
+
+        assert_eq!(
+            SubtitleFormat::WebVtt.render(&cues, None),
+            "WEBVTT\n\n\
+             1\n00:00:01.500 --> 00:00:04.250\nHello\n\n\
+             2\n01:02:03.004 --> 01:02:05.000\nWorld\n"
+        );
+        assert_eq!(
+            SubtitleFormat::Srt.render(&cues, None),
+            "1\n00:00:01,500 --> 00:00:04,250\nHello\n\n\
+             2\n01:02:03,004 --> 01:02:05,000\nWorld\n"
+        );
+        assert_eq!("webvtt".parse(), Ok(SubtitleFormat::WebVtt));
+        assert_eq!(SubtitleFormat::WebVtt.extension(), "vtt");
+    }
+
+    const TWO_CUES_ASS: &str = r"[Script Info]
+ScriptType: v4.00+
+PlayResX: 640
+PlayResY: 720
+WrapStyle: 0
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,40,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,0,5,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.23,0:00:04.00,Default,,0,0,0,,{\pos(300,630)}\{tag\} a\Nb
+Dialogue: 0,1:02:03.45,1:02:05.00,Default,,0,0,0,,{\pos(640,720)}top\Nbottom
+";
+
+    #[test]
+    fn ass_places_escaped_cues_at_their_clamped_roi_centre() {
+        let mut first = cue(0, 1_234, 4_005, "{tag} a\nb");
+        first.lines[0].region = OcrRegion::new(100.0, 600.0, 400.0, 60.0);
+        let mut second = cue(1, 3_723_450, 3_725_000, "bottom");
+        second.lines[0].region = OcrRegion::new(500.0, 700.0, 400.0, 100.0);
+        second.lines.push(SubtitleLine {
+            center: -1.0,
+            text: "top".to_string(),
+            region: OcrRegion::new(500.0, 650.0, 400.0, 40.0),
+            score: 1.0,
+        });
+
+        let script = SubtitleFormat::Ass.render(&[first, second], Some((640, 720)));
+        assert_eq!(script, TWO_CUES_ASS);
+        assert_eq!("ssa".parse(), Ok(SubtitleFormat::Ass));
+    }
+
+    #[test]
+    fn identical_cues_within_the_gap_merge_into_the_first() {
+        let mut cues = vec![
+            cue(0, 1_000, 2_000, "Hello there"),
+            cue(1, 2_040, 3_000, "hello there  "),
+            cue(2, 3_040, 4_000, "General Kenobi"),
+        ];
+        let gap = Duration::from_millis(100);
+
+        assert_eq!(merge_identical_cues(&mut cues.clone(), gap, false), 0);
+        assert_eq!(merge_identical_cues(&mut cues, gap, true), 1);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].lines[0].text, "Hello there");
+        assert_eq!(cues[0].end_time, Duration::from_millis(3_000));
+        assert_eq!(cues[1].lines[0].text, "General Kenobi");
+    }
+
+    #[test]
+    fn short_cues_are_dropped_or_extended_up_to_the_next_cue() {
+        let cues = vec![
+            cue(0, 0, 100, "flash"),
+            cue(1, 1_000, 1_150, "short"),
+            cue(2, 1_180, 2_000, "long"),
+        ];
+        let min = Duration::from_millis(200);
+
+        let mut dropped = cues.clone();
+        assert_eq!(
+            enforce_min_duration(&mut dropped, min, ShortCuePolicy::Drop),
+            2
+        );
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].lines[0].text, "long");
+
+        let mut extended = cues;
+        assert_eq!(
+            enforce_min_duration(&mut extended, min, ShortCuePolicy::Extend),
+            2
+        );
+        assert_eq!(extended.len(), 3);
+        // Long enough once extended to the minimum.
+        assert_eq!(extended[0].end_time, Duration::from_millis(200));
+        // Stops at the next cue instead of overlapping it.
+        assert_eq!(extended[1].end_time, Duration::from_millis(1_180));
+        assert_eq!(extended[2].end_time, Duration::from_millis(2_000));
+    }
+
+    #[test]
+    fn json_lists_each_cue_with_its_roi_and_scores() {
+        let mut first = cue(0, 1_000, 2_500, "Hello");
+        first.lines[0].region = OcrRegion::new(100.0, 600.0, 400.0, 50.0);
+        first.lines[0].score = 0.75;
+        let mut second = cue(1, 3_000, 4_000, "World");
+        second.lines[0].region = OcrRegion::new(120.0, 40.0, 300.0, 40.0);
+        second.lines[0].score = 0.5;
+
+        let json: serde_json::Value =
+            serde_json::from_str(&SubtitleFormat::Json.render(&[first, second], None)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "index": 1,
+                    "start_ms": 1000,
+                    "end_ms": 2500,
+                    "text": "Hello",
+                    "roi": { "x": 100.0, "y": 600.0, "width": 400.0, "height": 50.0 },
+                    "max_score": 0.75,
+                    "regions": [
+                        { "x": 100.0, "y": 600.0, "width": 400.0, "height": 50.0, "score": 0.75 }
+                    ]
+                },
+                {
+                    "index": 2,
+                    "start_ms": 3000,
+                    "end_ms": 4000,
+                    "text": "World",
+                    "roi": { "x": 120.0, "y": 40.0, "width": 300.0, "height": 40.0 },
+                    "max_score": 0.5,
+                    "regions": [
+                        { "x": 120.0, "y": 40.0, "width": 300.0, "height": 40.0, "score": 0.5 }
+                    ]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn retiming_offsets_and_scales_cues_and_drops_those_pushed_before_zero() {
+        let mut shifted = vec![cue(0, 2_000, 4_000, "shifted")];
+        assert_eq!(retime_cues(&mut shifted, 1_500, 1.0), 0);
+        assert_eq!(shifted[0].start_time, Duration::from_millis(3_500));
+        assert_eq!(shifted[0].end_time, Duration::from_millis(5_500));
+
+        let mut scaled = vec![cue(0, 60_000, 61_000, "scaled")];
+        retime_cues(&mut scaled, 0, 1.001);
+        assert_eq!(scaled[0].start_time, Duration::from_millis(60_060));
+        assert_eq!(scaled[0].end_time, Duration::from_millis(61_061));
+
+        let mut early = vec![cue(0, 1_000, 2_000, "gone"), cue(1, 1_000, 5_000, "kept")];
+        assert_eq!(retime_cues(&mut early, -3_000, 1.0), 1);
+        assert_eq!(early[0].lines[0].text, "kept");
+        assert_eq!(early[0].start_time, Duration::ZERO);
+        assert_eq!(early[0].end_time, Duration::from_millis(2_000));
+    }
 }
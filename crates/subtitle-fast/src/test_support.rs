@@ -0,0 +1,13 @@
+//! Fixtures shared by the unit tests of several modules.
+
+use std::time::Duration;
+
+use subtitle_fast_types::VideoFrame;
+
+/// The self-test clip's frame at `index`, timed at 60 fps.
+pub(crate) fn scripted_frame(index: u64, with_subtitle: bool) -> VideoFrame {
+    crate::selftest::scripted_frame(with_subtitle)
+        .expect("frame")
+        .with_pts(Some(Duration::from_secs_f64(index as f64 / 60.0)))
+        .with_index(Some(index))
+}
@@ -0,0 +1,180 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::stage::lifecycle::{LifecycleEvent, sample_time};
+
+pub const TIMELINE_CSV_HEADER: &str =
+    "frame_index,timestamp,has_subtitle,max_score,region_count,active_segment_id";
+
+/// Writes one CSV row per detection sample; ids of regions open at once are joined with `;`.
+pub struct TimelineCsvWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> TimelineCsvWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{TIMELINE_CSV_HEADER}")?;
+        Ok(Self { writer })
+    }
+
+    /// Appends the event's sample; flush events that only close regions carry none.
+    pub fn record(&mut self, event: &LifecycleEvent) -> io::Result<()> {
+        let Some(sample) = event.sample.as_ref() else {
+            return Ok(());
+        };
+        let active = event
+            .active
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(";");
+        writeln!(
+            self.writer,
+            "{},{:.3},{},{:.4},{},{}",
+            sample.sample.frame_index(),
+            sample_time(&sample.sample).as_secs_f64(),
+            sample.detection.has_subtitle,
+            sample.detection.max_score,
+            sample.detection.regions.len(),
+            active
+        )
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Timeline CSV file shared with the pipeline stream that feeds it. The first write error
+/// stops further rows and is reported by [`TimelineLog::finish`].
+#[derive(Clone)]
+pub struct TimelineLog {
+    inner: Arc<Mutex<TimelineState>>,
+}
+
+struct TimelineState {
+    writer: Option<TimelineCsvWriter<BufWriter<File>>>,
+    error: Option<io::Error>,
+}
+
+impl TimelineLog {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let writer = TimelineCsvWriter::new(BufWriter::new(File::create(path)?))?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(TimelineState {
+                writer: Some(writer),
+                error: None,
+            })),
+        })
+    }
+
+    pub fn record(&self, event: &LifecycleEvent) {
+        let mut state = self.inner.lock();
+        if let Some(writer) = state.writer.as_mut()
+            && let Err(err) = writer.record(event)
+        {
+            state.writer = None;
+            state.error = Some(err);
+        }
+    }
+
+    pub fn finish(&self) -> io::Result<()> {
+        let mut state = self.inner.lock();
+        if let Some(err) = state.error.take() {
+            return Err(err);
+        }
+        match state.writer.take() {
+            Some(writer) => writer.finish().map(drop),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use subtitle_fast_types::{DetectionRegion, SubtitleDetectionResult, VideoFrame};
+
+    use super::*;
+    use crate::stage::detector::DetectionSample;
+    use crate::stage::sampler::{FrameHistory, SampledFrame, SamplerContext};
+
+    fn sample_event(frame_index: u64, has_subtitle: bool, active: Vec<u64>) -> LifecycleEvent {
+        let frame = VideoFrame::from_nv12_owned(
+            64,
+            32,
+            64,
+            64,
+            Some(Duration::from_millis(frame_index * 40)),
+            None,
+            vec![0; 64 * 32],
+            vec![128; 64 * 16],
+        )
+        .unwrap();
+        let mut detection = SubtitleDetectionResult::empty();
+        if has_subtitle {
+            detection.has_subtitle = true;
+            detection.max_score = 0.75;
+            detection.regions.push(DetectionRegion {
+                x: 4.0,
+                y: 20.0,
+                width: 56.0,
+                height: 8.0,
+                score: 0.75,
+            });
+        }
+        LifecycleEvent {
+            sample: Some(DetectionSample {
+                sample: SampledFrame::new(
+                    frame_index,
+                    Arc::new(frame),
+                    FrameHistory::new(Vec::new()),
+                    Arc::new(SamplerContext::initial()),
+                ),
+                detection,
+                elapsed: Duration::ZERO,
+            }),
+            completed: Vec::new(),
+            active,
+            region_timings: None,
+        }
+    }
+
+    #[test]
+    fn rows_follow_the_emitted_samples() {
+        let events = [
+            sample_event(25, true, vec![0]),
+            sample_event(50, true, vec![0, 3]),
+            LifecycleEvent {
+                sample: None,
+                completed: Vec::new(),
+                active: Vec::new(),
+                region_timings: None,
+            },
+            sample_event(75, false, Vec::new()),
+        ];
+
+        let mut writer = TimelineCsvWriter::new(Vec::new()).unwrap();
+        for event in &events {
+            writer.record(event).unwrap();
+        }
+        let csv = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                TIMELINE_CSV_HEADER,
+                "25,1.000,true,0.7500,1,0",
+                "50,2.000,true,0.7500,1,0;3",
+                "75,3.000,false,0.0000,0,",
+            ]
+        );
+    }
+}